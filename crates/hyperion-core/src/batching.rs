@@ -0,0 +1,274 @@
+//! Draw-batch generation from the SoA render buffers.
+//!
+//! Every slot gets a sort key built from its `gpu_render_meta` and
+//! `gpu_tex_indices` words: opaque before transparent, then by
+//! `RenderPrimitive`, `MeshHandle` and texture tier. Slots sharing a key form
+//! one batch. Batches are emitted in key order together with their slot
+//! lists and `drawIndexedIndirect` arguments (5 u32 each, matching the layout
+//! `cull-pass.ts` resets), so the renderer can upload and draw them as-is.
+//!
+//! Per-key slot counts are kept across frames and only adjusted for slots
+//! whose key changed; the slot lists and arguments are rebuilt only when a
+//! key or the slot count changed.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::render_state::RENDER_META_TRANSPARENT;
+
+/// Index count of the unit quad (mesh handle 0), used for meshes without a
+/// registered index count.
+pub const QUAD_INDEX_COUNT: u32 = 6;
+
+/// u32 words per indirect draw argument record:
+/// index count, instance count, first index, base vertex, first instance.
+pub const INDIRECT_ARGS_STRIDE: usize = 5;
+
+/// Texture tier of a packed texture index: bits 16..18, or 8 for overflow
+/// textures (bit 31), which live outside the tiered arrays.
+pub fn texture_tier(tex: u32) -> u32 {
+    if tex & 0x8000_0000 != 0 { 8 } else { (tex >> 16) & 7 }
+}
+
+/// Batch sort key of a slot, from its second render meta word, mesh handle
+/// and packed texture index. Bits 44: transparent, 36..43: primitive,
+/// 4..35: mesh, 0..3: texture tier.
+pub fn batch_key(meta: u32, mesh: u32, tex: u32) -> u64 {
+    let transparent = u64::from(meta & RENDER_META_TRANSPARENT != 0);
+    (transparent << 44) | (u64::from(meta & 0xFF) << 36) | (u64::from(mesh) << 4) | u64::from(texture_tier(tex))
+}
+
+/// One draw batch: its key fields and its range in the batch slot list.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DrawBatch {
+    pub primitive: u32,
+    pub mesh: u32,
+    pub tier: u32,
+    /// 1 if the batch draws transparent entities.
+    pub transparent: u32,
+    /// Offset of the batch's first slot in the batch slot list.
+    pub first: u32,
+    pub count: u32,
+}
+
+impl DrawBatch {
+    fn from_key(key: u64, first: u32, count: u32) -> Self {
+        Self {
+            primitive: ((key >> 36) & 0xFF) as u32,
+            mesh: ((key >> 4) & 0xFFFF_FFFF) as u32,
+            tier: (key & 0xF) as u32,
+            transparent: (key >> 44) as u32 & 1,
+            first,
+            count,
+        }
+    }
+}
+
+/// Sorted draw batches, their slot lists and indirect draw arguments.
+#[derive(Debug, Default)]
+pub struct DrawBatches {
+    /// Key of each slot as of the last update.
+    slot_keys: Vec<u64>,
+    /// Number of slots per key; zero counts are pruned on rebuild.
+    key_counts: BTreeMap<u64, u32>,
+    /// Index counts of non-quad meshes.
+    mesh_index_counts: HashMap<u32, u32>,
+    batches: Vec<DrawBatch>,
+    /// Slots grouped by batch, ascending within each batch.
+    slots: Vec<u32>,
+    args: Vec<u32>,
+    /// Bumped whenever the outputs change.
+    version: u32,
+}
+
+impl DrawBatches {
+    /// Set the index count drawn for `mesh` (the quad's 6 by default).
+    pub fn set_mesh_index_count(&mut self, mesh: u32, index_count: u32) {
+        if self.mesh_index_counts.insert(mesh, index_count) != Some(index_count) {
+            self.write_args();
+            self.version = self.version.wrapping_add(1);
+        }
+    }
+
+    /// Update the batches for `count` slots. `meta` holds 2 u32 per slot
+    /// (mesh handle, primitive word) and `tex` 1 u32 per slot. Returns `true`
+    /// if the outputs changed.
+    pub fn update(&mut self, meta: &[u32], tex: &[u32], count: usize) -> bool {
+        let mut changed = false;
+        for &key in self.slot_keys.get(count..).unwrap_or_default() {
+            Self::adjust(&mut self.key_counts, key, false);
+            changed = true;
+        }
+        self.slot_keys.truncate(count);
+
+        for slot in 0..count {
+            let key = batch_key(meta[slot * 2 + 1], meta[slot * 2], tex[slot]);
+            match self.slot_keys.get_mut(slot) {
+                Some(old) if *old == key => continue,
+                Some(old) => {
+                    Self::adjust(&mut self.key_counts, *old, false);
+                    *old = key;
+                }
+                None => self.slot_keys.push(key),
+            }
+            Self::adjust(&mut self.key_counts, key, true);
+            changed = true;
+        }
+
+        if changed {
+            self.rebuild();
+            self.version = self.version.wrapping_add(1);
+        }
+        changed
+    }
+
+    fn adjust(key_counts: &mut BTreeMap<u64, u32>, key: u64, add: bool) {
+        let n = key_counts.entry(key).or_insert(0);
+        if add { *n += 1 } else { *n -= 1 }
+    }
+
+    /// Rebuild the batch list (in key order) and scatter slots into it.
+    fn rebuild(&mut self) {
+        self.key_counts.retain(|_, n| *n > 0);
+        self.batches.clear();
+        let mut keys = Vec::with_capacity(self.key_counts.len());
+        let mut first = 0u32;
+        for (&key, &count) in &self.key_counts {
+            self.batches.push(DrawBatch::from_key(key, first, count));
+            keys.push(key);
+            first += count;
+        }
+
+        self.slots.resize(first as usize, 0);
+        let mut cursor: Vec<u32> = self.batches.iter().map(|b| b.first).collect();
+        for (slot, key) in self.slot_keys.iter().enumerate() {
+            // Every slot key is in `keys`: key_counts counts each slot once.
+            let b = keys.binary_search(key).unwrap();
+            self.slots[cursor[b] as usize] = slot as u32;
+            cursor[b] += 1;
+        }
+        self.write_args();
+    }
+
+    fn write_args(&mut self) {
+        self.args.clear();
+        self.args.reserve(self.batches.len() * INDIRECT_ARGS_STRIDE);
+        for b in &self.batches {
+            let index_count = self.mesh_index_counts.get(&b.mesh).copied().unwrap_or(QUAD_INDEX_COUNT);
+            self.args.extend_from_slice(&[index_count, b.count, 0, 0, b.first]);
+        }
+    }
+
+    /// Forget every slot, batch and output (mesh index counts are kept).
+    pub fn clear(&mut self) {
+        self.slot_keys.clear();
+        self.key_counts.clear();
+        self.batches.clear();
+        self.slots.clear();
+        self.args.clear();
+        self.version = self.version.wrapping_add(1);
+    }
+
+    pub fn batches(&self) -> &[DrawBatch] {
+        &self.batches
+    }
+
+    /// Pointer to the batches (6 u32 each, see [`DrawBatch`]). Returns null if empty.
+    pub fn batches_ptr(&self) -> *const u32 {
+        if self.batches.is_empty() { std::ptr::null() } else { self.batches.as_ptr().cast() }
+    }
+
+    /// Slots grouped by batch: batch `b` owns `slots[b.first..b.first + b.count]`.
+    pub fn slots(&self) -> &[u32] {
+        &self.slots
+    }
+
+    /// Pointer to the batch slot list. Returns null if empty.
+    pub fn slots_ptr(&self) -> *const u32 {
+        if self.slots.is_empty() { std::ptr::null() } else { self.slots.as_ptr() }
+    }
+
+    /// Indirect draw arguments, [`INDIRECT_ARGS_STRIDE`] u32 per batch. The
+    /// first instance is the batch's offset into [`slots`](Self::slots).
+    pub fn indirect_args(&self) -> &[u32] {
+        &self.args
+    }
+
+    /// Pointer to the indirect arguments. Returns null if empty.
+    pub fn indirect_args_ptr(&self) -> *const u32 {
+        if self.args.is_empty() { std::ptr::null() } else { self.args.as_ptr() }
+    }
+
+    /// Incremented whenever batches, slots or arguments change, so callers
+    /// can skip re-uploading unchanged buffers.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (mesh, meta, tex) per slot, flattened into the SoA layout.
+    fn soa(slots: &[(u32, u32, u32)]) -> (Vec<u32>, Vec<u32>) {
+        let meta = slots.iter().flat_map(|&(mesh, meta, _)| [mesh, meta]).collect();
+        let tex = slots.iter().map(|&(_, _, tex)| tex).collect();
+        (meta, tex)
+    }
+
+    #[test]
+    fn keys_order_opaque_then_primitive_mesh_tier() {
+        let transparent = batch_key(RENDER_META_TRANSPARENT, 0, 0);
+        assert!(batch_key(5, 9, 3 << 16) < transparent);
+        assert!(batch_key(0, 9, 0) < batch_key(1, 0, 0));
+        assert!(batch_key(1, 0, 7 << 16) < batch_key(1, 1, 0));
+        assert!(batch_key(1, 1, 7 << 16) < batch_key(1, 1, 0x8000_0000));
+        // Render layers in the high meta bits don't split batches.
+        assert_eq!(batch_key(1 | (0b11 << 16), 0, 0), batch_key(1, 0, 0));
+        assert_eq!(texture_tier(0x8000_0000), 8);
+        assert_eq!(texture_tier((2 << 16) | 40), 2);
+    }
+
+    #[test]
+    fn batches_group_slots_and_emit_indirect_args() {
+        let mut b = DrawBatches::default();
+        b.set_mesh_index_count(3, 36);
+        let (meta, tex) = soa(&[
+            (0, RENDER_META_TRANSPARENT, 0),
+            (0, 0, 1 << 16),
+            (3, 0, 0),
+            (0, 0, 1 << 16),
+            (0, 0, 0),
+        ]);
+        assert!(b.update(&meta, &tex, 5));
+        let ranges: Vec<_> = b.batches().iter().map(|b| (b.mesh, b.tier, b.transparent, b.first, b.count)).collect();
+        assert_eq!(ranges, [(0, 0, 0, 0, 1), (0, 1, 0, 1, 2), (3, 0, 0, 3, 1), (0, 0, 1, 4, 1)]);
+        assert_eq!(b.slots(), [4, 1, 3, 2, 0]);
+        assert_eq!(b.indirect_args()[..10], [6, 1, 0, 0, 0, 6, 2, 0, 0, 1]);
+        assert_eq!(b.indirect_args()[10], 36);
+
+        let version = b.version();
+        assert!(!b.update(&meta, &tex, 5), "unchanged keys are a no-op");
+        assert_eq!(b.version(), version);
+    }
+
+    #[test]
+    fn updates_follow_key_changes_and_shrinking() {
+        let mut b = DrawBatches::default();
+        let (mut meta, tex) = soa(&[(0, 0, 0), (0, 0, 0), (0, 1, 0)]);
+        b.update(&meta, &tex, 3);
+        assert_eq!(b.batches().len(), 2);
+
+        meta[3] = 1; // slot 1 joins slot 2's primitive
+        assert!(b.update(&meta, &tex, 3));
+        assert_eq!(b.batches()[0].count, 1);
+        assert_eq!(b.batches()[1].count, 2);
+        assert_eq!(b.slots(), [0, 1, 2]);
+
+        assert!(b.update(&meta, &tex, 1));
+        assert_eq!(b.batches().len(), 1);
+        assert_eq!(b.slots(), [0]);
+        assert_eq!(b.indirect_args(), [6, 1, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,316 @@
+//! Engine-owned cameras.
+//!
+//! A `Camera` turns its entity into a view: the entity's position (and
+//! rotation) is the eye, and the component adds the projection, viewport,
+//! zoom, target following, bounds clamping and trauma-based shake. Following
+//! and shake advance once per fixed tick from seeded state, so replays and
+//! rollback reproduce camera motion exactly. After each update the engine
+//! exports every camera's view-projection matrix.
+//!
+//! A camera entity is an ordinary entity; hide it (`SetVisible`) if it
+//! should not draw. Positions are local, like `gpu_bounds`, so cameras and
+//! follow targets are expected to be root entities.
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Quat, Vec2, Vec3};
+use hecs::{With, World};
+
+use crate::command_processor::EntityMap;
+use crate::components::{Active, ExternalId, Position, Rotation, Transform2D};
+use crate::render_state::RenderState;
+use crate::rng::Rng;
+
+/// `Camera::follow` value for "no target".
+pub const CAMERA_NO_TARGET: u32 = u32::MAX;
+
+/// Bounds that never clamp (min > max).
+pub const CAMERA_UNBOUNDED: [f32; 4] = [0.0, 0.0, -1.0, -1.0];
+
+/// f32 values per exported camera: view-projection (16) + viewport (4).
+pub const CAMERA_EXPORT_STRIDE: usize = 20;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct Camera {
+    /// Vertical field of view in radians; 0 = orthographic (2D camera).
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+    /// Magnification: divides the orthographic extents or the field of view.
+    pub zoom: f32,
+    /// `[x, y, width, height]` in pixels. An orthographic camera shows one
+    /// world unit per pixel at zoom 1.
+    pub viewport: [f32; 4],
+    /// External ID of the followed entity, or `CAMERA_NO_TARGET`.
+    pub follow: u32,
+    /// Follow rate in 1/s (exponential approach); 0 = snap.
+    pub damping: f32,
+    /// Half-size of the box around the eye the target can move in freely.
+    pub deadzone: [f32; 2],
+    /// World-space `[min_x, min_y, max_x, max_y]`. Orthographic cameras keep
+    /// their whole view inside; perspective cameras keep their eye inside.
+    pub bounds: [f32; 4],
+    /// Shake intensity in `[0, 1]`; the shake scales with its square.
+    pub trauma: f32,
+    /// Trauma lost per second.
+    pub trauma_decay: f32,
+    /// Eye offset at full trauma, world units.
+    pub max_offset: f32,
+    /// Roll at full trauma, radians.
+    pub max_roll: f32,
+    /// Current shake `[x, y, roll]`, drawn once per tick.
+    pub shake: [f32; 3],
+    pub _pad: u32,
+    pub rng: Rng,
+}
+
+impl Camera {
+    /// A camera with a 1280×720 viewport, zoom 1 and no follow, bounds or shake.
+    pub fn new(fov_y: f32, near: f32, far: f32, seed: u32) -> Self {
+        Self {
+            fov_y,
+            near,
+            far,
+            zoom: 1.0,
+            viewport: [0.0, 0.0, 1280.0, 720.0],
+            follow: CAMERA_NO_TARGET,
+            damping: 0.0,
+            deadzone: [0.0; 2],
+            bounds: CAMERA_UNBOUNDED,
+            trauma: 0.0,
+            trauma_decay: 0.0,
+            max_offset: 0.0,
+            max_roll: 0.0,
+            shake: [0.0; 3],
+            _pad: 0,
+            rng: Rng::new(seed as u64),
+        }
+    }
+
+    /// Decode an `AttachCamera` payload: `[fov_y f32][near f32][far f32][seed u32]`.
+    pub fn from_payload(p: &[u8; 16]) -> Self {
+        let word = |i: usize| u32::from_le_bytes(p[i * 4..i * 4 + 4].try_into().unwrap());
+        Self::new(f32::from_bits(word(0)), f32::from_bits(word(1)), f32::from_bits(word(2)), word(3))
+    }
+
+    pub fn is_orthographic(&self) -> bool {
+        self.fov_y <= 0.0
+    }
+
+    /// Half the world-space size of an orthographic view.
+    pub fn half_extent(&self) -> Vec2 {
+        Vec2::new(self.viewport[2], self.viewport[3]) * (0.5 / self.zoom.max(f32::EPSILON))
+    }
+
+    pub fn projection(&self) -> Mat4 {
+        if self.is_orthographic() {
+            let half = self.half_extent();
+            Mat4::orthographic_rh(-half.x, half.x, -half.y, half.y, self.near, self.far)
+        } else {
+            let aspect = self.viewport[2] / self.viewport[3].max(f32::EPSILON);
+            Mat4::perspective_rh(self.fov_y / self.zoom.max(f32::EPSILON), aspect, self.near, self.far)
+        }
+    }
+
+    /// View-projection for an eye at `position` with `rotation`, shake included.
+    pub fn view_projection(&self, position: Vec3, rotation: Quat) -> Mat4 {
+        let rotation = rotation * Quat::from_rotation_z(self.shake[2]);
+        let eye = position + rotation * Vec3::new(self.shake[0], self.shake[1], 0.0);
+        self.projection() * Mat4::from_rotation_translation(rotation, eye).inverse()
+    }
+
+    /// Clamp an eye position to `bounds`. An orthographic view larger than
+    /// the bounds is centered on them.
+    fn clamp_to_bounds(&self, p: Vec2) -> Vec2 {
+        let [min_x, min_y, max_x, max_y] = self.bounds;
+        if min_x > max_x || min_y > max_y {
+            return p;
+        }
+        let half = if self.is_orthographic() { self.half_extent() } else { Vec2::ZERO };
+        let axis = |v: f32, min: f32, max: f32, half: f32| {
+            let (lo, hi) = (min + half, max - half);
+            if lo > hi { (min + max) * 0.5 } else { v.clamp(lo, hi) }
+        };
+        Vec2::new(axis(p.x, min_x, max_x, half.x), axis(p.y, min_y, max_y, half.y))
+    }
+
+    /// Advance trauma by `dt` and draw this tick's shake.
+    fn update_shake(&mut self, dt: f32) {
+        if self.trauma <= 0.0 {
+            self.shake = [0.0; 3];
+            return;
+        }
+        let s = self.trauma * self.trauma;
+        self.shake = [
+            self.max_offset * s * self.rng.range(-1.0, 1.0),
+            self.max_offset * s * self.rng.range(-1.0, 1.0),
+            self.max_roll * s * self.rng.range(-1.0, 1.0),
+        ];
+        self.trauma = (self.trauma - self.trauma_decay * dt).max(0.0);
+    }
+}
+
+/// Eye position and orientation of a 3D (`Position`/`Rotation`) or 2D
+/// (`Transform2D`) entity.
+fn pose(world: &World, entity: hecs::Entity) -> Option<(Vec3, Quat)> {
+    if let Ok(pos) = world.get::<&Position>(entity) {
+        let rot = world.get::<&Rotation>(entity).map_or(Quat::IDENTITY, |r| r.0);
+        return Some((pos.0, rot));
+    }
+    let t = world.get::<&Transform2D>(entity).ok()?;
+    Some((Vec3::new(t.x, t.y, 0.0), Quat::from_rotation_z(t.rot)))
+}
+
+fn set_xy(world: &World, entity: hecs::Entity, xy: Vec2) {
+    if let Ok(mut pos) = world.get::<&mut Position>(entity) {
+        pos.0.x = xy.x;
+        pos.0.y = xy.y;
+    } else if let Ok(mut t) = world.get::<&mut Transform2D>(entity) {
+        t.x = xy.x;
+        t.y = xy.y;
+    }
+}
+
+/// Move active cameras toward their targets, clamp them to their bounds and
+/// advance their shake. Cameras update in ascending external ID order, so a
+/// camera following another sees its position from the same tick.
+pub fn camera_system(world: &mut World, entity_map: &EntityMap, render_state: &mut RenderState, dt: f32) {
+    let mut cameras: Vec<(u32, hecs::Entity)> = world
+        .query::<With<(hecs::Entity, &ExternalId), (&Camera, &Active)>>()
+        .iter()
+        .map(|(entity, ext)| (ext.0, entity))
+        .collect();
+    cameras.sort_unstable_by_key(|&(id, _)| id);
+
+    for (_, entity) in cameras {
+        let Some((position, _)) = pose(world, entity) else { continue };
+        let mut camera = *world.get::<&Camera>(entity).unwrap();
+        let eye = position.truncate();
+        let mut next = eye;
+        if camera.follow != CAMERA_NO_TARGET
+            && let Some(target) = entity_map.get(camera.follow)
+            && let Some((target_pos, _)) = pose(world, target)
+        {
+            // Only the part of the offset outside the deadzone is chased.
+            let offset = target_pos.truncate() - eye;
+            let deadzone = Vec2::from(camera.deadzone).max(Vec2::ZERO);
+            let excess = offset.signum() * (offset.abs() - deadzone).max(Vec2::ZERO);
+            let t = if camera.damping > 0.0 { 1.0 - (-camera.damping * dt).exp() } else { 1.0 };
+            next += excess * t;
+        }
+        next = camera.clamp_to_bounds(next);
+        if next != eye {
+            set_xy(world, entity, next);
+            if let Some(slot) = render_state.get_slot(entity) {
+                render_state.dirty_tracker.mark_transform_dirty(slot as usize);
+                render_state.dirty_tracker.mark_bounds_dirty(slot as usize);
+            }
+        }
+        camera.update_shake(dt);
+        *world.get::<&mut Camera>(entity).unwrap() = camera;
+    }
+}
+
+/// Per-frame camera output: view-projection matrices and viewports of all
+/// active cameras, in ascending external ID order.
+#[derive(Debug, Default)]
+pub struct CameraExport {
+    ids: Vec<u32>,
+    /// `CAMERA_EXPORT_STRIDE` f32 per camera.
+    data: Vec<f32>,
+}
+
+impl CameraExport {
+    pub fn export(&mut self, world: &World) {
+        let mut cameras: Vec<(u32, hecs::Entity)> = world
+            .query::<With<(hecs::Entity, &ExternalId), (&Camera, &Active)>>()
+            .iter()
+            .map(|(entity, ext)| (ext.0, entity))
+            .collect();
+        cameras.sort_unstable_by_key(|&(id, _)| id);
+
+        self.ids.clear();
+        self.data.clear();
+        for (id, entity) in cameras {
+            let Some((position, rotation)) = pose(world, entity) else { continue };
+            let camera = world.get::<&Camera>(entity).unwrap();
+            self.ids.push(id);
+            self.data.extend_from_slice(&camera.view_projection(position, rotation).to_cols_array());
+            self.data.extend_from_slice(&camera.viewport);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+        self.data.clear();
+    }
+
+    pub fn count(&self) -> u32 {
+        self.ids.len() as u32
+    }
+
+    /// External IDs of the exported cameras.
+    pub fn ids(&self) -> &[u32] {
+        &self.ids
+    }
+
+    pub fn ids_ptr(&self) -> *const u32 {
+        if self.ids.is_empty() { std::ptr::null() } else { self.ids.as_ptr() }
+    }
+
+    /// View-projection (column-major) and viewport per camera.
+    pub fn data(&self) -> &[f32] {
+        &self.data
+    }
+
+    pub fn data_ptr(&self) -> *const f32 {
+        if self.data.is_empty() { std::ptr::null() } else { self.data.as_ptr() }
+    }
+
+    /// View-projection matrix of the camera with external ID `id`.
+    pub fn view_projection(&self, id: u32) -> Option<&[f32]> {
+        let i = self.ids.iter().position(|&c| c == id)?;
+        Some(&self.data[i * CAMERA_EXPORT_STRIDE..i * CAMERA_EXPORT_STRIDE + 16])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orthographic_view_covers_viewport_over_zoom() {
+        let mut camera = Camera::new(0.0, -1.0, 1000.0, 0);
+        camera.viewport = [0.0, 0.0, 200.0, 100.0];
+        camera.zoom = 2.0;
+        let vp = camera.view_projection(Vec3::new(10.0, 0.0, 0.0), Quat::IDENTITY);
+        // Half extents are 50 × 25 around the eye.
+        assert!(vp.project_point3(Vec3::new(60.0, 25.0, 0.0)).abs_diff_eq(Vec3::new(1.0, 1.0, 1.0 / 1001.0), 1e-5));
+        assert!(vp.project_point3(Vec3::new(-40.0, -25.0, 0.0)).truncate().abs_diff_eq(Vec2::NEG_ONE, 1e-5));
+    }
+
+    #[test]
+    fn bounds_keep_orthographic_view_inside() {
+        let mut camera = Camera::new(0.0, -1.0, 1.0, 0);
+        camera.viewport = [0.0, 0.0, 20.0, 200.0];
+        camera.bounds = [0.0, 0.0, 100.0, 50.0];
+        // x: clamped to [10, 90]; y: view taller than bounds, centered.
+        assert_eq!(camera.clamp_to_bounds(Vec2::new(-5.0, 7.0)), Vec2::new(10.0, 25.0));
+        assert_eq!(camera.clamp_to_bounds(Vec2::new(95.0, 7.0)), Vec2::new(90.0, 25.0));
+        camera.bounds = CAMERA_UNBOUNDED;
+        assert_eq!(camera.clamp_to_bounds(Vec2::new(-5.0, 7.0)), Vec2::new(-5.0, 7.0));
+    }
+
+    #[test]
+    fn shake_decays_and_replays_from_seed() {
+        let mut a = Camera::new(0.0, -1.0, 1.0, 7);
+        (a.trauma, a.trauma_decay, a.max_offset, a.max_roll) = (1.0, 2.0, 4.0, 0.1);
+        let mut b = a;
+        let shakes: Vec<[f32; 3]> = (0..40).map(|_| { a.update_shake(1.0 / 60.0); a.shake }).collect();
+        assert_eq!(shakes, (0..40).map(|_| { b.update_shake(1.0 / 60.0); b.shake }).collect::<Vec<_>>());
+        assert!(shakes[0][0].abs() <= 4.0 && shakes[0] != [0.0; 3]);
+        assert_eq!(a.trauma, 0.0);
+        a.update_shake(1.0 / 60.0);
+        assert_eq!(a.shake, [0.0; 3]);
+    }
+}
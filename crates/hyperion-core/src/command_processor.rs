@@ -2,9 +2,14 @@
 
 use hecs::World;
 
+use crate::camera::Camera;
 use crate::components::*;
+use crate::lifetime::Lifetime;
+use crate::particles::Emitter;
 use crate::render_state::RenderState;
 use crate::ring_buffer::{Command, CommandType};
+use crate::sprite_animation::SpriteAnimation;
+use crate::tween::{Tween, Tweens};
 
 /// Maps external entity IDs (from TypeScript) to internal hecs entities.
 pub struct EntityMap {
@@ -48,12 +53,18 @@ impl EntityMap {
     }
 
     /// Register a mapping from external ID to hecs entity.
+    ///
+    /// Reusing a freed ID (undo, rollback, client-side recycling) takes it off
+    /// the free list so `allocate()` cannot hand it out while it is live.
     pub fn insert(&mut self, external_id: u32, entity: hecs::Entity) {
         let idx = external_id as usize;
         if idx >= self.map.len() {
             self.map.resize(idx + 1, None);
             self.is_2d.resize(idx + 1, false);
         }
+        if self.map[idx].is_none() && external_id < self.next_id {
+            self.free_list.retain(|&id| id != external_id);
+        }
         self.map[idx] = Some(entity);
     }
 
@@ -252,7 +263,7 @@ fn flush_spawn_batch(
             )
         });
         let spawned: Vec<hecs::Entity> = world.spawn_batch(archetypes).collect();
-        for ((orig_idx, _), entity) in batch_3d.iter().zip(spawned.into_iter()) {
+        for ((orig_idx, _), entity) in batch_3d.iter().zip(spawned) {
             entities.push((*orig_idx, entity, false));
         }
     } else {
@@ -296,7 +307,7 @@ fn flush_spawn_batch(
             )
         });
         let spawned: Vec<hecs::Entity> = world.spawn_batch(archetypes).collect();
-        for ((orig_idx, _), entity) in batch_2d.iter().zip(spawned.into_iter()) {
+        for ((orig_idx, _), entity) in batch_2d.iter().zip(spawned) {
             entities.push((*orig_idx, entity, true));
         }
     } else {
@@ -336,6 +347,11 @@ fn flush_spawn_batch(
     }
 }
 
+/// Decode a payload of four little-endian f32s.
+fn read_f32x4(p: &[u8; 16]) -> [f32; 4] {
+    std::array::from_fn(|i| f32::from_le_bytes(p[i * 4..i * 4 + 4].try_into().unwrap()))
+}
+
 /// Process a single non-batch command against the ECS world.
 fn process_single_command(
     cmd: &Command,
@@ -399,22 +415,8 @@ fn process_single_command(
 
         CommandType::SetPosition => {
             if let Some(entity) = entity_map.get(cmd.entity_id) {
-                let x = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
-                let y = f32::from_le_bytes(cmd.payload[4..8].try_into().unwrap());
-                let z = f32::from_le_bytes(cmd.payload[8..12].try_into().unwrap());
-                if entity_map.is_entity_2d(cmd.entity_id) {
-                    if let Ok(mut t) = world.get::<&mut Transform2D>(entity) {
-                        t.x = x;
-                        t.y = y;
-                        // z ignored for 2D entities
-                    }
-                } else if let Ok(mut pos) = world.get::<&mut Position>(entity) {
-                    pos.0 = glam::Vec3::new(x, y, z);
-                }
-                if let Some(slot) = render_state.get_slot(entity) {
-                    render_state.dirty_tracker.mark_transform_dirty(slot as usize);
-                    render_state.dirty_tracker.mark_bounds_dirty(slot as usize);
-                }
+                write_position(cmd, entity, world, entity_map, render_state);
+                render_state.mark_teleport(entity);
             }
         }
 
@@ -436,6 +438,7 @@ fn process_single_command(
                 } else if let Ok(mut rot) = world.get::<&mut Rotation>(entity) {
                     rot.0 = glam::Quat::from_xyzw(x, y, z, w);
                 }
+                render_state.mark_teleport(entity);
                 if let Some(slot) = render_state.get_slot(entity) {
                     render_state.dirty_tracker.mark_transform_dirty(slot as usize);
                     render_state.dirty_tracker.mark_bounds_dirty(slot as usize);
@@ -457,6 +460,7 @@ fn process_single_command(
                 } else if let Ok(mut scale) = world.get::<&mut Scale>(entity) {
                     scale.0 = glam::Vec3::new(x, y, z);
                 }
+                render_state.mark_teleport(entity);
                 if let Some(slot) = render_state.get_slot(entity) {
                     render_state.dirty_tracker.mark_transform_dirty(slot as usize);
                     render_state.dirty_tracker.mark_bounds_dirty(slot as usize);
@@ -475,6 +479,217 @@ fn process_single_command(
             }
         }
 
+        // Motion components are optional: the first Set* inserts them.
+        // Physics-controlled entities ignore them (Rapier integrates those).
+        CommandType::SetAngularVelocity | CommandType::SetAcceleration => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let x = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                let y = f32::from_le_bytes(cmd.payload[4..8].try_into().unwrap());
+                let z = f32::from_le_bytes(cmd.payload[8..12].try_into().unwrap());
+                let v = glam::Vec3::new(x, y, z);
+                if cmd.cmd_type == CommandType::SetAngularVelocity {
+                    let _ = world.insert_one(entity, AngularVelocity(v));
+                } else {
+                    let _ = world.insert_one(entity, Acceleration(v));
+                }
+            }
+        }
+
+        CommandType::SetDrag => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let linear = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                let angular = f32::from_le_bytes(cmd.payload[4..8].try_into().unwrap());
+                let _ = world.insert(entity, (LinearDrag(linear), AngularDrag(angular)));
+            }
+        }
+
+        CommandType::StartTween => {
+            if let Some(entity) = entity_map.get(cmd.entity_id)
+                && let Some(tween) = Tween::from_payload(cmd.payload[..16].try_into().unwrap())
+            {
+                if let Ok(mut tweens) = world.get::<&mut Tweens>(entity) {
+                    tweens.push(tween);
+                } else {
+                    let mut tweens = Tweens::default();
+                    tweens.push(tween);
+                    let _ = world.insert_one(entity, tweens);
+                }
+            }
+        }
+
+        // Stopped tweens leave the channel at its current value.
+        CommandType::StopTweens => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let empty = world.get::<&mut Tweens>(entity).is_ok_and(|mut tweens| {
+                    tweens.stop(cmd.payload[0], cmd.payload[1]);
+                    tweens.is_empty()
+                });
+                if empty {
+                    let _ = world.remove_one::<Tweens>(entity);
+                }
+            }
+        }
+
+        // The clip ID is validated by the animation system, which owns the clips.
+        CommandType::PlaySpriteAnimation => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let _ = world.insert_one(entity, SpriteAnimation::from_payload(&cmd.payload));
+            }
+        }
+
+        CommandType::SetSpriteAnimationSpeed => {
+            if let Some(entity) = entity_map.get(cmd.entity_id)
+                && let Ok(mut anim) = world.get::<&mut SpriteAnimation>(entity)
+            {
+                anim.speed = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+            }
+        }
+
+        CommandType::StopSpriteAnimation => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let _ = world.remove_one::<SpriteAnimation>(entity);
+            }
+        }
+
+        // Expiry is handled by the engine after `lifetime_system`.
+        CommandType::SetLifetime => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let lifetime = Lifetime::from_payload(&cmd.payload);
+                if lifetime.ticks_left == 0 {
+                    let _ = world.remove_one::<Lifetime>(entity);
+                } else {
+                    let _ = world.insert_one(entity, lifetime);
+                }
+            }
+        }
+
+        // The config ID is validated by the particle system, which owns the configs.
+        CommandType::AttachEmitter => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let _ = world.insert_one(entity, Emitter::from_payload(&cmd.payload));
+            }
+        }
+
+        CommandType::EmitterBurst => {
+            if let Some(entity) = entity_map.get(cmd.entity_id)
+                && let Ok(mut emitter) = world.get::<&mut Emitter>(entity)
+            {
+                let count = u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                emitter.burst = emitter.burst.saturating_add(count);
+            }
+        }
+
+        CommandType::SetEmitterRate => {
+            if let Some(entity) = entity_map.get(cmd.entity_id)
+                && let Ok(mut emitter) = world.get::<&mut Emitter>(entity)
+            {
+                emitter.rate = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+            }
+        }
+
+        CommandType::DetachEmitter => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let _ = world.remove_one::<Emitter>(entity);
+            }
+        }
+
+        CommandType::SetPickLayer => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let mask = u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                let _ = world.insert_one(entity, PickLayer(mask));
+            }
+        }
+
+        CommandType::AttachCamera => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let _ = world.insert_one(entity, Camera::from_payload(&cmd.payload));
+            }
+        }
+
+        CommandType::SetCameraViewport => {
+            if let Some(entity) = entity_map.get(cmd.entity_id)
+                && let Ok(mut camera) = world.get::<&mut Camera>(entity)
+            {
+                camera.viewport = read_f32x4(&cmd.payload);
+            }
+        }
+
+        CommandType::SetCameraZoom => {
+            if let Some(entity) = entity_map.get(cmd.entity_id)
+                && let Ok(mut camera) = world.get::<&mut Camera>(entity)
+            {
+                camera.zoom = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+            }
+        }
+
+        CommandType::SetCameraFollow => {
+            if let Some(entity) = entity_map.get(cmd.entity_id)
+                && let Ok(mut camera) = world.get::<&mut Camera>(entity)
+            {
+                let [_, damping, dx, dy] = read_f32x4(&cmd.payload);
+                camera.follow = u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                camera.damping = damping;
+                camera.deadzone = [dx, dy];
+            }
+        }
+
+        CommandType::SetCameraBounds => {
+            if let Some(entity) = entity_map.get(cmd.entity_id)
+                && let Ok(mut camera) = world.get::<&mut Camera>(entity)
+            {
+                camera.bounds = read_f32x4(&cmd.payload);
+            }
+        }
+
+        // Trauma accumulates (capped at 1); the shake shape is replaced.
+        CommandType::AddCameraTrauma => {
+            if let Some(entity) = entity_map.get(cmd.entity_id)
+                && let Ok(mut camera) = world.get::<&mut Camera>(entity)
+            {
+                let [trauma, decay, max_offset, max_roll] = read_f32x4(&cmd.payload);
+                camera.trauma = (camera.trauma + trauma).clamp(0.0, 1.0);
+                camera.trauma_decay = decay;
+                camera.max_offset = max_offset;
+                camera.max_roll = max_roll;
+            }
+        }
+
+        CommandType::DetachCamera => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let _ = world.remove_one::<Camera>(entity);
+            }
+        }
+
+        CommandType::SetTint => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let rgba = u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                let _ = world.insert_one(entity, Tint(rgba));
+                if let Some(slot) = render_state.get_slot(entity) {
+                    render_state.dirty_tracker.mark_tint_dirty(slot as usize);
+                }
+            }
+        }
+
+        CommandType::SetSortOrder => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let order = i32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                let _ = world.insert_one(entity, SortOrder(order));
+                if let Some(slot) = render_state.get_slot(entity) {
+                    render_state.dirty_tracker.mark_meta_dirty(slot as usize);
+                }
+            }
+        }
+
+        CommandType::SetRenderLayer => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let mask = u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                let _ = world.insert_one(entity, RenderLayer(mask));
+                if let Some(slot) = render_state.get_slot(entity) {
+                    render_state.dirty_tracker.mark_meta_dirty(slot as usize);
+                }
+            }
+        }
+
         CommandType::SetTextureLayer => {
             if let Some(entity) = entity_map.get(cmd.entity_id) {
                 let packed = u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
@@ -568,6 +783,8 @@ fn process_single_command(
                     && let Some(parent_entity) = entity_map.get(new_parent_id)
                     && let Ok(mut children) =
                         world.get::<&mut Children>(parent_entity)
+                    // Already listed (e.g. stale entry left by a despawn being undone).
+                    && !children.as_slice().contains(&cmd.entity_id)
                     && !children.add(cmd.entity_id)
                 {
                     overflow_child = Some((parent_entity, cmd.entity_id));
@@ -632,6 +849,72 @@ fn process_single_command(
 
         CommandType::SetListenerPosition => {} // handled in Engine::process_commands
 
+        CommandType::HistoryMarker => {} // consumed by the history recorder in Engine
+
+        CommandType::ScheduleAt => {} // queued by Engine::process_commands; nested wrappers are dropped
+
+        // Engine-level simulation control, handled in Engine::process_commands
+        CommandType::SetTickRate
+        | CommandType::SetTimeScale
+        | CommandType::SetPaused
+        | CommandType::StepTicks => {}
+
+        // Generic component access through the registry, applied in order by Engine
+        CommandType::SetComponent | CommandType::RemoveComponent => {}
+
+        CommandType::SetActive => {
+            let active = cmd.payload[0] != 0;
+            for (_, entity) in hierarchy_targets(world, entity_map, cmd.entity_id, cmd.payload[1] != 0) {
+                if active {
+                    let _ = world.insert_one(entity, Active);
+                } else {
+                    let _ = world.remove_one::<Active>(entity);
+                }
+            }
+        }
+
+        CommandType::SetVisible => {
+            let visible = cmd.payload[0] != 0;
+            for (_, entity) in hierarchy_targets(world, entity_map, cmd.entity_id, cmd.payload[1] != 0) {
+                if visible {
+                    if world.remove_one::<Hidden>(entity).is_ok() {
+                        render_state.restore_slot(entity);
+                    }
+                } else if !world.satisfies::<&Hidden>(entity) && world.insert_one(entity, Hidden).is_ok() {
+                    render_state.release_slot(entity);
+                }
+            }
+        }
+
+        CommandType::AddTags | CommandType::RemoveTags => {
+            if let Some(entity) = entity_map.get(cmd.entity_id) {
+                let mask = u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                let old = world.get::<&Tags>(entity).map_or(0, |t| t.0);
+                let tags = if cmd.cmd_type == CommandType::AddTags { old | mask } else { old & !mask };
+                if tags == 0 {
+                    let _ = world.remove_one::<Tags>(entity);
+                } else {
+                    let _ = world.insert_one(entity, Tags(tags));
+                }
+            }
+        }
+
+        // Group commands: run the per-entity equivalent on every tagged entity.
+        CommandType::DespawnGroup | CommandType::SetGroupVisible | CommandType::SetGroupTextureLayer => {
+            for expanded in expand_group_command(cmd, world, entity_map) {
+                process_single_command(&expanded, world, entity_map, render_state);
+            }
+        }
+
+        // A relative move, not a teleport: interpolation stays on.
+        CommandType::TranslateGroup => {
+            for expanded in expand_group_command(cmd, world, entity_map) {
+                if let Some(entity) = entity_map.get(expanded.entity_id) {
+                    write_position(&expanded, entity, world, entity_map, render_state);
+                }
+            }
+        }
+
         CommandType::SetRotation2D => {
             if let Some(entity) = entity_map.get(cmd.entity_id) {
                 let angle = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
@@ -639,6 +922,7 @@ fn process_single_command(
                     if let Ok(mut t) = world.get::<&mut Transform2D>(entity) {
                         t.rot = angle;
                     }
+                    render_state.mark_teleport(entity);
                     if let Some(slot) = render_state.get_slot(entity) {
                         render_state.dirty_tracker.mark_transform_dirty(slot as usize);
                         render_state.dirty_tracker.mark_bounds_dirty(slot as usize);
@@ -686,6 +970,7 @@ fn process_single_command(
         | CommandType::SetCCDEnabled
         | CommandType::ApplyForce
         | CommandType::ApplyImpulse
+        | CommandType::ApplyGroupImpulse
         | CommandType::ApplyTorque
         | CommandType::SetColliderSensor
         | CommandType::SetColliderDensity
@@ -850,6 +1135,26 @@ fn process_single_command_physics(
             });
         }
 
+        // SetActive: toggle Active, then enable/disable the Rapier bodies.
+        CommandType::SetActive => {
+            process_single_command(cmd, world, entity_map, render_state);
+            let active = cmd.payload[0] != 0;
+            for (_, entity) in hierarchy_targets(world, entity_map, cmd.entity_id, cmd.payload[1] != 0) {
+                if let Ok(handle) = world.get::<&crate::physics::PhysicsBodyHandle>(entity)
+                    && let Some(body) = physics.rigid_body_set.get_mut(handle.0)
+                {
+                    body.set_enabled(active);
+                }
+            }
+        }
+
+        // Group commands: expand here so despawns take the physics path.
+        CommandType::DespawnGroup | CommandType::SetGroupVisible | CommandType::SetGroupTextureLayer => {
+            for expanded in expand_group_command(cmd, world, entity_map) {
+                process_single_command_physics(&expanded, world, entity_map, render_state, physics);
+            }
+        }
+
         // All other commands: delegate to the base (non-physics) handler
         _ => {
             process_single_command(cmd, world, entity_map, render_state);
@@ -857,6 +1162,118 @@ fn process_single_command_physics(
     }
 }
 
+/// Expand a group command into the per-entity commands it stands for:
+/// one per entity whose `Tags` intersect the mask in `payload[0..4]`, in
+/// ascending external ID order so replays stay deterministic.
+/// `TranslateGroup` expands to `SetPosition` at current position + delta,
+/// which the processors apply without the teleport flag.
+/// Returns an empty list for non-group commands.
+pub fn expand_group_command(cmd: &Command, world: &World, entity_map: &EntityMap) -> Vec<Command> {
+    let cmd_type = match cmd.cmd_type {
+        CommandType::DespawnGroup => CommandType::DespawnEntity,
+        CommandType::SetGroupVisible => CommandType::SetVisible,
+        CommandType::ApplyGroupImpulse => CommandType::ApplyImpulse,
+        CommandType::SetGroupTextureLayer => CommandType::SetTextureLayer,
+        CommandType::TranslateGroup => CommandType::SetPosition,
+        _ => return Vec::new(),
+    };
+    let mask = u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+    let args = &cmd.payload[4..];
+
+    let mut members: Vec<(u32, hecs::Entity)> = world
+        .query::<(hecs::Entity, &ExternalId, &Tags)>()
+        .iter()
+        .filter(|&(entity, ext, tags)| tags.0 & mask != 0 && entity_map.get(ext.0) == Some(entity))
+        .map(|(entity, ext, _)| (ext.0, entity))
+        .collect();
+    members.sort_unstable_by_key(|&(id, _)| id);
+
+    members
+        .into_iter()
+        .map(|(id, entity)| {
+            let mut payload = [0u8; 16];
+            match cmd_type {
+                // Visibility is per entity; tag children to include them.
+                CommandType::SetVisible => payload[0] = args[0],
+                CommandType::ApplyImpulse => payload[0..8].copy_from_slice(&args[0..8]),
+                CommandType::SetTextureLayer => payload[0..4].copy_from_slice(&args[0..4]),
+                CommandType::SetPosition => {
+                    let delta = |i: usize| f32::from_le_bytes(args[i * 4..i * 4 + 4].try_into().unwrap());
+                    let pos = if entity_map.is_entity_2d(id) {
+                        world.get::<&Transform2D>(entity).map_or([0.0; 3], |t| [t.x, t.y, 0.0])
+                    } else {
+                        world.get::<&Position>(entity).map_or([0.0; 3], |p| p.0.to_array())
+                    };
+                    for (i, v) in pos.into_iter().enumerate() {
+                        payload[i * 4..i * 4 + 4].copy_from_slice(&(v + delta(i)).to_le_bytes());
+                    }
+                }
+                _ => {}
+            }
+            Command { cmd_type, entity_id: id, payload }
+        })
+        .collect()
+}
+
+/// Write the position in a `SetPosition` payload to `entity` (z is ignored
+/// for 2D entities) and mark its slot dirty.
+fn write_position(
+    cmd: &Command,
+    entity: hecs::Entity,
+    world: &mut World,
+    entity_map: &EntityMap,
+    render_state: &mut RenderState,
+) {
+    let x = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+    let y = f32::from_le_bytes(cmd.payload[4..8].try_into().unwrap());
+    let z = f32::from_le_bytes(cmd.payload[8..12].try_into().unwrap());
+    if entity_map.is_entity_2d(cmd.entity_id) {
+        if let Ok(mut t) = world.get::<&mut Transform2D>(entity) {
+            t.x = x;
+            t.y = y;
+        }
+    } else if let Ok(mut pos) = world.get::<&mut Position>(entity) {
+        pos.0 = glam::Vec3::new(x, y, z);
+    }
+    if let Some(slot) = render_state.get_slot(entity) {
+        render_state.dirty_tracker.mark_transform_dirty(slot as usize);
+        render_state.dirty_tracker.mark_bounds_dirty(slot as usize);
+    }
+}
+
+/// `root` followed, if `cascade`, by all its descendants through `Children`
+/// and `OverflowChildren` (breadth-first, parents before children).
+pub fn hierarchy_targets(
+    world: &World,
+    entity_map: &EntityMap,
+    root: u32,
+    cascade: bool,
+) -> Vec<(u32, hecs::Entity)> {
+    let mut out: Vec<(u32, hecs::Entity)> = entity_map.get(root).map(|e| (root, e)).into_iter().collect();
+    let mut seen: std::collections::HashSet<u32> = out.iter().map(|&(id, _)| id).collect();
+    let mut next = 0;
+    while cascade && next < out.len() {
+        let entity = out[next].1;
+        next += 1;
+        let mut child_ids: Vec<u32> = Vec::new();
+        if let Ok(children) = world.get::<&Children>(entity) {
+            child_ids.extend_from_slice(children.as_slice());
+        }
+        if let Ok(overflow) = world.get::<&OverflowChildren>(entity) {
+            child_ids.extend_from_slice(&overflow.items);
+        }
+        for id in child_ids {
+            // Guard against malformed (cyclic) hierarchies.
+            if let Some(child) = entity_map.get(id)
+                && seen.insert(id)
+            {
+                out.push((id, child));
+            }
+        }
+    }
+    out
+}
+
 /// Clean up Rapier state for an entity being despawned or having its body destroyed.
 ///
 /// Clears reverse-map entries for all colliders attached to the body,
@@ -1015,6 +1432,18 @@ mod tests {
         assert_eq!(id3, 0); // recycled
     }
 
+    #[test]
+    fn reinserted_id_leaves_free_list() {
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let id = map.allocate();
+        map.insert(id, world.spawn(()));
+        map.remove(id);
+
+        map.insert(id, world.spawn(()));
+        assert_ne!(map.allocate(), id);
+    }
+
     #[test]
     fn set_texture_layer_updates_component() {
         let mut world = World::new();
@@ -0,0 +1,517 @@
+//! Component registry: stable IDs, sizes and byte layouts for generic
+//! component access.
+//!
+//! Core components keep the TLV IDs of `debug_get_components` (1–15),
+//! `Transform2D` is 16, and later core components follow from 17 (`Tags`,
+//! the motion components, then the pick, tint, render layer and sort order
+//! components). By convention plugin components use IDs
+//! 64–127, matching the plugin ranges of `MeshHandle` and `RenderPrimitive`.
+//! `state_hash` names components by these same IDs; 255 is reserved for its
+//! physics body hash and cannot be registered.
+//!
+//! Generic writes go through `ComponentRegistry::set` / `remove`, which mark
+//! the dirty bits the component feeds so the next frame re-uploads the slot.
+
+use bytemuck::Pod;
+use hecs::{Entity, World};
+
+use crate::components::*;
+use crate::render_state::RenderState;
+
+/// Stable component ID.
+pub type ComponentId = u8;
+
+pub const COMPONENT_POSITION: ComponentId = 1;
+pub const COMPONENT_VELOCITY: ComponentId = 2;
+pub const COMPONENT_ROTATION: ComponentId = 3;
+pub const COMPONENT_SCALE: ComponentId = 4;
+pub const COMPONENT_MODEL_MATRIX: ComponentId = 5;
+pub const COMPONENT_BOUNDING_RADIUS: ComponentId = 6;
+pub const COMPONENT_TEXTURE_LAYER: ComponentId = 7;
+pub const COMPONENT_MESH_HANDLE: ComponentId = 8;
+pub const COMPONENT_RENDER_PRIMITIVE: ComponentId = 9;
+pub const COMPONENT_PARENT: ComponentId = 10;
+pub const COMPONENT_ACTIVE: ComponentId = 11;
+pub const COMPONENT_EXTERNAL_ID: ComponentId = 12;
+pub const COMPONENT_PRIM_PARAMS: ComponentId = 13;
+pub const COMPONENT_LOCAL_MATRIX: ComponentId = 14;
+pub const COMPONENT_CHILDREN: ComponentId = 15;
+pub const COMPONENT_TRANSFORM_2D: ComponentId = 16;
+pub const COMPONENT_TAGS: ComponentId = 17;
+pub const COMPONENT_ANGULAR_VELOCITY: ComponentId = 18;
+pub const COMPONENT_ACCELERATION: ComponentId = 19;
+pub const COMPONENT_LINEAR_DRAG: ComponentId = 20;
+pub const COMPONENT_ANGULAR_DRAG: ComponentId = 21;
+pub const COMPONENT_PICK_LAYER: ComponentId = 22;
+pub const COMPONENT_TINT: ComponentId = 23;
+pub const COMPONENT_RENDER_LAYER: ComponentId = 24;
+pub const COMPONENT_SORT_ORDER: ComponentId = 25;
+/// Rapier body translation, rotation, linear and angular velocity in
+/// `state_hash` breakdowns. Not a registry component.
+pub const COMPONENT_PHYSICS_BODY: ComponentId = 255;
+
+/// Dirty bits marked on the entity's slot when a component is written or removed.
+pub const DIRTY_TRANSFORM: u8 = 1 << 0;
+pub const DIRTY_BOUNDS: u8 = 1 << 1;
+pub const DIRTY_META: u8 = 1 << 2;
+pub const DIRTY_TINT: u8 = 1 << 3;
+
+/// Scalar type of one field in a component's byte layout (little-endian).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    F32,
+    U32,
+    U8,
+    I32,
+}
+
+impl FieldType {
+    pub fn size(self) -> usize {
+        match self {
+            Self::F32 | Self::U32 | Self::I32 => 4,
+            Self::U8 => 1,
+        }
+    }
+}
+
+type GetFn = fn(&World, Entity, &mut Vec<u8>) -> bool;
+type SetFn = fn(&mut World, Entity, &[u8]) -> bool;
+type RemoveFn = fn(&mut World, Entity) -> bool;
+
+/// Registration of one component type.
+#[derive(Clone)]
+pub struct ComponentInfo {
+    pub id: ComponentId,
+    pub name: &'static str,
+    /// Field layout. Empty for markers; variable-size components repeat
+    /// their trailing layout (see `size`).
+    pub layout: &'static [FieldType],
+    /// Byte size, or `None` for variable-size components (`Children`).
+    pub size: Option<usize>,
+    /// `DIRTY_*` bits affected by writes.
+    pub dirty: u8,
+    get: GetFn,
+    /// `None` = read-only (derived or maintained by dedicated commands).
+    set: Option<SetFn>,
+    /// `None` = required by rendering and cannot be removed.
+    remove: Option<RemoveFn>,
+}
+
+impl ComponentInfo {
+    /// Registration for a `Pod` component, settable and removable.
+    pub fn pod<T: Pod + Send + Sync>(
+        id: ComponentId,
+        name: &'static str,
+        layout: &'static [FieldType],
+        dirty: u8,
+    ) -> Self {
+        debug_assert_eq!(layout.iter().map(|f| f.size()).sum::<usize>(), size_of::<T>());
+        Self {
+            id,
+            name,
+            layout,
+            size: Some(size_of::<T>()),
+            dirty,
+            get: get_pod::<T>,
+            set: Some(set_pod::<T>),
+            remove: Some(remove_one::<T>),
+        }
+    }
+
+    fn read_only(mut self) -> Self {
+        self.set = None;
+        self
+    }
+
+    fn required(mut self) -> Self {
+        self.remove = None;
+        self
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.set.is_some()
+    }
+
+    pub fn is_removable(&self) -> bool {
+        self.remove.is_some()
+    }
+}
+
+fn get_pod<T: Pod + Send + Sync>(world: &World, entity: Entity, out: &mut Vec<u8>) -> bool {
+    match world.get::<&T>(entity) {
+        Ok(v) => {
+            out.extend_from_slice(bytemuck::bytes_of(&*v));
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+fn set_pod<T: Pod + Send + Sync>(world: &mut World, entity: Entity, bytes: &[u8]) -> bool {
+    world.insert_one(entity, bytemuck::pod_read_unaligned::<T>(bytes)).is_ok()
+}
+
+fn remove_one<T: Send + Sync + 'static>(world: &mut World, entity: Entity) -> bool {
+    world.remove_one::<T>(entity).is_ok()
+}
+
+const F32X3: &[FieldType] = &[FieldType::F32; 3];
+const F32X4: &[FieldType] = &[FieldType::F32; 4];
+const F32X5: &[FieldType] = &[FieldType::F32; 5];
+const F32X8: &[FieldType] = &[FieldType::F32; 8];
+const F32X16: &[FieldType] = &[FieldType::F32; 16];
+
+/// Discriminants are the codes reported by `EVENT_COMPONENT_ERROR`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentError {
+    UnknownComponent = 1,
+    UnknownEntity = 2,
+    /// Registration: the ID is 0, reserved or already registered.
+    IdTaken = 3,
+    ReadOnly = 4,
+    NotRemovable = 5,
+    /// `bytes.len()` does not match the component size.
+    SizeMismatch = 6,
+    /// The entity does not have the component.
+    Missing = 7,
+    /// `SetComponent`: the component does not fit the 15 payload bytes of a
+    /// command. Use `Engine::set_component` instead.
+    TooLargeForCommand = 8,
+}
+
+/// Component ID → registration.
+pub struct ComponentRegistry {
+    /// Indexed by ID (`u8`), so lookups and ID-ordered iteration are trivial.
+    entries: Vec<Option<ComponentInfo>>,
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ComponentRegistry {
+    /// A registry with all core components.
+    pub fn new() -> Self {
+        use FieldType::{U8, U32};
+        const TB: u8 = DIRTY_TRANSFORM | DIRTY_BOUNDS;
+
+        let mut registry = Self { entries: vec![None; 256] };
+        let core = [
+            ComponentInfo::pod::<Position>(COMPONENT_POSITION, "Position", F32X3, TB).required(),
+            ComponentInfo::pod::<Velocity>(COMPONENT_VELOCITY, "Velocity", F32X3, 0),
+            ComponentInfo::pod::<Rotation>(COMPONENT_ROTATION, "Rotation", F32X4, TB).required(),
+            ComponentInfo::pod::<Scale>(COMPONENT_SCALE, "Scale", F32X3, TB).required(),
+            // Recomputed from the transform every frame.
+            ComponentInfo::pod::<ModelMatrix>(COMPONENT_MODEL_MATRIX, "ModelMatrix", F32X16, TB)
+                .read_only()
+                .required(),
+            ComponentInfo::pod::<BoundingRadius>(COMPONENT_BOUNDING_RADIUS, "BoundingRadius", &[FieldType::F32], DIRTY_BOUNDS)
+                .required(),
+            ComponentInfo::pod::<TextureLayerIndex>(COMPONENT_TEXTURE_LAYER, "TextureLayerIndex", &[U32], DIRTY_META)
+                .required(),
+            ComponentInfo::pod::<MeshHandle>(COMPONENT_MESH_HANDLE, "MeshHandle", &[U32], DIRTY_META).required(),
+            ComponentInfo::pod::<RenderPrimitive>(COMPONENT_RENDER_PRIMITIVE, "RenderPrimitive", &[U8], DIRTY_META)
+                .required(),
+            // Hierarchy links are maintained together by SetParent.
+            ComponentInfo {
+                id: COMPONENT_PARENT,
+                name: "Parent",
+                layout: &[U32],
+                size: Some(4),
+                dirty: DIRTY_TRANSFORM,
+                get: |world, entity, out| match world.get::<&Parent>(entity) {
+                    Ok(p) => {
+                        out.extend_from_slice(&p.0.to_le_bytes());
+                        true
+                    }
+                    Err(_) => false,
+                },
+                set: None,
+                remove: None,
+            },
+            ComponentInfo {
+                id: COMPONENT_ACTIVE,
+                name: "Active",
+                layout: &[],
+                size: Some(0),
+                dirty: 0,
+                get: |world, entity, _| world.get::<&Active>(entity).is_ok(),
+                set: None,
+                remove: None,
+            },
+            ComponentInfo::pod::<ExternalId>(COMPONENT_EXTERNAL_ID, "ExternalId", &[U32], 0)
+                .read_only()
+                .required(),
+            ComponentInfo::pod::<PrimitiveParams>(COMPONENT_PRIM_PARAMS, "PrimitiveParams", F32X8, DIRTY_META)
+                .required(),
+            ComponentInfo {
+                id: COMPONENT_LOCAL_MATRIX,
+                name: "LocalMatrix",
+                layout: F32X16,
+                size: Some(64),
+                dirty: DIRTY_TRANSFORM | DIRTY_BOUNDS,
+                get: |world, entity, out| match world.get::<&LocalMatrix>(entity) {
+                    Ok(m) => {
+                        out.extend_from_slice(bytemuck::cast_slice(&m.0));
+                        true
+                    }
+                    Err(_) => false,
+                },
+                set: Some(|world, entity, bytes| {
+                    let m: [f32; 16] = bytemuck::pod_read_unaligned(bytes);
+                    world.insert_one(entity, LocalMatrix(m)).is_ok()
+                }),
+                remove: Some(remove_one::<LocalMatrix>),
+            },
+            // count (u8) + child IDs (count × u32), like the debug TLV.
+            ComponentInfo {
+                id: COMPONENT_CHILDREN,
+                name: "Children",
+                layout: &[U8, U32],
+                size: None,
+                dirty: 0,
+                get: |world, entity, out| match world.get::<&Children>(entity) {
+                    Ok(c) => {
+                        out.push(c.count);
+                        for child in &c.slots[..c.count as usize] {
+                            out.extend_from_slice(&child.to_le_bytes());
+                        }
+                        true
+                    }
+                    Err(_) => false,
+                },
+                set: None,
+                remove: None,
+            },
+            ComponentInfo::pod::<Transform2D>(COMPONENT_TRANSFORM_2D, "Transform2D", F32X5, TB).required(),
+            ComponentInfo::pod::<Tags>(COMPONENT_TAGS, "Tags", &[U32], 0),
+            ComponentInfo::pod::<AngularVelocity>(COMPONENT_ANGULAR_VELOCITY, "AngularVelocity", F32X3, 0),
+            ComponentInfo::pod::<Acceleration>(COMPONENT_ACCELERATION, "Acceleration", F32X3, 0),
+            ComponentInfo::pod::<LinearDrag>(COMPONENT_LINEAR_DRAG, "LinearDrag", &[FieldType::F32], 0),
+            ComponentInfo::pod::<AngularDrag>(COMPONENT_ANGULAR_DRAG, "AngularDrag", &[FieldType::F32], 0),
+            ComponentInfo::pod::<PickLayer>(COMPONENT_PICK_LAYER, "PickLayer", &[U32], 0),
+            ComponentInfo::pod::<Tint>(COMPONENT_TINT, "Tint", &[U32], DIRTY_TINT),
+            ComponentInfo::pod::<RenderLayer>(COMPONENT_RENDER_LAYER, "RenderLayer", &[U32], DIRTY_META),
+            ComponentInfo::pod::<SortOrder>(COMPONENT_SORT_ORDER, "SortOrder", &[FieldType::I32], DIRTY_META),
+        ];
+        for info in core {
+            let id = info.id as usize;
+            registry.entries[id] = Some(info);
+        }
+        registry
+    }
+
+    /// Register an additional (e.g. plugin) component.
+    pub fn register(&mut self, info: ComponentInfo) -> Result<(), ComponentError> {
+        let slot = &mut self.entries[info.id as usize];
+        if info.id == 0 || info.id == COMPONENT_PHYSICS_BODY || slot.is_some() {
+            return Err(ComponentError::IdTaken);
+        }
+        *slot = Some(info);
+        Ok(())
+    }
+
+    pub fn info(&self, id: ComponentId) -> Option<&ComponentInfo> {
+        self.entries[id as usize].as_ref()
+    }
+
+    /// All registrations in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = &ComponentInfo> + '_ {
+        self.entries.iter().flatten()
+    }
+
+    /// Append the component's bytes to `out`. Returns `false` if the entity
+    /// lacks it or the ID is unknown.
+    pub fn get(&self, world: &World, entity: Entity, id: ComponentId, out: &mut Vec<u8>) -> bool {
+        self.info(id).is_some_and(|info| (info.get)(world, entity, out))
+    }
+
+    /// Insert or overwrite a component from its raw bytes and mark its dirty bits.
+    pub fn set(
+        &self,
+        world: &mut World,
+        render_state: &mut RenderState,
+        entity: Entity,
+        id: ComponentId,
+        bytes: &[u8],
+    ) -> Result<(), ComponentError> {
+        let info = self.info(id).ok_or(ComponentError::UnknownComponent)?;
+        let set = info.set.ok_or(ComponentError::ReadOnly)?;
+        if info.size != Some(bytes.len()) {
+            return Err(ComponentError::SizeMismatch);
+        }
+        if !set(world, entity, bytes) {
+            return Err(ComponentError::UnknownEntity);
+        }
+        if info.dirty & DIRTY_TRANSFORM != 0 {
+            // Written transforms are teleports, like SetPosition.
+            render_state.mark_teleport(entity);
+        }
+        mark_dirty(render_state, entity, info.dirty);
+        Ok(())
+    }
+
+    /// Remove a component and mark its dirty bits.
+    pub fn remove(
+        &self,
+        world: &mut World,
+        render_state: &mut RenderState,
+        entity: Entity,
+        id: ComponentId,
+    ) -> Result<(), ComponentError> {
+        let info = self.info(id).ok_or(ComponentError::UnknownComponent)?;
+        let remove = info.remove.ok_or(ComponentError::NotRemovable)?;
+        if !world.contains(entity) {
+            return Err(ComponentError::UnknownEntity);
+        }
+        if !remove(world, entity) {
+            return Err(ComponentError::Missing);
+        }
+        mark_dirty(render_state, entity, info.dirty);
+        Ok(())
+    }
+}
+
+fn mark_dirty(render_state: &mut RenderState, entity: Entity, dirty: u8) {
+    let Some(slot) = render_state.get_slot(entity) else {
+        return;
+    };
+    let slot = slot as usize;
+    if dirty & DIRTY_TRANSFORM != 0 {
+        render_state.dirty_tracker.mark_transform_dirty(slot);
+    }
+    if dirty & DIRTY_BOUNDS != 0 {
+        render_state.dirty_tracker.mark_bounds_dirty(slot);
+    }
+    if dirty & DIRTY_META != 0 {
+        render_state.dirty_tracker.mark_meta_dirty(slot);
+    }
+    if dirty & DIRTY_TINT != 0 {
+        render_state.dirty_tracker.mark_tint_dirty(slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_with_slot(world: &mut World, rs: &mut RenderState) -> (Entity, usize) {
+        let entity = world.spawn((Position::default(), Velocity::default(), TextureLayerIndex(0), Active));
+        let slot = rs.assign_slot(entity) as usize;
+        rs.dirty_tracker.clear();
+        (entity, slot)
+    }
+
+    #[test]
+    fn core_sizes_match_layouts() {
+        let registry = ComponentRegistry::new();
+        for info in registry.iter() {
+            if let Some(size) = info.size {
+                let layout: usize = info.layout.iter().map(|f| f.size()).sum();
+                assert_eq!(layout, size, "{}", info.name);
+            }
+        }
+        assert_eq!(registry.iter().map(|i| i.id).collect::<Vec<_>>(), (1..=25).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn set_writes_bytes_and_marks_dirty_bits() {
+        let registry = ComponentRegistry::new();
+        let mut world = World::new();
+        let mut rs = RenderState::new();
+        let (entity, slot) = spawn_with_slot(&mut world, &mut rs);
+
+        let bytes: Vec<u8> = [1.0f32, 2.0, 3.0].iter().flat_map(|f| f.to_le_bytes()).collect();
+        registry.set(&mut world, &mut rs, entity, COMPONENT_POSITION, &bytes).unwrap();
+        assert_eq!(world.get::<&Position>(entity).unwrap().0, glam::Vec3::new(1.0, 2.0, 3.0));
+        assert!(rs.dirty_tracker.is_transform_dirty(slot));
+        assert!(!rs.dirty_tracker.is_meta_dirty(slot));
+
+        registry.set(&mut world, &mut rs, entity, COMPONENT_TEXTURE_LAYER, &7u32.to_le_bytes()).unwrap();
+        assert!(rs.dirty_tracker.is_meta_dirty(slot));
+
+        let mut out = Vec::new();
+        assert!(registry.get(&world, entity, COMPONENT_POSITION, &mut out));
+        assert_eq!(out, bytes);
+    }
+
+    #[test]
+    fn render_components_mark_their_dirty_bits() {
+        let registry = ComponentRegistry::new();
+        let mut world = World::new();
+        let mut rs = RenderState::new();
+        let (entity, slot) = spawn_with_slot(&mut world, &mut rs);
+
+        registry.set(&mut world, &mut rs, entity, COMPONENT_TINT, &0x8000_00FFu32.to_le_bytes()).unwrap();
+        assert_eq!(*world.get::<&Tint>(entity).unwrap(), Tint(0x8000_00FF));
+        assert!(rs.dirty_tracker.is_tint_dirty(slot) && !rs.dirty_tracker.is_meta_dirty(slot));
+
+        registry.set(&mut world, &mut rs, entity, COMPONENT_SORT_ORDER, &(-2i32).to_le_bytes()).unwrap();
+        registry.set(&mut world, &mut rs, entity, COMPONENT_RENDER_LAYER, &0b10u32.to_le_bytes()).unwrap();
+        registry.set(&mut world, &mut rs, entity, COMPONENT_PICK_LAYER, &4u32.to_le_bytes()).unwrap();
+        assert!(rs.dirty_tracker.is_meta_dirty(slot));
+        let mut out = Vec::new();
+        assert!(registry.get(&world, entity, COMPONENT_SORT_ORDER, &mut out));
+        assert_eq!(out, (-2i32).to_le_bytes());
+        assert_eq!(*world.get::<&PickLayer>(entity).unwrap(), PickLayer(4));
+
+        rs.dirty_tracker.clear();
+        registry.remove(&mut world, &mut rs, entity, COMPONENT_TINT).unwrap();
+        assert!(world.get::<&Tint>(entity).is_err());
+        assert!(rs.dirty_tracker.is_tint_dirty(slot));
+    }
+
+    #[test]
+    fn set_and_remove_enforce_registration() {
+        let registry = ComponentRegistry::new();
+        let mut world = World::new();
+        let mut rs = RenderState::new();
+        let (entity, _) = spawn_with_slot(&mut world, &mut rs);
+
+        assert_eq!(
+            registry.set(&mut world, &mut rs, entity, COMPONENT_POSITION, &[0; 4]),
+            Err(ComponentError::SizeMismatch)
+        );
+        assert_eq!(
+            registry.set(&mut world, &mut rs, entity, COMPONENT_EXTERNAL_ID, &[0; 4]),
+            Err(ComponentError::ReadOnly)
+        );
+        assert_eq!(registry.set(&mut world, &mut rs, entity, 99, &[]), Err(ComponentError::UnknownComponent));
+        assert_eq!(
+            registry.remove(&mut world, &mut rs, entity, COMPONENT_POSITION),
+            Err(ComponentError::NotRemovable)
+        );
+        registry.remove(&mut world, &mut rs, entity, COMPONENT_VELOCITY).unwrap();
+        assert_eq!(
+            registry.remove(&mut world, &mut rs, entity, COMPONENT_VELOCITY),
+            Err(ComponentError::Missing)
+        );
+    }
+
+    #[test]
+    fn plugin_components_round_trip() {
+        #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+        #[repr(C)]
+        struct Health(f32, u32);
+
+        let mut registry = ComponentRegistry::new();
+        let info = ComponentInfo::pod::<Health>(64, "Health", &[FieldType::F32, FieldType::U32], 0);
+        registry.register(info.clone()).unwrap();
+        assert_eq!(registry.register(info).err(), Some(ComponentError::IdTaken));
+
+        let mut world = World::new();
+        let mut rs = RenderState::new();
+        let (entity, _) = spawn_with_slot(&mut world, &mut rs);
+        let bytes = bytemuck::bytes_of(&Health(0.5, 3)).to_vec();
+        registry.set(&mut world, &mut rs, entity, 64, &bytes).unwrap();
+        assert_eq!(world.get::<&Health>(entity).unwrap().1, 3);
+
+        let mut out = Vec::new();
+        assert!(registry.get(&world, entity, 64, &mut out));
+        assert_eq!(out, bytes);
+        registry.remove(&mut world, &mut rs, entity, 64).unwrap();
+        assert!(!registry.get(&world, entity, 64, &mut Vec::new()));
+    }
+}
@@ -26,6 +26,30 @@ pub struct Scale(pub Vec3);
 #[repr(C)]
 pub struct Velocity(pub Vec3);
 
+/// Angular velocity (radians per second) as a scaled rotation axis.
+/// 2D entities only use `z` (counter-clockwise spin in the XY plane).
+/// Optional; inserted by `SetAngularVelocity`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct AngularVelocity(pub Vec3);
+
+/// Linear acceleration (units per second²), added to `Velocity` every tick.
+/// Optional; inserted by `SetAcceleration`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct Acceleration(pub Vec3);
+
+/// Linear drag coefficient (1/s): each tick `v /= 1 + drag * dt`,
+/// the same damping model as Rapier. Optional; inserted by `SetDrag`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct LinearDrag(pub f32);
+
+/// Angular drag coefficient (1/s), applied to `AngularVelocity` like `LinearDrag`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct AngularDrag(pub f32);
+
 /// Computed 4x4 model matrix, updated by the transform system.
 /// This is what gets uploaded to the GPU.
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
@@ -209,6 +233,73 @@ pub struct Transparent(pub u8);
 #[derive(Debug, Clone, Copy)]
 pub struct Active;
 
+/// Marker: entity is hidden. It keeps simulating but holds no GPU slot.
+#[derive(Debug, Clone, Copy)]
+pub struct Hidden;
+
+/// Group membership bitmask: bit N set = entity carries tag N. 4 bytes.
+/// Only present on entities with at least one tag; group commands match
+/// entities whose tags intersect the command's mask.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Tags(pub u32);
+
+/// Pick layer bitmask: picking only reports entities whose layers intersect
+/// the query mask. 4 bytes. Entities without it are on `PICK_LAYER_DEFAULT`;
+/// 0 makes an entity unpickable.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct PickLayer(pub u32);
+
+/// Pick layer of entities without a `PickLayer` component (layer 0).
+pub const PICK_LAYER_DEFAULT: u32 = 1;
+
+impl Default for PickLayer {
+    fn default() -> Self {
+        Self(PICK_LAYER_DEFAULT)
+    }
+}
+
+/// Render layer bitmask (layers 0..15), encoded into `gpu_render_meta` so
+/// views and passes can draw only their own layers. 4 bytes. Entities
+/// without it are on `RENDER_LAYER_DEFAULT`; bits above 15 are ignored.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct RenderLayer(pub u32);
+
+/// Number of render layers.
+pub const RENDER_LAYER_COUNT: usize = 16;
+
+/// Render layers of entities without a `RenderLayer` component (layer 0).
+pub const RENDER_LAYER_DEFAULT: u32 = 1;
+
+impl Default for RenderLayer {
+    fn default() -> Self {
+        Self(RENDER_LAYER_DEFAULT)
+    }
+}
+
+/// Color multiplier, packed RGBA8 with R in the low byte. 4 bytes.
+/// Entities without it are opaque white (`TINT_WHITE`); alpha fades them.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct Tint(pub u32);
+
+/// Tint of entities without a `Tint` component.
+pub const TINT_WHITE: u32 = 0xFFFF_FFFF;
+
+impl Default for Tint {
+    fn default() -> Self {
+        Self(TINT_WHITE)
+    }
+}
+
+/// Tie-break for transparent sorting among entities at equal depth: lower
+/// values draw first (underneath). 4 bytes. Entities without it sort as 0.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct SortOrder(pub i32);
+
 impl Default for Position {
     fn default() -> Self {
         Self(Vec3::ZERO)
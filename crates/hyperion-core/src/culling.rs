@@ -0,0 +1,223 @@
+//! CPU frustum culling for devices without compute shaders.
+//!
+//! Mirrors `cull.wgsl` and `extractFrustumPlanes` in `camera.ts`: planes
+//! come from a column-major view-projection matrix (Gribb-Hartmann, WebGPU
+//! `[0, 1]` depth), and a bounding sphere from `gpu_bounds` is visible
+//! unless it lies entirely behind one of them. Spheres are tested
+//! [`LANES`] at a time with the planes in SoA form, so the inner loop is
+//! branch-free and auto-vectorizes.
+
+/// Maximum number of cull views (cameras) per render state.
+pub const MAX_CULL_VIEWS: usize = 8;
+
+/// Spheres tested per batch.
+const LANES: usize = 8;
+
+/// Six normalized planes in SoA form, ordered left, right, bottom, top,
+/// near, far. A point is inside a plane when `n·p + d >= 0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    nx: [f32; 6],
+    ny: [f32; 6],
+    nz: [f32; 6],
+    d: [f32; 6],
+}
+
+impl Frustum {
+    /// Extract the planes of a column-major view-projection matrix.
+    pub fn from_view_projection(m: &[f32; 16]) -> Self {
+        let row = |i: usize| [m[i], m[4 + i], m[8 + i], m[12 + i]];
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+        let add = |a: [f32; 4], b: [f32; 4]| [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]];
+        let sub = |a: [f32; 4], b: [f32; 4]| [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]];
+        let planes = [add(r3, r0), sub(r3, r0), add(r3, r1), sub(r3, r1), r2, sub(r3, r2)];
+
+        let mut frustum = Frustum { nx: [0.0; 6], ny: [0.0; 6], nz: [0.0; 6], d: [0.0; 6] };
+        for (i, p) in planes.into_iter().enumerate() {
+            let len = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+            let inv = if len > 0.0 { 1.0 / len } else { 1.0 };
+            frustum.nx[i] = p[0] * inv;
+            frustum.ny[i] = p[1] * inv;
+            frustum.nz[i] = p[2] * inv;
+            frustum.d[i] = p[3] * inv;
+        }
+        frustum
+    }
+
+    /// Whether any part of the sphere at `(x, y, z)` is inside.
+    pub fn intersects_sphere(&self, x: f32, y: f32, z: f32, radius: f32) -> bool {
+        (0..6).all(|i| self.nx[i] * x + self.ny[i] * y + self.nz[i] * z + self.d[i] >= -radius)
+    }
+
+    /// Append the slot index of every visible sphere in `bounds` (4 f32 per
+    /// slot: xyz + radius) to `out`.
+    pub fn cull(&self, bounds: &[f32], out: &mut Vec<u32>) {
+        let mut chunks = bounds.chunks_exact(4 * LANES);
+        let mut base = 0u32;
+        for chunk in &mut chunks {
+            let (mut x, mut y, mut z, mut r) = ([0.0f32; LANES], [0.0f32; LANES], [0.0f32; LANES], [0.0f32; LANES]);
+            for l in 0..LANES {
+                x[l] = chunk[l * 4];
+                y[l] = chunk[l * 4 + 1];
+                z[l] = chunk[l * 4 + 2];
+                r[l] = chunk[l * 4 + 3];
+            }
+            let mut inside = [true; LANES];
+            for i in 0..6 {
+                for l in 0..LANES {
+                    let dist = self.nx[i] * x[l] + self.ny[i] * y[l] + self.nz[i] * z[l] + self.d[i];
+                    inside[l] &= dist >= -r[l];
+                }
+            }
+            for (l, &visible) in inside.iter().enumerate() {
+                if visible {
+                    out.push(base + l as u32);
+                }
+            }
+            base += LANES as u32;
+        }
+        for (l, s) in chunks.remainder().chunks_exact(4).enumerate() {
+            if self.intersects_sphere(s[0], s[1], s[2], s[3]) {
+                out.push(base + l as u32);
+            }
+        }
+    }
+}
+
+/// Per-view frusta, render layer masks and visible-slot lists.
+#[derive(Debug, Default)]
+pub struct CullViews {
+    frusta: Vec<Option<Frustum>>,
+    /// Render layers each view draws (`RenderLayer` bits); all by default.
+    layers: Vec<u32>,
+    visible: Vec<Vec<u32>>,
+}
+
+impl CullViews {
+    /// Set the view-projection matrix of `view`. Returns `false` if `view`
+    /// is out of range.
+    pub fn set_view(&mut self, view: usize, view_projection: &[f32; 16]) -> bool {
+        if view >= MAX_CULL_VIEWS {
+            return false;
+        }
+        self.grow(view);
+        self.frusta[view] = Some(Frustum::from_view_projection(view_projection));
+        true
+    }
+
+    /// Restrict `view` to slots on a render layer in `mask`. Returns `false`
+    /// if `view` is out of range.
+    pub fn set_layers(&mut self, view: usize, mask: u32) -> bool {
+        if view >= MAX_CULL_VIEWS {
+            return false;
+        }
+        self.grow(view);
+        self.layers[view] = mask;
+        true
+    }
+
+    fn grow(&mut self, view: usize) {
+        if view >= self.frusta.len() {
+            self.frusta.resize(view + 1, None);
+            self.layers.resize(view + 1, u32::MAX);
+            self.visible.resize_with(view + 1, Vec::new);
+        }
+    }
+
+    /// Remove every view and its visible list.
+    pub fn clear(&mut self) {
+        self.frusta.clear();
+        self.layers.clear();
+        self.visible.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frusta.is_empty()
+    }
+
+    /// Recompute the visible list of every view against `bounds`, keeping
+    /// only slots whose layers (from `layers_of`) intersect the view's mask.
+    pub fn cull(&mut self, bounds: &[f32], layers_of: impl Fn(u32) -> u32) {
+        for ((frustum, &mask), visible) in self.frusta.iter().zip(&self.layers).zip(&mut self.visible) {
+            visible.clear();
+            if let Some(frustum) = frustum {
+                frustum.cull(bounds, visible);
+                if mask != u32::MAX {
+                    visible.retain(|&slot| layers_of(slot) & mask != 0);
+                }
+            }
+        }
+    }
+
+    /// Visible slot indices of `view` from the last cull, ascending. Empty
+    /// for unset views.
+    pub fn visible(&self, view: usize) -> &[u32] {
+        self.visible.get(view).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Mat4;
+
+    /// Ortho camera over x, y in [-10, 10], looking down -z, depth 0.1..100.
+    fn ortho() -> [f32; 16] {
+        Mat4::orthographic_rh(-10.0, 10.0, -10.0, 10.0, 0.1, 100.0).to_cols_array()
+    }
+
+    #[test]
+    fn ortho_planes_bound_the_view_box() {
+        let f = Frustum::from_view_projection(&ortho());
+        assert!(f.intersects_sphere(0.0, 0.0, -50.0, 0.0));
+        assert!(f.intersects_sphere(9.9, -9.9, -1.0, 0.0));
+        assert!(!f.intersects_sphere(10.5, 0.0, -1.0, 0.0));
+        assert!(f.intersects_sphere(10.5, 0.0, -1.0, 1.0));
+        assert!(!f.intersects_sphere(0.0, 0.0, 1.0, 0.5)); // behind near
+        assert!(!f.intersects_sphere(0.0, -12.0, -150.0, 1.0)); // past far
+    }
+
+    #[test]
+    fn batched_cull_matches_scalar_test() {
+        let f = Frustum::from_view_projection(&ortho());
+        // 21 slots: two full batches plus a remainder.
+        let bounds: Vec<f32> = (0..21)
+            .flat_map(|i| {
+                let x = i as f32 * 1.5 - 15.0;
+                [x, 0.0, -5.0, if i % 3 == 0 { 2.0 } else { 0.25 }]
+            })
+            .collect();
+        let mut visible = Vec::new();
+        f.cull(&bounds, &mut visible);
+        let expected: Vec<u32> = (0..21u32)
+            .filter(|&i| {
+                let s = &bounds[i as usize * 4..i as usize * 4 + 4];
+                f.intersects_sphere(s[0], s[1], s[2], s[3])
+            })
+            .collect();
+        assert_eq!(visible, expected);
+        assert_eq!(visible.first(), Some(&3)); // x = -10.5, radius 2
+        assert_eq!(visible.last(), Some(&18)); // x = 12.0, radius 2
+    }
+
+    #[test]
+    fn views_are_independent_and_bounded() {
+        let mut views = CullViews::default();
+        let shifted = (Mat4::from_cols_array(&ortho()) * Mat4::from_translation(glam::Vec3::new(-100.0, 0.0, 0.0)))
+            .to_cols_array();
+        assert!(views.set_view(0, &ortho()));
+        assert!(views.set_view(2, &shifted));
+        assert!(!views.set_view(MAX_CULL_VIEWS, &ortho()));
+        let bounds = [0.0, 0.0, -5.0, 1.0, 100.0, 0.0, -5.0, 1.0, 1.0, 0.0, -5.0, 1.0];
+        views.cull(&bounds, |slot| 1 << slot);
+        assert_eq!(views.visible(0), [0, 2]);
+        assert!(views.visible(1).is_empty());
+        assert_eq!(views.visible(2), [1]);
+        assert!(views.set_layers(0, 0b100));
+        views.cull(&bounds, |slot| 1 << slot);
+        assert_eq!(views.visible(0), [2]);
+        views.clear();
+        assert!(views.is_empty());
+        assert!(views.visible(0).is_empty());
+    }
+}
@@ -3,21 +3,43 @@
 
 use hecs::World;
 
-use crate::components::{Active, Parent, Velocity};
+use crate::camera::{CameraExport, camera_system};
+use crate::component_registry::{ComponentError, ComponentId, ComponentRegistry};
+use crate::components::{Acceleration, Active, AngularVelocity, ExternalId, Parent, Tags, Velocity};
+use crate::events::{EVENT_COMPONENT_ERROR, EVENT_LIFETIME_EXPIRED, EngineEvent};
+use crate::history::CommandHistory;
+use crate::lifetime::lifetime_system;
+use crate::particles::{EmitterError, ParticleSystem};
+use crate::picking::Picker;
+use crate::plugin::{HyperionPlugin, PluginContext, PluginError, PluginSet};
 use crate::render_state::RenderState;
-use crate::ring_buffer::{Command, CommandType};
-use crate::systems::{propagate_transforms, transform_system, transform_system_2d};
+use crate::rollback::RollbackBuffer;
+use crate::schedule::CommandSchedule;
+use crate::spatial::SpatialIndex;
+use crate::sprite_animation::{SpriteClipError, SpriteClips, sprite_animation_system};
+use crate::state_hash::{ComponentHash, StateHasher};
+use crate::ring_buffer::{Command, CommandType, ParsedCommand, PluginCommand, parse_command_stream};
+use crate::systems::{
+    BuiltinSystem, Scheduler, Stage, SystemContext, SystemKind, is_moving, now_ms, propagate_transforms,
+    transform_system, transform_system_2d,
+};
+use crate::tween::tween_system;
+#[cfg(feature = "dev-tools")]
+use crate::tween::Tweens;
 
 #[cfg(not(feature = "physics-2d"))]
 use crate::command_processor::process_commands;
 #[cfg(not(feature = "physics-2d"))]
 use crate::systems::{velocity_system, velocity_system_2d};
 
-use crate::command_processor::EntityMap;
+use crate::command_processor::{EntityMap, hierarchy_targets};
 
-/// Fixed timestep: 60 ticks per second.
+/// Default fixed timestep: 60 ticks per second.
 pub const FIXED_DT: f32 = 1.0 / 60.0;
 
+/// Default cap on fixed ticks per `update()` call (spiral-of-death guard).
+pub const DEFAULT_MAX_TICKS_PER_FRAME: u32 = 10;
+
 /// The core engine state.
 pub struct Engine {
     pub world: World,
@@ -27,9 +49,48 @@ pub struct Engine {
     pub physics: crate::physics::PhysicsWorld,
     accumulator: f32,
     tick_count: u64,
+    /// Seconds per fixed tick. Defaults to `FIXED_DT`.
+    fixed_dt: f32,
+    /// Simulation seconds per real second (slow motion < 1.0).
+    time_scale: f32,
+    /// When paused, `update()` runs no ticks but still refreshes render state.
+    paused: bool,
+    /// Ticks requested via `step()`, run on the next `update()` even when paused.
+    pending_steps: u32,
+    max_ticks_per_frame: u32,
+    /// Capture previous-tick world matrices for render interpolation.
+    interpolation: bool,
     listener_pos: [f32; 3],
     listener_prev_pos: [f32; 3],
     listener_vel: [f32; 3],
+    /// Undo/redo recorder. `None` = history disabled (default).
+    history: Option<CommandHistory>,
+    /// Per-tick state ring for rollback. `None` = rollback disabled (default).
+    rollback: Option<RollbackBuffer>,
+    /// Per-tick state hash recorder. `None` = hashing disabled (default).
+    state_hasher: Option<StateHasher>,
+    /// Built-in and custom systems, by stage.
+    scheduler: Scheduler,
+    /// Rust plugins, in execution order.
+    plugins: PluginSet,
+    /// Component IDs and layouts for generic component access.
+    components: ComponentRegistry,
+    /// Events raised by systems this frame (tweens, ...). Cleared by `update()`.
+    events: Vec<EngineEvent>,
+    /// Events raised while applying commands, reported with the next `update()`.
+    command_events: Vec<EngineEvent>,
+    /// Registered sprite animation clips.
+    sprite_clips: SpriteClips,
+    /// Commands deferred by `ScheduleAt`, released at the start of their tick.
+    schedule: CommandSchedule,
+    /// Emitter configs, live particles and their GPU buffers.
+    particles: ParticleSystem,
+    /// Grid over entity bounds for spatial queries. `None` = disabled (default).
+    spatial: Option<SpatialIndex>,
+    /// Point/rect picking and its result buffer.
+    picker: Picker,
+    /// View-projection matrices of the active cameras, refreshed every update.
+    cameras: CameraExport,
 }
 
 impl Default for Engine {
@@ -48,35 +109,163 @@ impl Engine {
             physics: crate::physics::PhysicsWorld::new(),
             accumulator: 0.0,
             tick_count: 0,
+            fixed_dt: FIXED_DT,
+            time_scale: 1.0,
+            paused: false,
+            pending_steps: 0,
+            max_ticks_per_frame: DEFAULT_MAX_TICKS_PER_FRAME,
+            interpolation: false,
             listener_pos: [0.0; 3],
             listener_prev_pos: [0.0; 3],
             listener_vel: [0.0; 3],
+            history: None,
+            rollback: None,
+            state_hasher: None,
+            scheduler: Scheduler::with_builtins(),
+            plugins: PluginSet::new(),
+            components: ComponentRegistry::new(),
+            events: Vec::new(),
+            command_events: Vec::new(),
+            sprite_clips: SpriteClips::new(),
+            schedule: CommandSchedule::new(),
+            particles: ParticleSystem::new(),
+            spatial: None,
+            picker: Picker::new(),
+            cameras: CameraExport::default(),
         }
     }
 
     /// Apply a batch of commands to the ECS world.
     /// Called before `update()` each frame.
+    ///
+    /// When history is enabled, commands are applied one at a time and each
+    /// inverse is captured just before its command runs; the batch forms one
+    /// undo group. Commands wrapped by `ScheduleAt` are queued instead and are
+    /// never recorded.
     pub fn process_commands(&mut self, commands: &[Command]) {
-        // Handle listener position (engine-level state, not entity-specific)
+        self.process_batch(commands);
+        if let Some(history) = self.history.as_mut() {
+            history.end_batch();
+        }
+    }
+
+    /// `process_commands` without closing the undo group.
+    fn process_batch(&mut self, commands: &[Command]) {
+        let commands = self.schedule.extract(commands, self.tick_count);
+        if self.history.is_none() {
+            self.apply_commands(&commands);
+            return;
+        }
+        for cmd in &commands {
+            if let Some(history) = self.history.as_mut() {
+                history.record(cmd, &self.world, &self.entity_map, &self.components);
+            }
+            self.apply_commands(std::slice::from_ref(cmd));
+        }
+    }
+
+    /// Parse raw command bytes, including registered plugin opcodes, and
+    /// apply them in wire order. The core commands form one undo group, like
+    /// a `process_commands` call; plugin commands are not recorded in the
+    /// history. `ScheduleAt` cannot defer a plugin command: a wrapper
+    /// followed by one is dropped together with it.
+    pub fn push_command_bytes(&mut self, data: &[u8]) {
+        let parsed = parse_command_stream(data, |op| self.plugins.payload_size(op));
+        let mut batch = Vec::new();
+        for cmd in parsed {
+            match cmd {
+                ParsedCommand::Core(cmd) => batch.push(cmd),
+                ParsedCommand::Plugin(cmd) => {
+                    if !batch.is_empty() {
+                        self.process_batch(&std::mem::take(&mut batch));
+                    }
+                    if self.schedule.take_dangling().is_none() {
+                        self.apply_plugin_command(&cmd);
+                    }
+                }
+            }
+        }
+        self.process_batch(&batch);
+        if let Some(history) = self.history.as_mut() {
+            history.end_batch();
+        }
+    }
+
+    fn apply_plugin_command(&mut self, cmd: &PluginCommand) {
+        let mut ctx = PluginContext {
+            world: &mut self.world,
+            entity_map: &self.entity_map,
+            render_state: &mut self.render_state,
+            #[cfg(feature = "physics-2d")]
+            physics: &mut self.physics,
+            dt: self.fixed_dt,
+            tick: self.tick_count,
+        };
+        self.plugins.apply_command(cmd, &mut ctx);
+    }
+
+    /// Add a plugin after all previously added ones. Its opcodes, components,
+    /// systems and render writer are registered atomically: on error nothing
+    /// changes.
+    pub fn add_plugin(&mut self, plugin: impl HyperionPlugin + 'static) -> Result<(), PluginError> {
+        self.plugins.add(Box::new(plugin), &mut self.scheduler, &mut self.components)
+    }
+
+    pub fn plugins(&self) -> &PluginSet {
+        &self.plugins
+    }
+
+    /// Apply commands without recording them in the history.
+    fn apply_commands(&mut self, commands: &[Command]) {
+        // Handle engine-level state (not entity-specific)
         for cmd in commands {
-            if cmd.cmd_type == CommandType::SetListenerPosition {
-                let x = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
-                let y = f32::from_le_bytes(cmd.payload[4..8].try_into().unwrap());
-                let z = f32::from_le_bytes(cmd.payload[8..12].try_into().unwrap());
-                let new_pos = [x, y, z];
-                let dt = FIXED_DT;
-                for ((vel, &np), &prev) in self.listener_vel.iter_mut()
-                    .zip(new_pos.iter())
-                    .zip(self.listener_prev_pos.iter())
-                {
-                    *vel = (np - prev) / dt;
+            match cmd.cmd_type {
+                CommandType::SetListenerPosition => {
+                    let x = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                    let y = f32::from_le_bytes(cmd.payload[4..8].try_into().unwrap());
+                    let z = f32::from_le_bytes(cmd.payload[8..12].try_into().unwrap());
+                    let new_pos = [x, y, z];
+                    let dt = self.fixed_dt;
+                    for ((vel, &np), &prev) in self.listener_vel.iter_mut()
+                        .zip(new_pos.iter())
+                        .zip(self.listener_prev_pos.iter())
+                    {
+                        *vel = (np - prev) / dt;
+                    }
+                    self.listener_pos = new_pos;
+                    self.listener_prev_pos = new_pos;
+                }
+                CommandType::SetTickRate => {
+                    self.set_tick_rate(u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap()));
+                }
+                CommandType::SetTimeScale => {
+                    self.set_time_scale(f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap()));
                 }
-                self.listener_pos = new_pos;
-                self.listener_prev_pos = new_pos;
+                CommandType::SetPaused => self.set_paused(cmd.payload[0] != 0),
+                CommandType::StepTicks => {
+                    self.step(u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap()));
+                }
+                _ => {}
+            }
+        }
+
+        // ECS command processing. Generic component commands split the batch
+        // so they apply in order with spawns and other entity commands.
+        let mut start = 0;
+        for (i, cmd) in commands.iter().enumerate() {
+            if matches!(cmd.cmd_type, CommandType::SetComponent | CommandType::RemoveComponent) {
+                self.process_ecs_commands(&commands[start..i]);
+                self.apply_component_command(cmd);
+                start = i + 1;
             }
         }
+        self.process_ecs_commands(&commands[start..]);
+    }
 
-        // ECS command processing
+    fn process_ecs_commands(&mut self, commands: &[Command]) {
+        if commands.is_empty() {
+            return;
+        }
         #[cfg(feature = "physics-2d")]
         {
             crate::command_processor::process_commands(
@@ -100,9 +289,80 @@ impl Engine {
         }
     }
 
+    /// `SetComponent` payload: component ID + raw bytes (size from the
+    /// registry, at most 15). `RemoveComponent` payload: component ID.
+    /// Rejected commands raise `EVENT_COMPONENT_ERROR` in the next frame.
+    fn apply_component_command(&mut self, cmd: &Command) {
+        let id = cmd.payload[0];
+        let result = if cmd.cmd_type == CommandType::RemoveComponent {
+            self.remove_component(cmd.entity_id, id)
+        } else {
+            match self.components.info(id).and_then(|info| info.size) {
+                Some(size) if size >= cmd.payload.len() => Err(ComponentError::TooLargeForCommand),
+                // Unknown and variable-size components are rejected by the registry.
+                size => {
+                    let bytes = &cmd.payload[1..1 + size.unwrap_or(cmd.payload.len() - 1)];
+                    self.set_component(cmd.entity_id, id, bytes)
+                }
+            }
+        };
+        if let Err(err) = result {
+            self.command_events.push(EngineEvent {
+                entity_id: cmd.entity_id,
+                kind: EVENT_COMPONENT_ERROR,
+                data: id as u32 | (err as u32) << 8,
+            });
+        }
+    }
+
+    /// Component registry (stable IDs, sizes and layouts).
+    pub fn components(&self) -> &ComponentRegistry {
+        &self.components
+    }
+
+    /// Mutable registry, for registering plugin components.
+    pub fn components_mut(&mut self) -> &mut ComponentRegistry {
+        &mut self.components
+    }
+
+    /// Insert or overwrite a component of `external_id` from raw bytes.
+    pub fn set_component(&mut self, external_id: u32, id: ComponentId, bytes: &[u8]) -> Result<(), ComponentError> {
+        let entity = self.entity_map.get(external_id).ok_or(ComponentError::UnknownEntity)?;
+        self.components.set(&mut self.world, &mut self.render_state, entity, id, bytes)
+    }
+
+    pub fn remove_component(&mut self, external_id: u32, id: ComponentId) -> Result<(), ComponentError> {
+        let entity = self.entity_map.get(external_id).ok_or(ComponentError::UnknownEntity)?;
+        self.components.remove(&mut self.world, &mut self.render_state, entity, id)
+    }
+
+    /// Raw bytes of a component of `external_id`, if present.
+    pub fn get_component(&self, external_id: u32, id: ComponentId) -> Option<Vec<u8>> {
+        let entity = self.entity_map.get(external_id)?;
+        let mut out = Vec::new();
+        self.components.get(&self.world, entity, id, &mut out).then_some(out)
+    }
+
+    /// External IDs of all entities whose tags intersect `mask`, ascending.
+    /// This is the member set a group command with the same mask acts on.
+    pub fn tagged_entities(&self, mask: u32) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .world
+            .query::<(&ExternalId, &Tags)>()
+            .iter()
+            .filter(|(_, tags)| tags.0 & mask != 0)
+            .map(|(ext, _)| ext.0)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
     /// Advance the engine by `dt` seconds (variable, from requestAnimationFrame).
     /// Runs fixed-timestep physics ticks, then recomputes transforms and
     /// collects render state.
+    ///
+    /// `dt` is scaled by the time scale; while paused no time accumulates,
+    /// but ticks queued with `step()` still run.
     pub fn update(&mut self, dt: f32) {
         // 0. Clear physics frame event buffers at start of frame.
         #[cfg(feature = "physics-2d")]
@@ -110,61 +370,213 @@ impl Engine {
             self.physics.frame_collision_events.clear();
             self.physics.frame_contact_force_events.clear();
         }
+        self.events.clear();
+        self.events.append(&mut self.command_events);
 
         // 1. Accumulate time and run fixed-timestep ticks.
-        self.accumulator += dt;
+        if !self.paused {
+            self.accumulator += dt * self.time_scale;
+        }
 
         // Cap accumulator to prevent spiral of death.
-        if self.accumulator > FIXED_DT * 10.0 {
-            self.accumulator = FIXED_DT * 10.0;
+        let max_accumulated = self.fixed_dt * self.max_ticks_per_frame as f32;
+        if self.accumulator > max_accumulated {
+            self.accumulator = max_accumulated;
         }
 
-        while self.accumulator >= FIXED_DT {
-            self.fixed_tick();
-            self.accumulator -= FIXED_DT;
-            self.tick_count += 1;
+        let mut ticked = false;
+        while self.accumulator >= self.fixed_dt {
+            self.step_tick();
+            self.accumulator -= self.fixed_dt;
+            ticked = true;
         }
 
-        // 1b. After all ticks, sync Rapier state back to ECS.
-        #[cfg(feature = "physics-2d")]
-        crate::physics::physics_sync_post(&mut self.world, &self.physics);
+        // 1a. Debugger single-steps, independent of the accumulator.
+        for _ in 0..std::mem::take(&mut self.pending_steps) {
+            self.step_tick();
+            ticked = true;
+        }
 
-        // 2. Recompute model matrices after all ticks.
-        transform_system(&mut self.world);
-        transform_system_2d(&mut self.world);
+        self.finish_frame(ticked, dt);
+    }
 
-        // 2b. Propagate parent transforms for scene graph.
-        {
-            let ext_to_entity: std::collections::HashMap<u32, hecs::Entity> =
-                self.entity_map.iter_mapped().collect();
-            propagate_transforms(&mut self.world, &ext_to_entity);
-        }
+    /// Post-tick frame work: the `PreRender` stage (physics write-back,
+    /// transforms, render state), then the previous-transform buffer.
+    /// `ticked` is whether any fixed tick ran since the last frame.
+    fn finish_frame(&mut self, ticked: bool, frame_dt: f32) {
+        self.run_stage(Stage::PreRender, frame_dt);
+        self.plugins.write_render_state(&self.world, &self.render_state);
 
-        // 2c. Mark velocity-driven and hierarchy-propagated entities as dirty.
-        // Systems (velocity_system, transform_system, propagate_transforms) modify
-        // ECS components directly, bypassing command_processor dirty marking.
-        self.mark_post_system_dirty();
+        // Keep the previous-tick transform buffer consistent with this frame.
+        self.render_state.update_prev_transforms(ticked);
+    }
+
+    /// Run every enabled system of `stage` in scheduler order, recording timings.
+    fn run_stage(&mut self, stage: Stage, dt: f32) {
+        // Take the scheduler so built-ins can borrow the rest of the engine.
+        let mut scheduler = std::mem::take(&mut self.scheduler);
+        let ctx = SystemContext { dt, tick: self.tick_count };
+        for pos in 0..scheduler.stage_len(stage) {
+            let start = now_ms();
+            match scheduler.enabled_at(stage, pos) {
+                Some(SystemKind::Builtin(builtin)) => {
+                    let builtin = *builtin;
+                    self.run_builtin(builtin);
+                }
+                Some(SystemKind::Custom(system)) => system(&mut self.world, &ctx),
+                Some(&mut SystemKind::Plugin { plugin, system }) => {
+                    let mut plugin_ctx = PluginContext {
+                        world: &mut self.world,
+                        entity_map: &self.entity_map,
+                        render_state: &mut self.render_state,
+                        #[cfg(feature = "physics-2d")]
+                        physics: &mut self.physics,
+                        dt,
+                        tick: ctx.tick,
+                    };
+                    self.plugins.run_system(plugin, system, &mut plugin_ctx);
+                }
+                None => continue,
+            }
+            scheduler.record_timing(stage, pos, now_ms() - start);
+        }
+        self.scheduler = scheduler;
+    }
 
-        // 3. Collect legacy render state (flat matrix buffer).
-        self.render_state.collect(&self.world);
+    fn run_builtin(&mut self, builtin: BuiltinSystem) {
+        match builtin {
+            // Consume pending bodies/colliders, sync kinematic positions.
+            #[cfg(feature = "physics-2d")]
+            BuiltinSystem::PhysicsSyncPre => crate::physics::physics_sync_pre(
+                &mut self.world,
+                &mut self.physics,
+                &self.entity_map,
+                self.fixed_dt,
+            ),
+            #[cfg(feature = "physics-2d")]
+            BuiltinSystem::PhysicsStep => self.physics.step(),
+            // Velocity integration: use filtered versions when physics is enabled
+            // so PhysicsControlled entities are not double-moved.
+            #[cfg(feature = "physics-2d")]
+            BuiltinSystem::Velocity => {
+                crate::systems::velocity_system_filtered(&mut self.world, self.fixed_dt);
+                crate::systems::velocity_system_2d_filtered(&mut self.world, self.fixed_dt);
+            }
+            #[cfg(not(feature = "physics-2d"))]
+            BuiltinSystem::Velocity => {
+                velocity_system(&mut self.world, self.fixed_dt);
+                velocity_system_2d(&mut self.world, self.fixed_dt);
+            }
+            // Tweens write their channels and mark the slots dirty themselves.
+            BuiltinSystem::Tween => {
+                tween_system(&mut self.world, &mut self.render_state, &mut self.events)
+            }
+            BuiltinSystem::SpriteAnimation => sprite_animation_system(
+                &mut self.world,
+                &self.sprite_clips,
+                &mut self.render_state,
+                &mut self.events,
+            ),
+            BuiltinSystem::Lifetime => self.expire_lifetimes(),
+            BuiltinSystem::Particles => self.particles.tick(&mut self.world, &self.entity_map, self.fixed_dt),
+            BuiltinSystem::Camera => {
+                camera_system(&mut self.world, &self.entity_map, &mut self.render_state, self.fixed_dt)
+            }
+            // Listener extrapolation.
+            BuiltinSystem::Listener => {
+                for (pos, &vel) in self.listener_pos.iter_mut().zip(self.listener_vel.iter()) {
+                    *pos += vel * self.fixed_dt;
+                }
+            }
+            // After all ticks, sync Rapier state back to ECS.
+            #[cfg(feature = "physics-2d")]
+            BuiltinSystem::PhysicsSyncPost => {
+                crate::physics::physics_sync_post(&mut self.world, &self.physics)
+            }
+            // Recompute model matrices after all ticks.
+            BuiltinSystem::Transform => {
+                transform_system(&mut self.world);
+                transform_system_2d(&mut self.world);
+            }
+            // Propagate parent transforms for scene graph.
+            BuiltinSystem::Propagate => {
+                let ext_to_entity: std::collections::HashMap<u32, hecs::Entity> =
+                    self.entity_map.iter_mapped().collect();
+                propagate_transforms(&mut self.world, &ext_to_entity);
+            }
+            // Needs propagated matrices for local-space particles.
+            BuiltinSystem::ParticleExport => self.particles.export(&self.world, &self.entity_map),
+            BuiltinSystem::CameraExport => self.cameras.export(&self.world),
+            // Systems (velocity_system, transform_system, propagate_transforms)
+            // modify ECS components directly, bypassing command_processor dirty marking.
+            BuiltinSystem::MarkDirty => self.mark_post_system_dirty(),
+            // Collect legacy render state, then flush despawns, sync dirty SoA
+            // slots, defragment slots, build the staging cache, count render
+            // layers, update the draw batches, sort transparent slots, and cull
+            // against the camera views.
+            BuiltinSystem::Collect => {
+                self.render_state.collect(&self.world);
+                // Reads the bounds-dirty bits before staging clears them.
+                if let Some(spatial) = self.spatial.as_mut() {
+                    spatial.sync(&self.world, &mut self.render_state);
+                }
+                self.render_state.defragment_slots();
+                self.render_state.collect_and_cache_dirty(&self.world);
+                self.render_state.update_layers();
+                self.render_state.update_batches();
+                self.render_state.sort_transparent(&self.world);
+                self.render_state.cull();
+            }
+            #[cfg(not(feature = "physics-2d"))]
+            BuiltinSystem::PhysicsSyncPre
+            | BuiltinSystem::PhysicsStep
+            | BuiltinSystem::PhysicsSyncPost => {}
+        }
+    }
 
-        // 4. Flush despawns, sync dirty SoA slots, and build staging cache.
-        // This replaces the legacy collect_gpu() — the retained slot mapping
-        // keeps SoA buffers up-to-date incrementally via write_slot().
-        self.render_state.collect_and_cache_dirty(&self.world);
+    /// Count lifetimes down and despawn expired entities (and, if cascading,
+    /// their descendants) through the regular `DespawnEntity` path.
+    fn expire_lifetimes(&mut self) {
+        let expired = lifetime_system(&mut self.world);
+        if expired.is_empty() {
+            return;
+        }
+        let mut despawns: Vec<Command> = Vec::new();
+        let mut queued = std::collections::HashSet::new();
+        for (root, cascade) in expired {
+            // Already despawned as a descendant of an earlier expired entity.
+            if queued.contains(&root) {
+                continue;
+            }
+            let targets = hierarchy_targets(&self.world, &self.entity_map, root, cascade);
+            self.events.push(EngineEvent {
+                entity_id: root,
+                kind: EVENT_LIFETIME_EXPIRED,
+                data: targets.len().saturating_sub(1) as u32,
+            });
+            // An earlier expired entity may also sit below this one.
+            despawns.extend(targets.into_iter().filter(|&(id, _)| queued.insert(id)).map(|(id, _)| Command {
+                cmd_type: CommandType::DespawnEntity,
+                entity_id: id,
+                payload: [0; 16],
+            }));
+        }
+        self.process_ecs_commands(&despawns);
     }
 
     /// Mark entities whose SoA data changed due to systems (not commands).
     ///
-    /// - Entities with non-zero velocity: velocity_system moved their Position,
-    ///   transform_system recomputed their ModelMatrix.
+    /// - Moving entities (linear/angular velocity or acceleration): velocity_system
+    ///   moved or rotated them, transform_system recomputed their ModelMatrix.
     /// - Children of dirty parents: propagate_transforms updated their ModelMatrix.
     fn mark_post_system_dirty(&mut self) {
         // Pass 1: velocity-driven entities (both 3D and 2D — query is archetype-agnostic)
-        for (entity, vel, _active) in
-            self.world.query::<(hecs::Entity, &Velocity, &Active)>().iter()
+        for (entity, vel, ang, acc, _active) in self
+            .world
+            .query::<(hecs::Entity, &Velocity, Option<&AngularVelocity>, Option<&Acceleration>, &Active)>()
+            .iter()
         {
-            if vel.0 != glam::Vec3::ZERO
+            if is_moving(vel, ang, acc)
                 && let Some(slot) = self.render_state.get_slot(entity)
             {
                 self.render_state.dirty_tracker.mark_transform_dirty(slot as usize);
@@ -208,33 +620,438 @@ impl Engine {
         }
     }
 
-    /// A single fixed-timestep tick.
-    fn fixed_tick(&mut self) {
-        // Physics sync: consume pending bodies/colliders, sync kinematic positions.
-        #[cfg(feature = "physics-2d")]
-        crate::physics::physics_sync_pre(&mut self.world, &mut self.physics, &self.entity_map, FIXED_DT);
+    /// Capture rollback state (if enabled), then run one fixed tick.
+    fn step_tick(&mut self) {
+        if let Some(rollback) = self.rollback.as_mut() {
+            rollback.capture(
+                self.tick_count,
+                &self.world,
+                [self.listener_pos, self.listener_prev_pos, self.listener_vel],
+                &self.schedule,
+                self.particles.particles(),
+                #[cfg(feature = "physics-2d")]
+                &self.physics,
+            );
+        }
+        if self.interpolation {
+            self.capture_prev_transforms();
+        }
+        self.fixed_tick();
+        if let Some(mask) = self.state_hasher.as_ref().map(|h| h.mask()) {
+            let hash = self.state_hash(mask);
+            if let Some(hasher) = self.state_hasher.as_mut() {
+                hasher.record(self.tick_count, hash);
+            }
+        }
+        self.tick_count += 1;
+    }
 
-        // Physics step.
-        #[cfg(feature = "physics-2d")]
-        self.physics.step();
+    /// Record the pre-tick world matrix of every active slot.
+    ///
+    /// Local matrices are built from the current transforms and composed down
+    /// the hierarchy, so children follow a moving parent. Physics bodies are
+    /// read from Rapier directly: their ECS transforms are only written back
+    /// once per frame.
+    fn capture_prev_transforms(&mut self) {
+        use crate::components::{ModelMatrix, Position, Rotation, Scale, Transform2D};
+        use crate::systems::matrix_2d;
+        use glam::Mat4;
+
+        self.render_state.begin_prev_capture();
+        // (entity, parent external ID, local matrix), indexed by external ID.
+        let mut nodes: Vec<(hecs::Entity, u32, Mat4)> = Vec::new();
+        let mut index: std::collections::HashMap<u32, usize> = std::collections::HashMap::new();
+        for (entity, ext, t2d, pos, rot, scale, parent, _active) in self
+            .world
+            .query::<(
+                hecs::Entity,
+                &ExternalId,
+                Option<&Transform2D>,
+                Option<&Position>,
+                Option<&Rotation>,
+                Option<&Scale>,
+                &Parent,
+                &Active,
+            )>()
+            .iter()
+        {
+            let local = match (t2d, pos, rot, scale) {
+                (Some(t), ..) => Mat4::from_cols_array(&matrix_2d(t.x, t.y, t.rot, t.sx, t.sy)),
+                (None, Some(p), Some(r), Some(s)) => Mat4::from_scale_rotation_translation(s.0, r.0, p.0),
+                _ => continue,
+            };
+            index.insert(ext.0, nodes.len());
+            nodes.push((entity, parent.0, local));
+        }
 
-        // Velocity integration: use filtered versions when physics is enabled
-        // so PhysicsControlled entities are not double-moved.
         #[cfg(feature = "physics-2d")]
+        for (ext, handle, t2d, pos, scale) in self
+            .world
+            .query::<(
+                &ExternalId,
+                &crate::physics::PhysicsBodyHandle,
+                Option<&Transform2D>,
+                Option<&Position>,
+                Option<&Scale>,
+            )>()
+            .iter()
         {
-            crate::systems::velocity_system_filtered(&mut self.world, FIXED_DT);
-            crate::systems::velocity_system_2d_filtered(&mut self.world, FIXED_DT);
+            let (Some(&i), Some(body)) = (index.get(&ext.0), self.physics.rigid_body_set.get(handle.0)) else {
+                continue;
+            };
+            let t = body.translation();
+            let angle = body.rotation().angle();
+            nodes[i].2 = match (t2d, pos, scale) {
+                (Some(t2d), ..) => Mat4::from_cols_array(&matrix_2d(t.x, t.y, angle, t2d.sx, t2d.sy)),
+                (None, Some(p), Some(s)) => Mat4::from_scale_rotation_translation(
+                    s.0,
+                    glam::Quat::from_rotation_z(angle),
+                    glam::Vec3::new(t.x, t.y, p.0.z),
+                ),
+                _ => continue,
+            };
+        }
+
+        // Compose each chain of unresolved ancestors top-down. Inactive parents
+        // do not move during the tick, so their current ModelMatrix is used.
+        let mut worlds: Vec<Option<Mat4>> = vec![None; nodes.len()];
+        let mut chain: Vec<usize> = Vec::new();
+        for start in 0..nodes.len() {
+            let mut base = None;
+            let mut i = start;
+            while worlds[i].is_none() && chain.len() <= nodes.len() {
+                chain.push(i);
+                let parent = nodes[i].1;
+                if parent == u32::MAX {
+                    break;
+                }
+                if let Some(&p) = index.get(&parent) {
+                    i = p;
+                    continue;
+                }
+                base = self
+                    .entity_map
+                    .get(parent)
+                    .and_then(|e| self.world.get::<&ModelMatrix>(e).ok().map(|m| Mat4::from_cols_array(&m.0)));
+                break;
+            }
+            if chain.last() != Some(&i) {
+                base = worlds[i];
+            }
+            while let Some(j) = chain.pop() {
+                let world = base.map_or(nodes[j].2, |b| b * nodes[j].2);
+                worlds[j] = Some(world);
+                base = Some(world);
+            }
+        }
+        for ((entity, ..), world) in nodes.iter().zip(worlds) {
+            if let Some(world) = world {
+                self.render_state.capture_prev_transform(*entity, &world.to_cols_array());
+            }
+        }
+    }
+
+    /// A single fixed-timestep tick: the `PreTick`, `Tick` and `PostTick` stages.
+    fn fixed_tick(&mut self) {
+        // Scheduled commands due this tick, applied before any system runs.
+        let due = self.schedule.take_due(self.tick_count);
+        if !due.is_empty() {
+            self.apply_commands(&due);
+        }
+        for stage in [Stage::PreTick, Stage::Tick, Stage::PostTick] {
+            self.run_stage(stage, self.fixed_dt);
+        }
+    }
+
+    /// System schedule (built-in and custom systems).
+    pub fn scheduler(&self) -> &Scheduler {
+        &self.scheduler
+    }
+
+    /// Mutable schedule, for registering, ordering and toggling systems.
+    pub fn scheduler_mut(&mut self) -> &mut Scheduler {
+        &mut self.scheduler
+    }
+
+    /// Start recording undo/redo history with the given memory budget.
+    /// Re-enabling keeps existing history and only updates the budget.
+    pub fn enable_history(&mut self, budget_bytes: usize) {
+        match self.history.as_mut() {
+            Some(history) => history.set_budget(budget_bytes),
+            None => self.history = Some(CommandHistory::new(budget_bytes)),
+        }
+    }
+
+    /// Stop recording and discard all history.
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    /// The history recorder, if enabled.
+    pub fn history(&self) -> Option<&CommandHistory> {
+        self.history.as_ref()
+    }
+
+    /// Revert the most recent undo group. Returns `false` if there is nothing to undo.
+    ///
+    /// Undo does not cover physics: entities re-created by undoing a despawn
+    /// come back without their rigid body or colliders.
+    pub fn undo(&mut self) -> bool {
+        let Some(group) = self.history.as_mut().and_then(|h| h.pop_undo()) else {
+            return false;
+        };
+        self.replay_group(&group.undo_commands());
+        if let Some(history) = self.history.as_mut() {
+            history.push_redo(group);
+        }
+        true
+    }
+
+    /// Re-apply the most recently undone group. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(group) = self.history.as_mut().and_then(|h| h.pop_redo()) else {
+            return false;
+        };
+        self.replay_group(&group.forward);
+        if let Some(history) = self.history.as_mut() {
+            history.push_undo_after_redo(group);
+        }
+        true
+    }
+
+    /// Apply one undo or redo group as a single batch without recording it.
+    /// A dangling `ScheduleAt` keeps waiting for the caller's next batch.
+    fn replay_group(&mut self, commands: &[Command]) {
+        let history = self.history.take();
+        let dangling = self.schedule.take_dangling();
+        self.process_batch(commands);
+        if let Some(wrapper) = dangling {
+            self.schedule.extract(&[wrapper], self.tick_count);
+        }
+        self.history = history;
+    }
+
+    /// Start capturing per-tick state for rollback, keeping the last `capacity`
+    /// ticks. A non-zero `physics_interval` also clones the Rapier world every
+    /// that many ticks, and only those ticks can then be rolled back to (no-op
+    /// without `physics-2d`). Re-enabling discards existing captures.
+    pub fn enable_rollback(&mut self, capacity: usize, physics_interval: u32) {
+        self.rollback = Some(RollbackBuffer::new(capacity, physics_interval));
+    }
+
+    /// Stop capturing and discard all captures.
+    pub fn disable_rollback(&mut self) {
+        self.rollback = None;
+    }
+
+    /// Start maintaining the spatial index with `cell_size` grid cells
+    /// (non-positive = default). Indexes every entity that has a render slot;
+    /// afterwards only entities with dirty bounds are re-indexed each frame.
+    pub fn enable_spatial_index(&mut self, cell_size: f32) {
+        let mut spatial = SpatialIndex::new(cell_size);
+        spatial.rebuild(&self.world, &self.render_state);
+        self.spatial = Some(spatial);
+    }
+
+    pub fn disable_spatial_index(&mut self) {
+        self.spatial = None;
+    }
+
+    /// The spatial index, if enabled. It reflects the world as of the last
+    /// `update()`.
+    pub fn spatial_index(&self) -> Option<&SpatialIndex> {
+        self.spatial.as_ref()
+    }
+
+    /// Mutable spatial index, for running queries into its result buffers.
+    pub fn spatial_index_mut(&mut self) -> Option<&mut SpatialIndex> {
+        self.spatial.as_mut()
+    }
+
+    /// Topmost entity at world-space `(x, y)` on a pick layer in `mask`.
+    /// Sees the render state of the last `update()`.
+    pub fn pick(&mut self, x: f32, y: f32, mask: u32) -> Option<u32> {
+        self.pick_all(x, y, mask).first().copied()
+    }
+
+    /// Every entity at world-space `(x, y)` on a pick layer in `mask`, topmost first.
+    pub fn pick_all(&mut self, x: f32, y: f32, mask: u32) -> &[u32] {
+        self.picker.pick_point(&self.render_state, &self.world, glam::Vec2::new(x, y), mask)
+    }
+
+    /// Entities touching (or, with `contained`, inside) the world-space
+    /// rectangle on a pick layer in `mask`, topmost first.
+    pub fn pick_rect(&mut self, min: [f32; 2], max: [f32; 2], mask: u32, contained: bool) -> &[u32] {
+        self.picker.pick_rect(&self.render_state, &self.world, min.into(), max.into(), mask, contained)
+    }
+
+    /// The picker, holding the results of the last pick.
+    pub fn picker(&self) -> &Picker {
+        &self.picker
+    }
+
+    /// The rollback buffer, if enabled.
+    pub fn rollback(&self) -> Option<&RollbackBuffer> {
+        self.rollback.as_ref()
+    }
+
+    /// Restore the state captured at the start of `tick`. Captures newer than
+    /// `tick` are discarded. Returns `false` if rollback is disabled or the
+    /// tick is no longer (or not yet) in the buffer.
+    ///
+    /// Render slots are marked dirty; call `resimulate()` or `update()` to
+    /// recompute transforms and render state.
+    pub fn rollback_to(&mut self, tick: u64) -> bool {
+        let Some(mut rollback) = self.rollback.take() else {
+            return false;
+        };
+        let restored = match rollback.get(tick) {
+            Some(state) => {
+                self.restore_tick_state(state);
+                true
+            }
+            None => false,
+        };
+        if restored {
+            rollback.discard_after(tick);
+            if let Some(hasher) = self.state_hasher.as_mut() {
+                hasher.discard_from(tick);
+            }
         }
+        self.rollback = Some(rollback);
+        restored
+    }
+
+    fn restore_tick_state(&mut self, state: &crate::rollback::TickState) {
+        #[cfg(feature = "physics-2d")]
+        let spawns = {
+            // Without a physics capture, Rapier keeps its current state, so
+            // bodies of despawned entities must be removed the normal way.
+            let physics = &mut self.physics;
+            let cleanup = state.physics().is_none();
+            crate::rollback::reconcile_entities(
+                state,
+                &mut self.world,
+                &mut self.entity_map,
+                &mut self.render_state,
+                |world, entity| {
+                    if cleanup {
+                        crate::command_processor::despawn_physics_cleanup(world, entity, physics);
+                    }
+                },
+            )
+        };
         #[cfg(not(feature = "physics-2d"))]
-        {
-            velocity_system(&mut self.world, FIXED_DT);
-            velocity_system_2d(&mut self.world, FIXED_DT);
+        let spawns = crate::rollback::reconcile_entities(
+            state,
+            &mut self.world,
+            &mut self.entity_map,
+            &mut self.render_state,
+            |_, _| {},
+        );
+        self.apply_commands(&spawns);
+        crate::rollback::apply_records(state, &mut self.world, &self.entity_map, &mut self.render_state);
+
+        #[cfg(feature = "physics-2d")]
+        if let Some(physics) = state.physics() {
+            self.physics.restore_state(physics);
         }
 
-        // Listener extrapolation.
-        for (pos, &vel) in self.listener_pos.iter_mut().zip(self.listener_vel.iter()) {
-            *pos += vel * FIXED_DT;
+        let [pos, prev_pos, vel] = state.listener();
+        self.listener_pos = pos;
+        self.listener_prev_pos = prev_pos;
+        self.listener_vel = vel;
+        self.schedule.restore(state.scheduled(), state.dangling());
+        self.particles.restore_particles(state.particles());
+        self.tick_count = state.tick();
+    }
+
+    /// Run `ticks` fixed ticks immediately (capturing rollback state as usual),
+    /// then recompute transforms and render state. Does not touch the
+    /// frame-time accumulator.
+    pub fn resimulate(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.step_tick();
         }
+        self.finish_frame(ticks > 0, ticks as f32 * self.fixed_dt);
+    }
+
+    /// Record a state hash after every fixed tick, over the components
+    /// selected by `mask` (`state_hash::HASH_*`), keeping the last `capacity`.
+    /// Re-enabling discards the existing history.
+    pub fn enable_state_hashing(&mut self, mask: u32, capacity: usize) {
+        self.state_hasher = Some(StateHasher::new(mask, capacity));
+    }
+
+    /// Stop recording state hashes and discard the history.
+    pub fn disable_state_hashing(&mut self) {
+        self.state_hasher = None;
+    }
+
+    /// The per-tick hash recorder, if enabled.
+    pub fn state_hasher(&self) -> Option<&StateHasher> {
+        self.state_hasher.as_ref()
+    }
+
+    /// Order-independent hash of the current simulation state.
+    pub fn state_hash(&self, mask: u32) -> u64 {
+        crate::state_hash::world_hash(
+            &self.world,
+            mask,
+            #[cfg(feature = "physics-2d")]
+            &self.physics,
+        )
+    }
+
+    /// Per-entity, per-component hashes of the current state, sorted by
+    /// `(external_id, component)`. Exchange these with a peer and compare with
+    /// `state_hash::first_divergence` to locate a desync.
+    pub fn state_hash_breakdown(&self, mask: u32) -> Vec<ComponentHash> {
+        crate::state_hash::breakdown(
+            &self.world,
+            mask,
+            #[cfg(feature = "physics-2d")]
+            &self.physics,
+        )
+    }
+
+    /// Events raised by systems during the last `update()`, in order.
+    pub fn events(&self) -> &[EngineEvent] {
+        &self.events
+    }
+
+    /// Register a sprite animation clip: packed texture layer indices with
+    /// per-frame durations in ticks. Frames listed in `event_frames` raise
+    /// `EVENT_SPRITE_FRAME` when reached. Returns the clip ID.
+    pub fn register_sprite_clip(
+        &mut self,
+        layers: &[u32],
+        durations: &[u16],
+        event_frames: &[u16],
+    ) -> Result<u32, SpriteClipError> {
+        self.sprite_clips.register(layers, durations, event_frames)
+    }
+
+    /// Register a particle emitter config from its raw `EmitterConfig` bytes.
+    /// Returns the config ID for `AttachEmitter`.
+    pub fn register_emitter_config(&mut self, bytes: &[u8]) -> Result<u32, EmitterError> {
+        self.particles.register(bytes)
+    }
+
+    /// Particle state and the exported particle buffers.
+    pub fn particles(&self) -> &ParticleSystem {
+        &self.particles
+    }
+
+    /// View-projection matrices and viewports of the active cameras as of
+    /// the last `update()`.
+    pub fn cameras(&self) -> &CameraExport {
+        &self.cameras
+    }
+
+    /// Commands queued by `ScheduleAt`, in release order.
+    pub fn scheduled_commands(&self) -> &CommandSchedule {
+        &self.schedule
     }
 
     /// How many fixed ticks have elapsed since engine start.
@@ -245,7 +1062,75 @@ impl Engine {
     /// The interpolation alpha for rendering between ticks.
     /// Ranges from 0.0 to 1.0.
     pub fn interpolation_alpha(&self) -> f32 {
-        self.accumulator / FIXED_DT
+        self.accumulator / self.fixed_dt
+    }
+
+    /// Set the fixed tick rate in ticks per second (e.g. 30, 60, 120).
+    /// Also updates the physics integration timestep. `0` is ignored.
+    pub fn set_tick_rate(&mut self, hz: u32) {
+        if hz > 0 {
+            self.set_fixed_dt(1.0 / hz as f32);
+        }
+    }
+
+    /// Set the fixed timestep in seconds. Non-positive or non-finite values are ignored.
+    pub fn set_fixed_dt(&mut self, dt: f32) {
+        if !(dt.is_finite() && dt > 0.0) {
+            return;
+        }
+        self.fixed_dt = dt;
+        #[cfg(feature = "physics-2d")]
+        {
+            self.physics.integration_parameters.dt = dt;
+        }
+    }
+
+    /// Seconds per fixed tick.
+    pub fn fixed_dt(&self) -> f32 {
+        self.fixed_dt
+    }
+
+    /// Scale applied to frame time before it is accumulated (1.0 = real time).
+    /// Negative or non-finite values are ignored.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        if scale.is_finite() && scale >= 0.0 {
+            self.time_scale = scale;
+        }
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Pause or resume the simulation. Render state keeps updating while paused.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Queue `ticks` fixed ticks to run on the next `update()`, regardless of
+    /// pause state and frame time.
+    pub fn step(&mut self, ticks: u32) {
+        self.pending_steps = self.pending_steps.saturating_add(ticks);
+    }
+
+    /// Enable or disable render interpolation. When enabled, every slot keeps
+    /// its previous-tick world matrix in `gpu_prev_transforms`; when disabled,
+    /// that buffer mirrors `gpu_transforms`.
+    pub fn set_interpolation(&mut self, enabled: bool) {
+        self.interpolation = enabled;
+    }
+
+    pub fn interpolation_enabled(&self) -> bool {
+        self.interpolation
+    }
+
+    /// Cap on accumulated ticks per `update()` (minimum 1).
+    pub fn set_max_ticks_per_frame(&mut self, max: u32) {
+        self.max_ticks_per_frame = max.max(1);
     }
 
     /// Returns the extrapolated listener X position.
@@ -276,12 +1161,27 @@ impl Engine {
         #[cfg(feature = "physics-2d")]
         {
             self.physics = crate::physics::PhysicsWorld::new();
+            self.physics.integration_parameters.dt = self.fixed_dt;
         }
         self.accumulator = 0.0;
         self.tick_count = 0;
+        self.pending_steps = 0;
         self.listener_pos = [0.0; 3];
         self.listener_prev_pos = [0.0; 3];
         self.listener_vel = [0.0; 3];
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
+        if let Some(rollback) = self.rollback.as_mut() {
+            rollback.clear();
+        }
+        if let Some(hasher) = self.state_hasher.as_mut() {
+            hasher.clear();
+        }
+        if let Some(spatial) = self.spatial.as_mut() {
+            spatial.clear();
+        }
+        self.cameras.clear();
     }
 
     /// Serialize the entire engine state into a binary snapshot.
@@ -290,8 +1190,11 @@ impl Engine {
     /// ```text
     /// [magic: 4B "HSNP"][version: u32][tick: u64][entity_count: u32]
     /// [entity_map_len: u32][entity_map: (ext_id: u32, hecs_id: u64) x N]
-    /// [per entity: hecs_id: u64, component_mask: u16, component_data...]
+    /// [per entity: hecs_id: u64, component_mask: u32, component_data...]
+    /// [scheduled_count: u32][scheduled: (tick: u64, cmd_type: u8, entity_id: u32, payload: 16B) x N]
     /// ```
+    /// Version 1 snapshots (16-bit component mask) and version 2 snapshots
+    /// (no scheduled commands) are still accepted by `snapshot_restore`.
     pub fn snapshot_create(&self) -> Vec<u8> {
         use crate::components::*;
 
@@ -299,7 +1202,7 @@ impl Engine {
 
         // Header
         buf.extend_from_slice(b"HSNP");
-        buf.extend_from_slice(&1u32.to_le_bytes()); // version
+        buf.extend_from_slice(&3u32.to_le_bytes()); // version
         buf.extend_from_slice(&self.tick_count.to_le_bytes());
 
         // Entity count — we'll come back and patch this
@@ -323,8 +1226,8 @@ impl Engine {
             buf.extend_from_slice(&e.to_bits().get().to_le_bytes());
 
             let mask_offset = buf.len();
-            buf.extend_from_slice(&0u16.to_le_bytes()); // placeholder mask
-            let mut mask: u16 = 0;
+            buf.extend_from_slice(&0u32.to_le_bytes()); // placeholder mask
+            let mut mask: u32 = 0;
 
             // bit 0: Position (12 bytes)
             if let Ok(v) = self.world.get::<&Position>(e) {
@@ -404,15 +1307,92 @@ impl Engine {
                     buf.extend_from_slice(&v.slots[i].to_le_bytes());
                 }
             }
+            // bit 15: Hidden (0 bytes, marker)
+            if self.world.get::<&Hidden>(e).is_ok() {
+                mask |= 1 << 15;
+            }
+            // bit 16: Tags (4 bytes)
+            if let Ok(v) = self.world.get::<&Tags>(e) {
+                mask |= 1 << 16;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 17: AngularVelocity (12 bytes)
+            if let Ok(v) = self.world.get::<&AngularVelocity>(e) {
+                mask |= 1 << 17;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 18: Acceleration (12 bytes)
+            if let Ok(v) = self.world.get::<&Acceleration>(e) {
+                mask |= 1 << 18;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 19: LinearDrag (4 bytes)
+            if let Ok(v) = self.world.get::<&LinearDrag>(e) {
+                mask |= 1 << 19;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 20: AngularDrag (4 bytes)
+            if let Ok(v) = self.world.get::<&AngularDrag>(e) {
+                mask |= 1 << 20;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 21: Tweens (1 byte track count + per track 1 byte len + len × 20 bytes)
+            if let Ok(v) = self.world.get::<&Tweens>(e) {
+                mask |= 1 << 21;
+                v.write_bytes(&mut buf);
+            }
+            // bit 22: SpriteAnimation (16 bytes)
+            if let Ok(v) = self.world.get::<&crate::sprite_animation::SpriteAnimation>(e) {
+                mask |= 1 << 22;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 23: Lifetime (8 bytes)
+            if let Ok(v) = self.world.get::<&crate::lifetime::Lifetime>(e) {
+                mask |= 1 << 23;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 24: Emitter (32 bytes). Live particles are not snapshotted.
+            if let Ok(v) = self.world.get::<&crate::particles::Emitter>(e) {
+                mask |= 1 << 24;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 25: PickLayer (4 bytes)
+            if let Ok(v) = self.world.get::<&PickLayer>(e) {
+                mask |= 1 << 25;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 26: Camera (104 bytes)
+            if let Ok(v) = self.world.get::<&crate::camera::Camera>(e) {
+                mask |= 1 << 26;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 27: Tint (4 bytes)
+            if let Ok(v) = self.world.get::<&Tint>(e) {
+                mask |= 1 << 27;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 28: RenderLayer (4 bytes)
+            if let Ok(v) = self.world.get::<&RenderLayer>(e) {
+                mask |= 1 << 28;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+            // bit 29: SortOrder (4 bytes)
+            if let Ok(v) = self.world.get::<&SortOrder>(e) {
+                mask |= 1 << 29;
+                buf.extend_from_slice(bytemuck::bytes_of(&*v));
+            }
+
+            // Patch mask
+            buf[mask_offset..mask_offset + 4].copy_from_slice(&mask.to_le_bytes());
+        }
 
-            // Patch mask
-            buf[mask_offset..mask_offset + 2].copy_from_slice(&mask.to_le_bytes());
-        }
-
         // Patch entity count
         buf[entity_count_offset..entity_count_offset + 4]
             .copy_from_slice(&entity_count.to_le_bytes());
 
+        // Scheduled commands
+        self.schedule.write_bytes(&mut buf);
+
         buf
     }
 
@@ -444,7 +1424,7 @@ impl Engine {
         }
 
         let version = read_pod!(u32);
-        if version != 1 {
+        if !(1..=3).contains(&version) {
             return false;
         }
 
@@ -470,7 +1450,7 @@ impl Engine {
 
         for _ in 0..entity_count {
             let old_hecs_bits = read_pod!(u64);
-            let mask = read_pod!(u16);
+            let mask = if version == 1 { read_pod!(u16) as u32 } else { read_pod!(u32) };
 
             // Read component data
             let position = if mask & (1 << 0) != 0 { read_pod!(Position) } else { Position::default() };
@@ -524,6 +1504,31 @@ impl Engine {
                 None
             };
 
+            let tags = if mask & (1 << 16) != 0 { Some(read_pod!(Tags)) } else { None };
+            let angular_velocity = if mask & (1 << 17) != 0 { Some(read_pod!(AngularVelocity)) } else { None };
+            let acceleration = if mask & (1 << 18) != 0 { Some(read_pod!(Acceleration)) } else { None };
+            let linear_drag = if mask & (1 << 19) != 0 { Some(read_pod!(LinearDrag)) } else { None };
+            let angular_drag = if mask & (1 << 20) != 0 { Some(read_pod!(AngularDrag)) } else { None };
+            let tweens = if mask & (1 << 21) != 0 {
+                let Some(tweens) = Tweens::read_bytes(data, &mut cursor) else { return false; };
+                Some(tweens)
+            } else {
+                None
+            };
+            let sprite_animation = if mask & (1 << 22) != 0 { Some(read_pod!(crate::sprite_animation::SpriteAnimation)) } else { None };
+            let lifetime = if mask & (1 << 23) != 0 { Some(read_pod!(crate::lifetime::Lifetime)) } else { None };
+            let emitter = if mask & (1 << 24) != 0 {
+                // Particles are dropped on restore, so nothing is alive yet.
+                Some(crate::particles::Emitter { alive: 0, ..read_pod!(crate::particles::Emitter) })
+            } else {
+                None
+            };
+            let pick_layer = if mask & (1 << 25) != 0 { Some(read_pod!(PickLayer)) } else { None };
+            let camera = if mask & (1 << 26) != 0 { Some(read_pod!(crate::camera::Camera)) } else { None };
+            let tint = if mask & (1 << 27) != 0 { Some(read_pod!(Tint)) } else { None };
+            let render_layer = if mask & (1 << 28) != 0 { Some(read_pod!(RenderLayer)) } else { None };
+            let sort_order = if mask & (1 << 29) != 0 { Some(read_pod!(SortOrder)) } else { None };
+
             // Spawn entity with baseline components
             let new_entity = new_world.spawn((
                 position,
@@ -551,9 +1556,67 @@ impl Engine {
                 let _ = new_world.insert_one(new_entity, lm);
             }
 
+            // Optionally add Hidden
+            if mask & (1 << 15) != 0 {
+                let _ = new_world.insert_one(new_entity, Hidden);
+            }
+
+            // Optionally add Tags
+            if let Some(tags) = tags {
+                let _ = new_world.insert_one(new_entity, tags);
+            }
+
+            // Optionally add motion components
+            if let Some(v) = angular_velocity {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = acceleration {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = linear_drag {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = angular_drag {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = tweens {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = sprite_animation {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = lifetime {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = emitter {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = pick_layer {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = camera {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = tint {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = render_layer {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+            if let Some(v) = sort_order {
+                let _ = new_world.insert_one(new_entity, v);
+            }
+
             old_to_new.insert(old_hecs_bits, new_entity);
         }
 
+        let schedule = if version >= 3 {
+            let Some(schedule) = CommandSchedule::read_bytes(data, &mut cursor) else { return false; };
+            schedule
+        } else {
+            CommandSchedule::new()
+        };
+
         // Rebuild entity map with new hecs entities
         for (ext_id, old_bits) in ext_to_old_hecs {
             if let Some(&new_entity) = old_to_new.get(&old_bits) {
@@ -567,9 +1630,26 @@ impl Engine {
         self.render_state = RenderState::new();
         self.accumulator = 0.0;
         self.tick_count = tick;
+        self.schedule = schedule;
+        self.particles.clear_particles();
+        // Entries are keyed by entities of the replaced world.
+        if let Some(spatial) = self.spatial.as_mut() {
+            spatial.clear();
+        }
+        self.cameras.clear();
         self.listener_pos = [0.0; 3];
         self.listener_prev_pos = [0.0; 3];
         self.listener_vel = [0.0; 3];
+        // Recorded inverses refer to the pre-restore world.
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
+        if let Some(rollback) = self.rollback.as_mut() {
+            rollback.clear();
+        }
+        if let Some(hasher) = self.state_hasher.as_mut() {
+            hasher.clear();
+        }
 
         true
     }
@@ -683,81 +1763,32 @@ impl Engine {
     ///
     /// TLV entry: `[type: u8][length: u16 LE][data: length bytes]`
     ///
-    /// Component type IDs:
+    /// Types are `component_registry` IDs, in ascending order:
     ///   Position=1, Velocity=2, Rotation=3, Scale=4, ModelMatrix=5,
     ///   BoundingRadius=6, TextureLayerIndex=7, MeshHandle=8, RenderPrimitive=9,
     ///   Parent=10, Active=11, ExternalId=12, PrimitiveParams=13,
-    ///   LocalMatrix=14, Children=15
+    ///   LocalMatrix=14, Children=15, Transform2D=16, then plugin components.
     pub fn debug_get_components(&self, external_id: u32, out: &mut [u8]) -> u32 {
-        use crate::components::*;
-
         let entity = match self.entity_map.get(external_id) {
             Some(e) => e,
             None => return 0,
         };
 
         let mut cursor = 0usize;
-
-        // Helper: write a TLV entry from raw bytes
-        let write_tlv = |typ: u8, data: &[u8], buf: &mut [u8], pos: &mut usize| -> bool {
+        let mut data = Vec::new();
+        for info in self.components.iter() {
+            data.clear();
+            if !self.components.get(&self.world, entity, info.id, &mut data) {
+                continue;
+            }
             let needed = 3 + data.len(); // 1 type + 2 length + data
-            if *pos + needed > buf.len() {
-                return false;
-            }
-            buf[*pos] = typ;
-            let len = data.len() as u16;
-            buf[*pos + 1] = len as u8;
-            buf[*pos + 2] = (len >> 8) as u8;
-            buf[*pos + 3..*pos + 3 + data.len()].copy_from_slice(data);
-            *pos += needed;
-            true
-        };
-
-        // Pod components: use bytemuck::bytes_of
-        // Collapsed if-let chains to satisfy clippy::collapsible_if
-        if let Ok(v) = self.world.get::<&Position>(entity)
-            && !write_tlv(1, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        if let Ok(v) = self.world.get::<&Velocity>(entity)
-            && !write_tlv(2, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        if let Ok(v) = self.world.get::<&Rotation>(entity)
-            && !write_tlv(3, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        if let Ok(v) = self.world.get::<&Scale>(entity)
-            && !write_tlv(4, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        if let Ok(v) = self.world.get::<&ModelMatrix>(entity)
-            && !write_tlv(5, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        if let Ok(v) = self.world.get::<&BoundingRadius>(entity)
-            && !write_tlv(6, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        if let Ok(v) = self.world.get::<&TextureLayerIndex>(entity)
-            && !write_tlv(7, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        if let Ok(v) = self.world.get::<&MeshHandle>(entity)
-            && !write_tlv(8, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        if let Ok(v) = self.world.get::<&RenderPrimitive>(entity)
-            && !write_tlv(9, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        // Parent: manual serialization (not Pod)
-        if let Ok(v) = self.world.get::<&Parent>(entity)
-            && !write_tlv(10, &v.0.to_le_bytes(), out, &mut cursor) { return cursor as u32; }
-        // Active: marker component, zero-length data
-        if self.world.get::<&Active>(entity).is_ok()
-            && !write_tlv(11, &[], out, &mut cursor) { return cursor as u32; }
-        if let Ok(v) = self.world.get::<&ExternalId>(entity)
-            && !write_tlv(12, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        if let Ok(v) = self.world.get::<&PrimitiveParams>(entity)
-            && !write_tlv(13, bytemuck::bytes_of(&*v), out, &mut cursor) { return cursor as u32; }
-        // LocalMatrix: manual serialization (not Pod)
-        if let Ok(v) = self.world.get::<&LocalMatrix>(entity) {
-            let bytes: &[u8] = bytemuck::cast_slice(&v.0);
-            if !write_tlv(14, bytes, out, &mut cursor) { return cursor as u32; }
-        }
-        // Children: count (u8) + child IDs (count × u32 LE)
-        if let Ok(v) = self.world.get::<&Children>(entity) {
-            let count = v.count as usize;
-            let data_len = 1 + count * 4;
-            let mut data = vec![0u8; data_len];
-            data[0] = v.count;
-            for i in 0..count {
-                data[1 + i * 4..1 + i * 4 + 4].copy_from_slice(&v.slots[i].to_le_bytes());
-            }
-            if !write_tlv(15, &data, out, &mut cursor) { return cursor as u32; }
+            if cursor + needed > out.len() {
+                break;
+            }
+            out[cursor] = info.id;
+            out[cursor + 1..cursor + 3].copy_from_slice(&(data.len() as u16).to_le_bytes());
+            out[cursor + 3..cursor + needed].copy_from_slice(&data);
+            cursor += needed;
         }
 
         cursor as u32
@@ -768,6 +1799,7 @@ impl Engine {
 mod tests {
     use super::*;
     use crate::ring_buffer::{Command, CommandType};
+    use crate::state_hash::HASH_ALL;
 
     fn spawn_cmd(id: u32) -> Command {
         Command {
@@ -789,172 +1821,1934 @@ mod tests {
         }
     }
 
-    fn velocity_cmd(id: u32, vx: f32, vy: f32, vz: f32) -> Command {
-        let mut payload = [0u8; 16];
-        payload[0..4].copy_from_slice(&vx.to_le_bytes());
-        payload[4..8].copy_from_slice(&vy.to_le_bytes());
-        payload[8..12].copy_from_slice(&vz.to_le_bytes());
-        Command {
-            cmd_type: CommandType::SetVelocity,
-            entity_id: id,
-            payload,
-        }
+    fn velocity_cmd(id: u32, vx: f32, vy: f32, vz: f32) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0..4].copy_from_slice(&vx.to_le_bytes());
+        payload[4..8].copy_from_slice(&vy.to_le_bytes());
+        payload[8..12].copy_from_slice(&vz.to_le_bytes());
+        Command {
+            cmd_type: CommandType::SetVelocity,
+            entity_id: id,
+            payload,
+        }
+    }
+
+    #[test]
+    fn engine_processes_commands_and_ticks() {
+        let mut engine = Engine::new();
+
+        // Spawn entity and set velocity.
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+
+        // Run for exactly 1 fixed tick (1/60th second).
+        engine.update(FIXED_DT);
+
+        let entity = engine.entity_map.get(0).unwrap();
+        let pos = engine.world.get::<&crate::components::Position>(entity).unwrap();
+        assert!((pos.0.x - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn fixed_timestep_accumulates() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+
+        // Run for half a tick — should not advance physics.
+        engine.update(FIXED_DT * 0.5);
+        assert_eq!(engine.tick_count(), 0);
+
+        // Run for another half — now one full tick should fire.
+        engine.update(FIXED_DT * 0.5);
+        assert_eq!(engine.tick_count(), 1);
+    }
+
+    #[test]
+    fn spiral_of_death_capped() {
+        let mut engine = Engine::new();
+        // Pass a huge dt — should be capped to 10 ticks max.
+        engine.update(100.0);
+        assert!(engine.tick_count() <= 10);
+    }
+
+    fn control_cmd(cmd_type: CommandType, bytes: &[u8]) -> Command {
+        let mut payload = [0u8; 16];
+        payload[..bytes.len()].copy_from_slice(bytes);
+        Command {
+            cmd_type,
+            entity_id: 0,
+            payload,
+        }
+    }
+
+    #[test]
+    fn tick_rate_changes_ticks_per_second() {
+        let mut engine = Engine::new();
+        engine.set_tick_rate(30);
+        assert!((engine.fixed_dt() - 1.0 / 30.0).abs() < 1e-7);
+        engine.update(0.1 + 1e-4);
+        assert_eq!(engine.tick_count(), 3);
+
+        engine.process_commands(&[control_cmd(CommandType::SetTickRate, &120u32.to_le_bytes())]);
+        engine.update(0.05);
+        assert_eq!(engine.tick_count(), 3 + 6);
+    }
+
+    #[test]
+    fn time_scale_slows_simulation() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[control_cmd(CommandType::SetTimeScale, &0.5f32.to_le_bytes())]);
+        engine.update(FIXED_DT * 4.0);
+        assert_eq!(engine.tick_count(), 2);
+    }
+
+    #[test]
+    fn paused_engine_updates_render_state_without_ticking() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            velocity_cmd(0, 60.0, 0.0, 0.0),
+            control_cmd(CommandType::SetPaused, &[1]),
+        ]);
+        engine.update(FIXED_DT * 3.0);
+        assert_eq!(engine.tick_count(), 0);
+        assert_eq!(engine.render_state.gpu_entity_count(), 1);
+
+        engine.process_commands(&[make_position_cmd(0, 5.0, 0.0, 0.0)]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_transforms()[12], 5.0);
+
+        engine.set_paused(false);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.tick_count(), 1);
+    }
+
+    #[test]
+    fn step_runs_exact_ticks_while_paused() {
+        let mut engine = Engine::new();
+        engine.set_paused(true);
+        engine.process_commands(&[control_cmd(CommandType::StepTicks, &3u32.to_le_bytes())]);
+        engine.update(0.0);
+        assert_eq!(engine.tick_count(), 3);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.tick_count(), 3, "steps are consumed once");
+    }
+
+    #[test]
+    fn max_ticks_per_frame_is_configurable() {
+        let mut engine = Engine::new();
+        engine.set_max_ticks_per_frame(2);
+        engine.update(1.0);
+        assert_eq!(engine.tick_count(), 2);
+    }
+
+    /// (previous x, current x) of slot 0's translation.
+    fn prev_and_current_x(engine: &Engine) -> (f32, f32) {
+        (
+            engine.render_state.gpu_prev_transforms()[12],
+            engine.render_state.gpu_transforms()[12],
+        )
+    }
+
+    #[test]
+    fn interpolation_keeps_previous_tick_transform() {
+        let mut engine = Engine::new();
+        engine.set_interpolation(true);
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+        engine.update(FIXED_DT);
+        assert_eq!(prev_and_current_x(&engine), (0.0, 1.0));
+
+        // Two ticks in one frame: previous is the state before the last tick.
+        engine.update(FIXED_DT * 2.0);
+        let (prev, cur) = prev_and_current_x(&engine);
+        assert!((prev - 2.0).abs() < 1e-4 && (cur - 3.0).abs() < 1e-4);
+
+        // No tick this frame: the pair is kept for a growing alpha.
+        engine.update(FIXED_DT * 0.25);
+        let (prev, cur) = prev_and_current_x(&engine);
+        assert!((prev - 2.0).abs() < 1e-4 && (cur - 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn interpolation_snaps_after_teleport_and_stop() {
+        let mut engine = Engine::new();
+        engine.set_interpolation(true);
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+        engine.update(FIXED_DT);
+
+        // Teleport on a frame without ticks.
+        engine.process_commands(&[make_position_cmd(0, 50.0, 0.0, 0.0)]);
+        engine.update(0.0);
+        assert_eq!(prev_and_current_x(&engine), (50.0, 50.0));
+
+        // Teleport before a tick: interpolation starts at the new position.
+        engine.process_commands(&[make_position_cmd(0, 100.0, 0.0, 0.0)]);
+        engine.update(FIXED_DT);
+        assert_eq!(prev_and_current_x(&engine), (100.0, 101.0));
+
+        // Stopping snaps previous to current.
+        engine.process_commands(&[velocity_cmd(0, 0.0, 0.0, 0.0)]);
+        engine.update(FIXED_DT);
+        assert_eq!(prev_and_current_x(&engine), (101.0, 101.0));
+    }
+
+    #[test]
+    fn translate_group_keeps_interpolation() {
+        let mut engine = Engine::new();
+        engine.set_interpolation(true);
+        engine.process_commands(&[
+            spawn_cmd(0),
+            velocity_cmd(0, 60.0, 0.0, 0.0),
+            tags_cmd(CommandType::AddTags, 0, ENEMY),
+        ]);
+        engine.update(FIXED_DT);
+
+        let mut delta = [0u8; 12];
+        delta[0..4].copy_from_slice(&5.0f32.to_le_bytes());
+        engine.process_commands(&[group_cmd(CommandType::TranslateGroup, ENEMY, &delta)]);
+        engine.update(0.0);
+        assert_eq!(prev_and_current_x(&engine), (0.0, 6.0));
+    }
+
+    #[test]
+    fn interpolation_captures_children_of_moving_parents() {
+        let mut engine = Engine::new();
+        engine.set_interpolation(true);
+        engine.process_commands(&[
+            spawn_cmd(0),
+            spawn_cmd(1),
+            velocity_cmd(0, 60.0, 0.0, 0.0),
+            make_position_cmd(1, 10.0, 0.0, 0.0),
+            parent_cmd(1, 0),
+        ]);
+        engine.update(FIXED_DT);
+        engine.update(FIXED_DT);
+
+        let slot = engine.render_state.get_slot(engine.entity_map.get(1).unwrap()).unwrap() as usize * 16;
+        let prev = engine.render_state.gpu_prev_transforms()[slot + 12];
+        assert_eq!((prev, engine.render_state.gpu_transforms()[slot + 12]), (11.0, 12.0));
+    }
+
+    #[test]
+    fn prev_transforms_mirror_current_when_interpolation_disabled() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+        engine.update(FIXED_DT);
+        engine.update(FIXED_DT);
+        assert_eq!(prev_and_current_x(&engine), (2.0, 2.0));
+    }
+
+    #[test]
+    fn listener_extrapolation_uses_fixed_dt() {
+        let mut engine = Engine::new();
+        engine.set_tick_rate(30);
+        let mut payload = [0u8; 16];
+        payload[0..4].copy_from_slice(&1.0f32.to_le_bytes());
+        engine.process_commands(&[Command {
+            cmd_type: CommandType::SetListenerPosition,
+            entity_id: 0,
+            payload,
+        }]);
+        // Velocity = 1.0 / (1/30) = 30 units/s; one 1/30 s tick moves 1 unit.
+        engine.update(1.0 / 30.0);
+        assert!((engine.listener_x() - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn model_matrix_updated_after_tick() {
+        let mut engine = Engine::new();
+        let mut pos_cmd = Command {
+            cmd_type: CommandType::SetPosition,
+            entity_id: 0,
+            payload: [0; 16],
+        };
+        pos_cmd.payload[0..4].copy_from_slice(&5.0f32.to_le_bytes());
+        pos_cmd.payload[4..8].copy_from_slice(&10.0f32.to_le_bytes());
+        pos_cmd.payload[8..12].copy_from_slice(&15.0f32.to_le_bytes());
+
+        engine.process_commands(&[spawn_cmd(0), pos_cmd]);
+        engine.update(FIXED_DT);
+
+        let entity = engine.entity_map.get(0).unwrap();
+        let matrix = engine.world.get::<&crate::components::ModelMatrix>(entity).unwrap();
+        assert!((matrix.0[12] - 5.0).abs() < 0.001);
+        assert!((matrix.0[13] - 10.0).abs() < 0.001);
+        assert!((matrix.0[14] - 15.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn render_state_collected_after_update() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1)]);
+        engine.update(FIXED_DT);
+
+        assert_eq!(engine.render_state.count(), 2);
+        assert!(!engine.render_state.as_ptr().is_null());
+    }
+
+    #[test]
+    fn engine_propagates_parent_transforms() {
+        let mut engine = Engine::new();
+
+        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1)]);
+
+        let mut pos_payload = [0u8; 16];
+        pos_payload[0..4].copy_from_slice(&10.0f32.to_le_bytes());
+        engine.process_commands(&[Command {
+            cmd_type: CommandType::SetPosition,
+            entity_id: 0,
+            payload: pos_payload,
+        }]);
+
+        let mut child_pos = [0u8; 16];
+        child_pos[0..4].copy_from_slice(&5.0f32.to_le_bytes());
+        engine.process_commands(&[Command {
+            cmd_type: CommandType::SetPosition,
+            entity_id: 1,
+            payload: child_pos,
+        }]);
+
+        let mut parent_payload = [0u8; 16];
+        parent_payload[0..4].copy_from_slice(&0u32.to_le_bytes());
+        engine.process_commands(&[Command {
+            cmd_type: CommandType::SetParent,
+            entity_id: 1,
+            payload: parent_payload,
+        }]);
+
+        engine.update(FIXED_DT);
+
+        let child_entity = engine.entity_map.get(1).unwrap();
+        let matrix = engine
+            .world
+            .get::<&crate::components::ModelMatrix>(child_entity)
+            .unwrap();
+        assert!((matrix.0[12] - 15.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn engine_listener_defaults_to_origin() {
+        let engine = Engine::new();
+        assert_eq!(engine.listener_x(), 0.0);
+        assert_eq!(engine.listener_y(), 0.0);
+        assert_eq!(engine.listener_z(), 0.0);
+    }
+
+    #[test]
+    fn engine_listener_extrapolates_position() {
+        let mut engine = Engine::new();
+
+        let mut payload = [0u8; 16];
+        payload[0..4].copy_from_slice(&10.0f32.to_le_bytes());
+        engine.process_commands(&[Command {
+            cmd_type: CommandType::SetListenerPosition,
+            entity_id: 0,
+            payload,
+        }]);
+
+        // Velocity = (10 - 0) / (1/60) = 600 units/sec
+        // After 1 tick, position: 10 + 600 * (1/60) = 20
+        engine.update(FIXED_DT);
+
+        assert!((engine.listener_x() - 20.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn engine_processes_set_listener_position() {
+        let mut engine = Engine::new();
+
+        let mut payload = [0u8; 16];
+        payload[0..4].copy_from_slice(&5.0f32.to_le_bytes());
+        payload[4..8].copy_from_slice(&10.0f32.to_le_bytes());
+        payload[8..12].copy_from_slice(&0.0f32.to_le_bytes());
+
+        let cmd = Command {
+            cmd_type: CommandType::SetListenerPosition,
+            entity_id: 0,
+            payload,
+        };
+        engine.process_commands(&[cmd]);
+
+        assert!((engine.listener_x() - 5.0).abs() < 0.001);
+        assert!((engine.listener_y() - 10.0).abs() < 0.001);
+    }
+
+    fn history_marker_cmd(kind: u8) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0] = kind;
+        Command {
+            cmd_type: CommandType::HistoryMarker,
+            entity_id: 0,
+            payload,
+        }
+    }
+
+    fn position_of(engine: &Engine, id: u32) -> glam::Vec3 {
+        let entity = engine.entity_map.get(id).unwrap();
+        engine.world.get::<&crate::components::Position>(entity).unwrap().0
+    }
+
+    #[test]
+    fn history_disabled_by_default() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0)]);
+        assert!(engine.history().is_none());
+        assert!(!engine.undo());
+    }
+
+    #[test]
+    fn undo_redo_set_position() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0), make_position_cmd(0, 1.0, 2.0, 3.0)]);
+        engine.process_commands(&[make_position_cmd(0, 10.0, 20.0, 30.0)]);
+
+        assert!(engine.undo());
+        assert_eq!(position_of(&engine, 0), glam::Vec3::new(1.0, 2.0, 3.0));
+
+        assert!(engine.redo());
+        assert_eq!(position_of(&engine, 0), glam::Vec3::new(10.0, 20.0, 30.0));
+
+        // Undo both groups: the spawn is reverted too.
+        assert!(engine.undo());
+        assert!(engine.undo());
+        assert!(engine.entity_map.get(0).is_none());
+        assert!(!engine.undo());
+    }
+
+    #[test]
+    fn undo_batch_writing_same_entity_twice() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0), make_position_cmd(0, 1.0, 1.0, 1.0)]);
+        let mut delta = [0u8; 12];
+        delta[0..4].copy_from_slice(&5.0f32.to_le_bytes());
+        engine.process_commands(&[
+            spawn_cmd(1),
+            make_position_cmd(1, 4.0, 4.0, 4.0),
+            make_position_cmd(1, 5.0, 5.0, 5.0),
+            // Entity 0 only joins the group once the tag is applied.
+            tags_cmd(CommandType::AddTags, 0, ENEMY),
+            group_cmd(CommandType::TranslateGroup, ENEMY, &delta),
+        ]);
+        assert_eq!(position_of(&engine, 0), glam::Vec3::new(6.0, 1.0, 1.0));
+
+        assert!(engine.undo());
+        assert_eq!(position_of(&engine, 0), glam::Vec3::splat(1.0));
+        assert!(engine.entity_map.get(1).is_none());
+        assert!(engine.tagged_entities(ENEMY).is_empty());
+
+        assert!(engine.redo());
+        assert_eq!(position_of(&engine, 0), glam::Vec3::new(6.0, 1.0, 1.0));
+        assert_eq!(position_of(&engine, 1), glam::Vec3::splat(5.0));
+
+        // Undoing the first batch reverts its spawn and set alike.
+        assert!(engine.undo());
+        assert!(engine.undo());
+        assert!(engine.entity_map.get(0).is_none());
+    }
+
+    #[test]
+    fn spawn_after_undone_despawn_gets_a_fresh_id() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        let a = engine.entity_map.allocate();
+        let b = engine.entity_map.allocate();
+        engine.process_commands(&[spawn_cmd(a), spawn_cmd(b)]);
+        engine.process_commands(&[Command { cmd_type: CommandType::DespawnEntity, entity_id: b, payload: [0; 16] }]);
+        assert!(engine.undo());
+        assert!(engine.entity_map.get(b).is_some());
+
+        let c = engine.entity_map.allocate();
+        assert!(c != a && c != b, "allocated live ID {c}");
+        engine.process_commands(&[spawn_cmd(c)]);
+        assert_eq!(engine.entity_map.iter_mapped().count(), 3);
+    }
+
+    #[test]
+    fn undo_despawn_restores_entity_and_render_slot() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0), make_position_cmd(0, 7.0, 8.0, 9.0)]);
+        engine.update(FIXED_DT);
+        engine.process_commands(&[Command {
+            cmd_type: CommandType::DespawnEntity,
+            entity_id: 0,
+            payload: [0; 16],
+        }]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_entity_count(), 0);
+
+        assert!(engine.undo());
+        engine.update(FIXED_DT);
+        assert_eq!(position_of(&engine, 0), glam::Vec3::new(7.0, 8.0, 9.0));
+        assert_eq!(engine.render_state.gpu_entity_count(), 1);
+    }
+
+    #[test]
+    fn history_markers_group_multiple_batches() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0)]);
+
+        engine.process_commands(&[history_marker_cmd(crate::history::MARKER_BEGIN_GROUP)]);
+        engine.process_commands(&[make_position_cmd(0, 1.0, 0.0, 0.0)]);
+        engine.process_commands(&[make_position_cmd(0, 2.0, 0.0, 0.0)]);
+        engine.process_commands(&[history_marker_cmd(crate::history::MARKER_END_GROUP)]);
+        assert_eq!(engine.history().unwrap().undo_len(), 2);
+
+        assert!(engine.undo());
+        assert_eq!(position_of(&engine, 0), glam::Vec3::ZERO);
+    }
+
+    #[test]
+    fn undo_and_redo_are_not_recorded() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0)]);
+        engine.process_commands(&[make_position_cmd(0, 1.0, 0.0, 0.0)]);
+        assert!(engine.undo());
+        let h = engine.history().unwrap();
+        assert_eq!((h.undo_len(), h.redo_len()), (1, 1));
+    }
+
+    #[test]
+    fn rollback_disabled_by_default() {
+        let mut engine = Engine::new();
+        engine.update(FIXED_DT);
+        assert!(engine.rollback().is_none());
+        assert!(!engine.rollback_to(0));
+    }
+
+    #[test]
+    fn rollback_and_resimulate_reproduces_state() {
+        let mut engine = Engine::new();
+        engine.enable_rollback(crate::rollback::DEFAULT_ROLLBACK_CAPACITY, 0);
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 3.0, -1.0, 0.5)]);
+        for _ in 0..5 {
+            engine.update(FIXED_DT);
+        }
+        let hash_at_5 = engine.state_hash(HASH_ALL);
+        for _ in 0..5 {
+            engine.update(FIXED_DT);
+        }
+        let hash_at_10 = engine.state_hash(HASH_ALL);
+
+        assert!(engine.rollback_to(5));
+        assert_eq!(engine.tick_count(), 5);
+        assert_eq!(engine.state_hash(HASH_ALL), hash_at_5);
+
+        engine.resimulate(5);
+        assert_eq!(engine.tick_count(), 10);
+        assert_eq!(engine.state_hash(HASH_ALL), hash_at_10);
+    }
+
+    #[test]
+    fn rollback_reverts_spawns_and_despawns() {
+        let mut engine = Engine::new();
+        engine.enable_rollback(16, 0);
+        engine.process_commands(&[spawn_cmd(0), make_position_cmd(0, 4.0, 5.0, 6.0)]);
+        engine.update(FIXED_DT);
+        engine.update(FIXED_DT); // tick 1 captured with entity 0 only
+        let hash_at_2 = engine.state_hash(HASH_ALL);
+
+        engine.process_commands(&[
+            Command {
+                cmd_type: CommandType::DespawnEntity,
+                entity_id: 0,
+                payload: [0; 16],
+            },
+            spawn_cmd(1),
+        ]);
+        engine.update(FIXED_DT);
+        assert!(engine.entity_map.get(0).is_none());
+
+        assert!(engine.rollback_to(1));
+        engine.resimulate(1);
+        assert!(engine.entity_map.get(1).is_none());
+        assert_eq!(position_of(&engine, 0), glam::Vec3::new(4.0, 5.0, 6.0));
+        assert_eq!(engine.state_hash(HASH_ALL), hash_at_2);
+        assert_eq!(engine.render_state.gpu_entity_count(), 1);
+    }
+
+    #[test]
+    fn rollback_discards_future_and_rejects_evicted_ticks() {
+        let mut engine = Engine::new();
+        engine.enable_rollback(4, 0);
+        engine.process_commands(&[spawn_cmd(0)]);
+        for _ in 0..10 {
+            engine.update(FIXED_DT);
+        }
+        assert!(!engine.rollback_to(2), "tick 2 was evicted");
+        assert!(!engine.rollback_to(10), "tick 10 has not started");
+        assert!(engine.rollback_to(7));
+        assert!(!engine.rollback_to(8), "captures after the rollback target are dropped");
+    }
+
+    #[test]
+    fn state_hash_recorded_per_tick() {
+        let mut engine = Engine::new();
+        engine.enable_state_hashing(HASH_ALL, 8);
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 1.0, 0.0, 0.0)]);
+        engine.update(FIXED_DT * 3.0);
+
+        let hasher = engine.state_hasher().unwrap();
+        let ticks: Vec<u64> = hasher.history().map(|(t, _)| t).collect();
+        assert_eq!(ticks, vec![0, 1, 2]);
+        assert_ne!(hasher.hash_for_tick(0), hasher.hash_for_tick(1));
+        assert_eq!(hasher.latest().unwrap().1, engine.state_hash(HASH_ALL));
+    }
+
+    #[test]
+    fn state_hash_matches_across_frame_pacing() {
+        let cmds = [spawn_cmd(0), velocity_cmd(0, 2.0, 3.0, 0.0), spawn_cmd(1)];
+        let mut a = Engine::new();
+        let mut b = Engine::new();
+        a.enable_state_hashing(HASH_ALL, 16);
+        b.enable_state_hashing(HASH_ALL, 16);
+        a.process_commands(&cmds);
+        b.process_commands(&cmds);
+        for _ in 0..4 {
+            a.update(FIXED_DT * 2.0);
+        }
+        for _ in 0..8 {
+            b.update(FIXED_DT);
+        }
+        let ha: Vec<_> = a.state_hasher().unwrap().history().collect();
+        let hb: Vec<_> = b.state_hasher().unwrap().history().collect();
+        assert_eq!(ha, hb);
+    }
+
+    #[test]
+    fn state_hash_diff_reports_divergent_entity() {
+        let mut a = Engine::new();
+        let mut b = Engine::new();
+        a.process_commands(&[spawn_cmd(0), spawn_cmd(1)]);
+        b.process_commands(&[spawn_cmd(0), spawn_cmd(1), make_position_cmd(1, 0.0, 1.0, 0.0)]);
+        let d = crate::state_hash::first_divergence(
+            &a.state_hash_breakdown(HASH_ALL),
+            &b.state_hash_breakdown(HASH_ALL),
+        )
+        .unwrap();
+        assert_eq!((d.external_id, d.component), (1, crate::component_registry::COMPONENT_POSITION));
+    }
+
+    #[test]
+    fn rollback_discards_hashes_from_target_tick() {
+        let mut engine = Engine::new();
+        engine.enable_rollback(16, 0);
+        engine.enable_state_hashing(HASH_ALL, 16);
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 1.0, 0.0, 0.0)]);
+        for _ in 0..6 {
+            engine.update(FIXED_DT);
+        }
+        let original: Vec<_> = engine.state_hasher().unwrap().history().collect();
+
+        assert!(engine.rollback_to(3));
+        assert_eq!(engine.state_hasher().unwrap().latest().map(|(t, _)| t), Some(2));
+        engine.resimulate(3);
+        let replayed: Vec<_> = engine.state_hasher().unwrap().history().collect();
+        assert_eq!(original, replayed);
+    }
+
+    #[test]
+    fn custom_systems_run_in_stage_order_each_tick() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut engine = Engine::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        for (name, stage) in [
+            ("render", Stage::PreRender),
+            ("post", Stage::PostTick),
+            ("pre", Stage::PreTick),
+        ] {
+            let log = Rc::clone(&log);
+            engine
+                .scheduler_mut()
+                .add_system(name, stage, move |_, ctx| log.borrow_mut().push((name, ctx.tick)))
+                .unwrap();
+        }
+        engine.update(FIXED_DT * 2.0 + 1e-4);
+        assert_eq!(
+            *log.borrow(),
+            [("pre", 0), ("post", 0), ("pre", 1), ("post", 1), ("render", 2)]
+        );
+    }
+
+    #[test]
+    fn custom_system_can_be_ordered_before_builtin() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+        // Zero velocities before integration: the entity must not move.
+        engine
+            .scheduler_mut()
+            .add_system("brake", Stage::Tick, |world, _| {
+                for vel in world.query_mut::<&mut Velocity>() {
+                    vel.0 = glam::Vec3::ZERO;
+                }
+            })
+            .unwrap();
+        engine.scheduler_mut().add_ordering("brake", "velocity").unwrap();
+        engine.update(FIXED_DT);
+        assert_eq!(position_of(&engine, 0), glam::Vec3::ZERO);
+    }
+
+    #[test]
+    fn disabled_builtin_is_skipped_and_not_timed() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+        assert!(engine.scheduler_mut().set_enabled("velocity", false));
+        engine.update(FIXED_DT * 3.0 + 1e-4);
+        assert_eq!(position_of(&engine, 0), glam::Vec3::ZERO);
+        assert_eq!(engine.scheduler().timing("velocity").unwrap().runs, 0);
+        assert_eq!(engine.scheduler().timing("listener").unwrap().runs, 3);
+        assert_eq!(engine.scheduler().timing("collect").unwrap().runs, 1);
+
+        engine.scheduler_mut().set_enabled("velocity", true);
+        engine.update(FIXED_DT);
+        assert!((position_of(&engine, 0).x - 1.0).abs() < 0.001);
+    }
+
+    fn component_cmd(cmd_type: CommandType, id: u32, component: u8, data: &[u8]) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0] = component;
+        payload[1..1 + data.len()].copy_from_slice(data);
+        Command { cmd_type, entity_id: id, payload }
+    }
+
+    #[test]
+    fn set_component_command_applies_in_order_with_spawn() {
+        use crate::component_registry::{COMPONENT_POSITION, COMPONENT_VELOCITY};
+
+        let mut engine = Engine::new();
+        let pos: Vec<u8> = [4.0f32, 5.0, 6.0].iter().flat_map(|f| f.to_le_bytes()).collect();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            component_cmd(CommandType::SetComponent, 0, COMPONENT_POSITION, &pos),
+            component_cmd(CommandType::RemoveComponent, 0, COMPONENT_VELOCITY, &[]),
+        ]);
+        assert_eq!(position_of(&engine, 0), glam::Vec3::new(4.0, 5.0, 6.0));
+        assert_eq!(engine.get_component(0, COMPONENT_POSITION), Some(pos));
+        assert_eq!(engine.get_component(0, COMPONENT_VELOCITY), None);
+
+        // The write reaches the GPU buffers on the next frame.
+        engine.update(0.0);
+        assert_eq!(engine.render_state.gpu_transforms()[12], 4.0);
+    }
+
+    #[test]
+    fn set_component_rejects_oversized_ring_payloads() {
+        use crate::component_registry::{COMPONENT_ROTATION, ComponentError};
+
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0)]);
+        // Rotation is 16 bytes: too large for the ring command, fine directly.
+        engine.process_commands(&[component_cmd(CommandType::SetComponent, 0, COMPONENT_ROTATION, &[0xFF; 15])]);
+        let before = engine.get_component(0, COMPONENT_ROTATION).unwrap();
+        assert_eq!(bytemuck::cast_slice::<u8, f32>(&before), [0.0, 0.0, 0.0, 1.0]);
+
+        let quat = glam::Quat::from_rotation_z(1.0);
+        engine.set_component(0, COMPONENT_ROTATION, bytemuck::bytes_of(&quat)).unwrap();
+        assert_eq!(engine.set_component(9, COMPONENT_ROTATION, &[0; 16]), Err(ComponentError::UnknownEntity));
+    }
+
+    #[test]
+    fn rejected_component_commands_raise_error_events() {
+        use crate::component_registry::{COMPONENT_EXTERNAL_ID, COMPONENT_ROTATION, ComponentError};
+        use crate::events::{EVENT_COMPONENT_ERROR, EngineEvent};
+
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            component_cmd(CommandType::SetComponent, 0, COMPONENT_ROTATION, &[0; 15]),
+            component_cmd(CommandType::SetComponent, 0, COMPONENT_EXTERNAL_ID, &[0; 4]),
+            component_cmd(CommandType::RemoveComponent, 3, 200, &[]),
+        ]);
+        engine.update(0.0);
+        let error = |entity_id, component: u8, err: ComponentError| EngineEvent {
+            entity_id,
+            kind: EVENT_COMPONENT_ERROR,
+            data: component as u32 | (err as u32) << 8,
+        };
+        assert_eq!(
+            engine.events(),
+            [
+                error(0, COMPONENT_ROTATION, ComponentError::TooLargeForCommand),
+                error(0, COMPONENT_EXTERNAL_ID, ComponentError::ReadOnly),
+                error(3, 200, ComponentError::UnknownEntity),
+            ]
+        );
+        engine.update(0.0);
+        assert!(engine.events().is_empty());
+    }
+
+    #[test]
+    fn component_commands_are_undoable() {
+        use crate::component_registry::{COMPONENT_LINEAR_DRAG, COMPONENT_POSITION, COMPONENT_VELOCITY};
+
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0), make_position_cmd(0, 1.0, 2.0, 3.0)]);
+        let pos: Vec<u8> = [4.0f32, 5.0, 6.0].iter().flat_map(|f| f.to_le_bytes()).collect();
+        let velocity = engine.get_component(0, COMPONENT_VELOCITY);
+        engine.process_commands(&[
+            component_cmd(CommandType::SetComponent, 0, COMPONENT_POSITION, &pos),
+            component_cmd(CommandType::SetComponent, 0, COMPONENT_LINEAR_DRAG, &0.5f32.to_le_bytes()),
+            component_cmd(CommandType::RemoveComponent, 0, COMPONENT_VELOCITY, &[]),
+        ]);
+        assert_eq!(engine.get_component(0, COMPONENT_VELOCITY), None);
+
+        assert!(engine.undo());
+        assert_eq!(position_of(&engine, 0), glam::Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(engine.get_component(0, COMPONENT_LINEAR_DRAG), None);
+        assert_eq!(engine.get_component(0, COMPONENT_VELOCITY), velocity);
+
+        assert!(engine.redo());
+        assert_eq!(engine.get_component(0, COMPONENT_POSITION), Some(pos));
+        assert_eq!(engine.get_component(0, COMPONENT_LINEAR_DRAG), Some(0.5f32.to_le_bytes().to_vec()));
+    }
+
+    // ── Activation / visibility ──
+
+    fn flag_cmd(cmd_type: CommandType, id: u32, on: bool, cascade: bool) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0] = u8::from(on);
+        payload[1] = u8::from(cascade);
+        Command { cmd_type, entity_id: id, payload }
+    }
+
+    fn parent_cmd(id: u32, parent: u32) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0..4].copy_from_slice(&parent.to_le_bytes());
+        Command { cmd_type: CommandType::SetParent, entity_id: id, payload }
+    }
+
+    #[test]
+    fn set_active_cascades_to_children_and_freezes_velocity() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            spawn_cmd(1),
+            spawn_cmd(2),
+            parent_cmd(1, 0),
+            velocity_cmd(1, 60.0, 0.0, 0.0),
+            velocity_cmd(2, 60.0, 0.0, 0.0),
+            flag_cmd(CommandType::SetActive, 0, false, true),
+        ]);
+        let child = engine.entity_map.get(1).unwrap();
+        let other = engine.entity_map.get(2).unwrap();
+        assert!(!engine.world.satisfies::<&crate::components::Active>(child));
+
+        engine.update(FIXED_DT);
+        let pos = |e| engine.world.get::<&crate::components::Position>(e).unwrap().0.x;
+        assert_eq!(pos(child), 0.0);
+        assert!(pos(other) > 0.0);
+    }
+
+    #[test]
+    fn set_visible_releases_and_restores_gpu_slot() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1)]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_entity_count(), 2);
+
+        engine.process_commands(&[flag_cmd(CommandType::SetVisible, 0, false, false)]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_entity_count(), 1);
+        let hidden = engine.entity_map.get(0).unwrap();
+        assert_eq!(engine.render_state.get_slot(hidden), None);
+
+        engine.process_commands(&[flag_cmd(CommandType::SetVisible, 0, true, false)]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_entity_count(), 2);
+        assert!(engine.render_state.get_slot(hidden).is_some());
+    }
+
+    #[test]
+    fn hiding_twice_releases_slot_once() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1)]);
+        engine.update(FIXED_DT);
+
+        engine.process_commands(&[
+            flag_cmd(CommandType::SetVisible, 0, false, false),
+            flag_cmd(CommandType::SetVisible, 0, false, false),
+        ]);
+        assert_eq!(engine.render_state.pending_despawns.len(), 1);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_entity_count(), 1);
+    }
+
+    #[test]
+    fn hide_then_show_in_same_frame_keeps_slot() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0)]);
+        engine.update(FIXED_DT);
+        let entity = engine.entity_map.get(0).unwrap();
+        let slot = engine.render_state.get_slot(entity);
+
+        engine.process_commands(&[
+            flag_cmd(CommandType::SetVisible, 0, false, false),
+            flag_cmd(CommandType::SetVisible, 0, true, false),
+        ]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.get_slot(entity), slot);
+        assert_eq!(engine.render_state.gpu_entity_count(), 1);
+    }
+
+    #[test]
+    fn despawn_after_hide_in_same_frame_frees_one_slot() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1)]);
+        engine.update(FIXED_DT);
+        engine.process_commands(&[
+            flag_cmd(CommandType::SetVisible, 0, false, false),
+            Command { cmd_type: CommandType::DespawnEntity, entity_id: 0, payload: [0; 16] },
+        ]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_entity_count(), 1);
+        let survivor = engine.entity_map.get(1).unwrap();
+        assert_eq!(engine.render_state.get_slot(survivor), Some(0));
+    }
+
+    #[test]
+    fn undo_restores_cascaded_visibility() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1), parent_cmd(1, 0)]);
+        engine.process_commands(&[flag_cmd(CommandType::SetVisible, 0, false, true)]);
+        let child = engine.entity_map.get(1).unwrap();
+        assert!(engine.world.satisfies::<&crate::components::Hidden>(child));
+
+        assert!(engine.undo());
+        assert!(!engine.world.satisfies::<&crate::components::Hidden>(child));
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_entity_count(), 2);
+    }
+
+    #[cfg(feature = "dev-tools")]
+    #[test]
+    fn snapshot_roundtrip_preserves_hidden() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), flag_cmd(CommandType::SetVisible, 0, false, false)]);
+        let snapshot = engine.snapshot_create();
+        assert!(engine.snapshot_restore(&snapshot));
+        let entity = engine.entity_map.get(0).unwrap();
+        assert!(engine.world.satisfies::<&crate::components::Hidden>(entity));
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_entity_count(), 0);
+    }
+
+    #[cfg(feature = "dev-tools")]
+    #[test]
+    fn snapshot_roundtrip_preserves_emitter_and_later_components() {
+        use crate::camera::Camera;
+        use crate::components::{PickLayer, RenderLayer, SortOrder, Tint};
+        use crate::particles::Emitter;
+
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0)]);
+        let entity = engine.entity_map.get(0).unwrap();
+        let emitter = Emitter { burst: 7, ..Emitter::new(3, 42) };
+        let camera = Camera { zoom: 2.5, ..Camera::new(0.0, 0.1, 100.0, 9) };
+        engine
+            .world
+            .insert(
+                entity,
+                (emitter, PickLayer(0xABCD), camera, Tint(0x8040_20FF), RenderLayer(0b1010), SortOrder(-3)),
+            )
+            .unwrap();
+
+        let snapshot = engine.snapshot_create();
+        assert!(engine.snapshot_restore(&snapshot));
+
+        let entity = engine.entity_map.get(0).unwrap();
+        assert_eq!(*engine.world.get::<&Emitter>(entity).unwrap(), emitter);
+        assert_eq!(*engine.world.get::<&PickLayer>(entity).unwrap(), PickLayer(0xABCD));
+        assert_eq!(*engine.world.get::<&Camera>(entity).unwrap(), camera);
+        assert_eq!(*engine.world.get::<&Tint>(entity).unwrap(), Tint(0x8040_20FF));
+        assert_eq!(*engine.world.get::<&RenderLayer>(entity).unwrap(), RenderLayer(0b1010));
+        assert_eq!(*engine.world.get::<&SortOrder>(entity).unwrap(), SortOrder(-3));
+    }
+
+    // ── Tags / groups ──
+
+    fn tags_cmd(cmd_type: CommandType, id: u32, mask: u32) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0..4].copy_from_slice(&mask.to_le_bytes());
+        Command { cmd_type, entity_id: id, payload }
+    }
+
+    fn group_cmd(cmd_type: CommandType, mask: u32, args: &[u8]) -> Command {
+        let mut cmd = tags_cmd(cmd_type, 0, mask);
+        cmd.payload[4..4 + args.len()].copy_from_slice(args);
+        cmd
+    }
+
+    const ENEMY: u32 = 1 << 0;
+    const UI: u32 = 1 << 1;
+
+    #[test]
+    fn tags_add_remove_and_query() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            spawn_cmd(1),
+            spawn_cmd(2),
+            tags_cmd(CommandType::AddTags, 2, ENEMY | UI),
+            tags_cmd(CommandType::AddTags, 0, ENEMY),
+            tags_cmd(CommandType::AddTags, 1, UI),
+        ]);
+        assert_eq!(engine.tagged_entities(ENEMY), vec![0, 2]);
+        assert_eq!(engine.tagged_entities(ENEMY | UI), vec![0, 1, 2]);
+
+        engine.process_commands(&[tags_cmd(CommandType::RemoveTags, 2, ENEMY), tags_cmd(CommandType::RemoveTags, 1, UI)]);
+        assert_eq!(engine.tagged_entities(ENEMY), vec![0]);
+        assert_eq!(engine.tagged_entities(UI), vec![2]);
+        // Clearing the last tag drops the component.
+        let e1 = engine.entity_map.get(1).unwrap();
+        assert!(!engine.world.satisfies::<&crate::components::Tags>(e1));
+    }
+
+    #[test]
+    fn group_commands_apply_to_tagged_entities_only() {
+        use crate::components::{Position, TextureLayerIndex};
+
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            spawn_cmd(1),
+            spawn_cmd(2),
+            make_position_cmd(0, 1.0, 2.0, 3.0),
+            tags_cmd(CommandType::AddTags, 0, ENEMY),
+            tags_cmd(CommandType::AddTags, 1, ENEMY),
+        ]);
+        let mut delta = [0u8; 12];
+        delta[0..4].copy_from_slice(&10.0f32.to_le_bytes());
+        delta[8..12].copy_from_slice(&(-1.0f32).to_le_bytes());
+        engine.process_commands(&[
+            group_cmd(CommandType::TranslateGroup, ENEMY, &delta),
+            group_cmd(CommandType::SetGroupTextureLayer, ENEMY, &7u32.to_le_bytes()),
+            group_cmd(CommandType::SetGroupVisible, ENEMY, &[0]),
+        ]);
+        let pos = |id| engine.world.get::<&Position>(engine.entity_map.get(id).unwrap()).unwrap().0;
+        assert_eq!(pos(0), glam::Vec3::new(11.0, 2.0, 2.0));
+        assert_eq!(pos(1), glam::Vec3::new(10.0, 0.0, -1.0));
+        assert_eq!(pos(2), glam::Vec3::ZERO);
+        let layer = |id| engine.world.get::<&TextureLayerIndex>(engine.entity_map.get(id).unwrap()).unwrap().0;
+        assert_eq!((layer(0), layer(1), layer(2)), (7, 7, 0));
+
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_entity_count(), 1);
+    }
+
+    #[test]
+    fn despawn_group_is_undoable_with_tags() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[
+            spawn_cmd(0),
+            spawn_cmd(1),
+            spawn_cmd(2),
+            tags_cmd(CommandType::AddTags, 0, ENEMY),
+            tags_cmd(CommandType::AddTags, 2, ENEMY | UI),
+        ]);
+        engine.process_commands(&[group_cmd(CommandType::DespawnGroup, ENEMY, &[])]);
+        engine.update(FIXED_DT);
+        assert!(engine.entity_map.get(0).is_none() && engine.entity_map.get(2).is_none());
+        assert_eq!(engine.render_state.gpu_entity_count(), 1);
+
+        assert!(engine.undo());
+        assert_eq!(engine.tagged_entities(ENEMY), vec![0, 2]);
+        assert_eq!(engine.tagged_entities(UI), vec![2]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_entity_count(), 3);
+    }
+
+    #[cfg(feature = "dev-tools")]
+    #[test]
+    fn snapshot_roundtrip_preserves_tags() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1), tags_cmd(CommandType::AddTags, 1, UI)]);
+        let snapshot = engine.snapshot_create();
+        engine.process_commands(&[tags_cmd(CommandType::AddTags, 0, UI)]);
+        assert!(engine.snapshot_restore(&snapshot));
+        assert_eq!(engine.tagged_entities(UI), vec![1]);
+    }
+
+    // ── Motion ──
+
+    fn f32s_cmd(cmd_type: CommandType, id: u32, values: &[f32]) -> Command {
+        let mut payload = [0u8; 16];
+        for (i, v) in values.iter().enumerate() {
+            payload[i * 4..i * 4 + 4].copy_from_slice(&v.to_le_bytes());
+        }
+        Command { cmd_type, entity_id: id, payload }
+    }
+
+    #[test]
+    fn spinning_entity_is_reuploaded_every_frame() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            f32s_cmd(CommandType::SetAngularVelocity, 0, &[0.0, 0.0, 6.0]),
+            f32s_cmd(CommandType::SetDrag, 0, &[0.0, 0.0]),
+        ]);
+        engine.update(FIXED_DT);
+        let first = engine.render_state.gpu_transforms()[0];
+        engine.update(FIXED_DT);
+        let second = engine.render_state.gpu_transforms()[0];
+        // Column 0 is (cos θ, sin θ, ...): the angle keeps growing with zero velocity.
+        assert!(first < 1.0 && second < first, "{first} {second}");
+    }
+
+    #[test]
+    fn rollback_restores_motion_components() {
+        use crate::components::{AngularVelocity, Rotation};
+
+        let mut engine = Engine::new();
+        engine.enable_rollback(crate::rollback::DEFAULT_ROLLBACK_CAPACITY, 0);
+        engine.process_commands(&[
+            spawn_cmd(0),
+            velocity_cmd(0, 3.0, 0.0, 0.0),
+            f32s_cmd(CommandType::SetAngularVelocity, 0, &[0.0, 0.0, 2.0]),
+            f32s_cmd(CommandType::SetAcceleration, 0, &[0.0, 1.0, 0.0]),
+            f32s_cmd(CommandType::SetDrag, 0, &[0.5, 0.5]),
+        ]);
+        for _ in 0..10 {
+            engine.update(FIXED_DT);
+        }
+        let entity = engine.entity_map.get(0).unwrap();
+        let read = |engine: &Engine| {
+            let w = engine.world.get::<&AngularVelocity>(entity).unwrap().0;
+            let r = engine.world.get::<&Rotation>(entity).unwrap().0;
+            (engine.state_hash(HASH_ALL), w, r)
+        };
+        let at_10 = read(&engine);
+
+        assert!(engine.rollback_to(4));
+        engine.resimulate(6);
+        assert_eq!(read(&engine), at_10);
+    }
+
+    #[test]
+    fn undo_restores_absent_motion_as_zero() {
+        use crate::components::Acceleration;
+
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0)]);
+        engine.process_commands(&[f32s_cmd(CommandType::SetAcceleration, 0, &[0.0, -9.8, 0.0])]);
+        assert!(engine.undo());
+        let entity = engine.entity_map.get(0).unwrap();
+        assert_eq!(engine.world.get::<&Acceleration>(entity).unwrap().0, glam::Vec3::ZERO);
+        engine.update(FIXED_DT);
+        let pos = engine.world.get::<&crate::components::Position>(entity).unwrap().0;
+        assert_eq!(pos, glam::Vec3::ZERO);
+    }
+
+    fn tween_cmd(id: u32, target: u8, field: u8, to: f32, duration: u16, flags: u8) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0] = target;
+        payload[1] = field;
+        payload[3] = flags;
+        payload[8..12].copy_from_slice(&to.to_le_bytes());
+        payload[12..14].copy_from_slice(&duration.to_le_bytes());
+        Command { cmd_type: CommandType::StartTween, entity_id: id, payload }
+    }
+
+    #[test]
+    fn tween_completes_with_event_and_uploads() {
+        use crate::events::{EVENT_TWEEN_COMPLETE, EngineEvent};
+        use crate::tween::TARGET_POSITION;
+
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), tween_cmd(0, TARGET_POSITION, 1, 8.0, 4, 0)]);
+        engine.update(FIXED_DT * 2.0);
+        assert!(engine.events().is_empty());
+        // Column 3 holds the translation: y is halfway after 2 of 4 ticks.
+        assert_eq!(engine.render_state.gpu_transforms()[13], 4.0);
+
+        engine.update(FIXED_DT * 2.0);
+        let entity = engine.entity_map.get(0).unwrap();
+        assert_eq!(engine.world.get::<&crate::components::Position>(entity).unwrap().0.y, 8.0);
+        assert_eq!(
+            engine.events(),
+            [EngineEvent { entity_id: 0, kind: EVENT_TWEEN_COMPLETE, data: TARGET_POSITION as u32 | 1 << 8 }]
+        );
+
+        // Events only live for one frame.
+        engine.update(FIXED_DT);
+        assert!(engine.events().is_empty());
+    }
+
+    #[test]
+    fn stop_tweens_freezes_channel() {
+        use crate::tween::{TARGET_ALL, TARGET_POSITION, TWEEN_LOOP, Tweens};
+
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), tween_cmd(0, TARGET_POSITION, 0, 10.0, 10, TWEEN_LOOP)]);
+        engine.update(FIXED_DT * 5.0);
+        let mut payload = [0u8; 16];
+        payload[0] = TARGET_ALL;
+        engine.process_commands(&[Command { cmd_type: CommandType::StopTweens, entity_id: 0, payload }]);
+        engine.update(FIXED_DT * 3.0);
+        let entity = engine.entity_map.get(0).unwrap();
+        assert_eq!(engine.world.get::<&crate::components::Position>(entity).unwrap().0.x, 5.0);
+        assert!(!engine.world.satisfies::<&Tweens>(entity));
+    }
+
+    #[test]
+    fn tweens_survive_rollback() {
+        use crate::components::Scale;
+        use crate::tween::TARGET_SCALE;
+
+        let mut engine = Engine::new();
+        engine.enable_rollback(crate::rollback::DEFAULT_ROLLBACK_CAPACITY, 0);
+        engine.process_commands(&[spawn_cmd(0), tween_cmd(0, TARGET_SCALE, 0, 3.0, 10, 0)]);
+        for _ in 0..6 {
+            engine.update(FIXED_DT);
+        }
+        let entity = engine.entity_map.get(0).unwrap();
+        let read = |engine: &Engine| {
+            let e = engine.entity_map.get(0).unwrap();
+            engine.world.get::<&Scale>(e).unwrap().0.x
+        };
+        let at_6 = read(&engine);
+        assert!(engine.rollback_to(2));
+        engine.resimulate(4);
+        assert_eq!(read(&engine), at_6);
+        assert!(engine.world.satisfies::<&crate::tween::Tweens>(entity));
+    }
+
+    fn play_sprite_cmd(id: u32, clip: u32, speed: f32, mode: u8) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0..4].copy_from_slice(&clip.to_le_bytes());
+        payload[4..8].copy_from_slice(&speed.to_le_bytes());
+        payload[8] = mode;
+        Command { cmd_type: CommandType::PlaySpriteAnimation, entity_id: id, payload }
+    }
+
+    #[test]
+    fn sprite_animation_uploads_frames_and_finishes() {
+        use crate::events::{EVENT_SPRITE_FINISHED, EngineEvent};
+        use crate::sprite_animation::SPRITE_ONCE;
+
+        let mut engine = Engine::new();
+        let clip = engine.register_sprite_clip(&[0x0001_0000, 0x0001_0001], &[2, 2], &[]).unwrap();
+        engine.process_commands(&[spawn_cmd(0), play_sprite_cmd(0, clip, 1.0, SPRITE_ONCE)]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_tex_indices()[0], 0x0001_0000);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_tex_indices()[0], 0x0001_0001);
+        assert!(engine.events().is_empty());
+        engine.update(FIXED_DT * 2.0);
+        assert_eq!(engine.events(), [EngineEvent { entity_id: 0, kind: EVENT_SPRITE_FINISHED, data: clip }]);
+        assert_eq!(engine.render_state.gpu_tex_indices()[0], 0x0001_0001);
+    }
+
+    #[test]
+    fn sprite_animation_speed_and_stop_commands() {
+        use crate::components::TextureLayerIndex;
+        use crate::sprite_animation::{SPRITE_LOOP, SpriteAnimation};
+
+        let mut engine = Engine::new();
+        let clip = engine.register_sprite_clip(&[1, 2, 3], &[1, 1, 1], &[]).unwrap();
+        engine.process_commands(&[spawn_cmd(0), play_sprite_cmd(0, clip, 1.0, SPRITE_LOOP)]);
+        engine.update(FIXED_DT);
+        let entity = engine.entity_map.get(0).unwrap();
+        assert_eq!(engine.world.get::<&TextureLayerIndex>(entity).unwrap().0, 2);
+
+        engine.process_commands(&[f32s_cmd(CommandType::SetSpriteAnimationSpeed, 0, &[0.0])]);
+        engine.update(FIXED_DT * 3.0);
+        assert_eq!(engine.world.get::<&TextureLayerIndex>(entity).unwrap().0, 2, "paused");
+
+        engine.process_commands(&[Command { cmd_type: CommandType::StopSpriteAnimation, entity_id: 0, payload: [0; 16] }]);
+        assert!(!engine.world.satisfies::<&SpriteAnimation>(entity));
+        assert_eq!(engine.world.get::<&TextureLayerIndex>(entity).unwrap().0, 2);
+    }
+
+    #[test]
+    fn sprite_animation_survives_rollback() {
+        use crate::components::TextureLayerIndex;
+        use crate::sprite_animation::SPRITE_PING_PONG;
+
+        let mut engine = Engine::new();
+        engine.enable_rollback(crate::rollback::DEFAULT_ROLLBACK_CAPACITY, 0);
+        let clip = engine.register_sprite_clip(&[1, 2, 3, 4], &[1, 2, 1, 3], &[]).unwrap();
+        engine.process_commands(&[spawn_cmd(0), play_sprite_cmd(0, clip, 0.5, SPRITE_PING_PONG)]);
+        for _ in 0..12 {
+            engine.update(FIXED_DT);
+        }
+        let read = |engine: &Engine| {
+            let e = engine.entity_map.get(0).unwrap();
+            engine.world.get::<&TextureLayerIndex>(e).unwrap().0
+        };
+        let at_12 = read(&engine);
+        assert!(engine.rollback_to(5));
+        engine.resimulate(7);
+        assert_eq!(read(&engine), at_12);
+    }
+
+    fn lifetime_cmd(id: u32, ticks: u32, cascade: bool) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0..4].copy_from_slice(&ticks.to_le_bytes());
+        payload[4] = cascade as u8;
+        Command { cmd_type: CommandType::SetLifetime, entity_id: id, payload }
+    }
+
+    #[test]
+    fn lifetime_despawns_with_children_and_emits_event() {
+        use crate::events::{EVENT_LIFETIME_EXPIRED, EngineEvent};
+
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            spawn_cmd(1),
+            spawn_cmd(2),
+            parent_cmd(1, 0),
+            parent_cmd(2, 1),
+            lifetime_cmd(0, 3, true),
+        ]);
+        engine.update(FIXED_DT * 2.0);
+        assert_eq!(engine.render_state.gpu_entity_count(), 3);
+        assert!(engine.events().is_empty());
+
+        engine.update(FIXED_DT);
+        assert_eq!(engine.events(), [EngineEvent { entity_id: 0, kind: EVENT_LIFETIME_EXPIRED, data: 2 }]);
+        assert!((0..3).all(|id| engine.entity_map.get(id).is_none()));
+        assert_eq!(engine.render_state.gpu_entity_count(), 0);
+        assert_eq!(engine.world.len(), 0);
+    }
+
+    #[test]
+    fn lifetimes_expiring_together_despawn_each_entity_once() {
+        let mut engine = Engine::new();
+        let mut cmds: Vec<Command> = (0..64).map(spawn_cmd).collect();
+        for id in 1..64 {
+            cmds.push(parent_cmd(id, id - 1));
+        }
+        cmds.extend((0..64).map(|id| lifetime_cmd(id, 1, true)));
+        engine.process_commands(&cmds);
+        engine.update(FIXED_DT);
+
+        // Entities already despawned with an expired ancestor report nothing.
+        let expired: std::collections::HashSet<u32> = engine.events().iter().map(|e| e.entity_id).collect();
+        assert_eq!(expired.len(), engine.events().len());
+        assert!(expired.contains(&0));
+        assert_eq!(engine.world.len(), 0);
+        assert_eq!(engine.render_state.gpu_entity_count(), 0);
+    }
+
+    #[test]
+    fn lifetime_without_cascade_keeps_children_and_can_be_cleared() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            spawn_cmd(1),
+            spawn_cmd(2),
+            parent_cmd(1, 0),
+            lifetime_cmd(0, 1, false),
+            lifetime_cmd(2, 1, false),
+            lifetime_cmd(2, 0, false),
+        ]);
+        engine.update(FIXED_DT);
+        assert!(engine.entity_map.get(0).is_none());
+        assert!(engine.entity_map.get(1).is_some());
+        assert!(engine.entity_map.get(2).is_some(), "cleared lifetime never expires");
+        assert_eq!(engine.render_state.gpu_entity_count(), 2);
+    }
+
+    #[test]
+    fn lifetime_expiry_is_undone_by_rollback() {
+        let mut engine = Engine::new();
+        engine.enable_rollback(crate::rollback::DEFAULT_ROLLBACK_CAPACITY, 0);
+        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 1.0, 0.0, 0.0), lifetime_cmd(0, 4, false)]);
+        for _ in 0..6 {
+            engine.update(FIXED_DT);
+        }
+        assert!(engine.entity_map.get(0).is_none());
+        assert!(engine.rollback_to(2));
+        assert!(engine.entity_map.get(0).is_some());
+        engine.resimulate(1);
+        assert!(engine.entity_map.get(0).is_some());
+        engine.resimulate(1);
+        assert!(engine.entity_map.get(0).is_none());
+    }
+
+    fn schedule_cmd(tick: u64, relative: bool) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0..8].copy_from_slice(&tick.to_le_bytes());
+        payload[8] = relative as u8;
+        Command { cmd_type: CommandType::ScheduleAt, entity_id: 0, payload }
+    }
+
+    #[test]
+    fn scheduled_command_applies_at_start_of_target_tick() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0)]);
+        engine.update(FIXED_DT * 2.0);
+        assert_eq!(engine.tick_count(), 2);
+        // Relative to the next tick (2): released at the start of tick 5.
+        engine.process_commands(&[schedule_cmd(3, true), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+        assert_eq!(engine.scheduled_commands().len(), 1);
+
+        engine.update(FIXED_DT * 3.0);
+        let entity = engine.entity_map.get(0).unwrap();
+        assert_eq!(engine.world.get::<&crate::components::Position>(entity).unwrap().0.x, 0.0);
+        engine.update(FIXED_DT);
+        // Applied before tick 5's velocity integration.
+        let x = engine.world.get::<&crate::components::Position>(entity).unwrap().0.x;
+        assert!((x - 1.0).abs() < 1e-5, "{x}");
+        assert!(engine.scheduled_commands().is_empty());
+    }
+
+    #[test]
+    fn scheduled_despawn_replays_after_rollback() {
+        let mut engine = Engine::new();
+        engine.enable_rollback(crate::rollback::DEFAULT_ROLLBACK_CAPACITY, 0);
+        engine.process_commands(&[
+            spawn_cmd(0),
+            schedule_cmd(4, false),
+            Command { cmd_type: CommandType::DespawnEntity, entity_id: 0, payload: [0; 16] },
+        ]);
+        assert!(engine.entity_map.get(0).is_some());
+        for _ in 0..6 {
+            engine.update(FIXED_DT);
+        }
+        assert!(engine.entity_map.get(0).is_none());
+
+        assert!(engine.rollback_to(3));
+        assert_eq!(engine.scheduled_commands().len(), 1);
+        engine.resimulate(1);
+        assert!(engine.entity_map.get(0).is_some());
+        engine.resimulate(1);
+        assert!(engine.entity_map.get(0).is_none());
+    }
+
+    #[test]
+    fn rollback_restores_dangling_schedule_at() {
+        let mut engine = Engine::new();
+        engine.enable_rollback(crate::rollback::DEFAULT_ROLLBACK_CAPACITY, 0);
+        let despawn = Command { cmd_type: CommandType::DespawnEntity, entity_id: 0, payload: [0; 16] };
+        // The ScheduleAt ends the batch and waits for the next one.
+        engine.process_commands(&[spawn_cmd(0), schedule_cmd(4, false)]);
+        engine.update(FIXED_DT);
+        engine.process_commands(std::slice::from_ref(&despawn));
+        assert_eq!(engine.scheduled_commands().len(), 1);
+
+        assert!(engine.rollback_to(0));
+        assert!(engine.scheduled_commands().is_empty());
+        engine.process_commands(&[despawn]);
+        assert_eq!(engine.scheduled_commands().len(), 1);
+        assert!(engine.entity_map.get(0).is_some());
+    }
+
+    #[test]
+    fn undo_does_not_consume_dangling_schedule_at() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0)]);
+        engine.process_commands(&[make_position_cmd(0, 1.0, 0.0, 0.0), schedule_cmd(4, false)]);
+        assert!(engine.undo());
+        assert_eq!(position_of(&engine, 0), glam::Vec3::ZERO);
+        assert!(engine.scheduled_commands().is_empty());
+
+        engine.process_commands(&[make_position_cmd(0, 2.0, 0.0, 0.0)]);
+        assert_eq!(engine.scheduled_commands().len(), 1);
+        assert_eq!(position_of(&engine, 0), glam::Vec3::ZERO);
+    }
+
+    #[test]
+    fn scheduled_commands_are_not_recorded_in_history() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0)]);
+        engine.process_commands(&[schedule_cmd(0, true), make_position_cmd(0, 4.0, 0.0, 0.0)]);
+        assert_eq!(engine.history().unwrap().undo_len(), 1);
+    }
+
+    #[cfg(feature = "dev-tools")]
+    #[test]
+    fn scheduled_commands_survive_snapshot() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_cmd(0), schedule_cmd(3, false), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+        engine.update(FIXED_DT);
+        let snapshot = engine.snapshot_create();
+        engine.process_commands(&[schedule_cmd(0, true), velocity_cmd(0, 0.0, 60.0, 0.0)]);
+        assert_eq!(engine.scheduled_commands().len(), 2);
+
+        assert!(engine.snapshot_restore(&snapshot));
+        assert_eq!(engine.scheduled_commands().len(), 1);
+        engine.update(FIXED_DT * 3.0);
+        let entity = engine.entity_map.get(0).unwrap();
+        let pos = engine.world.get::<&crate::components::Position>(entity).unwrap().0;
+        assert!((pos.x - 1.0).abs() < 1e-5 && pos.y == 0.0, "{pos}");
+    }
+
+    fn emitter_config(rate: f32, cone_angle: f32) -> crate::particles::EmitterConfig {
+        crate::particles::EmitterConfig {
+            rate,
+            lifetime_min: 0.5,
+            lifetime_max: 1.0,
+            speed_min: 1.0,
+            speed_max: 3.0,
+            direction: [0.0, 1.0, 0.0],
+            cone_angle,
+            gravity: [0.0, -9.8, 0.0],
+            size: [1.0; 4],
+            color: [[1.0; 4]; 4],
+            max_particles: 256,
+            flags: 0,
+        }
+    }
+
+    fn emitter_cmd(cmd_type: CommandType, id: u32, a: u32, b: u32) -> Command {
+        let mut payload = [0u8; 16];
+        payload[0..4].copy_from_slice(&a.to_le_bytes());
+        payload[4..8].copy_from_slice(&b.to_le_bytes());
+        Command { cmd_type, entity_id: id, payload }
+    }
+
+    #[test]
+    fn emitter_commands_drive_particle_buffers() {
+        let mut engine = Engine::new();
+        let config = engine.register_emitter_config(bytemuck::bytes_of(&emitter_config(0.0, 0.3))).unwrap();
+        assert!(engine.register_emitter_config(&[0; 4]).is_err());
+        engine.process_commands(&[
+            spawn_cmd(0),
+            make_position_cmd(0, 5.0, 0.0, 0.0),
+            emitter_cmd(CommandType::AttachEmitter, 0, config, 9),
+            emitter_cmd(CommandType::EmitterBurst, 0, 4, 0),
+        ]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.particles().count(), 4);
+        assert_eq!(engine.particles().gpu_positions().len(), 16);
+        assert_eq!(engine.particles().gpu_colors().len(), 16);
+
+        // 60/s at 60 Hz: one per tick.
+        engine.process_commands(&[emitter_cmd(CommandType::SetEmitterRate, 0, 60f32.to_bits(), 0)]);
+        engine.update(FIXED_DT * 3.0);
+        assert_eq!(engine.particles().count(), 7);
+
+        // World-space particles outlive their emitter.
+        engine.process_commands(&[emitter_cmd(CommandType::DetachEmitter, 0, 0, 0)]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.particles().count(), 7);
+    }
+
+    #[test]
+    fn particles_replay_after_rollback() {
+        let mut engine = Engine::new();
+        engine.enable_rollback(crate::rollback::DEFAULT_ROLLBACK_CAPACITY, 0);
+        let config = engine.register_emitter_config(bytemuck::bytes_of(&emitter_config(90.0, 1.2))).unwrap();
+        engine.process_commands(&[spawn_cmd(0), emitter_cmd(CommandType::AttachEmitter, 0, config, 3)]);
+        for _ in 0..20 {
+            engine.update(FIXED_DT);
+        }
+        let at_20 = engine.particles().particles().clone();
+        assert!(!at_20.is_empty());
+        assert!(engine.rollback_to(8));
+        assert_ne!(*engine.particles().particles(), at_20);
+        engine.resimulate(12);
+        assert_eq!(*engine.particles().particles(), at_20);
+    }
+
+    #[cfg(feature = "dev-tools")]
+    #[test]
+    fn emitter_survives_snapshot_without_particles() {
+        let mut engine = Engine::new();
+        let config = engine.register_emitter_config(bytemuck::bytes_of(&emitter_config(60.0, 0.0))).unwrap();
+        engine.process_commands(&[spawn_cmd(0), emitter_cmd(CommandType::AttachEmitter, 0, config, 1)]);
+        engine.update(FIXED_DT * 5.0);
+        let snapshot = engine.snapshot_create();
+        assert!(engine.snapshot_restore(&snapshot));
+        assert!(engine.particles().particles().is_empty());
+        let e = engine.entity_map.get(0).unwrap();
+        let emitter = *engine.world.get::<&crate::particles::Emitter>(e).unwrap();
+        assert_eq!((emitter.config, emitter.alive), (config, 0));
+        engine.update(FIXED_DT);
+        assert_eq!(engine.particles().particles().len(), 1);
+    }
+
+    #[test]
+    fn spatial_index_tracks_moves_and_despawns() {
+        use glam::Vec2;
+
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            spawn_cmd(1),
+            spawn_cmd(2),
+            make_position_cmd(1, 100.0, 0.0, 0.0),
+            make_position_cmd(2, 0.0, 100.0, 5.0),
+        ]);
+        engine.update(FIXED_DT);
+        // Enabling indexes existing entities.
+        engine.enable_spatial_index(16.0);
+        let spatial = engine.spatial_index_mut().unwrap();
+        assert_eq!(spatial.len(), 3);
+        assert_eq!(spatial.query_circle(Vec2::new(95.0, 0.0), 10.0), [1]);
+        assert_eq!(spatial.query_knn(Vec2::new(10.0, 90.0), 2), [2, 0]);
+
+        engine.process_commands(&[
+            make_position_cmd(0, 100.0, 10.0, 0.0),
+            Command { cmd_type: CommandType::DespawnEntity, entity_id: 2, payload: [0; 16] },
+        ]);
+        engine.update(FIXED_DT);
+        let spatial = engine.spatial_index_mut().unwrap();
+        assert_eq!(spatial.len(), 2);
+        assert_eq!(spatial.query_rect(Vec2::new(90.0, -5.0), Vec2::new(110.0, 20.0)), [0, 1]);
+        assert_eq!(spatial.query_ray(Vec2::new(100.0, -50.0), Vec2::Y, 100.0), [1, 0]);
+    }
+
+    #[test]
+    fn pick_orders_by_depth_and_respects_quad_extents_and_layers() {
+        let rot = glam::Quat::from_rotation_z(std::f32::consts::FRAC_PI_2).to_array();
+        let layer = |id: u32, mask: u32| {
+            let mut payload = [0u8; 16];
+            payload[0..4].copy_from_slice(&mask.to_le_bytes());
+            Command { cmd_type: CommandType::SetPickLayer, entity_id: id, payload }
+        };
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            spawn_cmd(1),
+            spawn_cmd(2),
+            // 4×1 quad turned upright: x in ±0.5, y in ±2.
+            f32s_cmd(CommandType::SetScale, 0, &[4.0, 1.0, 1.0]),
+            f32s_cmd(CommandType::SetRotation, 0, &rot),
+            make_position_cmd(1, 0.0, 1.5, 0.0),
+            make_position_cmd(2, 0.0, 1.5, 0.0),
+            f32s_cmd(CommandType::SetDepth, 1, &[5.0]),
+            f32s_cmd(CommandType::SetDepth, 2, &[5.0]),
+            flag_cmd(CommandType::SetTransparent, 2, true, false),
+        ]);
+        engine.update(FIXED_DT);
+
+        assert_eq!(engine.pick_all(0.0, 1.5, u32::MAX), [0, 2, 1]);
+        assert_eq!(engine.pick(0.0, 1.5, u32::MAX), Some(0));
+        // Inside the unrotated extents only.
+        assert_eq!(engine.pick(1.0, 0.0, u32::MAX), None);
+
+        engine.process_commands(&[layer(0, 0b10)]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.pick_all(0.0, 1.5, crate::components::PICK_LAYER_DEFAULT), [2, 1]);
+        assert_eq!(engine.pick_all(0.0, 1.5, 0b10), [0]);
+
+        assert_eq!(engine.pick_rect([-1.0, 1.2], [1.0, 1.3], u32::MAX, false), [0, 2, 1]);
+        assert_eq!(engine.pick_rect([-1.0, 0.9], [1.0, 2.1], u32::MAX, true), [2, 1]);
+        assert_eq!(engine.picker().results(), [2, 1]);
     }
 
     #[test]
-    fn engine_processes_commands_and_ticks() {
+    fn update_culls_each_view_into_visible_slots() {
+        let vp = glam::Mat4::orthographic_rh(-10.0, 10.0, -10.0, 10.0, 0.1, 100.0);
         let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            spawn_cmd(1),
+            spawn_cmd(2),
+            make_position_cmd(0, 0.0, 0.0, -5.0),
+            make_position_cmd(1, 50.0, 0.0, -5.0),
+            make_position_cmd(2, -9.0, 0.0, -5.0),
+        ]);
+        assert!(engine.render_state.set_cull_view(0, &vp.to_cols_array()));
+        let far = vp * glam::Mat4::from_translation(glam::Vec3::new(-50.0, 0.0, 0.0));
+        assert!(engine.render_state.set_cull_view(1, &far.to_cols_array()));
+        engine.update(FIXED_DT);
 
-        // Spawn entity and set velocity.
-        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+        let ids = |engine: &Engine, view: usize| -> Vec<u32> {
+            let rs = &engine.render_state;
+            rs.visible_slots(view).iter().map(|&s| rs.gpu_entity_ids()[s as usize]).collect()
+        };
+        assert_eq!(ids(&engine, 0), [0, 2]);
+        assert_eq!(ids(&engine, 1), [1]);
+        assert_eq!(engine.render_state.visible_count(0), 2);
 
-        // Run for exactly 1 fixed tick (1/60th second).
+        engine.process_commands(&[make_position_cmd(2, -30.0, 0.0, -5.0)]);
         engine.update(FIXED_DT);
+        assert_eq!(ids(&engine, 0), [0]);
 
-        let entity = engine.entity_map.get(0).unwrap();
-        let pos = engine.world.get::<&crate::components::Position>(entity).unwrap();
-        assert!((pos.0.x - 1.0).abs() < 0.001);
+        engine.render_state.clear_cull_views();
+        engine.update(FIXED_DT);
+        assert!(engine.render_state.visible_slots(0).is_empty());
+    }
+
+    /// Ortho camera on entity 0 with a 100×100 viewport.
+    fn camera_cmds(seed: u32) -> [Command; 2] {
+        [
+            f32s_cmd(CommandType::AttachCamera, 0, &[0.0, -1.0, 1000.0, f32::from_bits(seed)]),
+            f32s_cmd(CommandType::SetCameraViewport, 0, &[0.0, 0.0, 100.0, 100.0]),
+        ]
     }
 
     #[test]
-    fn fixed_timestep_accumulates() {
+    fn camera_follows_target_within_bounds_and_exports_view_projection() {
+        use crate::components::Position;
+        use glam::Vec3;
+
         let mut engine = Engine::new();
-        engine.process_commands(&[spawn_cmd(0), velocity_cmd(0, 60.0, 0.0, 0.0)]);
+        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1), make_position_cmd(1, 50.0, 0.0, 0.0)]);
+        engine.process_commands(&camera_cmds(0));
+        engine.process_commands(&[f32s_cmd(CommandType::SetCameraFollow, 0, &[f32::from_bits(1), 0.0, 10.0, 10.0])]);
+        engine.update(FIXED_DT);
 
-        // Run for half a tick — should not advance physics.
-        engine.update(FIXED_DT * 0.5);
-        assert_eq!(engine.tick_count(), 0);
+        // Snapped to the deadzone edge: 10 units left of the target.
+        let eye = |engine: &Engine| engine.world.get::<&Position>(engine.entity_map.get(0).unwrap()).unwrap().0;
+        assert_eq!(eye(&engine), Vec3::new(40.0, 0.0, 0.0));
+        assert_eq!(engine.cameras().ids(), [0]);
+        let vp = glam::Mat4::from_cols_slice(engine.cameras().view_projection(0).unwrap());
+        assert!((vp.project_point3(Vec3::new(50.0, 0.0, 0.0)).x - 0.2).abs() < 1e-6);
+        assert_eq!(engine.cameras().data()[16..20], [0.0, 0.0, 100.0, 100.0]);
 
-        // Run for another half — now one full tick should fire.
-        engine.update(FIXED_DT * 0.5);
-        assert_eq!(engine.tick_count(), 1);
+        // Half the view is 50 wide, so bounds of ±100 stop the eye at 50.
+        engine.process_commands(&[
+            f32s_cmd(CommandType::SetCameraBounds, 0, &[-100.0, -100.0, 100.0, 100.0]),
+            make_position_cmd(1, 200.0, 0.0, 0.0),
+        ]);
+        engine.update(FIXED_DT);
+        assert_eq!(eye(&engine).x, 50.0);
+
+        engine.process_commands(&[
+            f32s_cmd(CommandType::SetCameraFollow, 0, &[f32::from_bits(1), 10.0, 0.0, 0.0]),
+            make_position_cmd(1, 0.0, 0.0, 0.0),
+        ]);
+        engine.update(FIXED_DT);
+        let expected = 50.0 - 50.0 * (1.0 - (-10.0 * FIXED_DT).exp());
+        assert!((eye(&engine).x - expected).abs() < 1e-4);
+
+        engine.process_commands(&[f32s_cmd(CommandType::DetachCamera, 0, &[])]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.cameras().count(), 0);
     }
 
     #[test]
-    fn spiral_of_death_capped() {
+    fn camera_shake_replays_after_rollback() {
         let mut engine = Engine::new();
-        // Pass a huge dt — should be capped to 10 ticks max.
-        engine.update(100.0);
-        assert!(engine.tick_count() <= 10);
+        engine.enable_rollback(crate::rollback::DEFAULT_ROLLBACK_CAPACITY, 0);
+        engine.process_commands(&[spawn_cmd(0)]);
+        engine.process_commands(&camera_cmds(11));
+        engine.process_commands(&[f32s_cmd(CommandType::AddCameraTrauma, 0, &[0.8, 0.5, 6.0, 0.1])]);
+        for _ in 0..20 {
+            engine.update(FIXED_DT);
+        }
+        let at_20 = engine.cameras().data().to_vec();
+        let camera = *engine.world.get::<&crate::camera::Camera>(engine.entity_map.get(0).unwrap()).unwrap();
+        assert_ne!(camera.shake, [0.0; 3]);
+        assert!(engine.rollback_to(8));
+        engine.resimulate(12);
+        assert_eq!(engine.cameras().data(), at_20);
+        let e = engine.entity_map.get(0).unwrap();
+        assert_eq!(*engine.world.get::<&crate::camera::Camera>(e).unwrap(), camera);
     }
 
+    #[cfg(feature = "dev-tools")]
     #[test]
-    fn model_matrix_updated_after_tick() {
+    fn camera_survives_snapshot() {
         let mut engine = Engine::new();
-        let mut pos_cmd = Command {
-            cmd_type: CommandType::SetPosition,
-            entity_id: 0,
-            payload: [0; 16],
-        };
-        pos_cmd.payload[0..4].copy_from_slice(&5.0f32.to_le_bytes());
-        pos_cmd.payload[4..8].copy_from_slice(&10.0f32.to_le_bytes());
-        pos_cmd.payload[8..12].copy_from_slice(&15.0f32.to_le_bytes());
-
-        engine.process_commands(&[spawn_cmd(0), pos_cmd]);
+        engine.process_commands(&[spawn_cmd(0)]);
+        engine.process_commands(&camera_cmds(3));
+        engine.process_commands(&[f32s_cmd(CommandType::AddCameraTrauma, 0, &[1.0, 0.1, 6.0, 0.1])]);
+        engine.update(FIXED_DT * 3.0);
+        let snapshot = engine.snapshot_create();
+        let mut restored = Engine::new();
+        assert!(restored.snapshot_restore(&snapshot));
         engine.update(FIXED_DT);
-
-        let entity = engine.entity_map.get(0).unwrap();
-        let matrix = engine.world.get::<&crate::components::ModelMatrix>(entity).unwrap();
-        assert!((matrix.0[12] - 5.0).abs() < 0.001);
-        assert!((matrix.0[13] - 10.0).abs() < 0.001);
-        assert!((matrix.0[14] - 15.0).abs() < 0.001);
+        restored.update(FIXED_DT);
+        assert_eq!(restored.cameras().data(), engine.cameras().data());
     }
 
     #[test]
-    fn render_state_collected_after_update() {
+    fn set_tint_reaches_soa_and_staging_and_undoes_to_white() {
+        use crate::components::TINT_WHITE;
+
+        let tint = |id: u32, rgba: u32| {
+            let mut payload = [0u8; 16];
+            payload[0..4].copy_from_slice(&rgba.to_le_bytes());
+            Command { cmd_type: CommandType::SetTint, entity_id: id, payload }
+        };
         let mut engine = Engine::new();
-        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1)]);
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0)]);
         engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_tints()[0], TINT_WHITE);
 
-        assert_eq!(engine.render_state.count(), 2);
-        assert!(!engine.render_state.as_ptr().is_null());
+        engine.process_commands(&[tint(0, 0x80FF_0000)]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_tints()[0], 0x80FF_0000);
+        assert_eq!(engine.render_state.dirty_count(), 1);
+
+        assert!(engine.undo());
+        engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.gpu_tints()[0], TINT_WHITE);
     }
 
     #[test]
-    fn engine_propagates_parent_transforms() {
+    fn render_layers_count_and_mask_cull_views() {
+        let layer = |id: u32, mask: u32| {
+            let mut payload = [0u8; 16];
+            payload[0..4].copy_from_slice(&mask.to_le_bytes());
+            Command { cmd_type: CommandType::SetRenderLayer, entity_id: id, payload }
+        };
+        let vp = glam::Mat4::orthographic_rh(-10.0, 10.0, -10.0, 10.0, 0.1, 100.0).to_cols_array();
         let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1), spawn_cmd(2)]);
+        for id in 0..3 {
+            engine.process_commands(&[make_position_cmd(id, id as f32, 0.0, -5.0)]);
+        }
+        engine.process_commands(&[layer(1, 0b10), layer(2, 0b11)]);
+        engine.render_state.set_layer_lists_enabled(true);
+        assert!(engine.render_state.set_cull_view(0, &vp));
+        assert!(engine.render_state.set_cull_view(1, &vp));
+        assert!(engine.render_state.set_cull_view_layers(1, 0b10));
+        engine.update(FIXED_DT);
 
-        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1)]);
+        let ids = |engine: &Engine, slots: &[u32]| -> Vec<u32> {
+            let mut ids: Vec<u32> =
+                slots.iter().map(|&s| engine.render_state.gpu_entity_ids()[s as usize]).collect();
+            ids.sort_unstable();
+            ids
+        };
+        assert_eq!(engine.render_state.layer_counts()[..2], [2, 2]);
+        assert_eq!(ids(&engine, engine.render_state.layer_slots(1)), [1, 2]);
+        assert_eq!(ids(&engine, engine.render_state.visible_slots(0)), [0, 1, 2]);
+        assert_eq!(ids(&engine, engine.render_state.visible_slots(1)), [1, 2]);
 
-        let mut pos_payload = [0u8; 16];
-        pos_payload[0..4].copy_from_slice(&10.0f32.to_le_bytes());
-        engine.process_commands(&[Command {
-            cmd_type: CommandType::SetPosition,
-            entity_id: 0,
-            payload: pos_payload,
-        }]);
+        // Undo reverts both entities to the default layer.
+        assert!(engine.undo());
+        engine.update(FIXED_DT);
+        assert!(engine.render_state.visible_slots(1).is_empty());
+        assert_eq!(engine.render_state.layer_counts()[..2], [3, 0]);
+    }
 
-        let mut child_pos = [0u8; 16];
-        child_pos[0..4].copy_from_slice(&5.0f32.to_le_bytes());
-        engine.process_commands(&[Command {
-            cmd_type: CommandType::SetPosition,
-            entity_id: 1,
-            payload: child_pos,
-        }]);
+    #[test]
+    fn draw_batches_track_primitives_meshes_and_transparency() {
+        let u32_cmd = |cmd_type: CommandType, id: u32, value: u32| {
+            let mut payload = [0u8; 16];
+            payload[0..4].copy_from_slice(&value.to_le_bytes());
+            Command { cmd_type, entity_id: id, payload }
+        };
+        let mut engine = Engine::new();
+        engine.render_state.set_batching_enabled(true);
+        engine.render_state.set_mesh_index_count(5, 36);
+        engine.process_commands(&[spawn_cmd(0), spawn_cmd(1), spawn_cmd(2), spawn_cmd(3)]);
+        engine.process_commands(&[
+            u32_cmd(CommandType::SetRenderPrimitive, 1, 2),
+            flag_cmd(CommandType::SetTransparent, 2, true, false),
+            u32_cmd(CommandType::SetMeshHandle, 3, 5),
+        ]);
+        engine.update(FIXED_DT);
 
-        let mut parent_payload = [0u8; 16];
-        parent_payload[0..4].copy_from_slice(&0u32.to_le_bytes());
-        engine.process_commands(&[Command {
-            cmd_type: CommandType::SetParent,
-            entity_id: 1,
-            payload: parent_payload,
-        }]);
+        let summary = |engine: &Engine| -> Vec<(u32, u32, u32, Vec<u32>)> {
+            let rs = &engine.render_state;
+            let batches = rs.draw_batches();
+            batches
+                .batches()
+                .iter()
+                .map(|b| {
+                    let slots = &batches.slots()[b.first as usize..(b.first + b.count) as usize];
+                    let ids = slots.iter().map(|&s| rs.gpu_entity_ids()[s as usize]).collect();
+                    (b.primitive, b.mesh, b.transparent, ids)
+                })
+                .collect()
+        };
+        assert_eq!(summary(&engine), [(0, 0, 0, vec![0]), (0, 5, 0, vec![3]), (2, 0, 0, vec![1]), (0, 0, 1, vec![2])]);
+        assert_eq!(engine.render_state.draw_batches().indirect_args()[5..7], [36, 1]);
 
+        let version = engine.render_state.draw_batches().version();
         engine.update(FIXED_DT);
+        assert_eq!(engine.render_state.draw_batches().version(), version, "no changes, no rebuild");
 
-        let child_entity = engine.entity_map.get(1).unwrap();
-        let matrix = engine
-            .world
-            .get::<&crate::components::ModelMatrix>(child_entity)
-            .unwrap();
-        assert!((matrix.0[12] - 15.0).abs() < 0.001);
+        engine.process_commands(&[Command { cmd_type: CommandType::DespawnEntity, entity_id: 1, payload: [0; 16] }]);
+        engine.update(FIXED_DT);
+        assert_eq!(summary(&engine), [(0, 0, 0, vec![0]), (0, 5, 0, vec![3]), (0, 0, 1, vec![2])]);
     }
 
     #[test]
-    fn engine_listener_defaults_to_origin() {
-        let engine = Engine::new();
-        assert_eq!(engine.listener_x(), 0.0);
-        assert_eq!(engine.listener_y(), 0.0);
-        assert_eq!(engine.listener_z(), 0.0);
+    fn batch_slot_order_makes_batches_contiguous() {
+        let mut engine = Engine::new();
+        engine.render_state.set_batching_enabled(true);
+        engine.render_state.set_slot_order(crate::render_state::SlotOrder::Batch, 1);
+        let mut cmds: Vec<Command> = (0..6).map(spawn_cmd).collect();
+        for id in [1, 3, 5] {
+            cmds.push(flag_cmd(CommandType::SetTransparent, id, true, false));
+        }
+        engine.process_commands(&cmds);
+        for _ in 0..3 {
+            engine.update(FIXED_DT);
+        }
+
+        let rs = &engine.render_state;
+        let batches = rs.draw_batches();
+        assert_eq!(batches.batches().len(), 2);
+        assert_eq!(batches.slots(), [0, 1, 2, 3, 4, 5], "each batch is one slot range");
+        let mut opaque: Vec<u32> = rs.gpu_entity_ids()[..3].to_vec();
+        opaque.sort_unstable();
+        assert_eq!(opaque, [0, 2, 4]);
+        for slot in 0..6 {
+            let entity = rs.slot_entity(slot).unwrap();
+            assert_eq!(rs.get_slot(entity), Some(slot));
+            let id = engine.world.get::<&crate::components::ExternalId>(entity).unwrap().0;
+            assert_eq!(rs.gpu_entity_ids()[slot as usize], id);
+        }
+        assert_eq!(engine.render_state.defragment_slots(), 0, "already sorted");
     }
 
     #[test]
-    fn engine_listener_extrapolates_position() {
+    fn transparent_order_follows_depth_and_sort_order_commands() {
         let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        engine.render_state.set_transparent_sort_enabled(true);
+        let mut cmds: Vec<Command> = (0..3).map(spawn_cmd).collect();
+        for id in 0..3 {
+            cmds.push(flag_cmd(CommandType::SetTransparent, id, true, false));
+        }
+        cmds.push(f32s_cmd(CommandType::SetDepth, 0, &[2.0]));
+        engine.process_commands(&cmds);
+        engine.update(FIXED_DT);
 
-        let mut payload = [0u8; 16];
-        payload[0..4].copy_from_slice(&10.0f32.to_le_bytes());
-        engine.process_commands(&[Command {
-            cmd_type: CommandType::SetListenerPosition,
-            entity_id: 0,
-            payload,
-        }]);
+        let ids = |engine: &Engine| -> Vec<u32> {
+            let rs = &engine.render_state;
+            rs.transparent_order().iter().map(|&s| rs.gpu_entity_ids()[s as usize]).collect()
+        };
+        assert_eq!(ids(&engine), [0, 1, 2]);
 
-        // Velocity = (10 - 0) / (1/60) = 600 units/sec
-        // After 1 tick, position: 10 + 600 * (1/60) = 20
+        let mut payload = [0u8; 16];
+        payload[0..4].copy_from_slice(&(-1i32).to_le_bytes());
+        engine.process_commands(&[Command { cmd_type: CommandType::SetSortOrder, entity_id: 2, payload }]);
         engine.update(FIXED_DT);
+        assert_eq!(ids(&engine), [0, 2, 1]);
 
-        assert!((engine.listener_x() - 20.0).abs() < 0.1);
+        assert!(engine.undo());
+        engine.update(FIXED_DT);
+        assert_eq!(ids(&engine), [0, 1, 2]);
     }
 
+    #[cfg(feature = "dev-tools")]
     #[test]
-    fn engine_processes_set_listener_position() {
-        let mut engine = Engine::new();
-
-        let mut payload = [0u8; 16];
-        payload[0..4].copy_from_slice(&5.0f32.to_le_bytes());
-        payload[4..8].copy_from_slice(&10.0f32.to_le_bytes());
-        payload[8..12].copy_from_slice(&0.0f32.to_le_bytes());
+    fn tweens_survive_snapshot() {
+        use crate::components::Scale;
+        use crate::tween::{TARGET_SCALE, TWEEN_SEQUENCE};
 
-        let cmd = Command {
-            cmd_type: CommandType::SetListenerPosition,
-            entity_id: 0,
-            payload,
+        let mut engine = Engine::new();
+        engine.process_commands(&[
+            spawn_cmd(0),
+            tween_cmd(0, TARGET_SCALE, 0, 3.0, 4, 0),
+            tween_cmd(0, TARGET_SCALE, 0, 1.0, 4, TWEEN_SEQUENCE),
+        ]);
+        engine.update(FIXED_DT * 2.0);
+        let read = |engine: &Engine| {
+            let e = engine.entity_map.get(0).unwrap();
+            engine.world.get::<&Scale>(e).unwrap().0.x
         };
-        engine.process_commands(&[cmd]);
-
-        assert!((engine.listener_x() - 5.0).abs() < 0.001);
-        assert!((engine.listener_y() - 10.0).abs() < 0.001);
+        let snapshot = engine.snapshot_create();
+        engine.update(FIXED_DT * 4.0);
+        let at_6 = read(&engine);
+        assert!(engine.snapshot_restore(&snapshot));
+        engine.update(FIXED_DT * 4.0);
+        assert_eq!(read(&engine), at_6);
     }
 
     #[cfg(feature = "dev-tools")]
@@ -992,7 +3786,7 @@ mod tests {
         // First TLV entry is decodable
         let comp_type = out[0];
         let data_len = u16::from_le_bytes([out[1], out[2]]) as usize;
-        assert!(comp_type >= 1 && comp_type <= 15);
+        assert!((1..=15).contains(&comp_type));
         assert!(data_len > 0);
     }
 
@@ -1058,7 +3852,7 @@ mod tests {
         assert!(!snapshot.is_empty());
         assert_eq!(&snapshot[0..4], b"HSNP");
         let version = u32::from_le_bytes(snapshot[4..8].try_into().unwrap());
-        assert_eq!(version, 1);
+        assert_eq!(version, 3);
         let tick = u64::from_le_bytes(snapshot[8..16].try_into().unwrap());
         assert!(tick > 0);
         let entity_count = u32::from_le_bytes(snapshot[16..20].try_into().unwrap());
@@ -1154,6 +3948,66 @@ mod tests {
         assert!(t.y > 1.0, "ball should have fallen: y={}", t.y);
     }
 
+    #[cfg(feature = "physics-2d")]
+    #[test]
+    fn inactive_entity_body_is_disabled() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_2d_cmd(0), spawn_2d_cmd(1)]);
+        engine.process_commands(&[create_rigid_body_cmd(0, 0), create_rigid_body_cmd(1, 0)]);
+        engine.process_commands(&[create_circle_collider_cmd(0, 10.0), create_circle_collider_cmd(1, 10.0)]);
+        // Entity 1 goes inactive before its body exists; entity 0 after.
+        engine.process_commands(&[flag_cmd(CommandType::SetActive, 1, false, false)]);
+        engine.update(FIXED_DT);
+        engine.process_commands(&[flag_cmd(CommandType::SetActive, 0, false, false)]);
+        let y = |engine: &Engine, id| {
+            let entity = engine.entity_map.get(id).unwrap();
+            engine.world.get::<&crate::components::Transform2D>(entity).unwrap().y
+        };
+        let before = y(&engine, 0);
+        for _ in 0..10 {
+            engine.update(FIXED_DT);
+        }
+        assert_eq!(y(&engine, 0), before, "deactivated body should not fall");
+        assert_eq!(y(&engine, 1), 0.0, "body created inactive should not fall");
+
+        engine.process_commands(&[flag_cmd(CommandType::SetActive, 0, true, false)]);
+        for _ in 0..10 {
+            engine.update(FIXED_DT);
+        }
+        assert!(y(&engine, 0) > before, "reactivated body should fall");
+    }
+
+    #[cfg(feature = "physics-2d")]
+    #[test]
+    fn group_impulse_and_despawn_take_physics_path() {
+        let mut engine = Engine::new();
+        engine.physics.gravity = rapier2d::math::Vector::new(0.0, 0.0);
+        for id in 0..3 {
+            engine.process_commands(&[
+                spawn_2d_cmd(id),
+                make_position_cmd(id, 0.0, id as f32 * 100.0, 0.0),
+                create_rigid_body_cmd(id, 0),
+                create_circle_collider_cmd(id, 5.0),
+            ]);
+        }
+        engine.process_commands(&[tags_cmd(CommandType::AddTags, 0, ENEMY), tags_cmd(CommandType::AddTags, 2, ENEMY)]);
+        engine.update(FIXED_DT);
+
+        let mut impulse = [0u8; 8];
+        impulse[0..4].copy_from_slice(&1000.0f32.to_le_bytes());
+        engine.process_commands(&[group_cmd(CommandType::ApplyGroupImpulse, ENEMY, &impulse)]);
+        for _ in 0..5 {
+            engine.update(FIXED_DT);
+        }
+        let x = |id| engine.world.get::<&crate::components::Transform2D>(engine.entity_map.get(id).unwrap()).unwrap().x;
+        assert!(x(0) > 0.0 && x(2) > 0.0);
+        assert_eq!(x(1), 0.0);
+
+        engine.process_commands(&[group_cmd(CommandType::DespawnGroup, ENEMY, &[])]);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.physics.body_count(), 1);
+    }
+
     #[cfg(feature = "physics-2d")]
     #[test]
     fn despawn_removes_rapier_body() {
@@ -1175,6 +4029,20 @@ mod tests {
         assert_eq!(engine.physics.body_count(), 0);
     }
 
+    #[cfg(feature = "physics-2d")]
+    #[test]
+    fn lifetime_expiry_removes_rapier_body() {
+        let mut engine = Engine::new();
+        engine.process_commands(&[spawn_2d_cmd(0)]);
+        engine.process_commands(&[create_rigid_body_cmd(0, 0), create_circle_collider_cmd(0, 5.0)]);
+        engine.process_commands(&[lifetime_cmd(0, 3, false)]);
+        engine.update(FIXED_DT * 2.0);
+        assert_eq!(engine.physics.body_count(), 1);
+        engine.update(FIXED_DT);
+        assert_eq!(engine.physics.body_count(), 0);
+        assert!(engine.entity_map.get(0).is_none());
+    }
+
     #[cfg(feature = "physics-2d")]
     #[test]
     fn destroy_rigid_body_removes_from_rapier() {
@@ -1347,6 +4215,60 @@ mod tests {
         assert!(!engine.physics.character_map.get(&0).unwrap().state.grounded);
     }
 
+    #[cfg(feature = "physics-2d")]
+    #[test]
+    fn interpolation_captures_physics_body_state() {
+        let mut engine = Engine::new();
+        engine.set_interpolation(true);
+        engine.process_commands(&[spawn_2d_cmd(0)]);
+        engine.process_commands(&[create_rigid_body_cmd(0, 0)]);
+        engine.process_commands(&[create_circle_collider_cmd(0, 10.0)]);
+        for _ in 0..3 {
+            engine.update(FIXED_DT);
+        }
+        engine.update(FIXED_DT * 2.0);
+
+        let prev_y = engine.render_state.gpu_prev_transforms()[13];
+        let cur_y = engine.render_state.gpu_transforms()[13];
+        assert!(prev_y > 0.0, "previous transform comes from the Rapier body: {prev_y}");
+        assert!(cur_y > prev_y, "ball keeps falling: prev={prev_y} cur={cur_y}");
+    }
+
+    #[cfg(feature = "physics-2d")]
+    #[test]
+    fn physics_dt_follows_tick_rate() {
+        let mut engine = Engine::new();
+        engine.set_tick_rate(120);
+        assert!((engine.physics.integration_parameters.dt - 1.0 / 120.0).abs() < 1e-7);
+    }
+
+    #[cfg(feature = "physics-2d")]
+    #[test]
+    fn rollback_restores_physics_state() {
+        let mut engine = Engine::new();
+        engine.enable_rollback(32, 1);
+        engine.process_commands(&[spawn_2d_cmd(0)]);
+        engine.process_commands(&[create_rigid_body_cmd(0, 0)]);
+        engine.process_commands(&[create_circle_collider_cmd(0, 10.0)]);
+        for _ in 0..4 {
+            engine.update(FIXED_DT);
+        }
+        for _ in 0..8 {
+            engine.update(FIXED_DT);
+        }
+        let hash_at_12 = engine.state_hash(HASH_ALL);
+
+        // Roll back past body creation (tick 0 still has the pending body).
+        assert!(engine.rollback_to(0));
+        let entity = engine.entity_map.get(0).unwrap();
+        assert!(engine.world.get::<&crate::physics::PendingRigidBody>(entity).is_ok());
+        assert_eq!(engine.physics.rigid_body_set.len(), 0);
+
+        engine.resimulate(12);
+        assert_eq!(engine.state_hash(HASH_ALL), hash_at_12);
+        assert_eq!(engine.physics.rigid_body_set.len(), 1);
+    }
+
     #[cfg(feature = "physics-2d")]
     #[test]
     fn character_controller_despawn_cleanup() {
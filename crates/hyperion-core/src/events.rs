@@ -0,0 +1,31 @@
+//! Engine events reported back to JS.
+//!
+//! Systems push `EngineEvent`s into the engine's frame buffer while a frame
+//! runs. The buffer is cleared at the start of every `update()` and exported
+//! to JS as a `#[repr(C)]` array, like the physics collision events.
+
+use bytemuck::{Pod, Zeroable};
+
+/// A tween finished (not emitted for looping tweens). `data` = target | field << 8.
+pub const EVENT_TWEEN_COMPLETE: u32 = 1;
+/// A looping tween started a new cycle. `data` = target | field << 8.
+pub const EVENT_TWEEN_LOOP: u32 = 2;
+/// A sprite animation reached a frame marked as an event frame. `data` = frame index.
+pub const EVENT_SPRITE_FRAME: u32 = 3;
+/// A non-looping sprite animation played its last frame. `data` = clip ID.
+pub const EVENT_SPRITE_FINISHED: u32 = 4;
+/// A `Lifetime` ran out and the entity was despawned. `data` = number of
+/// descendants despawned with it.
+pub const EVENT_LIFETIME_EXPIRED: u32 = 5;
+/// A `SetComponent`/`RemoveComponent` command was rejected.
+/// `data` = component ID | `ComponentError` code << 8.
+pub const EVENT_COMPONENT_ERROR: u32 = 6;
+
+/// One engine event, addressed by external entity ID.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
+pub struct EngineEvent {
+    pub entity_id: u32, // 4B
+    pub kind: u32,      // 4B: EVENT_*
+    pub data: u32,      // 4B: kind-specific
+} // 12 bytes total, 4-byte aligned
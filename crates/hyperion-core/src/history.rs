@@ -0,0 +1,1025 @@
+//! Undo/redo command history.
+//!
+//! When enabled, `Engine::process_commands` asks this module for the inverse
+//! of every mutating command just *before* that command is applied, and
+//! records the forward commands together with their inverses as one undo
+//! group. Capturing per command keeps inverses correct when a batch touches
+//! the same entity more than once.
+//!
+//! Groups are delimited either implicitly (one group per `process_commands`
+//! call) or explicitly with `HistoryMarker` commands in the stream
+//! (payload `0` = begin group, `1` = end group), so an editor action that
+//! spans several frames still undoes as a single step.
+//!
+//! Inverses are ordinary ring-buffer commands, so undo/redo replays through
+//! the regular command path and keeps dirty tracking and slot management
+//! consistent. Physics commands are not recorded: Rapier state cannot be
+//! reconstructed from the command stream.
+//!
+//! Tweens, sprite animations, lifetimes, emitters and cameras are restored
+//! from their configuration, not their runtime progress, which no command can
+//! express: restored tweens restart (keeping any remaining delay), animations
+//! resume at the start of their current frame, and emitters and cameras are
+//! reseeded from their generator state, so their random streams differ from
+//! the undone ones. `EmitterBurst` is not recorded: its particles are
+//! simulation output, like the channel values written by tweens.
+
+use std::collections::VecDeque;
+
+use hecs::World;
+
+use crate::camera::Camera;
+use crate::command_processor::{EntityMap, expand_group_command, hierarchy_targets};
+use crate::component_registry::ComponentRegistry;
+use crate::components::*;
+use crate::lifetime::Lifetime;
+use crate::particles::Emitter;
+use crate::ring_buffer::{Command, CommandType};
+use crate::sprite_animation::SpriteAnimation;
+use crate::tween::{TARGET_ALL, TWEEN_FROM_CURRENT, TWEEN_SEQUENCE, Tween, Tweens};
+
+/// Default memory budget for recorded history (1 MiB).
+pub const DEFAULT_HISTORY_BUDGET: usize = 1024 * 1024;
+
+/// `HistoryMarker` payload: open an explicit undo group.
+pub const MARKER_BEGIN_GROUP: u8 = 0;
+/// `HistoryMarker` payload: close the current explicit undo group.
+pub const MARKER_END_GROUP: u8 = 1;
+
+/// One undoable step: the commands that were applied and their inverses.
+///
+/// `inverse` holds each forward command's inverse chunk reversed, appended in
+/// application order. Reversing the whole list therefore undoes the last
+/// command first while keeping every multi-command chunk (e.g. the spawn +
+/// setters that restore a despawned entity) in its original order.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryGroup {
+    pub forward: Vec<Command>,
+    pub inverse: Vec<Command>,
+}
+
+impl HistoryGroup {
+    /// Approximate heap footprint, used for the memory budget.
+    pub fn size_bytes(&self) -> usize {
+        (self.forward.len() + self.inverse.len()) * std::mem::size_of::<Command>()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.forward.is_empty()
+    }
+
+    /// Inverse commands in replay order (last applied command undone first).
+    pub fn undo_commands(&self) -> Vec<Command> {
+        self.inverse.iter().rev().cloned().collect()
+    }
+}
+
+/// Bounded undo/redo stacks.
+pub struct CommandHistory {
+    undo_stack: VecDeque<HistoryGroup>,
+    redo_stack: Vec<HistoryGroup>,
+    /// Group being filled by the current `process_commands` call or by an
+    /// explicit `HistoryMarker` begin/end pair.
+    open_group: HistoryGroup,
+    /// Nesting depth of explicit groups. 0 = implicit per-call grouping.
+    explicit_depth: u32,
+    budget_bytes: usize,
+    used_bytes: usize,
+}
+
+impl CommandHistory {
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            open_group: HistoryGroup::default(),
+            explicit_depth: 0,
+            budget_bytes,
+            used_bytes: 0,
+        }
+    }
+
+    /// Record one command about to be applied to `world`.
+    ///
+    /// Must be called right BEFORE `cmd` is processed: its inverse captures
+    /// the component values `cmd` is about to overwrite.
+    pub fn record(&mut self, cmd: &Command, world: &World, entity_map: &EntityMap, components: &ComponentRegistry) {
+        if cmd.cmd_type == CommandType::HistoryMarker {
+            match cmd.payload[0] {
+                MARKER_BEGIN_GROUP => self.explicit_depth += 1,
+                MARKER_END_GROUP => {
+                    self.explicit_depth = self.explicit_depth.saturating_sub(1);
+                    if self.explicit_depth == 0 {
+                        self.commit_open_group();
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+        if !is_recorded(cmd.cmd_type) {
+            return;
+        }
+        self.open_group.forward.push(cmd.clone());
+        let start = self.open_group.inverse.len();
+        push_inverse(cmd, world, entity_map, components, &mut self.open_group.inverse);
+        self.open_group.inverse[start..].reverse();
+    }
+
+    /// Close the implicit group of the current batch. Explicit groups stay
+    /// open until their `MARKER_END_GROUP`.
+    pub fn end_batch(&mut self) {
+        if self.explicit_depth == 0 {
+            self.commit_open_group();
+        }
+    }
+
+    fn commit_open_group(&mut self) {
+        let group = std::mem::take(&mut self.open_group);
+        if group.is_empty() {
+            return;
+        }
+        // New forward history invalidates anything that was undone.
+        self.redo_stack.clear();
+        self.push_undo(group);
+    }
+
+    fn push_undo(&mut self, group: HistoryGroup) {
+        self.used_bytes += group.size_bytes();
+        self.undo_stack.push_back(group);
+        self.enforce_budget();
+    }
+
+    /// Drop the oldest groups until the undo stack fits the budget.
+    /// The most recent group is always kept, even if it alone exceeds it.
+    fn enforce_budget(&mut self) {
+        while self.used_bytes > self.budget_bytes && self.undo_stack.len() > 1 {
+            if let Some(oldest) = self.undo_stack.pop_front() {
+                self.used_bytes -= oldest.size_bytes();
+            }
+        }
+    }
+
+    /// Pop the most recent group for undoing. The caller replays
+    /// `group.undo_commands()` and hands the group back via `push_redo`.
+    pub fn pop_undo(&mut self) -> Option<HistoryGroup> {
+        let group = self.undo_stack.pop_back()?;
+        self.used_bytes -= group.size_bytes();
+        Some(group)
+    }
+
+    /// Pop the most recently undone group for redoing. The caller replays
+    /// `group.forward` and hands the group back via `push_undo_after_redo`.
+    pub fn pop_redo(&mut self) -> Option<HistoryGroup> {
+        self.redo_stack.pop()
+    }
+
+    pub fn push_redo(&mut self, group: HistoryGroup) {
+        self.redo_stack.push(group);
+    }
+
+    pub fn push_undo_after_redo(&mut self, group: HistoryGroup) {
+        self.push_undo(group);
+    }
+
+    /// Number of groups available to undo.
+    pub fn undo_len(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Number of groups available to redo.
+    pub fn redo_len(&self) -> usize {
+        self.redo_stack.len()
+    }
+
+    /// Approximate bytes held by the undo stack.
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+
+    /// Change the memory budget, evicting old groups if necessary.
+    pub fn set_budget(&mut self, budget_bytes: usize) {
+        self.budget_bytes = budget_bytes;
+        self.enforce_budget();
+    }
+
+    /// Forget all recorded history.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.open_group = HistoryGroup::default();
+        self.explicit_depth = 0;
+        self.used_bytes = 0;
+    }
+}
+
+/// Whether a command type mutates entity state that history can restore.
+fn is_recorded(cmd_type: CommandType) -> bool {
+    matches!(
+        cmd_type,
+        CommandType::SpawnEntity
+            | CommandType::DespawnEntity
+            | CommandType::SetPosition
+            | CommandType::SetRotation
+            | CommandType::SetScale
+            | CommandType::SetVelocity
+            | CommandType::SetTextureLayer
+            | CommandType::SetMeshHandle
+            | CommandType::SetRenderPrimitive
+            | CommandType::SetParent
+            | CommandType::SetPrimParams0
+            | CommandType::SetPrimParams1
+            | CommandType::SetRotation2D
+            | CommandType::SetTransparent
+            | CommandType::SetDepth
+            | CommandType::SetActive
+            | CommandType::SetVisible
+            | CommandType::AddTags
+            | CommandType::RemoveTags
+            | CommandType::DespawnGroup
+            | CommandType::SetGroupVisible
+            | CommandType::SetGroupTextureLayer
+            | CommandType::TranslateGroup
+            | CommandType::SetAngularVelocity
+            | CommandType::SetAcceleration
+            | CommandType::SetDrag
+            | CommandType::SetPickLayer
+            | CommandType::SetTint
+            | CommandType::SetRenderLayer
+            | CommandType::SetSortOrder
+            | CommandType::SetComponent
+            | CommandType::RemoveComponent
+            | CommandType::StartTween
+            | CommandType::StopTweens
+            | CommandType::PlaySpriteAnimation
+            | CommandType::SetSpriteAnimationSpeed
+            | CommandType::StopSpriteAnimation
+            | CommandType::SetLifetime
+            | CommandType::AttachEmitter
+            | CommandType::SetEmitterRate
+            | CommandType::DetachEmitter
+            | CommandType::AttachCamera
+            | CommandType::SetCameraViewport
+            | CommandType::SetCameraZoom
+            | CommandType::SetCameraFollow
+            | CommandType::SetCameraBounds
+            | CommandType::AddCameraTrauma
+            | CommandType::DetachCamera
+    )
+}
+
+fn make_cmd(cmd_type: CommandType, entity_id: u32, data: &[u8]) -> Command {
+    let mut payload = [0u8; 16];
+    payload[..data.len()].copy_from_slice(data);
+    Command {
+        cmd_type,
+        entity_id,
+        payload,
+    }
+}
+
+fn f32s_cmd(cmd_type: CommandType, entity_id: u32, values: &[f32]) -> Command {
+    let mut payload = [0u8; 16];
+    for (i, v) in values.iter().enumerate() {
+        payload[i * 4..i * 4 + 4].copy_from_slice(&v.to_le_bytes());
+    }
+    Command {
+        cmd_type,
+        entity_id,
+        payload,
+    }
+}
+
+/// Append the commands that undo `cmd` against the current `world` state.
+///
+/// Commands targeting entities that do not exist produce no inverse
+/// (the forward command is a no-op for them too).
+pub fn push_inverse(
+    cmd: &Command,
+    world: &World,
+    entity_map: &EntityMap,
+    components: &ComponentRegistry,
+    out: &mut Vec<Command>,
+) {
+    let id = cmd.entity_id;
+
+    if cmd.cmd_type == CommandType::SpawnEntity {
+        // Respawning over a live ID replaces the mapping; restore the old entity.
+        if entity_map.get(id).is_some() {
+            push_restore_entity(id, world, entity_map, out);
+        }
+        out.push(make_cmd(CommandType::DespawnEntity, id, &[]));
+        return;
+    }
+
+    // Group commands are undone entity by entity, like their expansion is applied.
+    let expanded = expand_group_command(cmd, world, entity_map);
+    if !expanded.is_empty() {
+        for member_cmd in &expanded {
+            push_inverse(member_cmd, world, entity_map, components, out);
+        }
+        return;
+    }
+
+    let Some(entity) = entity_map.get(id) else {
+        return;
+    };
+    let is_2d = entity_map.is_entity_2d(id);
+
+    match cmd.cmd_type {
+        CommandType::DespawnEntity => push_restore_entity(id, world, entity_map, out),
+
+        CommandType::SetPosition => {
+            if is_2d {
+                if let Ok(t) = world.get::<&Transform2D>(entity) {
+                    out.push(f32s_cmd(CommandType::SetPosition, id, &[t.x, t.y, 0.0]));
+                }
+            } else if let Ok(p) = world.get::<&Position>(entity) {
+                out.push(f32s_cmd(CommandType::SetPosition, id, &p.0.to_array()));
+            }
+        }
+
+        CommandType::SetRotation | CommandType::SetRotation2D => {
+            if is_2d {
+                if let Ok(t) = world.get::<&Transform2D>(entity) {
+                    out.push(f32s_cmd(CommandType::SetRotation2D, id, &[t.rot]));
+                }
+            } else if let Ok(r) = world.get::<&Rotation>(entity) {
+                out.push(f32s_cmd(CommandType::SetRotation, id, &r.0.to_array()));
+            }
+        }
+
+        CommandType::SetScale => {
+            if is_2d {
+                if let Ok(t) = world.get::<&Transform2D>(entity) {
+                    out.push(f32s_cmd(CommandType::SetScale, id, &[t.sx, t.sy, 1.0]));
+                }
+            } else if let Ok(s) = world.get::<&Scale>(entity) {
+                out.push(f32s_cmd(CommandType::SetScale, id, &s.0.to_array()));
+            }
+        }
+
+        CommandType::SetVelocity => {
+            if let Ok(v) = world.get::<&Velocity>(entity) {
+                out.push(f32s_cmd(CommandType::SetVelocity, id, &v.0.to_array()));
+            }
+        }
+
+        // Absent motion components behave like zero, so zero restores them.
+        CommandType::SetAngularVelocity => {
+            let w = world.get::<&AngularVelocity>(entity).map_or([0.0; 3], |w| w.0.to_array());
+            out.push(f32s_cmd(CommandType::SetAngularVelocity, id, &w));
+        }
+
+        CommandType::SetAcceleration => {
+            let a = world.get::<&Acceleration>(entity).map_or([0.0; 3], |a| a.0.to_array());
+            out.push(f32s_cmd(CommandType::SetAcceleration, id, &a));
+        }
+
+        CommandType::SetDrag => {
+            let linear = world.get::<&LinearDrag>(entity).map_or(0.0, |d| d.0);
+            let angular = world.get::<&AngularDrag>(entity).map_or(0.0, |d| d.0);
+            out.push(f32s_cmd(CommandType::SetDrag, id, &[linear, angular]));
+        }
+
+        CommandType::SetTextureLayer => {
+            if let Ok(t) = world.get::<&TextureLayerIndex>(entity) {
+                out.push(make_cmd(CommandType::SetTextureLayer, id, &t.0.to_le_bytes()));
+            }
+        }
+
+        CommandType::SetMeshHandle => {
+            if let Ok(m) = world.get::<&MeshHandle>(entity) {
+                out.push(make_cmd(CommandType::SetMeshHandle, id, &m.0.to_le_bytes()));
+            }
+        }
+
+        CommandType::SetRenderPrimitive => {
+            if let Ok(p) = world.get::<&RenderPrimitive>(entity) {
+                out.push(make_cmd(CommandType::SetRenderPrimitive, id, &[p.0]));
+            }
+        }
+
+        CommandType::SetParent => {
+            if let Ok(p) = world.get::<&Parent>(entity) {
+                out.push(make_cmd(CommandType::SetParent, id, &p.0.to_le_bytes()));
+            }
+        }
+
+        CommandType::SetPrimParams0 => {
+            if let Ok(pp) = world.get::<&PrimitiveParams>(entity) {
+                out.push(f32s_cmd(CommandType::SetPrimParams0, id, &pp.0[0..4]));
+            }
+        }
+
+        CommandType::SetPrimParams1 => {
+            if let Ok(pp) = world.get::<&PrimitiveParams>(entity) {
+                out.push(f32s_cmd(CommandType::SetPrimParams1, id, &pp.0[4..8]));
+            }
+        }
+
+        CommandType::SetTransparent => {
+            let was_transparent = world.get::<&Transparent>(entity).is_ok();
+            out.push(make_cmd(CommandType::SetTransparent, id, &[u8::from(was_transparent)]));
+        }
+
+        CommandType::SetDepth => {
+            // There is no "remove Depth" command. When the entity had no Depth,
+            // restore the value the render state would have derived anyway
+            // (Position.z for 3D, 0.0 for 2D).
+            let depth = if let Ok(d) = world.get::<&Depth>(entity) {
+                d.0
+            } else if let Ok(p) = world.get::<&Position>(entity) {
+                p.0.z
+            } else {
+                0.0
+            };
+            out.push(f32s_cmd(CommandType::SetDepth, id, &[depth]));
+        }
+
+        // Cascading forms touch every descendant; restore each one individually.
+        CommandType::SetActive | CommandType::SetVisible => {
+            let cascade = cmd.payload[1] != 0;
+            for (target_id, target) in hierarchy_targets(world, entity_map, id, cascade) {
+                let flag = if cmd.cmd_type == CommandType::SetActive {
+                    world.get::<&Active>(target).is_ok()
+                } else {
+                    world.get::<&Hidden>(target).is_err()
+                };
+                out.push(make_cmd(cmd.cmd_type, target_id, &[u8::from(flag), 0]));
+            }
+        }
+
+        CommandType::SetPickLayer => {
+            let old = world.get::<&PickLayer>(entity).map_or(PICK_LAYER_DEFAULT, |l| l.0);
+            out.push(make_cmd(CommandType::SetPickLayer, id, &old.to_le_bytes()));
+        }
+
+        CommandType::SetTint => {
+            let old = world.get::<&Tint>(entity).map_or(TINT_WHITE, |t| t.0);
+            out.push(make_cmd(CommandType::SetTint, id, &old.to_le_bytes()));
+        }
+
+        CommandType::SetRenderLayer => {
+            let old = world.get::<&RenderLayer>(entity).map_or(RENDER_LAYER_DEFAULT, |l| l.0);
+            out.push(make_cmd(CommandType::SetRenderLayer, id, &old.to_le_bytes()));
+        }
+
+        CommandType::SetSortOrder => {
+            let old = world.get::<&SortOrder>(entity).map_or(0, |o| o.0);
+            out.push(make_cmd(CommandType::SetSortOrder, id, &old.to_le_bytes()));
+        }
+
+        // Tween queues are replaced wholesale so track order survives the undo.
+        CommandType::StartTween | CommandType::StopTweens => {
+            out.push(make_cmd(CommandType::StopTweens, id, &[TARGET_ALL, 0]));
+            if let Ok(tweens) = world.get::<&Tweens>(entity) {
+                push_tweens(id, &tweens, out);
+            }
+        }
+
+        CommandType::PlaySpriteAnimation | CommandType::StopSpriteAnimation => {
+            push_animation(id, world.get::<&SpriteAnimation>(entity).ok().as_deref(), out);
+        }
+
+        CommandType::SetSpriteAnimationSpeed => {
+            if let Ok(anim) = world.get::<&SpriteAnimation>(entity) {
+                out.push(f32s_cmd(CommandType::SetSpriteAnimationSpeed, id, &[anim.speed]));
+            }
+        }
+
+        CommandType::SetLifetime => {
+            out.push(lifetime_cmd(id, world.get::<&Lifetime>(entity).ok().as_deref()));
+        }
+
+        CommandType::AttachEmitter | CommandType::DetachEmitter => {
+            push_emitter(id, world.get::<&Emitter>(entity).ok().as_deref(), out);
+        }
+
+        CommandType::SetEmitterRate => {
+            if let Ok(emitter) = world.get::<&Emitter>(entity) {
+                out.push(f32s_cmd(CommandType::SetEmitterRate, id, &[emitter.rate]));
+            }
+        }
+
+        CommandType::AttachCamera | CommandType::DetachCamera => {
+            push_camera(id, world.get::<&Camera>(entity).ok().as_deref(), out);
+        }
+
+        CommandType::SetCameraViewport
+        | CommandType::SetCameraZoom
+        | CommandType::SetCameraFollow
+        | CommandType::SetCameraBounds => {
+            if let Ok(camera) = world.get::<&Camera>(entity) {
+                out.push(camera_setter_cmd(cmd.cmd_type, id, &camera));
+            }
+        }
+
+        // Trauma is clamped to [0, 1], so undo adds back exactly what the command changed.
+        CommandType::AddCameraTrauma => {
+            if let Ok(camera) = world.get::<&Camera>(entity) {
+                let added = f32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+                let applied = (camera.trauma + added).clamp(0.0, 1.0) - camera.trauma;
+                let old = [-applied, camera.trauma_decay, camera.max_offset, camera.max_roll];
+                out.push(f32s_cmd(CommandType::AddCameraTrauma, id, &old));
+            }
+        }
+
+        // Restore the previous bytes through the registry serializer, or
+        // remove a component the command added. Components too large for a
+        // `SetComponent` payload (over 15 bytes) cannot be restored this way.
+        CommandType::SetComponent | CommandType::RemoveComponent => {
+            let component = cmd.payload[0];
+            let Some(info) = components.info(component) else {
+                return;
+            };
+            let allowed = if cmd.cmd_type == CommandType::SetComponent {
+                info.is_writable()
+            } else {
+                info.is_removable()
+            };
+            if !allowed {
+                return;
+            }
+            let mut old = vec![component];
+            if components.get(world, entity, component, &mut old) {
+                if old.len() <= cmd.payload.len() {
+                    out.push(make_cmd(CommandType::SetComponent, id, &old));
+                }
+            } else if cmd.cmd_type == CommandType::SetComponent {
+                out.push(make_cmd(CommandType::RemoveComponent, id, &[component]));
+            }
+        }
+
+        CommandType::AddTags | CommandType::RemoveTags => {
+            let mask = u32::from_le_bytes(cmd.payload[0..4].try_into().unwrap());
+            let old = world.get::<&Tags>(entity).map_or(0, |t| t.0);
+            if cmd.cmd_type == CommandType::AddTags {
+                out.push(make_cmd(CommandType::RemoveTags, id, &(mask & !old).to_le_bytes()));
+            } else {
+                out.push(make_cmd(CommandType::AddTags, id, &(mask & old).to_le_bytes()));
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// Emit the commands that recreate entity `id` with its full current state:
+/// spawn, every spawn-time component, optional markers, and the parent/child
+/// links that a despawn would sever. Physics bodies and colliders are not
+/// restored: the re-created entity has none. Tweens, animations, emitters and
+/// cameras come back from their configuration (see the module doc).
+fn push_restore_entity(id: u32, world: &World, entity_map: &EntityMap, out: &mut Vec<Command>) {
+    let Some(entity) = entity_map.get(id) else {
+        return;
+    };
+    let is_2d = entity_map.is_entity_2d(id);
+
+    out.push(make_cmd(CommandType::SpawnEntity, id, &[u8::from(is_2d)]));
+
+    if is_2d {
+        if let Ok(t) = world.get::<&Transform2D>(entity) {
+            out.push(f32s_cmd(CommandType::SetPosition, id, &[t.x, t.y, 0.0]));
+            out.push(f32s_cmd(CommandType::SetRotation2D, id, &[t.rot]));
+            out.push(f32s_cmd(CommandType::SetScale, id, &[t.sx, t.sy, 1.0]));
+        }
+    } else {
+        if let Ok(p) = world.get::<&Position>(entity) {
+            out.push(f32s_cmd(CommandType::SetPosition, id, &p.0.to_array()));
+        }
+        if let Ok(r) = world.get::<&Rotation>(entity) {
+            out.push(f32s_cmd(CommandType::SetRotation, id, &r.0.to_array()));
+        }
+        if let Ok(s) = world.get::<&Scale>(entity) {
+            out.push(f32s_cmd(CommandType::SetScale, id, &s.0.to_array()));
+        }
+    }
+    if let Ok(v) = world.get::<&Velocity>(entity) {
+        out.push(f32s_cmd(CommandType::SetVelocity, id, &v.0.to_array()));
+    }
+    if let Ok(w) = world.get::<&AngularVelocity>(entity) {
+        out.push(f32s_cmd(CommandType::SetAngularVelocity, id, &w.0.to_array()));
+    }
+    if let Ok(a) = world.get::<&Acceleration>(entity) {
+        out.push(f32s_cmd(CommandType::SetAcceleration, id, &a.0.to_array()));
+    }
+    if world.satisfies::<&LinearDrag>(entity) || world.satisfies::<&AngularDrag>(entity) {
+        let linear = world.get::<&LinearDrag>(entity).map_or(0.0, |d| d.0);
+        let angular = world.get::<&AngularDrag>(entity).map_or(0.0, |d| d.0);
+        out.push(f32s_cmd(CommandType::SetDrag, id, &[linear, angular]));
+    }
+    if let Ok(t) = world.get::<&TextureLayerIndex>(entity) {
+        out.push(make_cmd(CommandType::SetTextureLayer, id, &t.0.to_le_bytes()));
+    }
+    if let Ok(m) = world.get::<&MeshHandle>(entity) {
+        out.push(make_cmd(CommandType::SetMeshHandle, id, &m.0.to_le_bytes()));
+    }
+    if let Ok(p) = world.get::<&RenderPrimitive>(entity) {
+        out.push(make_cmd(CommandType::SetRenderPrimitive, id, &[p.0]));
+    }
+    if let Ok(pp) = world.get::<&PrimitiveParams>(entity) {
+        out.push(f32s_cmd(CommandType::SetPrimParams0, id, &pp.0[0..4]));
+        out.push(f32s_cmd(CommandType::SetPrimParams1, id, &pp.0[4..8]));
+    }
+    if world.get::<&Transparent>(entity).is_ok() {
+        out.push(make_cmd(CommandType::SetTransparent, id, &[1]));
+    }
+    if let Ok(d) = world.get::<&Depth>(entity) {
+        out.push(f32s_cmd(CommandType::SetDepth, id, &[d.0]));
+    }
+    if let Ok(p) = world.get::<&Parent>(entity)
+        && p.0 != u32::MAX
+    {
+        out.push(make_cmd(CommandType::SetParent, id, &p.0.to_le_bytes()));
+    }
+    if world.get::<&Active>(entity).is_err() {
+        out.push(make_cmd(CommandType::SetActive, id, &[0, 0]));
+    }
+    if world.get::<&Hidden>(entity).is_ok() {
+        out.push(make_cmd(CommandType::SetVisible, id, &[0, 0]));
+    }
+    if let Ok(t) = world.get::<&Tags>(entity) {
+        out.push(make_cmd(CommandType::AddTags, id, &t.0.to_le_bytes()));
+    }
+    if let Ok(l) = world.get::<&PickLayer>(entity) {
+        out.push(make_cmd(CommandType::SetPickLayer, id, &l.0.to_le_bytes()));
+    }
+    if let Ok(t) = world.get::<&Tint>(entity) {
+        out.push(make_cmd(CommandType::SetTint, id, &t.0.to_le_bytes()));
+    }
+    if let Ok(l) = world.get::<&RenderLayer>(entity) {
+        out.push(make_cmd(CommandType::SetRenderLayer, id, &l.0.to_le_bytes()));
+    }
+    if let Ok(o) = world.get::<&SortOrder>(entity) {
+        out.push(make_cmd(CommandType::SetSortOrder, id, &o.0.to_le_bytes()));
+    }
+    if let Ok(tweens) = world.get::<&Tweens>(entity) {
+        push_tweens(id, &tweens, out);
+    }
+    if let Ok(anim) = world.get::<&SpriteAnimation>(entity) {
+        push_animation(id, Some(&anim), out);
+    }
+    if let Ok(lifetime) = world.get::<&Lifetime>(entity) {
+        out.push(lifetime_cmd(id, Some(&lifetime)));
+    }
+    if let Ok(emitter) = world.get::<&Emitter>(entity) {
+        push_emitter(id, Some(&emitter), out);
+    }
+    if let Ok(camera) = world.get::<&Camera>(entity) {
+        push_camera(id, Some(&camera), out);
+    }
+
+    // Re-link children: their Parent still names `id`, but the respawned
+    // entity starts with an empty Children list.
+    let mut child_ids: Vec<u32> = Vec::new();
+    if let Ok(children) = world.get::<&Children>(entity) {
+        child_ids.extend_from_slice(children.as_slice());
+    }
+    if let Ok(overflow) = world.get::<&OverflowChildren>(entity) {
+        child_ids.extend_from_slice(&overflow.items);
+    }
+    for child_id in child_ids {
+        out.push(make_cmd(CommandType::SetParent, child_id, &id.to_le_bytes()));
+    }
+}
+
+/// `StartTween` commands rebuilding `tweens` track by track. Each tween
+/// restarts; a started tween keeps the `from` it resolved.
+fn push_tweens(id: u32, tweens: &Tweens, out: &mut Vec<Command>) {
+    for track in &tweens.tracks {
+        for (i, tween) in track.iter().enumerate() {
+            out.push(tween_cmd(id, tween, i > 0));
+        }
+    }
+}
+
+fn tween_cmd(id: u32, tween: &Tween, sequence: bool) -> Command {
+    let mut flags = tween.flags & !TWEEN_SEQUENCE;
+    if sequence {
+        flags |= TWEEN_SEQUENCE;
+    }
+    if tween.started != 0 {
+        flags &= !TWEEN_FROM_CURRENT;
+    }
+    let mut data = [0u8; 16];
+    data[..4].copy_from_slice(&[tween.target, tween.field, tween.easing, flags]);
+    data[4..8].copy_from_slice(&tween.from.to_le_bytes());
+    data[8..12].copy_from_slice(&tween.to.to_le_bytes());
+    data[12..14].copy_from_slice(&tween.duration.to_le_bytes());
+    data[14..16].copy_from_slice(&tween.delay.to_le_bytes());
+    make_cmd(CommandType::StartTween, id, &data)
+}
+
+/// Replay `anim` from the start of its current frame, or stop playback.
+fn push_animation(id: u32, anim: Option<&SpriteAnimation>, out: &mut Vec<Command>) {
+    let Some(anim) = anim else {
+        out.push(make_cmd(CommandType::StopSpriteAnimation, id, &[]));
+        return;
+    };
+    let mut data = [0u8; 11];
+    data[0..4].copy_from_slice(&anim.clip.to_le_bytes());
+    data[4..8].copy_from_slice(&anim.speed.to_le_bytes());
+    data[8] = anim.mode;
+    data[9..11].copy_from_slice(&anim.frame.to_le_bytes());
+    out.push(make_cmd(CommandType::PlaySpriteAnimation, id, &data));
+}
+
+/// `SetLifetime` restoring `lifetime`; 0 ticks clears it.
+fn lifetime_cmd(id: u32, lifetime: Option<&Lifetime>) -> Command {
+    let (ticks, cascade) = lifetime.map_or((0, 0), |l| (l.ticks_left, l.cascade as u8));
+    let mut data = [0u8; 5];
+    data[0..4].copy_from_slice(&ticks.to_le_bytes());
+    data[4] = cascade;
+    make_cmd(CommandType::SetLifetime, id, &data)
+}
+
+/// Re-attach `emitter` with its rate and pending burst, or detach.
+fn push_emitter(id: u32, emitter: Option<&Emitter>, out: &mut Vec<Command>) {
+    let Some(emitter) = emitter else {
+        out.push(make_cmd(CommandType::DetachEmitter, id, &[]));
+        return;
+    };
+    let seed = emitter.rng.0 as u32;
+    let mut data = [0u8; 8];
+    data[0..4].copy_from_slice(&emitter.config.to_le_bytes());
+    data[4..8].copy_from_slice(&seed.to_le_bytes());
+    out.push(make_cmd(CommandType::AttachEmitter, id, &data));
+    out.push(f32s_cmd(CommandType::SetEmitterRate, id, &[emitter.rate]));
+    if emitter.burst > 0 {
+        out.push(make_cmd(CommandType::EmitterBurst, id, &emitter.burst.to_le_bytes()));
+    }
+}
+
+/// Re-attach `camera` with every setter-controlled field and its trauma, or detach.
+fn push_camera(id: u32, camera: Option<&Camera>, out: &mut Vec<Command>) {
+    let Some(camera) = camera else {
+        out.push(make_cmd(CommandType::DetachCamera, id, &[]));
+        return;
+    };
+    let mut attach = f32s_cmd(CommandType::AttachCamera, id, &[camera.fov_y, camera.near, camera.far]);
+    attach.payload[12..16].copy_from_slice(&(camera.rng.0 as u32).to_le_bytes());
+    out.push(attach);
+    for setter in [
+        CommandType::SetCameraViewport,
+        CommandType::SetCameraZoom,
+        CommandType::SetCameraFollow,
+        CommandType::SetCameraBounds,
+    ] {
+        out.push(camera_setter_cmd(setter, id, camera));
+    }
+    let shake = [camera.trauma, camera.trauma_decay, camera.max_offset, camera.max_roll];
+    out.push(f32s_cmd(CommandType::AddCameraTrauma, id, &shake));
+}
+
+/// Camera setter `cmd_type` carrying `camera`'s current value.
+fn camera_setter_cmd(cmd_type: CommandType, id: u32, camera: &Camera) -> Command {
+    match cmd_type {
+        CommandType::SetCameraViewport => f32s_cmd(cmd_type, id, &camera.viewport),
+        CommandType::SetCameraZoom => f32s_cmd(cmd_type, id, &[camera.zoom]),
+        CommandType::SetCameraFollow => {
+            let [dx, dy] = camera.deadzone;
+            let mut follow = f32s_cmd(cmd_type, id, &[0.0, camera.damping, dx, dy]);
+            follow.payload[0..4].copy_from_slice(&camera.follow.to_le_bytes());
+            follow
+        }
+        _ => f32s_cmd(cmd_type, id, &camera.bounds),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render_state::RenderState;
+
+    fn run(commands: &[Command], world: &mut World, map: &mut EntityMap, rs: &mut RenderState) {
+        #[cfg(feature = "physics-2d")]
+        {
+            let mut physics = crate::physics::PhysicsWorld::new();
+            crate::command_processor::process_commands(commands, world, map, rs, &mut physics);
+        }
+        #[cfg(not(feature = "physics-2d"))]
+        {
+            crate::command_processor::process_commands(commands, world, map, rs);
+        }
+    }
+
+    fn spawn(id: u32) -> Command {
+        make_cmd(CommandType::SpawnEntity, id, &[0])
+    }
+
+    fn position(id: u32, x: f32, y: f32, z: f32) -> Command {
+        f32s_cmd(CommandType::SetPosition, id, &[x, y, z])
+    }
+
+    fn marker(kind: u8) -> Command {
+        make_cmd(CommandType::HistoryMarker, 0, &[kind])
+    }
+
+    /// Record `commands` as one batch without applying them.
+    fn record_batch(history: &mut CommandHistory, commands: &[Command], world: &World, map: &EntityMap) {
+        for cmd in commands {
+            history.record(cmd, world, map, &ComponentRegistry::new());
+        }
+        history.end_batch();
+    }
+
+    #[test]
+    fn set_position_inverse_restores_prior_value() {
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut rs = RenderState::new();
+        run(&[spawn(0), position(0, 1.0, 2.0, 3.0)], &mut world, &mut map, &mut rs);
+
+        let mut inverse = Vec::new();
+        push_inverse(&position(0, 9.0, 9.0, 9.0), &world, &map, &ComponentRegistry::new(), &mut inverse);
+        assert_eq!(inverse, vec![position(0, 1.0, 2.0, 3.0)]);
+    }
+
+    #[test]
+    fn spawn_inverse_is_despawn() {
+        let world = World::new();
+        let map = EntityMap::new();
+        let mut inverse = Vec::new();
+        push_inverse(&spawn(3), &world, &map, &ComponentRegistry::new(), &mut inverse);
+        assert_eq!(inverse.len(), 1);
+        assert_eq!(inverse[0].cmd_type, CommandType::DespawnEntity);
+        assert_eq!(inverse[0].entity_id, 3);
+    }
+
+    #[test]
+    fn despawn_inverse_restores_full_state() {
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut rs = RenderState::new();
+        run(
+            &[
+                spawn(0),
+                spawn(1),
+                position(1, 4.0, 5.0, 6.0),
+                make_cmd(CommandType::SetTextureLayer, 1, &0x0002_0003u32.to_le_bytes()),
+                make_cmd(CommandType::SetParent, 1, &0u32.to_le_bytes()),
+            ],
+            &mut world,
+            &mut map,
+            &mut rs,
+        );
+
+        let mut inverse = Vec::new();
+        push_inverse(&make_cmd(CommandType::DespawnEntity, 1, &[]), &world, &map, &ComponentRegistry::new(), &mut inverse);
+        run(&[make_cmd(CommandType::DespawnEntity, 1, &[])], &mut world, &mut map, &mut rs);
+        assert!(map.get(1).is_none());
+
+        run(&inverse, &mut world, &mut map, &mut rs);
+        let e = map.get(1).unwrap();
+        assert_eq!(world.get::<&Position>(e).unwrap().0, glam::Vec3::new(4.0, 5.0, 6.0));
+        assert_eq!(world.get::<&TextureLayerIndex>(e).unwrap().0, 0x0002_0003);
+        assert_eq!(world.get::<&Parent>(e).unwrap().0, 0);
+        let parent = map.get(0).unwrap();
+        let children = world.get::<&Children>(parent).unwrap();
+        assert_eq!(children.as_slice(), &[1]);
+    }
+
+    #[test]
+    fn despawn_inverse_restores_configuration_not_progress() {
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut rs = RenderState::new();
+        let mut tween = [0u8; 16];
+        tween[0] = crate::tween::TARGET_POSITION;
+        tween[4..8].copy_from_slice(&1.0f32.to_le_bytes());
+        tween[8..12].copy_from_slice(&5.0f32.to_le_bytes());
+        tween[12..14].copy_from_slice(&20u16.to_le_bytes());
+        let mut anim = [0u8; 11];
+        anim[0..4].copy_from_slice(&3u32.to_le_bytes());
+        anim[4..8].copy_from_slice(&0.5f32.to_le_bytes());
+        anim[9..11].copy_from_slice(&2u16.to_le_bytes());
+        run(
+            &[
+                spawn(0),
+                make_cmd(CommandType::StartTween, 0, &tween),
+                make_cmd(CommandType::PlaySpriteAnimation, 0, &anim),
+                make_cmd(CommandType::SetLifetime, 0, &[30, 0, 0, 0, 1]),
+                make_cmd(CommandType::AttachEmitter, 0, &[7, 0, 0, 0, 1, 0, 0, 0]),
+                f32s_cmd(CommandType::SetEmitterRate, 0, &[12.0]),
+                f32s_cmd(CommandType::AttachCamera, 0, &[0.0, -1.0, 1.0]),
+                f32s_cmd(CommandType::SetCameraZoom, 0, &[2.0]),
+                f32s_cmd(CommandType::AddCameraTrauma, 0, &[0.4, 0.5, 6.0, 0.1]),
+            ],
+            &mut world,
+            &mut map,
+            &mut rs,
+        );
+        // Progress the systems would have made before the despawn.
+        let e = map.get(0).unwrap();
+        world.get::<&mut Tweens>(e).unwrap().tracks[0][0].elapsed = 5;
+        world.get::<&mut SpriteAnimation>(e).unwrap().elapsed = 1.5;
+        world.get::<&mut Emitter>(e).unwrap().accumulator = 0.5;
+        let camera_rng = world.get::<&Camera>(e).unwrap().rng;
+
+        let despawn = make_cmd(CommandType::DespawnEntity, 0, &[]);
+        let mut inverse = Vec::new();
+        push_inverse(&despawn, &world, &map, &ComponentRegistry::new(), &mut inverse);
+        run(&[despawn], &mut world, &mut map, &mut rs);
+        run(&inverse, &mut world, &mut map, &mut rs);
+
+        let e = map.get(0).unwrap();
+        let tween = world.get::<&Tweens>(e).unwrap().tracks[0][0];
+        assert_eq!((tween.from, tween.to, tween.duration), (1.0, 5.0, 20));
+        assert_eq!(tween.elapsed, 0, "tweens restart");
+        let anim = *world.get::<&SpriteAnimation>(e).unwrap();
+        assert_eq!((anim.clip, anim.speed, anim.frame), (3, 0.5, 2));
+        assert_eq!(anim.elapsed, 0.0, "animations resume at the start of their frame");
+        let lifetime = *world.get::<&Lifetime>(e).unwrap();
+        assert_eq!((lifetime.ticks_left, lifetime.cascade), (30, 1));
+        let emitter = *world.get::<&Emitter>(e).unwrap();
+        assert_eq!((emitter.config, emitter.rate), (7, 12.0));
+        assert_eq!(emitter.accumulator, 0.0);
+        let camera = *world.get::<&Camera>(e).unwrap();
+        assert_eq!((camera.zoom, camera.trauma, camera.max_offset), (2.0, 0.4, 6.0));
+        assert_ne!(camera.rng, camera_rng, "cameras are reseeded");
+    }
+
+    #[test]
+    fn runtime_component_setters_are_undone() {
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut rs = RenderState::new();
+        run(
+            &[
+                spawn(0),
+                f32s_cmd(CommandType::AttachCamera, 0, &[0.0, -1.0, 1.0]),
+                f32s_cmd(CommandType::AddCameraTrauma, 0, &[0.7, 1.0, 4.0, 0.0]),
+            ],
+            &mut world,
+            &mut map,
+            &mut rs,
+        );
+        let forward = [
+            make_cmd(CommandType::SetLifetime, 0, &[10, 0, 0, 0, 0]),
+            f32s_cmd(CommandType::AddCameraTrauma, 0, &[0.6, 2.0, 8.0, 0.2]),
+            f32s_cmd(CommandType::SetCameraZoom, 0, &[3.0]),
+        ];
+        let mut inverse = Vec::new();
+        for cmd in &forward {
+            let start = inverse.len();
+            push_inverse(cmd, &world, &map, &ComponentRegistry::new(), &mut inverse);
+            inverse[start..].reverse();
+            run(std::slice::from_ref(cmd), &mut world, &mut map, &mut rs);
+        }
+        inverse.reverse();
+        run(&inverse, &mut world, &mut map, &mut rs);
+
+        let e = map.get(0).unwrap();
+        assert!(world.get::<&Lifetime>(e).is_err());
+        let camera = *world.get::<&Camera>(e).unwrap();
+        assert_eq!((camera.zoom, camera.trauma_decay, camera.max_offset), (1.0, 1.0, 4.0));
+        assert!((camera.trauma - 0.7).abs() < 1e-6, "clamped trauma is restored");
+    }
+
+    #[test]
+    fn explicit_group_spans_batches() {
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut history = CommandHistory::new(DEFAULT_HISTORY_BUDGET);
+
+        record_batch(&mut history, &[marker(MARKER_BEGIN_GROUP), spawn(0)], &world, &map);
+        assert_eq!(history.undo_len(), 0, "group still open");
+        let mut rs = RenderState::new();
+        run(&[spawn(0)], &mut world, &mut map, &mut rs);
+        record_batch(&mut history, &[position(0, 1.0, 0.0, 0.0), marker(MARKER_END_GROUP)], &world, &map);
+
+        assert_eq!(history.undo_len(), 1);
+        let group = history.pop_undo().unwrap();
+        assert_eq!(group.forward.len(), 2);
+    }
+
+    #[test]
+    fn budget_evicts_oldest_groups() {
+        let world = World::new();
+        let map = EntityMap::new();
+        let per_group = 2 * std::mem::size_of::<Command>();
+        let mut history = CommandHistory::new(per_group * 3);
+
+        for id in 0..10 {
+            record_batch(&mut history, &[spawn(id)], &world, &map);
+        }
+        assert_eq!(history.undo_len(), 3);
+        assert!(history.used_bytes() <= per_group * 3);
+        // Most recent groups survive.
+        assert_eq!(history.pop_undo().unwrap().forward[0].entity_id, 9);
+    }
+
+    #[test]
+    fn new_group_clears_redo_stack() {
+        let world = World::new();
+        let map = EntityMap::new();
+        let mut history = CommandHistory::new(DEFAULT_HISTORY_BUDGET);
+        record_batch(&mut history, &[spawn(0)], &world, &map);
+        let g = history.pop_undo().unwrap();
+        history.push_redo(g);
+        assert_eq!(history.redo_len(), 1);
+        record_batch(&mut history, &[spawn(1)], &world, &map);
+        assert_eq!(history.redo_len(), 0);
+    }
+}
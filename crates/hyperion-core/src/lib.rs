@@ -2,16 +2,33 @@ use std::ptr::addr_of_mut;
 
 use wasm_bindgen::prelude::*;
 
+pub mod batching;
+pub mod camera;
 pub mod command_processor;
+pub mod component_registry;
 pub mod components;
+pub mod culling;
 pub mod engine;
+pub mod events;
+pub mod history;
+pub mod lifetime;
+pub mod particles;
 #[cfg(feature = "physics-2d")]
 pub mod physics;
 #[cfg(feature = "physics-2d")]
 pub mod physics_commands;
+pub mod picking;
+pub mod plugin;
 pub mod render_state;
 pub mod ring_buffer;
+pub mod rng;
+pub mod rollback;
+pub mod schedule;
+pub mod spatial;
+pub mod sprite_animation;
+pub mod state_hash;
 pub mod systems;
+pub mod tween;
 
 use engine::Engine;
 use ring_buffer::RingBufferConsumer;
@@ -47,16 +64,16 @@ pub fn engine_attach_ring_buffer(ptr: *mut u8, capacity: usize) {
 ///
 /// The Worker extracts unread bytes from the SharedArrayBuffer ring buffer
 /// and passes them here. wasm-bindgen handles the `&[u8]` → WASM memory
-/// copy automatically.
+/// copy automatically. Opcodes registered by Rust plugins are routed to them
+/// in wire order.
 ///
 /// Call this BEFORE `engine_update()` each frame.
 #[wasm_bindgen]
 pub fn engine_push_commands(data: &[u8]) {
-    let commands = ring_buffer::parse_commands(data);
     // SAFETY: wasm32 is single-threaded; no concurrent access.
     unsafe {
         if let Some(ref mut engine) = *addr_of_mut!(ENGINE) {
-            engine.process_commands(&commands);
+            engine.push_command_bytes(data);
         }
     }
 }
@@ -288,6 +305,85 @@ pub fn engine_gpu_depths_f32_len() -> usize {
     }
 }
 
+/// Pointer to the previous-tick transforms buffer (16 f32 per entity, parallel
+/// to the transforms buffer). Blend with `engine_interpolation_alpha()`.
+#[wasm_bindgen]
+pub fn engine_gpu_prev_transforms_ptr() -> *const f32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.render_state.gpu_prev_transforms_ptr())
+    }
+}
+
+/// Number of f32 values in the previous-tick transforms buffer.
+#[wasm_bindgen]
+pub fn engine_gpu_prev_transforms_f32_len() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0, |e| e.render_state.gpu_prev_transforms_f32_len())
+    }
+}
+
+/// Pointer to the tints buffer (1 packed RGBA8 u32 per entity, R in the low byte).
+#[wasm_bindgen]
+pub fn engine_gpu_tints_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.render_state.gpu_tints_ptr())
+    }
+}
+
+/// Number of u32 values in the tints buffer.
+#[wasm_bindgen]
+pub fn engine_gpu_tints_len() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0, |e| e.render_state.gpu_tints_len())
+    }
+}
+
+/// Blend factor between previous and current transforms (0.0..1.0).
+#[wasm_bindgen]
+pub fn engine_interpolation_alpha() -> f32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0.0, |e| e.interpolation_alpha())
+    }
+}
+
+/// Enable or disable capture of previous-tick world matrices.
+#[wasm_bindgen]
+pub fn engine_set_interpolation(enabled: bool) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.set_interpolation(enabled);
+        }
+    }
+}
+
+/// Whether previous-tick world matrices are captured. When false the
+/// previous-tick transforms buffer mirrors the transforms buffer.
+#[wasm_bindgen]
+pub fn engine_interpolation_enabled() -> bool {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .is_some_and(|e| e.interpolation_enabled())
+    }
+}
+
 // ── Dirty staging WASM exports ──────────────────────────────────
 
 /// Returns the number of dirty entities from the last staging collection.
@@ -312,7 +408,7 @@ pub fn engine_dirty_ratio() -> f32 {
     }
 }
 
-/// Pointer to the staging buffer (32 u32 per dirty entity).
+/// Pointer to the staging buffer (`STAGING_STRIDE` = 33 u32 per dirty entity).
 #[wasm_bindgen]
 pub fn engine_staging_ptr() -> *const u32 {
     // SAFETY: wasm32 is single-threaded.
@@ -449,6 +545,990 @@ pub fn engine_listener_z() -> f32 {
     }
 }
 
+// ── Simulation control WASM exports ────────────────────────────
+
+/// Set the fixed tick rate in ticks per second (0 is ignored).
+#[wasm_bindgen]
+pub fn engine_set_tick_rate(hz: u32) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.set_tick_rate(hz);
+        }
+    }
+}
+
+/// Seconds per fixed tick.
+#[wasm_bindgen]
+pub fn engine_fixed_dt() -> f32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(engine::FIXED_DT, |e| e.fixed_dt())
+    }
+}
+
+/// Set the simulation time scale (1.0 = real time).
+#[wasm_bindgen]
+pub fn engine_set_time_scale(scale: f32) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.set_time_scale(scale);
+        }
+    }
+}
+
+/// Pause or resume the simulation. Render state keeps updating while paused.
+#[wasm_bindgen]
+pub fn engine_set_paused(paused: bool) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.set_paused(paused);
+        }
+    }
+}
+
+/// Queue `ticks` fixed ticks to run on the next `engine_update()`, even when paused.
+#[wasm_bindgen]
+pub fn engine_step_ticks(ticks: u32) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.step(ticks);
+        }
+    }
+}
+
+/// Cap on fixed ticks run per `engine_update()` call (default 10).
+#[wasm_bindgen]
+pub fn engine_set_max_ticks_per_frame(max: u32) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.set_max_ticks_per_frame(max);
+        }
+    }
+}
+
+// ── Scheduler WASM exports ─────────────────────────────────────
+
+/// Enable or disable a system by name. Returns false if no such system.
+#[wasm_bindgen]
+pub fn engine_system_set_enabled(name: &str, enabled: bool) -> bool {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .is_some_and(|e| e.scheduler_mut().set_enabled(name, enabled))
+    }
+}
+
+/// Require system `first` to run before `then` (same stage).
+/// Returns false if either is unknown, they differ in stage, or it would cycle.
+#[wasm_bindgen]
+pub fn engine_system_add_ordering(first: &str, then: &str) -> bool {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .is_some_and(|e| e.scheduler_mut().add_ordering(first, then).is_ok())
+    }
+}
+
+/// Timing counters of a system as `[runs, last_ms, total_ms]`, or an empty
+/// array if no such system.
+#[wasm_bindgen]
+pub fn engine_system_timing(name: &str) -> Vec<f64> {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .and_then(|e| e.scheduler().timing(name))
+            .map_or_else(Vec::new, |t| vec![t.runs as f64, t.last_ms, t.total_ms])
+    }
+}
+
+/// System names of a stage (0=PreTick, 1=Tick, 2=PostTick, 3=PreRender) in
+/// execution order, comma-separated.
+#[wasm_bindgen]
+pub fn engine_system_order(stage: u8) -> String {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        match ((*addr_of_mut!(ENGINE)).as_ref(), systems::Stage::from_u8(stage)) {
+            (Some(e), Some(stage)) => e.scheduler().stage_order(stage).collect::<Vec<_>>().join(","),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Zero all system timing counters.
+#[wasm_bindgen]
+pub fn engine_system_reset_timings() {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.scheduler_mut().reset_timings();
+        }
+    }
+}
+
+// ── Component WASM exports ─────────────────────────────────────
+
+/// Insert or overwrite a component from raw little-endian bytes, by
+/// component registry ID. Returns false on unknown entity/component,
+/// read-only component, or size mismatch.
+#[wasm_bindgen]
+pub fn engine_set_component(external_id: u32, component_id: u8, bytes: &[u8]) -> bool {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .is_some_and(|e| e.set_component(external_id, component_id, bytes).is_ok())
+    }
+}
+
+/// Remove a component by registry ID. Returns false if it could not be removed.
+#[wasm_bindgen]
+pub fn engine_remove_component(external_id: u32, component_id: u8) -> bool {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .is_some_and(|e| e.remove_component(external_id, component_id).is_ok())
+    }
+}
+
+/// Raw bytes of a component, or an empty array if the entity lacks it.
+#[wasm_bindgen]
+pub fn engine_get_component(external_id: u32, component_id: u8) -> Vec<u8> {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .and_then(|e| e.get_component(external_id, component_id))
+            .unwrap_or_default()
+    }
+}
+
+/// Byte size of a registered component; -1 if unknown or variable-size.
+#[wasm_bindgen]
+pub fn engine_component_size(component_id: u8) -> i32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .and_then(|e| e.components().info(component_id))
+            .and_then(|info| info.size)
+            .map_or(-1, |size| size as i32)
+    }
+}
+
+// ── Tag / group WASM exports ────────────────────────────────────
+
+/// External IDs of all entities whose tags intersect `mask`, ascending.
+#[wasm_bindgen]
+pub fn engine_tagged_entities(mask: u32) -> Vec<u32> {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map(|e| e.tagged_entities(mask))
+            .unwrap_or_default()
+    }
+}
+
+// ── Engine event WASM exports ───────────────────────────────────
+
+/// Pointer to the engine events buffer.
+/// Buffer layout: N × 12 bytes (EngineEvent, #[repr(C)]).
+/// Valid from engine_update() return until next engine_update() call.
+#[wasm_bindgen]
+pub fn engine_events_ptr() -> *const u8 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.events().as_ptr() as *const u8)
+    }
+}
+
+/// Number of engine events raised during the last update.
+#[wasm_bindgen]
+pub fn engine_events_count() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0, |e| e.events().len() as u32)
+    }
+}
+
+// ── Sprite animation WASM exports ───────────────────────────────
+
+/// Register a sprite animation clip: packed texture layer indices and
+/// per-frame durations in ticks (same length). Frames in `event_frames`
+/// raise `EVENT_SPRITE_FRAME`. Returns the clip ID, or -1 if invalid.
+#[wasm_bindgen]
+pub fn engine_register_sprite_clip(layers: &[u32], durations: &[u16], event_frames: &[u16]) -> i32 {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .and_then(|e| e.register_sprite_clip(layers, durations, event_frames).ok())
+            .map_or(-1, |id| id as i32)
+    }
+}
+
+// ── Particle WASM exports ───────────────────────────────────────
+
+/// Register a particle emitter config from its raw `EmitterConfig` bytes.
+/// Returns the config ID, or -1 if invalid.
+#[wasm_bindgen]
+pub fn engine_register_emitter(bytes: &[u8]) -> i32 {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .and_then(|e| e.register_emitter_config(bytes).ok())
+            .map_or(-1, |id| id as i32)
+    }
+}
+
+/// Number of particles in the exported particle buffers.
+#[wasm_bindgen]
+pub fn engine_particle_count() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe { (*addr_of_mut!(ENGINE)).as_ref().map_or(0, |e| e.particles().count()) }
+}
+
+/// Pointer to the particle positions buffer (4 f32 per particle: xyz + size).
+#[wasm_bindgen]
+pub fn engine_particle_positions_ptr() -> *const f32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.particles().gpu_positions_ptr())
+    }
+}
+
+/// Number of f32 values in the particle positions buffer.
+#[wasm_bindgen]
+pub fn engine_particle_positions_f32_len() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0, |e| e.particles().gpu_positions().len() as u32)
+    }
+}
+
+/// Pointer to the particle colors buffer (4 f32 per particle: RGBA).
+#[wasm_bindgen]
+pub fn engine_particle_colors_ptr() -> *const f32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.particles().gpu_colors_ptr())
+    }
+}
+
+/// Number of f32 values in the particle colors buffer.
+#[wasm_bindgen]
+pub fn engine_particle_colors_f32_len() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0, |e| e.particles().gpu_colors().len() as u32)
+    }
+}
+
+// ── Spatial index WASM exports ──────────────────────────────────
+
+/// Enable the spatial index with the given grid cell size (0 = default).
+#[wasm_bindgen]
+pub fn engine_spatial_enable(cell_size: f32) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.enable_spatial_index(cell_size);
+        }
+    }
+}
+
+/// Disable the spatial index and free it.
+#[wasm_bindgen]
+pub fn engine_spatial_disable() {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.disable_spatial_index();
+        }
+    }
+}
+
+/// Entities overlapping the rectangle. Returns the number of results.
+#[wasm_bindgen]
+pub fn engine_spatial_query_rect(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> u32 {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .and_then(|e| e.spatial_index_mut())
+            .map_or(0, |s| s.query_rect(glam::Vec2::new(min_x, min_y), glam::Vec2::new(max_x, max_y)).len() as u32)
+    }
+}
+
+/// Entities overlapping the circle. Returns the number of results.
+#[wasm_bindgen]
+pub fn engine_spatial_query_circle(x: f32, y: f32, radius: f32) -> u32 {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .and_then(|e| e.spatial_index_mut())
+            .map_or(0, |s| s.query_circle(glam::Vec2::new(x, y), radius).len() as u32)
+    }
+}
+
+/// The `k` nearest entities, nearest first. Returns the number of results.
+#[wasm_bindgen]
+pub fn engine_spatial_query_knn(x: f32, y: f32, k: u32) -> u32 {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .and_then(|e| e.spatial_index_mut())
+            .map_or(0, |s| s.query_knn(glam::Vec2::new(x, y), k as usize).len() as u32)
+    }
+}
+
+/// Entities hit by the ray within `max_dist`, nearest first. Returns the
+/// number of results.
+#[wasm_bindgen]
+pub fn engine_spatial_query_ray(ox: f32, oy: f32, dx: f32, dy: f32, max_dist: f32) -> u32 {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .and_then(|e| e.spatial_index_mut())
+            .map_or(0, |s| s.query_ray(glam::Vec2::new(ox, oy), glam::Vec2::new(dx, dy), max_dist).len() as u32)
+    }
+}
+
+/// Pointer to the last query's external entity IDs (one u32 per result).
+#[wasm_bindgen]
+pub fn engine_spatial_results_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .and_then(|e| e.spatial_index())
+            .map_or(std::ptr::null(), |s| s.results_ptr())
+    }
+}
+
+/// Pointer to the last k-nearest or ray query's distances (one f32 per
+/// result; null after rect and circle queries).
+#[wasm_bindgen]
+pub fn engine_spatial_distances_ptr() -> *const f32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .and_then(|e| e.spatial_index())
+            .map_or(std::ptr::null(), |s| s.distances_ptr())
+    }
+}
+
+// ── Picking WASM exports ────────────────────────────────────────
+
+/// Topmost entity at world-space `(x, y)` whose pick layers intersect
+/// `mask`, or `u32::MAX` if none.
+#[wasm_bindgen]
+pub fn engine_pick(x: f32, y: f32, mask: u32) -> u32 {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .and_then(|e| e.pick(x, y, mask))
+            .unwrap_or(u32::MAX)
+    }
+}
+
+/// Every entity at world-space `(x, y)` on a layer in `mask`, topmost
+/// first. Returns the number of results.
+#[wasm_bindgen]
+pub fn engine_pick_all(x: f32, y: f32, mask: u32) -> u32 {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .map_or(0, |e| e.pick_all(x, y, mask).len() as u32)
+    }
+}
+
+/// Entities touching the world-space rectangle (entirely inside it if
+/// `contained`) on a layer in `mask`, topmost first. Returns the number of results.
+#[wasm_bindgen]
+pub fn engine_pick_rect(min_x: f32, min_y: f32, max_x: f32, max_y: f32, mask: u32, contained: bool) -> u32 {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .map_or(0, |e| e.pick_rect([min_x, min_y], [max_x, max_y], mask, contained).len() as u32)
+    }
+}
+
+/// Pointer to the last pick's external entity IDs (one u32 per result).
+#[wasm_bindgen]
+pub fn engine_pick_results_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.picker().results_ptr())
+    }
+}
+
+// ── Frustum culling WASM exports ────────────────────────────────
+
+/// Set the column-major view-projection matrix (16 f32) of cull `view`.
+/// Returns `false` if `vp` is not 16 floats or `view` is out of range.
+/// Culling runs at the end of each `engine_update`.
+#[wasm_bindgen]
+pub fn engine_cull_set_view(view: u32, vp: &[f32]) -> bool {
+    let Ok(vp) = <&[f32; 16]>::try_from(vp) else {
+        return false;
+    };
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .is_some_and(|e| e.render_state.set_cull_view(view as usize, vp))
+    }
+}
+
+/// Restrict cull `view` to entities on a render layer in `mask` (all by
+/// default). Returns `false` if `view` is out of range.
+#[wasm_bindgen]
+pub fn engine_cull_set_view_layers(view: u32, mask: u32) -> bool {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_mut()
+            .is_some_and(|e| e.render_state.set_cull_view_layers(view as usize, mask))
+    }
+}
+
+/// Remove every cull view.
+#[wasm_bindgen]
+pub fn engine_cull_clear_views() {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.render_state.clear_cull_views();
+        }
+    }
+}
+
+/// Re-cull every view against the current bounds, e.g. after moving the
+/// camera without an `engine_update`.
+#[wasm_bindgen]
+pub fn engine_cull() {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.render_state.cull();
+        }
+    }
+}
+
+/// Pointer to the visible slot indices of `view` (one u32 per slot, ascending).
+#[wasm_bindgen]
+pub fn engine_cull_visible_ptr(view: u32) -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.render_state.visible_slots_ptr(view as usize))
+    }
+}
+
+/// Number of visible slots of `view`.
+#[wasm_bindgen]
+pub fn engine_cull_visible_count(view: u32) -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0, |e| e.render_state.visible_count(view as usize))
+    }
+}
+
+// ── Render layer WASM exports ───────────────────────────────────
+
+/// Pointer to the per-layer entity counts (16 u32, one per render layer).
+#[wasm_bindgen]
+pub fn engine_render_layer_counts_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.render_state.layer_counts_ptr())
+    }
+}
+
+/// Build per-layer slot lists each update (off by default).
+#[wasm_bindgen]
+pub fn engine_render_layer_lists_enable(enabled: bool) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.render_state.set_layer_lists_enabled(enabled);
+        }
+    }
+}
+
+/// Pointer to the concatenated per-layer slot lists.
+#[wasm_bindgen]
+pub fn engine_render_layer_slots_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.render_state.layer_slots_ptr())
+    }
+}
+
+/// Pointer to the 17 u32 offsets into the slot lists: layer `l` spans
+/// `offsets[l]..offsets[l + 1]`.
+#[wasm_bindgen]
+pub fn engine_render_layer_offsets_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.render_state.layer_offsets_ptr())
+    }
+}
+
+// ── Draw batch WASM exports ─────────────────────────────────────
+
+/// Maintain draw batches each update (off by default).
+#[wasm_bindgen]
+pub fn engine_batching_enable(enabled: bool) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.render_state.set_batching_enabled(enabled);
+        }
+    }
+}
+
+/// Set the index count drawn for `mesh` (6, the quad, by default).
+#[wasm_bindgen]
+pub fn engine_set_mesh_index_count(mesh: u32, index_count: u32) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.render_state.set_mesh_index_count(mesh, index_count);
+        }
+    }
+}
+
+/// Number of draw batches.
+#[wasm_bindgen]
+pub fn engine_batch_count() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0, |e| e.render_state.draw_batches().batches().len() as u32)
+    }
+}
+
+/// Pointer to the draw batches: 6 u32 each (primitive, mesh, tier,
+/// transparent, first, count), sorted opaque first.
+#[wasm_bindgen]
+pub fn engine_batches_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.render_state.draw_batches().batches_ptr())
+    }
+}
+
+/// Pointer to the batch slot list (one u32 per slot, grouped by batch).
+#[wasm_bindgen]
+pub fn engine_batch_slots_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.render_state.draw_batches().slots_ptr())
+    }
+}
+
+/// Number of u32 in the batch slot list.
+#[wasm_bindgen]
+pub fn engine_batch_slots_len() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0, |e| e.render_state.draw_batches().slots().len() as u32)
+    }
+}
+
+/// Pointer to the indirect draw arguments (5 u32 per batch).
+#[wasm_bindgen]
+pub fn engine_batch_indirect_args_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.render_state.draw_batches().indirect_args_ptr())
+    }
+}
+
+/// Changes whenever the batches, slot list or arguments change.
+#[wasm_bindgen]
+pub fn engine_batch_version() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0, |e| e.render_state.draw_batches().version())
+    }
+}
+
+// ── Transparent sort WASM exports ───────────────────────────────
+
+/// Maintain a back-to-front order of transparent slots each update (off by
+/// default), for devices without the GPU radix sort.
+#[wasm_bindgen]
+pub fn engine_transparent_sort_enable(enabled: bool) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.render_state.set_transparent_sort_enabled(enabled);
+        }
+    }
+}
+
+/// Pointer to the transparent slots, back to front (one u32 per slot).
+#[wasm_bindgen]
+pub fn engine_transparent_order_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.render_state.transparent_order_ptr())
+    }
+}
+
+/// Number of slots in the transparent order.
+#[wasm_bindgen]
+pub fn engine_transparent_order_len() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(0, |e| e.render_state.transparent_order_len())
+    }
+}
+
+// ── Slot defragmentation WASM exports ───────────────────────────
+
+/// Gradually sort GPU slots by `order` (0 = off, 1 = draw batch, 2 = depth,
+/// 3 = external ID), swapping at most `max_swaps` slot pairs per update.
+/// Returns `false` for an unknown order.
+#[wasm_bindgen]
+pub fn engine_set_slot_order(order: u8, max_swaps: u32) -> bool {
+    let Some(order) = render_state::SlotOrder::from_u8(order) else {
+        return false;
+    };
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.render_state.set_slot_order(order, max_swaps);
+        }
+    }
+    true
+}
+
+// ── Camera WASM exports ─────────────────────────────────────────
+
+/// Number of active cameras exported by the last `engine_update`.
+#[wasm_bindgen]
+pub fn engine_camera_count() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe { (*addr_of_mut!(ENGINE)).as_ref().map_or(0, |e| e.cameras().count()) }
+}
+
+/// Pointer to the exported cameras' external IDs (ascending, one u32 each).
+#[wasm_bindgen]
+pub fn engine_camera_ids_ptr() -> *const u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.cameras().ids_ptr())
+    }
+}
+
+/// Pointer to the exported camera data: per camera, a column-major
+/// view-projection matrix (16 f32) followed by its viewport (4 f32).
+#[wasm_bindgen]
+pub fn engine_camera_data_ptr() -> *const f32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .map_or(std::ptr::null(), |e| e.cameras().data_ptr())
+    }
+}
+
+// ── Undo/redo WASM exports ──────────────────────────────────────
+
+/// Enable undo/redo recording with a memory budget in bytes (0 = default 1 MiB).
+#[wasm_bindgen]
+pub fn engine_history_enable(budget_bytes: u32) {
+    let budget = if budget_bytes == 0 {
+        history::DEFAULT_HISTORY_BUDGET
+    } else {
+        budget_bytes as usize
+    };
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.enable_history(budget);
+        }
+    }
+}
+
+/// Disable undo/redo recording and discard recorded history.
+#[wasm_bindgen]
+pub fn engine_history_disable() {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.disable_history();
+        }
+    }
+}
+
+/// Undo the most recent group. Returns false if there is nothing to undo.
+#[wasm_bindgen]
+pub fn engine_undo() -> bool {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.undo()
+        } else {
+            false
+        }
+    }
+}
+
+/// Redo the most recently undone group. Returns false if there is nothing to redo.
+#[wasm_bindgen]
+pub fn engine_redo() -> bool {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.redo()
+        } else {
+            false
+        }
+    }
+}
+
+/// Number of groups available to undo (0 when history is disabled).
+#[wasm_bindgen]
+pub fn engine_history_undo_count() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .and_then(|e| e.history())
+            .map_or(0, |h| h.undo_len() as u32)
+    }
+}
+
+/// Number of groups available to redo (0 when history is disabled).
+#[wasm_bindgen]
+pub fn engine_history_redo_count() -> u32 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .and_then(|e| e.history())
+            .map_or(0, |h| h.redo_len() as u32)
+    }
+}
+
+// ── Rollback WASM exports ──────────────────────────────────────
+
+/// Enable per-tick rollback captures for the last `capacity` ticks
+/// (0 = default 120). A non-zero `physics_interval` also captures Rapier
+/// state every that many ticks; only those ticks can then be rolled back to.
+#[wasm_bindgen]
+pub fn engine_rollback_enable(capacity: u32, physics_interval: u32) {
+    let capacity = if capacity == 0 {
+        rollback::DEFAULT_ROLLBACK_CAPACITY
+    } else {
+        capacity as usize
+    };
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.enable_rollback(capacity, physics_interval);
+        }
+    }
+}
+
+/// Disable rollback and discard all captures.
+#[wasm_bindgen]
+pub fn engine_rollback_disable() {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.disable_rollback();
+        }
+    }
+}
+
+/// Restore the state captured at the start of `tick`.
+/// Returns false if the tick is not in the rollback buffer.
+#[wasm_bindgen]
+pub fn engine_rollback_to(tick: u64) -> bool {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.rollback_to(tick)
+        } else {
+            false
+        }
+    }
+}
+
+/// Run `ticks` fixed ticks immediately after a rollback.
+#[wasm_bindgen]
+pub fn engine_resimulate(ticks: u32) {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.resimulate(ticks);
+        }
+    }
+}
+
+/// Oldest tick that can still be rolled back to, or `u64::MAX` if none.
+#[wasm_bindgen]
+pub fn engine_rollback_oldest_tick() -> u64 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .and_then(|e| e.rollback())
+            .and_then(|r| r.oldest_tick())
+            .unwrap_or(u64::MAX)
+    }
+}
+
+// ── State hash WASM exports ────────────────────────────────────
+
+/// Record a state hash after every fixed tick. `mask` selects components
+/// (`state_hash::HASH_*`, 0 = all); `capacity` is the history length (0 = default 600).
+#[wasm_bindgen]
+pub fn engine_state_hash_enable(mask: u32, capacity: u32) {
+    let mask = if mask == 0 { state_hash::HASH_ALL } else { mask };
+    let capacity = if capacity == 0 {
+        state_hash::DEFAULT_HASH_HISTORY
+    } else {
+        capacity as usize
+    };
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.enable_state_hashing(mask, capacity);
+        }
+    }
+}
+
+/// Stop recording state hashes.
+#[wasm_bindgen]
+pub fn engine_state_hash_disable() {
+    // SAFETY: wasm32 is single-threaded; no concurrent access.
+    unsafe {
+        if let Some(ref mut e) = *addr_of_mut!(ENGINE) {
+            e.disable_state_hashing();
+        }
+    }
+}
+
+/// Hash recorded after fixed tick `tick`, or 0 if not in history.
+#[wasm_bindgen]
+pub fn engine_state_hash_for_tick(tick: u64) -> u64 {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE))
+            .as_ref()
+            .and_then(|e| e.state_hasher())
+            .and_then(|h| h.hash_for_tick(tick))
+            .unwrap_or(0)
+    }
+}
+
+/// Per-component hashes of the current state as `[ext_id, component, hash_lo, hash_hi]`
+/// quadruples, using the recorder's mask (all components if hashing is disabled).
+#[wasm_bindgen]
+pub fn engine_state_hash_breakdown() -> Vec<u32> {
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE)).as_ref().map_or_else(Vec::new, |e| {
+            let mask = e.state_hasher().map_or(state_hash::HASH_ALL, |h| h.mask());
+            e.state_hash_breakdown(mask)
+                .iter()
+                .flat_map(|c| [c.external_id, c.component as u32, c.hash as u32, (c.hash >> 32) as u32])
+                .collect()
+        })
+    }
+}
+
+/// Compare the current state against a peer's `engine_state_hash_breakdown()`.
+/// Returns an empty array if they match, else
+/// `[ext_id, component, in_local, in_remote]` of the first divergence.
+#[wasm_bindgen]
+pub fn engine_state_hash_diff(remote: &[u32]) -> Vec<u32> {
+    let remote: Vec<state_hash::ComponentHash> = remote
+        .chunks_exact(4)
+        .map(|q| state_hash::ComponentHash {
+            external_id: q[0],
+            component: q[1] as u8,
+            hash: q[2] as u64 | ((q[3] as u64) << 32),
+        })
+        .collect();
+    // SAFETY: wasm32 is single-threaded.
+    unsafe {
+        (*addr_of_mut!(ENGINE)).as_ref().map_or_else(Vec::new, |e| {
+            let mask = e.state_hasher().map_or(state_hash::HASH_ALL, |h| h.mask());
+            state_hash::first_divergence(&e.state_hash_breakdown(mask), &remote)
+                .map_or_else(Vec::new, |d| {
+                    vec![d.external_id, d.component as u32, d.in_local as u32, d.in_remote as u32]
+                })
+        })
+    }
+}
+
 /// Expose WASM linear memory to JavaScript.
 /// wasm-bindgen does not auto-export `WebAssembly.Memory`; callers need
 /// it to create typed array views over SoA GPU buffers (transforms, bounds, etc.).
@@ -0,0 +1,60 @@
+//! Timed despawn.
+//!
+//! `Lifetime` counts down once per fixed tick on active entities. When it
+//! reaches zero the engine despawns the entity through the regular
+//! `DespawnEntity` path (render slot release, physics cleanup), optionally
+//! together with its descendants, and raises `EVENT_LIFETIME_EXPIRED`.
+
+use bytemuck::{Pod, Zeroable};
+use hecs::{With, World};
+
+use crate::components::{Active, ExternalId};
+
+/// Remaining lifetime of an entity, in fixed ticks.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct Lifetime {
+    pub ticks_left: u32,
+    /// Non-zero: despawn all descendants along with the entity.
+    pub cascade: u32,
+}
+
+impl Lifetime {
+    /// Decode a `SetLifetime` payload: `[ticks u32][cascade u8]`.
+    pub fn from_payload(p: &[u8; 16]) -> Self {
+        Self { ticks_left: u32::from_le_bytes(p[0..4].try_into().unwrap()), cascade: (p[4] != 0) as u32 }
+    }
+}
+
+/// Count every lifetime down by one tick and return the entities that
+/// expired, as `(external ID, cascade)` in ascending ID order.
+pub fn lifetime_system(world: &mut World) -> Vec<(u32, bool)> {
+    let mut expired: Vec<(u32, bool)> = world
+        .query_mut::<With<(&ExternalId, &mut Lifetime), &Active>>()
+        .into_iter()
+        .filter_map(|(ext, lifetime)| {
+            lifetime.ticks_left = lifetime.ticks_left.saturating_sub(1);
+            (lifetime.ticks_left == 0).then_some((ext.0, lifetime.cascade != 0))
+        })
+        .collect();
+    expired.sort_unstable_by_key(|&(id, _)| id);
+    expired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_down_and_reports_expired_in_id_order() {
+        let mut world = World::new();
+        world.spawn((ExternalId(5), Lifetime { ticks_left: 2, cascade: 1 }, Active));
+        world.spawn((ExternalId(3), Lifetime { ticks_left: 2, cascade: 0 }, Active));
+        world.spawn((ExternalId(1), Lifetime { ticks_left: 3, cascade: 0 }, Active));
+        let frozen = world.spawn((ExternalId(2), Lifetime { ticks_left: 1, cascade: 0 }));
+
+        assert!(lifetime_system(&mut world).is_empty());
+        assert_eq!(lifetime_system(&mut world), [(3, false), (5, true)]);
+        assert_eq!(world.get::<&Lifetime>(frozen).unwrap().ticks_left, 1, "inactive entities are frozen");
+    }
+}
@@ -0,0 +1,490 @@
+//! CPU particle simulation.
+//!
+//! Emitter configurations are registered once (`ParticleSystem::register`)
+//! and attached to entities through the `Emitter` component. Every fixed tick
+//! the system ages, integrates and culls live particles, then spawns new ones
+//! from each active emitter (continuous rate plus queued bursts) using the
+//! emitter's own seeded `Rng`, so emission replays identically.
+//!
+//! World-space particles spawn at the emitter's world position and live on
+//! after the emitter goes away. Local-space particles (`EMITTER_LOCAL_SPACE`)
+//! follow the emitter's `ModelMatrix`, including its parents, and die with it.
+//!
+//! After transforms are propagated, `export` writes two SoA buffers for the
+//! renderer, parallel by particle index:
+//! - positions: 4 f32 per particle (x, y, z, size)
+//! - colors: 4 f32 per particle (r, g, b, a)
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3};
+use hecs::{With, World};
+
+use crate::command_processor::EntityMap;
+use crate::components::{Active, ExternalId, ModelMatrix, Parent, Position, Transform2D};
+use crate::rng::Rng;
+
+/// Particles are simulated relative to the emitter and follow it.
+pub const EMITTER_LOCAL_SPACE: u32 = 1 << 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmitterError {
+    /// The config bytes are not `size_of::<EmitterConfig>()` long.
+    SizeMismatch,
+    /// Lifetimes must be finite with `0 < min <= max`.
+    InvalidLifetime,
+}
+
+/// Emitter parameters. Plain data so JS can pass it as raw bytes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct EmitterConfig {
+    /// Continuous emission, particles per second (per-emitter overridable).
+    pub rate: f32,
+    /// Particle lifetime range in seconds.
+    pub lifetime_min: f32,
+    pub lifetime_max: f32,
+    /// Initial speed range.
+    pub speed_min: f32,
+    pub speed_max: f32,
+    /// Cone axis (normalized on registration; zero = +Y).
+    pub direction: [f32; 3],
+    /// Cone half-angle in radians (0 = straight along `direction`, π = sphere).
+    pub cone_angle: f32,
+    /// Constant acceleration.
+    pub gravity: [f32; 3],
+    /// Size over life: 4 keys evenly spaced over [0, 1], linearly interpolated.
+    pub size: [f32; 4],
+    /// RGBA over life, same key spacing as `size`.
+    pub color: [[f32; 4]; 4],
+    /// Cap on live particles from one emitter.
+    pub max_particles: u32,
+    /// `EMITTER_*` bits.
+    pub flags: u32,
+}
+
+/// Particle emitter attached to an entity.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct Emitter {
+    /// Registered `EmitterConfig` ID.
+    pub config: u32,
+    /// Particles per second; negative uses the config's `rate`.
+    pub rate: f32,
+    /// Fractional particles carried over between ticks.
+    pub accumulator: f32,
+    /// Particles to emit on the next tick regardless of `rate`.
+    pub burst: u32,
+    /// Live particles emitted by this emitter.
+    pub alive: u32,
+    pub _pad: u32,
+    pub rng: Rng,
+}
+
+impl Emitter {
+    /// A fresh emitter for config `config` at the config's rate, seeded with `seed`.
+    pub fn new(config: u32, seed: u32) -> Self {
+        Self { config, rate: -1.0, accumulator: 0.0, burst: 0, alive: 0, _pad: 0, rng: Rng::new(seed as u64) }
+    }
+
+    /// Decode an `AttachEmitter` payload: `[config u32][seed u32]`.
+    pub fn from_payload(payload: &[u8; 16]) -> Self {
+        Self::new(
+            u32::from_le_bytes(payload[0..4].try_into().unwrap()),
+            u32::from_le_bytes(payload[4..8].try_into().unwrap()),
+        )
+    }
+}
+
+/// Live particle state (SoA, unordered).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Particles {
+    pos: Vec<Vec3>,
+    vel: Vec<Vec3>,
+    age: Vec<f32>,
+    lifetime: Vec<f32>,
+    /// Emitting entity's external ID (stable across rollback respawns).
+    owner: Vec<u32>,
+    config: Vec<u32>,
+}
+
+impl Particles {
+    pub fn len(&self) -> usize {
+        self.pos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos.is_empty()
+    }
+
+    /// Particle positions: world space, or emitter-local for local-space emitters.
+    pub fn positions(&self) -> &[Vec3] {
+        &self.pos
+    }
+
+    fn swap_remove(&mut self, i: usize) {
+        self.pos.swap_remove(i);
+        self.vel.swap_remove(i);
+        self.age.swap_remove(i);
+        self.lifetime.swap_remove(i);
+        self.owner.swap_remove(i);
+        self.config.swap_remove(i);
+    }
+}
+
+/// Registered configs, live particles and the exported GPU buffers.
+#[derive(Debug, Default)]
+pub struct ParticleSystem {
+    configs: Vec<EmitterConfig>,
+    particles: Particles,
+    gpu_positions: Vec<f32>,
+    gpu_colors: Vec<f32>,
+}
+
+/// Evaluate a 4-key curve at `t` in [0, 1].
+fn curve<const N: usize>(keys: &[[f32; N]; 4], t: f32) -> [f32; N] {
+    let x = t.clamp(0.0, 1.0) * 3.0;
+    let i = (x as usize).min(2);
+    let f = x - i as f32;
+    std::array::from_fn(|c| keys[i][c] + (keys[i + 1][c] - keys[i][c]) * f)
+}
+
+/// Unit vector within `half_angle` of `axis`, uniform over the cone's solid angle.
+fn cone_direction(rng: &mut Rng, axis: Vec3, half_angle: f32) -> Vec3 {
+    let cos_theta = 1.0 - rng.next_f32() * (1.0 - half_angle.cos());
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = rng.next_f32() * std::f32::consts::TAU;
+    let (u, v) = axis.any_orthonormal_pair();
+    axis * cos_theta + (u * phi.cos() + v * phi.sin()) * sin_theta
+}
+
+/// World-space position of an emitter entity. Children use the last
+/// propagated `ModelMatrix`; roots read their transform directly so a freshly
+/// spawned emitter already emits from the right place.
+fn emitter_origin(
+    parent: Option<&Parent>,
+    pos: Option<&Position>,
+    t2d: Option<&Transform2D>,
+    matrix: Option<&ModelMatrix>,
+) -> Vec3 {
+    match (parent, t2d, pos, matrix) {
+        (Some(p), .., Some(m)) if p.0 != u32::MAX => Vec3::new(m.0[12], m.0[13], m.0[14]),
+        (_, Some(t), ..) => Vec3::new(t.x, t.y, 0.0),
+        (_, _, Some(p), _) => p.0,
+        (.., Some(m)) => Vec3::new(m.0[12], m.0[13], m.0[14]),
+        _ => Vec3::ZERO,
+    }
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an emitter config from its raw bytes and return its ID.
+    pub fn register(&mut self, bytes: &[u8]) -> Result<u32, EmitterError> {
+        if bytes.len() != size_of::<EmitterConfig>() {
+            return Err(EmitterError::SizeMismatch);
+        }
+        let mut config: EmitterConfig = bytemuck::pod_read_unaligned(bytes);
+        let (min, max) = (config.lifetime_min, config.lifetime_max);
+        if !(min.is_finite() && max.is_finite() && min > 0.0 && min <= max) {
+            return Err(EmitterError::InvalidLifetime);
+        }
+        config.direction = Vec3::from(config.direction).try_normalize().unwrap_or(Vec3::Y).to_array();
+        self.configs.push(config);
+        Ok(self.configs.len() as u32 - 1)
+    }
+
+    pub fn config(&self, id: u32) -> Option<&EmitterConfig> {
+        self.configs.get(id as usize)
+    }
+
+    pub fn particles(&self) -> &Particles {
+        &self.particles
+    }
+
+    /// Replace the live particles (rollback restore).
+    pub fn restore_particles(&mut self, particles: &Particles) {
+        self.particles.clone_from(particles);
+    }
+
+    /// Drop every live particle (snapshot restore).
+    pub fn clear_particles(&mut self) {
+        self.particles = Particles::default();
+    }
+
+    /// Advance one fixed tick: age and integrate live particles, cull the
+    /// expired, then emit from every active emitter.
+    pub fn tick(&mut self, world: &mut World, entity_map: &EntityMap, dt: f32) {
+        let Self { configs, particles, .. } = self;
+
+        let mut i = 0;
+        while i < particles.len() {
+            let owner = entity_map.get(particles.owner[i]).filter(|&e| world.satisfies::<&Emitter>(e));
+            let config = &configs[particles.config[i] as usize];
+            let orphaned = config.flags & EMITTER_LOCAL_SPACE != 0 && owner.is_none();
+            particles.age[i] += dt;
+            if orphaned || particles.age[i] >= particles.lifetime[i] {
+                if let Some(owner) = owner
+                    && let Ok(mut emitter) = world.get::<&mut Emitter>(owner)
+                {
+                    emitter.alive = emitter.alive.saturating_sub(1);
+                }
+                particles.swap_remove(i);
+                continue;
+            }
+            particles.vel[i] += Vec3::from(config.gravity) * dt;
+            particles.pos[i] += particles.vel[i] * dt;
+            i += 1;
+        }
+
+        for (ext, (emitter, parent, pos, t2d, matrix)) in world.query_mut::<With<
+            (
+                &ExternalId,
+                (&mut Emitter, Option<&Parent>, Option<&Position>, Option<&Transform2D>, Option<&ModelMatrix>),
+            ),
+            &Active,
+        >>() {
+            let Some(config) = configs.get(emitter.config as usize) else {
+                continue;
+            };
+            let rate = if emitter.rate < 0.0 { config.rate } else { emitter.rate };
+            emitter.accumulator += rate.max(0.0) * dt;
+            let from_rate = emitter.accumulator.floor();
+            emitter.accumulator -= from_rate;
+            let wanted = std::mem::take(&mut emitter.burst).saturating_add(from_rate as u32);
+            let count = wanted.min(config.max_particles.saturating_sub(emitter.alive));
+            if count == 0 {
+                continue;
+            }
+            let origin = if config.flags & EMITTER_LOCAL_SPACE != 0 {
+                Vec3::ZERO
+            } else {
+                emitter_origin(parent, pos, t2d, matrix)
+            };
+            for _ in 0..count {
+                let rng = &mut emitter.rng;
+                let dir = cone_direction(rng, Vec3::from(config.direction), config.cone_angle);
+                let speed = rng.range(config.speed_min, config.speed_max);
+                let lifetime = rng.range(config.lifetime_min, config.lifetime_max);
+                particles.pos.push(origin);
+                particles.vel.push(dir * speed);
+                particles.age.push(0.0);
+                particles.lifetime.push(lifetime);
+                particles.owner.push(ext.0);
+                particles.config.push(emitter.config);
+            }
+            emitter.alive += count;
+        }
+    }
+
+    /// Fill the GPU buffers from the live particles. Run after transform
+    /// propagation so local-space particles follow their emitter.
+    pub fn export(&mut self, world: &World, entity_map: &EntityMap) {
+        let Self { configs, particles, gpu_positions, gpu_colors } = self;
+        gpu_positions.clear();
+        gpu_colors.clear();
+        for i in 0..particles.len() {
+            let config = &configs[particles.config[i] as usize];
+            let t = particles.age[i] / particles.lifetime[i];
+            let mut pos = particles.pos[i];
+            if config.flags & EMITTER_LOCAL_SPACE != 0
+                && let Some(owner) = entity_map.get(particles.owner[i])
+                && let Ok(m) = world.get::<&ModelMatrix>(owner)
+            {
+                pos = Mat4::from_cols_array(&m.0).transform_point3(pos);
+            }
+            let [size] = curve(&config.size.map(|s| [s]), t);
+            gpu_positions.extend_from_slice(&[pos.x, pos.y, pos.z, size]);
+            gpu_colors.extend_from_slice(&curve(&config.color, t));
+        }
+    }
+
+    /// Number of particles in the exported buffers.
+    pub fn count(&self) -> u32 {
+        (self.gpu_positions.len() / 4) as u32
+    }
+
+    pub fn gpu_positions(&self) -> &[f32] {
+        &self.gpu_positions
+    }
+
+    pub fn gpu_positions_ptr(&self) -> *const f32 {
+        if self.gpu_positions.is_empty() { std::ptr::null() } else { self.gpu_positions.as_ptr() }
+    }
+
+    pub fn gpu_colors(&self) -> &[f32] {
+        &self.gpu_colors
+    }
+
+    pub fn gpu_colors_ptr(&self) -> *const f32 {
+        if self.gpu_colors.is_empty() { std::ptr::null() } else { self.gpu_colors.as_ptr() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 0.1;
+
+    fn config() -> EmitterConfig {
+        EmitterConfig {
+            rate: 20.0,
+            lifetime_min: 1.0,
+            lifetime_max: 1.0,
+            speed_min: 1.0,
+            speed_max: 1.0,
+            direction: [0.0, 1.0, 0.0],
+            cone_angle: 0.0,
+            gravity: [0.0; 3],
+            size: [1.0, 2.0, 3.0, 4.0],
+            color: [[1.0; 4], [1.0; 4], [1.0; 4], [0.0; 4]],
+            max_particles: 1000,
+            flags: 0,
+        }
+    }
+
+    /// Spawn the (single) emitter entity of a test as external ID 0.
+    fn spawn(world: &mut World, map: &mut EntityMap, bundle: impl hecs::DynamicBundle) -> hecs::Entity {
+        let entity = world.spawn(bundle);
+        world.insert_one(entity, ExternalId(0)).unwrap();
+        map.insert(0, entity);
+        entity
+    }
+
+    fn system(config: EmitterConfig) -> ParticleSystem {
+        let mut ps = ParticleSystem::new();
+        ps.register(bytemuck::bytes_of(&config)).unwrap();
+        ps
+    }
+
+    #[test]
+    fn register_validates_config() {
+        let mut ps = ParticleSystem::new();
+        assert_eq!(ps.register(&[0; 8]), Err(EmitterError::SizeMismatch));
+        let mut bad = config();
+        bad.lifetime_max = 0.5;
+        assert_eq!(ps.register(bytemuck::bytes_of(&bad)), Err(EmitterError::InvalidLifetime));
+        let mut c = config();
+        c.direction = [0.0, 0.0, 2.0];
+        assert_eq!(ps.register(bytemuck::bytes_of(&c)), Ok(0));
+        assert_eq!(ps.config(0).unwrap().direction, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn rate_and_burst_emit_and_lifetime_culls() {
+        let mut ps = system(config());
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut emitter = Emitter::new(0, 1);
+        emitter.burst = 5;
+        let e = spawn(&mut world, &mut map, (Position(Vec3::new(3.0, 0.0, 0.0)), emitter, Active));
+
+        ps.tick(&mut world, &map, DT);
+        assert_eq!(ps.particles().len(), 7, "burst + 2 from rate");
+        assert!(ps.particles().positions().iter().all(|p| *p == Vec3::new(3.0, 0.0, 0.0)));
+        for _ in 0..9 {
+            ps.tick(&mut world, &map, DT);
+        }
+        assert_eq!(ps.particles().len(), 25);
+        // The first 7 expire on the tick their age reaches 1.0 s.
+        ps.tick(&mut world, &map, DT);
+        assert_eq!(ps.particles().len(), 20);
+        assert_eq!(world.get::<&Emitter>(e).unwrap().alive, 20);
+    }
+
+    #[test]
+    fn max_particles_caps_emission() {
+        let mut c = config();
+        c.max_particles = 3;
+        let mut ps = system(c);
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut emitter = Emitter::new(0, 1);
+        emitter.burst = 10;
+        spawn(&mut world, &mut map, (Position(Vec3::ZERO), emitter, Active));
+        ps.tick(&mut world, &map, DT);
+        ps.tick(&mut world, &map, DT);
+        assert_eq!(ps.particles().len(), 3);
+    }
+
+    #[test]
+    fn same_seed_emits_identical_particles() {
+        let mut c = config();
+        c.cone_angle = 1.0;
+        c.speed_max = 5.0;
+        let run = |seed| {
+            let mut ps = system(c);
+            let mut world = World::new();
+            let mut map = EntityMap::new();
+            spawn(&mut world, &mut map, (Position(Vec3::ZERO), Emitter::new(0, seed), Active));
+            for _ in 0..5 {
+                ps.tick(&mut world, &map, DT);
+            }
+            ps.particles().clone()
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn cone_directions_stay_within_half_angle() {
+        let mut rng = Rng::new(3);
+        for _ in 0..500 {
+            let d = cone_direction(&mut rng, Vec3::X, 0.5);
+            assert!((d.length() - 1.0).abs() < 1e-4);
+            assert!(d.angle_between(Vec3::X) <= 0.5 + 1e-3);
+        }
+    }
+
+    #[test]
+    fn gravity_integrates_and_curves_export() {
+        let mut c = config();
+        c.rate = 0.0;
+        c.speed_min = 0.0;
+        c.speed_max = 0.0;
+        c.gravity = [0.0, -10.0, 0.0];
+        let mut ps = system(c);
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut emitter = Emitter::new(0, 1);
+        emitter.burst = 1;
+        spawn(&mut world, &mut map, (Position(Vec3::ZERO), emitter, Active));
+        ps.tick(&mut world, &map, DT);
+        for _ in 0..5 {
+            ps.tick(&mut world, &map, DT);
+        }
+        ps.export(&world, &map);
+        assert_eq!(ps.count(), 1);
+        let p = ps.gpu_positions();
+        assert!(p[1] < -1.0, "fell: {}", p[1]);
+        // t = 0.5 lands between keys 1 and 2: size 2.5, alpha still 1.0.
+        assert!((p[3] - 2.5).abs() < 1e-4);
+        assert!((ps.gpu_colors()[3] - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn local_space_particles_follow_emitter_and_die_with_it() {
+        let mut c = config();
+        c.flags = EMITTER_LOCAL_SPACE;
+        c.rate = 0.0;
+        let mut ps = system(c);
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut emitter = Emitter::new(0, 1);
+        emitter.burst = 2;
+        let translate = |x: f32| ModelMatrix(Mat4::from_translation(Vec3::new(x, 0.0, 0.0)).to_cols_array());
+        let e = spawn(&mut world, &mut map, (translate(10.0), emitter, Active));
+        ps.tick(&mut world, &map, DT);
+        ps.export(&world, &map);
+        assert_eq!(ps.gpu_positions()[0], 10.0);
+        *world.get::<&mut ModelMatrix>(e).unwrap() = translate(20.0);
+        ps.export(&world, &map);
+        assert_eq!(ps.gpu_positions()[0], 20.0);
+
+        world.remove_one::<Emitter>(e).unwrap();
+        ps.tick(&mut world, &map, DT);
+        assert!(ps.particles().is_empty());
+    }
+}
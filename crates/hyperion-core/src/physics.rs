@@ -6,6 +6,7 @@
 pub mod types {
     /// Pending rigid body creation. Accumulates override commands before
     /// physics_sync_pre() creates the actual Rapier body.
+    #[derive(Clone)]
     pub struct PendingRigidBody {
         pub body_type: u8, // 0=dynamic, 1=fixed, 2=kinematic
         pub gravity_scale: f32,
@@ -36,6 +37,7 @@ pub mod types {
     }
 
     /// Pending collider creation. Consumed in physics_sync_pre().
+    #[derive(Clone)]
     pub struct PendingCollider {
         pub shape_type: u8,
         pub shape_params: [f32; 4],
@@ -103,19 +105,21 @@ pub mod types {
     pub struct PhysicsControlled;
 
     /// State result from the last `move_shape()` call.
-    #[derive(Default)]
+    #[derive(Default, Clone)]
     pub struct CharacterState {
         pub grounded: bool,
         pub is_sliding_down_slope: bool,
     }
 
     /// A character controller entry: Rapier KCC + last-frame state.
+    #[derive(Clone)]
     pub struct CharacterEntry {
         pub controller: rapier2d::control::KinematicCharacterController,
         pub state: CharacterState,
     }
 
     /// A live joint tracked in PhysicsWorld.joint_map.
+    #[derive(Clone)]
     pub struct JointEntry {
         pub handle: rapier2d::prelude::ImpulseJointHandle,
         pub entity_a: u32,
@@ -123,6 +127,7 @@ pub mod types {
     }
 
     /// The type of joint to create, parsed from ring buffer payloads.
+    #[derive(Clone)]
     pub enum PendingJointType {
         Revolute { anchor_ax: f32, anchor_ay: f32 },
         Prismatic { axis_x: f32, axis_y: f32 },
@@ -132,6 +137,7 @@ pub mod types {
     }
 
     /// A pending joint creation. Consumed in physics_sync_pre() step 4.
+    #[derive(Clone)]
     pub struct PendingJoint {
         pub joint_id: u32,
         pub entity_a_ext: u32,
@@ -429,6 +435,63 @@ mod world {
             Self::new()
         }
     }
+
+    /// Cloned simulation state of a `PhysicsWorld`, used by the rollback buffer.
+    ///
+    /// Excludes configuration (`gravity`, `integration_parameters`), the
+    /// pipeline's scratch buffers, event channels, and per-frame event buffers:
+    /// none of them influence the next step's result.
+    #[derive(Clone)]
+    pub struct PhysicsState {
+        island_manager: IslandManager,
+        broad_phase: DefaultBroadPhase,
+        narrow_phase: NarrowPhase,
+        rigid_body_set: RigidBodySet,
+        collider_set: ColliderSet,
+        impulse_joint_set: ImpulseJointSet,
+        multibody_joint_set: MultibodyJointSet,
+        collider_to_entity: Vec<Option<u32>>,
+        joint_map: std::collections::HashMap<u32, super::types::JointEntry>,
+        pending_joints: Vec<super::types::PendingJoint>,
+        character_map: std::collections::HashMap<u32, super::types::CharacterEntry>,
+        pending_moves: Vec<(u32, f32, f32)>,
+    }
+
+    impl PhysicsWorld {
+        /// Clone the simulation state for later `restore_state`.
+        pub fn save_state(&self) -> PhysicsState {
+            PhysicsState {
+                island_manager: self.island_manager.clone(),
+                broad_phase: self.broad_phase.clone(),
+                narrow_phase: self.narrow_phase.clone(),
+                rigid_body_set: self.rigid_body_set.clone(),
+                collider_set: self.collider_set.clone(),
+                impulse_joint_set: self.impulse_joint_set.clone(),
+                multibody_joint_set: self.multibody_joint_set.clone(),
+                collider_to_entity: self.collider_to_entity.clone(),
+                joint_map: self.joint_map.clone(),
+                pending_joints: self.pending_joints.clone(),
+                character_map: self.character_map.clone(),
+                pending_moves: self.pending_moves.clone(),
+            }
+        }
+
+        /// Overwrite the simulation state with a previously saved one.
+        pub fn restore_state(&mut self, state: &PhysicsState) {
+            self.island_manager = state.island_manager.clone();
+            self.broad_phase = state.broad_phase.clone();
+            self.narrow_phase = state.narrow_phase.clone();
+            self.rigid_body_set = state.rigid_body_set.clone();
+            self.collider_set = state.collider_set.clone();
+            self.impulse_joint_set = state.impulse_joint_set.clone();
+            self.multibody_joint_set = state.multibody_joint_set.clone();
+            self.collider_to_entity = state.collider_to_entity.clone();
+            self.joint_map = state.joint_map.clone();
+            self.pending_joints = state.pending_joints.clone();
+            self.character_map = state.character_map.clone();
+            self.pending_moves = state.pending_moves.clone();
+        }
+    }
 }
 
 #[cfg(feature = "physics-2d")]
@@ -3,7 +3,7 @@
 //! that need &mut PhysicsWorld.
 
 #[cfg(feature = "physics-2d")]
-use crate::command_processor::EntityMap;
+use crate::command_processor::{EntityMap, expand_group_command};
 #[cfg(feature = "physics-2d")]
 use crate::physics::{PhysicsBodyHandle, PhysicsWorld};
 #[cfg(feature = "physics-2d")]
@@ -140,6 +140,12 @@ pub fn process_physics_commands(
                 physics.pending_moves.push((cmd.entity_id, dx, dy));
                 continue;
             }
+            // Group impulse: one ApplyImpulse per tagged entity.
+            CommandType::ApplyGroupImpulse => {
+                let expanded = expand_group_command(cmd, world, entity_map);
+                process_physics_commands(&expanded, world, entity_map, physics);
+                continue;
+            }
             _ => {}
         }
 
@@ -0,0 +1,202 @@
+//! Point and rectangle picking against the rendered entities.
+//!
+//! Picking reads the SoA render buffers of the last `update()`, so it sees
+//! exactly what was drawn. Coordinates are world-space XY (unproject screen
+//! positions with the camera first). Entities drawn on the unit quad are
+//! tested against the quad's actual extents from their model matrix, so
+//! rotation, scale and parent transforms are exact. Lines, whose geometry
+//! comes from their primitive params, are tested against their bounding
+//! circle (`gpu_bounds` radius around the model translation).
+//!
+//! Hits are ordered topmost first: smaller depth first, then transparent
+//! before opaque (transparent entities draw over opaque ones), then higher
+//! external ID first.
+
+use glam::Vec2;
+use hecs::World;
+
+use crate::components::{PICK_LAYER_DEFAULT, PickLayer};
+use crate::render_state::RenderState;
+
+/// `RenderPrimitive` value of lines.
+const PRIMITIVE_LINE: u32 = 1;
+
+/// Transparent flag in the second `gpu_render_meta` word.
+const META_TRANSPARENT: u32 = 0x100;
+
+#[derive(Debug, Clone, Copy)]
+enum Shape {
+    /// Parallelogram `center + a * u + b * v` with `|a|, |b| <= 0.5`.
+    Quad { center: Vec2, u: Vec2, v: Vec2 },
+    Circle { center: Vec2, radius: f32 },
+}
+
+impl Shape {
+    fn of_slot(render_state: &RenderState, slot: usize) -> Self {
+        let m = &render_state.gpu_transforms()[slot * 16..slot * 16 + 16];
+        let center = Vec2::new(m[12], m[13]);
+        if render_state.gpu_render_meta()[slot * 2 + 1] & 0xFF == PRIMITIVE_LINE {
+            Shape::Circle { center, radius: render_state.gpu_bounds()[slot * 4 + 3] }
+        } else {
+            Shape::Quad { center, u: Vec2::new(m[0], m[1]), v: Vec2::new(m[4], m[5]) }
+        }
+    }
+
+    fn contains(&self, p: Vec2) -> bool {
+        match *self {
+            Shape::Quad { center, u, v } => {
+                let det = u.perp_dot(v);
+                if det.abs() <= f32::EPSILON {
+                    return false; // seen edge-on
+                }
+                let d = p - center;
+                let a = d.perp_dot(v) / det;
+                let b = u.perp_dot(d) / det;
+                a.abs() <= 0.5 && b.abs() <= 0.5
+            }
+            Shape::Circle { center, radius } => center.distance_squared(p) <= radius * radius,
+        }
+    }
+
+    /// Axis-aligned extents.
+    fn aabb(&self) -> (Vec2, Vec2) {
+        match *self {
+            Shape::Quad { center, u, v } => {
+                let half = (u.abs() + v.abs()) * 0.5;
+                (center - half, center + half)
+            }
+            Shape::Circle { center, radius } => (center - radius, center + radius),
+        }
+    }
+
+    fn overlaps_rect(&self, min: Vec2, max: Vec2) -> bool {
+        let (lo, hi) = self.aabb();
+        if lo.x > max.x || lo.y > max.y || hi.x < min.x || hi.y < min.y {
+            return false;
+        }
+        match *self {
+            // The rect axes passed above; test the quad's edge normals.
+            Shape::Quad { center, u, v } => {
+                let rect_center = (min + max) * 0.5;
+                let rect_half = (max - min) * 0.5;
+                [u.perp(), v.perp()].into_iter().all(|axis| {
+                    let quad_r = (axis.dot(u).abs() + axis.dot(v).abs()) * 0.5;
+                    let rect_r = axis.x.abs() * rect_half.x + axis.y.abs() * rect_half.y;
+                    axis.dot(center - rect_center).abs() <= quad_r + rect_r
+                })
+            }
+            Shape::Circle { center, radius } => center.clamp(min, max).distance_squared(center) <= radius * radius,
+        }
+    }
+
+    fn inside_rect(&self, min: Vec2, max: Vec2) -> bool {
+        let (lo, hi) = self.aabb();
+        lo.cmpge(min).all() && hi.cmple(max).all()
+    }
+}
+
+/// Pick queries and their engine-owned result buffer.
+#[derive(Debug, Default)]
+pub struct Picker {
+    /// (depth, opaque, id) of each hit, sorted into `results`.
+    hits: Vec<(f32, bool, u32)>,
+    results: Vec<u32>,
+}
+
+impl Picker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn collect(&mut self, render_state: &RenderState, world: &World, mask: u32, hit: impl Fn(&Shape) -> bool) -> &[u32] {
+        self.hits.clear();
+        for slot in 0..render_state.gpu_entity_count() {
+            let s = slot as usize;
+            if !hit(&Shape::of_slot(render_state, s)) {
+                continue;
+            }
+            let layer = render_state
+                .slot_entity(slot)
+                .and_then(|e| world.get::<&PickLayer>(e).ok().map(|l| l.0))
+                .unwrap_or(PICK_LAYER_DEFAULT);
+            if layer & mask == 0 {
+                continue;
+            }
+            let opaque = render_state.gpu_render_meta()[s * 2 + 1] & META_TRANSPARENT == 0;
+            self.hits.push((render_state.gpu_depths()[s], opaque, render_state.gpu_entity_ids()[s]));
+        }
+        self.hits.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(b.2.cmp(&a.2)));
+        self.results.clear();
+        self.results.extend(self.hits.iter().map(|h| h.2));
+        &self.results
+    }
+
+    /// Every entity on a layer in `mask` under `p`, topmost first.
+    pub fn pick_point(&mut self, render_state: &RenderState, world: &World, p: Vec2, mask: u32) -> &[u32] {
+        self.collect(render_state, world, mask, |shape| shape.contains(p))
+    }
+
+    /// Entities on a layer in `mask` touching the rectangle (or, with
+    /// `contained`, lying entirely inside it), topmost first.
+    pub fn pick_rect(
+        &mut self,
+        render_state: &RenderState,
+        world: &World,
+        min: Vec2,
+        max: Vec2,
+        mask: u32,
+        contained: bool,
+    ) -> &[u32] {
+        let (min, max) = (min.min(max), min.max(max));
+        if contained {
+            self.collect(render_state, world, mask, |shape| shape.inside_rect(min, max))
+        } else {
+            self.collect(render_state, world, mask, |shape| shape.overlaps_rect(min, max))
+        }
+    }
+
+    /// External IDs from the last pick.
+    pub fn results(&self) -> &[u32] {
+        &self.results
+    }
+
+    pub fn results_ptr(&self) -> *const u32 {
+        if self.results.is_empty() { std::ptr::null() } else { self.results.as_ptr() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_4;
+
+    fn quad(center: Vec2, size: Vec2, angle: f32) -> Shape {
+        let (sin, cos) = angle.sin_cos();
+        Shape::Quad { center, u: Vec2::new(cos, sin) * size.x, v: Vec2::new(-sin, cos) * size.y }
+    }
+
+    #[test]
+    fn rotated_quad_contains_only_its_extents() {
+        let shape = quad(Vec2::new(10.0, 0.0), Vec2::new(4.0, 2.0), FRAC_PI_4);
+        assert!(shape.contains(Vec2::new(10.0, 0.0)));
+        // Along the rotated long axis, inside the half-length of 2.
+        assert!(shape.contains(Vec2::new(10.0, 0.0) + Vec2::splat(1.9 / 2f32.sqrt())));
+        // Inside the bounding box, outside the rotated quad.
+        assert!(!shape.contains(Vec2::new(12.0, 1.5)));
+        let flat = Shape::Quad { center: Vec2::ZERO, u: Vec2::X, v: Vec2::ZERO };
+        assert!(!flat.contains(Vec2::ZERO));
+    }
+
+    #[test]
+    fn rect_overlap_uses_quad_edges() {
+        let diamond = quad(Vec2::ZERO, Vec2::splat(2.0), FRAC_PI_4);
+        // Inside the diamond's AABB corner but outside the diamond itself.
+        assert!(!diamond.overlaps_rect(Vec2::new(0.9, 0.9), Vec2::new(1.4, 1.4)));
+        assert!(diamond.overlaps_rect(Vec2::new(0.5, 0.5), Vec2::new(1.4, 1.4)));
+        assert!(diamond.inside_rect(Vec2::splat(-1.5), Vec2::splat(1.5)));
+        assert!(!diamond.inside_rect(Vec2::splat(-1.0), Vec2::splat(1.0)));
+        let circle = Shape::Circle { center: Vec2::ZERO, radius: 1.0 };
+        assert!(!circle.overlaps_rect(Vec2::new(0.8, 0.8), Vec2::splat(2.0)));
+        assert!(circle.overlaps_rect(Vec2::new(0.5, 0.5), Vec2::splat(2.0)));
+    }
+}
@@ -0,0 +1,415 @@
+//! Rust-side extension point for engine plugins.
+//!
+//! A plugin declares everything it contributes once, in
+//! `HyperionPlugin::build`:
+//! - command opcodes in the plugin range (`128..=255`) with payload sizes,
+//! - systems at scheduler stages, named `"<plugin>/<system>"`,
+//! - components with stable IDs (64–127 by convention) for generic access,
+//! - whether it writes render state each frame.
+//!
+//! Plugins run in the order they were added: commands are dispatched in wire
+//! order, systems in scheduler order (registration order unless constrained),
+//! and render writers in plugin order after the `PreRender` stage.
+//!
+//! Plugin commands are not recorded in undo history, and plugin state is not
+//! captured by rollback or snapshots.
+
+use hecs::World;
+
+use crate::command_processor::EntityMap;
+use crate::component_registry::{ComponentInfo, ComponentRegistry};
+use crate::render_state::RenderState;
+use crate::ring_buffer::{PLUGIN_OPCODE_START, PluginCommand};
+use crate::systems::{ScheduleError, Scheduler, Stage};
+
+/// Maximum payload size of a plugin command (same as core commands).
+pub const MAX_PLUGIN_PAYLOAD: usize = 16;
+
+const PLUGIN_OPCODE_COUNT: usize = 256 - PLUGIN_OPCODE_START as usize;
+
+/// Engine state handed to plugin commands and systems.
+pub struct PluginContext<'a> {
+    pub world: &'a mut World,
+    pub entity_map: &'a EntityMap,
+    pub render_state: &'a mut RenderState,
+    #[cfg(feature = "physics-2d")]
+    pub physics: &'a mut crate::physics::PhysicsWorld,
+    /// Fixed timestep in tick stages; frame time in `PreRender` and for commands.
+    pub dt: f32,
+    pub tick: u64,
+}
+
+/// A Rust plugin extending the engine with commands, systems and render output.
+pub trait HyperionPlugin {
+    /// Unique plugin name, used as the prefix of its system names.
+    fn name(&self) -> &str;
+
+    /// Declare opcodes, systems and render writers. Called once by `Engine::add_plugin`.
+    fn build(&mut self, registrar: &mut PluginRegistrar);
+
+    /// Apply one command with an opcode this plugin registered.
+    fn apply_command(&mut self, _ctx: &mut PluginContext, _cmd: &PluginCommand) {}
+
+    /// Run the system with the ID returned by `PluginRegistrar::add_system`.
+    fn run_system(&mut self, _system: u32, _ctx: &mut PluginContext) {}
+
+    /// Write plugin-owned per-slot render data. Runs once per frame after the
+    /// `PreRender` stage, when slot assignments and dirty staging are final.
+    fn write_render_state(&mut self, _world: &World, _render_state: &RenderState) {}
+}
+
+/// Collects what a plugin contributes during `HyperionPlugin::build`.
+#[derive(Default)]
+pub struct PluginRegistrar {
+    commands: Vec<(u8, usize)>,
+    systems: Vec<(String, Stage)>,
+    orderings: Vec<(String, String)>,
+    components: Vec<ComponentInfo>,
+    render_writer: bool,
+}
+
+impl PluginRegistrar {
+    /// Claim `opcode` (`128..=255`) with a fixed payload size in bytes (≤ 16).
+    pub fn add_command(&mut self, opcode: u8, payload_size: usize) {
+        self.commands.push((opcode, payload_size));
+    }
+
+    /// Schedule a system at the end of `stage`. Returns the ID passed to
+    /// `HyperionPlugin::run_system`.
+    pub fn add_system(&mut self, name: &str, stage: Stage) -> u32 {
+        self.systems.push((name.to_owned(), stage));
+        (self.systems.len() - 1) as u32
+    }
+
+    /// Require `first` to run before `then`. Names of this plugin's own
+    /// systems are resolved first; anything else names an existing system
+    /// (e.g. a built-in like `"velocity"`).
+    pub fn add_ordering(&mut self, first: &str, then: &str) {
+        self.orderings.push((first.to_owned(), then.to_owned()));
+    }
+
+    /// Register a component for `SetComponent`/`RemoveComponent`/`GetComponent`.
+    pub fn add_component(&mut self, info: ComponentInfo) {
+        self.components.push(info);
+    }
+
+    /// Call `HyperionPlugin::write_render_state` every frame.
+    pub fn add_render_writer(&mut self) {
+        self.render_writer = true;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginError {
+    DuplicateName,
+    /// Opcode below the plugin range.
+    OpcodeOutOfRange(u8),
+    /// Opcode already claimed by an earlier plugin (or twice by this one).
+    OpcodeTaken(u8),
+    PayloadTooLarge(u8),
+    /// Component ID already registered (or 0).
+    ComponentIdTaken(u8),
+    Schedule(ScheduleError),
+}
+
+struct PluginEntry {
+    plugin: Box<dyn HyperionPlugin>,
+    render_writer: bool,
+}
+
+/// The engine's plugins, in registration order, and their opcode table.
+pub struct PluginSet {
+    plugins: Vec<PluginEntry>,
+    /// `(plugin index, payload size)` per opcode, indexed by `opcode - 128`.
+    opcodes: Vec<Option<(usize, usize)>>,
+}
+
+impl Default for PluginSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PluginSet {
+    pub fn new() -> Self {
+        Self {
+            plugins: Vec::new(),
+            opcodes: vec![None; PLUGIN_OPCODE_COUNT],
+        }
+    }
+
+    /// Build `plugin` and register its opcodes, components and systems. On
+    /// error nothing is registered.
+    pub fn add(
+        &mut self,
+        mut plugin: Box<dyn HyperionPlugin>,
+        scheduler: &mut Scheduler,
+        components: &mut ComponentRegistry,
+    ) -> Result<(), PluginError> {
+        let name = plugin.name().to_owned();
+        if self.plugins.iter().any(|p| p.plugin.name() == name) {
+            return Err(PluginError::DuplicateName);
+        }
+        let mut registrar = PluginRegistrar::default();
+        plugin.build(&mut registrar);
+
+        let mut claimed = Vec::with_capacity(registrar.commands.len());
+        for &(opcode, size) in &registrar.commands {
+            if opcode < PLUGIN_OPCODE_START {
+                return Err(PluginError::OpcodeOutOfRange(opcode));
+            }
+            if size > MAX_PLUGIN_PAYLOAD {
+                return Err(PluginError::PayloadTooLarge(opcode));
+            }
+            let idx = (opcode - PLUGIN_OPCODE_START) as usize;
+            if self.opcodes[idx].is_some() || claimed.contains(&idx) {
+                return Err(PluginError::OpcodeTaken(opcode));
+            }
+            claimed.push(idx);
+        }
+        for (i, info) in registrar.components.iter().enumerate() {
+            if info.id == 0
+                || components.info(info.id).is_some()
+                || registrar.components[..i].iter().any(|c| c.id == info.id)
+            {
+                return Err(PluginError::ComponentIdTaken(info.id));
+            }
+        }
+
+        let index = self.plugins.len();
+        let full_names: Vec<String> =
+            registrar.systems.iter().map(|(sys, _)| format!("{name}/{sys}")).collect();
+        let resolve = |n: &str| {
+            registrar
+                .systems
+                .iter()
+                .position(|(sys, _)| sys == n)
+                .map_or_else(|| n.to_owned(), |i| full_names[i].clone())
+        };
+        let mut added = 0;
+        let mut result = Ok(());
+        for (id, (full, (_, stage))) in full_names.iter().zip(&registrar.systems).enumerate() {
+            result = scheduler.add_plugin_system(full, *stage, index, id as u32);
+            if result.is_err() {
+                break;
+            }
+            added += 1;
+        }
+        if result.is_ok() {
+            for (first, then) in &registrar.orderings {
+                result = scheduler.add_ordering(&resolve(first), &resolve(then));
+                if result.is_err() {
+                    break;
+                }
+            }
+        }
+        if let Err(e) = result {
+            for full in &full_names[..added] {
+                scheduler.remove_system(full);
+            }
+            return Err(PluginError::Schedule(e));
+        }
+
+        for &(opcode, size) in &registrar.commands {
+            self.opcodes[(opcode - PLUGIN_OPCODE_START) as usize] = Some((index, size));
+        }
+        for info in registrar.components {
+            // IDs were checked above.
+            let _ = components.register(info);
+        }
+        self.plugins.push(PluginEntry {
+            plugin,
+            render_writer: registrar.render_writer,
+        });
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Plugin names in execution order.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.plugins.iter().map(|p| p.plugin.name())
+    }
+
+    /// Payload size of a registered plugin opcode.
+    pub fn payload_size(&self, opcode: u8) -> Option<usize> {
+        let idx = opcode.checked_sub(PLUGIN_OPCODE_START)? as usize;
+        self.opcodes[idx].map(|(_, size)| size)
+    }
+
+    /// Route a command to the plugin that registered its opcode.
+    /// Returns `false` if no plugin owns the opcode.
+    pub(crate) fn apply_command(&mut self, cmd: &PluginCommand, ctx: &mut PluginContext) -> bool {
+        let Some(idx) = cmd.opcode.checked_sub(PLUGIN_OPCODE_START) else {
+            return false;
+        };
+        match self.opcodes[idx as usize] {
+            Some((plugin, _)) => {
+                self.plugins[plugin].plugin.apply_command(ctx, cmd);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn run_system(&mut self, plugin: usize, system: u32, ctx: &mut PluginContext) {
+        self.plugins[plugin].plugin.run_system(system, ctx);
+    }
+
+    pub(crate) fn write_render_state(&mut self, world: &World, render_state: &RenderState) {
+        for entry in self.plugins.iter_mut().filter(|p| p.render_writer) {
+            entry.plugin.write_render_state(world, render_state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::components::Position;
+    use crate::engine::{Engine, FIXED_DT};
+    use crate::ring_buffer::CommandType;
+
+    /// Moves entities by a per-command offset and logs everything it runs.
+    struct Nudge {
+        log: Rc<RefCell<Vec<String>>>,
+        pending: Vec<(u32, f32)>,
+    }
+
+    impl HyperionPlugin for Nudge {
+        fn name(&self) -> &str {
+            "nudge"
+        }
+
+        fn build(&mut self, registrar: &mut PluginRegistrar) {
+            registrar.add_command(200, 4);
+            registrar.add_system("apply", Stage::Tick);
+            registrar.add_ordering("apply", "velocity");
+            registrar.add_render_writer();
+        }
+
+        fn apply_command(&mut self, _ctx: &mut PluginContext, cmd: &PluginCommand) {
+            let dx = f32::from_le_bytes(cmd.bytes().try_into().unwrap());
+            self.pending.push((cmd.entity_id, dx));
+            self.log.borrow_mut().push(format!("cmd {}", cmd.entity_id));
+        }
+
+        fn run_system(&mut self, system: u32, ctx: &mut PluginContext) {
+            assert_eq!(system, 0);
+            for (id, dx) in self.pending.drain(..) {
+                if let Some(entity) = ctx.entity_map.get(id)
+                    && let Ok(mut pos) = ctx.world.get::<&mut Position>(entity)
+                {
+                    pos.0.x += dx;
+                }
+            }
+            self.log.borrow_mut().push(format!("tick {}", ctx.tick));
+        }
+
+        fn write_render_state(&mut self, _world: &World, render_state: &RenderState) {
+            self.log.borrow_mut().push(format!("render {}", render_state.gpu_entity_count()));
+        }
+    }
+
+    fn nudge() -> (Nudge, Rc<RefCell<Vec<String>>>) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        (Nudge { log: Rc::clone(&log), pending: Vec::new() }, log)
+    }
+
+    fn message(opcode: u8, entity_id: u32, payload: &[u8]) -> Vec<u8> {
+        let mut out = vec![opcode];
+        out.extend(entity_id.to_le_bytes());
+        out.extend(payload);
+        out
+    }
+
+    #[test]
+    fn plugin_commands_systems_and_writers_run_in_order() {
+        let mut engine = Engine::new();
+        let (plugin, log) = nudge();
+        engine.add_plugin(plugin).unwrap();
+        let order: Vec<_> = engine.scheduler().stage_order(Stage::Tick).collect();
+        let pos = |name| order.iter().position(|&n| n == name).unwrap();
+        assert!(pos("nudge/apply") < pos("velocity"));
+
+        // Spawn, then a plugin command for the same entity, in one stream.
+        let mut data = message(CommandType::SpawnEntity as u8, 7, &[0]);
+        data.extend(message(200, 7, &2.5f32.to_le_bytes()));
+        engine.push_command_bytes(&data);
+        engine.update(FIXED_DT);
+
+        let entity = engine.entity_map.get(7).unwrap();
+        assert_eq!(engine.world.get::<&Position>(entity).unwrap().0.x, 2.5);
+        assert_eq!(*log.borrow(), ["cmd 7", "tick 0", "render 1"]);
+    }
+
+    #[test]
+    fn pushed_batch_is_one_undo_group_around_plugin_commands() {
+        let mut engine = Engine::new();
+        engine.enable_history(crate::history::DEFAULT_HISTORY_BUDGET);
+        let (plugin, log) = nudge();
+        engine.add_plugin(plugin).unwrap();
+
+        let mut data = message(CommandType::SpawnEntity as u8, 1, &[0]);
+        data.extend(message(200, 1, &1.0f32.to_le_bytes()));
+        data.extend(message(CommandType::SpawnEntity as u8, 2, &[0]));
+        // A ScheduleAt in front of a plugin command is dropped with it.
+        let mut schedule = vec![0u8; 9];
+        schedule[0..8].copy_from_slice(&5u64.to_le_bytes());
+        data.extend(message(CommandType::ScheduleAt as u8, 0, &schedule));
+        data.extend(message(200, 2, &1.0f32.to_le_bytes()));
+        data.extend(message(CommandType::SpawnEntity as u8, 3, &[0]));
+        engine.push_command_bytes(&data);
+
+        assert_eq!(*log.borrow(), ["cmd 1"]);
+        assert!(engine.scheduled_commands().is_empty());
+        assert!(engine.entity_map.get(3).is_some());
+        assert_eq!(engine.history().unwrap().undo_len(), 1);
+        assert!(engine.undo());
+        assert!((1..=3).all(|id| engine.entity_map.get(id).is_none()));
+    }
+
+    #[test]
+    fn invalid_registrations_leave_engine_unchanged() {
+        struct Bad(u8, usize, Stage);
+        impl HyperionPlugin for Bad {
+            fn name(&self) -> &str {
+                "bad"
+            }
+            fn build(&mut self, registrar: &mut PluginRegistrar) {
+                registrar.add_command(self.0, self.1);
+                registrar.add_system("sys", self.2);
+                // Cross-stage ordering fails for PreRender systems.
+                registrar.add_ordering("sys", "velocity");
+            }
+        }
+
+        let mut engine = Engine::new();
+        let (plugin, _) = nudge();
+        engine.add_plugin(plugin).unwrap();
+        let (dup, _) = nudge();
+        assert_eq!(engine.add_plugin(dup), Err(PluginError::DuplicateName));
+        assert_eq!(engine.add_plugin(Bad(10, 0, Stage::Tick)), Err(PluginError::OpcodeOutOfRange(10)));
+        assert_eq!(engine.add_plugin(Bad(200, 0, Stage::Tick)), Err(PluginError::OpcodeTaken(200)));
+        assert_eq!(engine.add_plugin(Bad(201, 17, Stage::Tick)), Err(PluginError::PayloadTooLarge(201)));
+        assert_eq!(
+            engine.add_plugin(Bad(201, 0, Stage::PreRender)),
+            Err(PluginError::Schedule(ScheduleError::DifferentStages))
+        );
+        assert!(engine.scheduler().timing("bad/sys").is_none());
+        assert_eq!(engine.plugins().payload_size(201), None);
+        assert_eq!(engine.plugins().names().collect::<Vec<_>>(), ["nudge"]);
+
+        engine.add_plugin(Bad(201, 0, Stage::Tick)).unwrap();
+        assert_eq!(engine.plugins().payload_size(201), Some(0));
+    }
+}
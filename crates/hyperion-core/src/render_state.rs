@@ -4,12 +4,23 @@
 //! (transforms, bounds, renderMeta, texIndices) instead of one interleaved buffer.
 //! This enables partial upload, better GPU cache performance, and extensibility.
 
+use std::collections::HashMap;
+
 use hecs::World;
 
 use crate::components::{
-    Active, BoundingRadius, Depth, ExternalId, MeshHandle, ModelMatrix, Parent, Position,
-    PrimitiveParams, RenderPrimitive, Rotation, Scale, TextureLayerIndex, Transform2D, Transparent,
+    Active, BoundingRadius, Depth, ExternalId, Hidden, MeshHandle, ModelMatrix, Parent, Position,
+    PrimitiveParams, RENDER_LAYER_COUNT, RENDER_LAYER_DEFAULT, RenderLayer, RenderPrimitive, Rotation, Scale,
+    SortOrder, TINT_WHITE, TextureLayerIndex, Tint, Transform2D, Transparent,
 };
+use crate::batching::{DrawBatches, batch_key};
+use crate::culling::CullViews;
+
+/// Transparent flag in the second `gpu_render_meta` word.
+pub const RENDER_META_TRANSPARENT: u32 = 0x100;
+
+/// `RenderLayer` bits occupy the top 16 bits of the second `gpu_render_meta` word.
+pub const RENDER_META_LAYER_SHIFT: u32 = 16;
 
 /// Compact bitset for tracking dirty flags per entity slot.
 ///
@@ -65,6 +76,21 @@ impl BitSet {
         self.count
     }
 
+    /// Indices of the set bits, ascending. Skips empty words.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(w, &word)| {
+            let mut rest = word;
+            std::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                Some(w * 64 + bit)
+            })
+        })
+    }
+
     /// Pointer to the raw backing words, reinterpreted as `u32`.
     ///
     /// Each `u64` word contributes two consecutive `u32` values (little-endian).
@@ -89,10 +115,11 @@ impl BitSet {
 
 /// Tracks which entity slots have been modified since the last frame.
 ///
-/// Maintains three independent dirty bitsets corresponding to the SoA GPU buffers:
+/// Maintains four independent dirty bitsets corresponding to the SoA GPU buffers:
 /// - `transform_dirty`: entity model matrix changed
 /// - `bounds_dirty`: entity position or bounding radius changed
 /// - `meta_dirty`: entity mesh handle, render primitive, or texture index changed
+/// - `tint_dirty`: entity tint changed
 ///
 /// Used to determine whether a full or partial GPU buffer upload is beneficial.
 /// Rule of thumb: if `transform_dirty_ratio(total) < 0.3`, a partial upload wins.
@@ -100,6 +127,7 @@ pub struct DirtyTracker {
     transform_dirty: BitSet,
     bounds_dirty: BitSet,
     meta_dirty: BitSet,
+    tint_dirty: BitSet,
 }
 
 impl DirtyTracker {
@@ -109,6 +137,7 @@ impl DirtyTracker {
             transform_dirty: BitSet::new(capacity),
             bounds_dirty: BitSet::new(capacity),
             meta_dirty: BitSet::new(capacity),
+            tint_dirty: BitSet::new(capacity),
         }
     }
 
@@ -127,6 +156,11 @@ impl DirtyTracker {
         self.meta_dirty.set(idx);
     }
 
+    /// Mark entity at `idx` as having a dirty tint.
+    pub fn mark_tint_dirty(&mut self, idx: usize) {
+        self.tint_dirty.set(idx);
+    }
+
     /// Check if entity at `idx` has a dirty transform.
     pub fn is_transform_dirty(&self, idx: usize) -> bool {
         self.transform_dirty.get(idx)
@@ -142,6 +176,11 @@ impl DirtyTracker {
         self.meta_dirty.get(idx)
     }
 
+    /// Check if entity at `idx` has a dirty tint.
+    pub fn is_tint_dirty(&self, idx: usize) -> bool {
+        self.tint_dirty.get(idx)
+    }
+
     /// Fraction of entities with dirty transforms: `dirty_count / total`.
     /// Returns 0.0 if `total` is 0.
     pub fn transform_dirty_ratio(&self, total: usize) -> f32 {
@@ -181,6 +220,7 @@ impl DirtyTracker {
         self.transform_dirty.ensure_capacity(capacity);
         self.bounds_dirty.ensure_capacity(capacity);
         self.meta_dirty.ensure_capacity(capacity);
+        self.tint_dirty.ensure_capacity(capacity);
     }
 
     /// Clear all dirty flags for the next frame.
@@ -188,9 +228,61 @@ impl DirtyTracker {
         self.transform_dirty.clear();
         self.bounds_dirty.clear();
         self.meta_dirty.clear();
+        self.tint_dirty.clear();
+    }
+}
+
+/// Key `defragment_slots` gradually sorts slots by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SlotOrder {
+    /// Leave slots in assignment order.
+    #[default]
+    None = 0,
+    /// Draw-batch key (transparency, primitive, mesh, texture tier), so
+    /// every batch becomes one contiguous slot range.
+    Batch = 1,
+    /// Ascending `gpu_depths`.
+    Depth = 2,
+    /// Ascending external entity ID.
+    ExternalId = 3,
+}
+
+impl SlotOrder {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::None),
+            1 => Some(Self::Batch),
+            2 => Some(Self::Depth),
+            3 => Some(Self::ExternalId),
+            _ => None,
+        }
     }
 }
 
+/// u32 per dirty entity in the staging buffer (see `DirtyStagingResult`).
+pub const STAGING_STRIDE: usize = 33;
+
+/// Default bound on slot swaps per `defragment_slots` call.
+pub const DEFAULT_DEFRAG_SWAPS: u32 = 256;
+
+/// Element shifts per entry `sort_transparent` allows its insertion sort
+/// before falling back to a full sort.
+const TRANSPARENT_SORT_MAX_SHIFTS: usize = 8;
+
+/// Order-preserving map of f32 onto u32, like `floatToSortKey` in
+/// `radix-sort-pass.ts`.
+pub fn float_sort_key(f: f32) -> u32 {
+    let bits = f.to_bits();
+    if bits & 0x8000_0000 != 0 { !bits } else { bits | 0x8000_0000 }
+}
+
+/// Swap the `stride`-element chunks `a` and `b` of `v`.
+fn swap_chunks<T>(v: &mut [T], stride: usize, a: usize, b: usize) {
+    let (lo, hi) = (a.min(b), a.max(b));
+    let (head, tail) = v.split_at_mut(hi * stride);
+    head[lo * stride..(lo + 1) * stride].swap_with_slice(&mut tail[..stride]);
+}
+
 /// Contiguous buffers of render data for all active entities.
 /// Updated once per frame after all physics ticks and transform recomputation.
 pub struct RenderState {
@@ -206,6 +298,8 @@ pub struct RenderState {
     gpu_prim_params: Vec<f32>,   // 8 f32/entity (primitive-specific parameters)
     gpu_entity_ids: Vec<u32>,    // 1 u32/entity (external entity ID for picking)
     gpu_depths: Vec<f32>,        // 1 f32/entity (depth for back-to-front sorting)
+    gpu_prev_transforms: Vec<f32>, // 16 f32/entity (previous-tick mat4x4 for interpolation)
+    gpu_tints: Vec<u32>,         // 1 u32/entity (RGBA8 tint, R in the low byte)
     gpu_count: u32,
 
     /// Per-buffer dirty tracking for partial upload optimization.
@@ -221,11 +315,63 @@ pub struct RenderState {
     staging_indices_cache: Vec<u32>,
     staging_dirty_count: u32,
     staging_dirty_ratio: f32,
+
+    // Render interpolation bookkeeping (see update_prev_transforms)
+    /// Entities whose previous transform was captured at the start of the current tick.
+    prev_captured: Vec<hecs::Entity>,
+    /// Entities whose previous transform differs from the current one.
+    interpolated: Vec<hecs::Entity>,
+    /// Entities moved by a command since the last frame; never interpolated.
+    teleports: Vec<hecs::Entity>,
+    prev_fresh: BitSet,
+
+    /// Camera frusta for CPU culling and their visible-slot lists.
+    cull_views: CullViews,
+
+    // Per-layer bookkeeping (see update_layers)
+    layer_counts: [u32; RENDER_LAYER_COUNT],
+    /// Whether update_layers also builds the per-layer slot lists.
+    layer_lists_enabled: bool,
+    /// Slots of layer `l` are `layer_slots[layer_offsets[l]..layer_offsets[l + 1]]`.
+    layer_offsets: [u32; RENDER_LAYER_COUNT + 1],
+    layer_slots: Vec<u32>,
+
+    /// Whether update_batches maintains the draw batches.
+    batching_enabled: bool,
+    draw_batches: DrawBatches,
+
+    // Slot defragmentation (see defragment_slots)
+    slot_order: SlotOrder,
+    max_defrag_swaps: u32,
+    /// Cached sort key per slot, refreshed only for slots in `defrag_stale`.
+    defrag_keys: Vec<u64>,
+    /// Slots whose SoA data was rewritten since their key was cached.
+    defrag_stale: BitSet,
+    /// Whether `defrag_sorted` and `defrag_misplaced` match `defrag_keys`.
+    defrag_planned: bool,
+    defrag_sorted: Vec<u64>,
+    /// Misplaced slots by current key, popped lowest slot first.
+    defrag_misplaced: HashMap<u64, Vec<u32>>,
+    /// First slot the next `defragment_slots` call looks at.
+    defrag_cursor: usize,
+
+    // Back-to-front transparent order (see sort_transparent)
+    transparent_sort_enabled: bool,
+    /// Transparent slots, back to front.
+    transparent_order: Vec<u32>,
+    transparent_listed: BitSet,
+    /// Cached (depth descending, `SortOrder`, external ID) per slot,
+    /// refreshed only for slots in `transparent_stale`.
+    transparent_keys: Vec<(u32, i32, u32)>,
+    /// Slots whose SoA data was rewritten since their key was cached.
+    transparent_stale: BitSet,
+    /// Key and slot of each entry.
+    transparent_scratch: Vec<((u32, i32, u32), u32)>,
 }
 
 /// Result of collect_dirty_staging: compact staging buffer + indices for GPU scatter.
 pub struct DirtyStagingResult {
-    /// 32 u32 per dirty entity (128 bytes each): transforms(16) + bounds(4) + meta(2) + tex(1) + params(8) + format(1)
+    /// 33 u32 per dirty entity (132 bytes each): transforms(16) + bounds(4) + meta(2) + tex(1) + params(8) + format(1) + tint(1)
     /// Format flag at offset 31: 0 = compressed 2D (pos+rot+scale), 1 = pre-computed mat4x4
     pub staging: Vec<u32>,
     /// Destination slot index for each dirty entity
@@ -247,6 +393,8 @@ impl RenderState {
             gpu_prim_params: Vec::new(),
             gpu_entity_ids: Vec::new(),
             gpu_depths: Vec::new(),
+            gpu_prev_transforms: Vec::new(),
+            gpu_tints: Vec::new(),
             gpu_count: 0,
             dirty_tracker: DirtyTracker::new(0),
             slot_to_entity: Vec::new(),
@@ -256,14 +404,42 @@ impl RenderState {
             staging_indices_cache: Vec::new(),
             staging_dirty_count: 0,
             staging_dirty_ratio: 0.0,
+            prev_captured: Vec::new(),
+            interpolated: Vec::new(),
+            teleports: Vec::new(),
+            prev_fresh: BitSet::new(0),
+            cull_views: CullViews::default(),
+            layer_counts: [0; RENDER_LAYER_COUNT],
+            layer_lists_enabled: false,
+            layer_offsets: [0; RENDER_LAYER_COUNT + 1],
+            layer_slots: Vec::new(),
+            batching_enabled: false,
+            draw_batches: DrawBatches::default(),
+            slot_order: SlotOrder::None,
+            max_defrag_swaps: DEFAULT_DEFRAG_SWAPS,
+            defrag_keys: Vec::new(),
+            defrag_stale: BitSet::new(0),
+            defrag_planned: false,
+            defrag_sorted: Vec::new(),
+            defrag_misplaced: HashMap::new(),
+            defrag_cursor: 0,
+            transparent_sort_enabled: false,
+            transparent_order: Vec::new(),
+            transparent_listed: BitSet::new(0),
+            transparent_keys: Vec::new(),
+            transparent_stale: BitSet::new(0),
+            transparent_scratch: Vec::new(),
         }
     }
 
-    /// Collect model matrices from all active entities.
+    /// Collect model matrices from all active, visible entities.
     /// Clears previous data and repopulates from the current world state.
     pub fn collect(&mut self, world: &World) {
         self.matrices.clear();
-        for (matrix, _active) in world.query::<(&ModelMatrix, &Active)>().iter() {
+        for (matrix, _active) in world
+            .query::<hecs::Without<(&ModelMatrix, &Active), &Hidden>>()
+            .iter()
+        {
             self.matrices.push(matrix.0);
         }
     }
@@ -295,8 +471,11 @@ impl RenderState {
     /// - `gpu_bounds`: 4 f32/entity (position xyz + bounding radius)
     /// - `gpu_render_meta`: 2 u32/entity (mesh handle + render primitive)
     /// - `gpu_tex_indices`: 1 u32/entity (texture layer index)
+    /// - `gpu_tints`: 1 u32/entity (RGBA8 tint)
     pub fn collect_gpu(&mut self, world: &World) {
         self.dirty_tracker.clear();
+        self.defrag_keys.clear();
+        self.transparent_keys.clear();
 
         self.gpu_transforms.clear();
         self.gpu_bounds.clear();
@@ -305,6 +484,7 @@ impl RenderState {
         self.gpu_prim_params.clear();
         self.gpu_entity_ids.clear();
         self.gpu_depths.clear();
+        self.gpu_tints.clear();
 
         // Pre-allocate based on previous frame's entity count to avoid reallocation.
         let hint = self.gpu_count as usize;
@@ -315,11 +495,12 @@ impl RenderState {
         self.gpu_prim_params.reserve(hint * 8);
         self.gpu_entity_ids.reserve(hint);
         self.gpu_depths.reserve(hint);
+        self.gpu_tints.reserve(hint);
         self.dirty_tracker.ensure_capacity(hint);
         self.gpu_count = 0;
 
-        for (pos, matrix, radius, tex, mesh, prim, pp, ext_id, _active) in world
-            .query::<(
+        for (pos, matrix, radius, tex, mesh, prim, pp, ext_id, _active, tint) in world
+            .query::<hecs::Without<(
                 &Position,
                 &ModelMatrix,
                 &BoundingRadius,
@@ -329,7 +510,8 @@ impl RenderState {
                 &PrimitiveParams,
                 &ExternalId,
                 &Active,
-            )>()
+                Option<&Tint>,
+            ), &Hidden>>()
             .iter()
         {
             // Buffer A: Transform (16 f32)
@@ -355,6 +537,9 @@ impl RenderState {
             // Depth (1 f32) — legacy path uses position.z as fallback
             self.gpu_depths.push(pos.0.z);
 
+            // Tint (1 u32)
+            self.gpu_tints.push(tint.map_or(TINT_WHITE, |t| t.0));
+
             self.gpu_count += 1;
         }
 
@@ -365,6 +550,7 @@ impl RenderState {
         debug_assert_eq!(self.gpu_count as usize * 8, self.gpu_prim_params.len());
         debug_assert_eq!(self.gpu_count as usize, self.gpu_entity_ids.len());
         debug_assert_eq!(self.gpu_count as usize, self.gpu_depths.len());
+        debug_assert_eq!(self.gpu_count as usize, self.gpu_tints.len());
     }
 
     /// Number of entities in the GPU buffer.
@@ -521,6 +707,125 @@ impl RenderState {
         self.gpu_count as usize
     }
 
+    // --- SoA buffer accessors: tints ---
+
+    /// Tints, one packed RGBA8 u32 per GPU entity (R in the low byte).
+    /// Entities without a `Tint` are opaque white.
+    pub fn gpu_tints(&self) -> &[u32] {
+        &self.gpu_tints
+    }
+
+    /// Raw pointer to the tints buffer for WASM export. Returns null if empty.
+    pub fn gpu_tints_ptr(&self) -> *const u32 {
+        if self.gpu_tints.is_empty() {
+            std::ptr::null()
+        } else {
+            self.gpu_tints.as_ptr()
+        }
+    }
+
+    /// Number of tints (same as gpu_entity_count).
+    pub fn gpu_tints_len(&self) -> u32 {
+        self.gpu_count
+    }
+
+    /// Previous-tick transforms (16 f32 per slot), parallel to `gpu_transforms`.
+    /// Blend with `Engine::interpolation_alpha()` for smooth motion.
+    pub fn gpu_prev_transforms(&self) -> &[f32] {
+        &self.gpu_prev_transforms
+    }
+
+    /// Pointer to the previous-tick transforms buffer for WASM export.
+    pub fn gpu_prev_transforms_ptr(&self) -> *const f32 {
+        if self.gpu_prev_transforms.is_empty() {
+            std::ptr::null()
+        } else {
+            self.gpu_prev_transforms.as_ptr()
+        }
+    }
+
+    /// Number of f32 values in the previous-tick transforms buffer.
+    pub fn gpu_prev_transforms_f32_len(&self) -> u32 {
+        self.gpu_count * 16
+    }
+
+    /// Start a new tick's previous-transform capture. Only the captures of the
+    /// last tick in a frame are kept.
+    pub fn begin_prev_capture(&mut self) {
+        self.prev_captured.clear();
+    }
+
+    /// Record `matrix` as the entity's transform before the current tick.
+    pub fn capture_prev_transform(&mut self, entity: hecs::Entity, matrix: &[f32; 16]) {
+        if let Some(slot) = self.get_slot(entity) {
+            let t = slot as usize * 16;
+            self.gpu_prev_transforms[t..t + 16].copy_from_slice(matrix);
+            self.prev_captured.push(entity);
+        }
+    }
+
+    /// Disable interpolation for an entity until its next captured tick
+    /// (e.g. after `SetPosition`).
+    pub fn mark_teleport(&mut self, entity: hecs::Entity) {
+        self.teleports.push(entity);
+    }
+
+    /// Bring `gpu_prev_transforms` in line with `gpu_transforms`.
+    /// Call after `collect_and_cache_dirty()`; `ticked` is whether any fixed
+    /// tick ran this frame.
+    ///
+    /// Slots captured during the last tick keep their previous transform.
+    /// Every other changed slot (new, teleported, stopped, or moved outside
+    /// the tick loop) gets previous = current, so it renders without blending.
+    pub fn update_prev_transforms(&mut self, ticked: bool) {
+        self.prev_fresh.ensure_capacity(self.gpu_count as usize);
+        self.prev_fresh.clear();
+
+        if ticked {
+            for i in 0..self.prev_captured.len() {
+                if let Some(slot) = self.get_slot(self.prev_captured[i]) {
+                    self.prev_fresh.set(slot as usize);
+                }
+            }
+            // Entities that stopped moving: snap to current.
+            for i in 0..self.interpolated.len() {
+                if let Some(slot) = self.get_slot(self.interpolated[i])
+                    && !self.prev_fresh.get(slot as usize)
+                {
+                    self.snap_prev_transform(slot);
+                }
+            }
+            std::mem::swap(&mut self.interpolated, &mut self.prev_captured);
+            self.prev_captured.clear();
+        } else {
+            // Captures taken during a tick already include earlier teleports,
+            // so only frames without ticks need explicit snapping.
+            for i in 0..self.teleports.len() {
+                if let Some(slot) = self.get_slot(self.teleports[i]) {
+                    self.snap_prev_transform(slot);
+                }
+            }
+            for i in 0..self.interpolated.len() {
+                if let Some(slot) = self.get_slot(self.interpolated[i]) {
+                    self.prev_fresh.set(slot as usize);
+                }
+            }
+        }
+        self.teleports.clear();
+
+        for i in 0..self.staging_indices_cache.len() {
+            let slot = self.staging_indices_cache[i];
+            if !self.prev_fresh.get(slot as usize) {
+                self.snap_prev_transform(slot);
+            }
+        }
+    }
+
+    fn snap_prev_transform(&mut self, slot: u32) {
+        let t = slot as usize * 16;
+        self.gpu_prev_transforms[t..t + 16].copy_from_slice(&self.gpu_transforms[t..t + 16]);
+    }
+
     /// Assign a stable GPU slot to an entity. Returns the slot index.
     pub fn assign_slot(&mut self, entity: hecs::Entity) -> u32 {
         let slot = self.gpu_count;
@@ -550,12 +855,16 @@ impl RenderState {
         self.gpu_prim_params.resize((self.gpu_count as usize) * 8, 0.0);
         self.gpu_entity_ids.resize(self.gpu_count as usize, 0);
         self.gpu_depths.resize(self.gpu_count as usize, 0.0);
+        self.gpu_prev_transforms.resize((self.gpu_count as usize) * 16, 0.0);
+        self.gpu_tints.resize(self.gpu_count as usize, TINT_WHITE);
 
         // Mark all dirty
+        self.mark_rewritten(slot as usize);
         self.dirty_tracker.ensure_capacity(self.gpu_count as usize);
         self.dirty_tracker.mark_transform_dirty(slot as usize);
         self.dirty_tracker.mark_bounds_dirty(slot as usize);
         self.dirty_tracker.mark_meta_dirty(slot as usize);
+        self.dirty_tracker.mark_tint_dirty(slot as usize);
 
         slot
     }
@@ -564,6 +873,7 @@ impl RenderState {
     /// Used for initial population and dirty updates.
     pub fn write_slot(&mut self, slot: u32, world: &World, entity: hecs::Entity) {
         let s = slot as usize;
+        self.mark_rewritten(s);
 
         if let Ok(matrix) = world.get::<&ModelMatrix>(entity) {
             let t = s * 16;
@@ -586,10 +896,12 @@ impl RenderState {
         if let Ok(prim) = world.get::<&RenderPrimitive>(entity) {
             self.gpu_render_meta[s * 2 + 1] = prim.0 as u32;
         }
-        // Encode Transparent flag in bit 8 of renderMeta[s*2+1]
+        // Encode Transparent flag in bit 8 and render layers in bits 16..31 of renderMeta[s*2+1]
         if world.get::<&Transparent>(entity).is_ok() {
-            self.gpu_render_meta[s * 2 + 1] |= 0x100;
+            self.gpu_render_meta[s * 2 + 1] |= RENDER_META_TRANSPARENT;
         }
+        let layers = world.get::<&RenderLayer>(entity).map_or(RENDER_LAYER_DEFAULT, |l| l.0);
+        self.gpu_render_meta[s * 2 + 1] |= (layers & 0xFFFF) << RENDER_META_LAYER_SHIFT;
 
         if let Ok(tex) = world.get::<&TextureLayerIndex>(entity) {
             self.gpu_tex_indices[s] = tex.0;
@@ -612,6 +924,8 @@ impl RenderState {
         } else {
             self.gpu_depths[s] = 0.0;
         }
+
+        self.gpu_tints[s] = world.get::<&Tint>(entity).map_or(TINT_WHITE, |t| t.0);
     }
 
     /// Write all SoA data for a 2D entity (Transform2D archetype) into its assigned slot.
@@ -619,6 +933,7 @@ impl RenderState {
     /// Position/Rotation/Scale + pre-computed ModelMatrix.
     pub fn write_slot_2d(&mut self, slot: u32, world: &World, entity: hecs::Entity) {
         let s = slot as usize;
+        self.mark_rewritten(s);
 
         // Build ModelMatrix from Transform2D directly
         if let Ok(transform) = world.get::<&Transform2D>(entity) {
@@ -659,10 +974,12 @@ impl RenderState {
         if let Ok(prim) = world.get::<&RenderPrimitive>(entity) {
             self.gpu_render_meta[s * 2 + 1] = prim.0 as u32;
         }
-        // Encode Transparent flag in bit 8 of renderMeta[s*2+1]
+        // Encode Transparent flag in bit 8 and render layers in bits 16..31 of renderMeta[s*2+1]
         if world.get::<&Transparent>(entity).is_ok() {
-            self.gpu_render_meta[s * 2 + 1] |= 0x100;
+            self.gpu_render_meta[s * 2 + 1] |= RENDER_META_TRANSPARENT;
         }
+        let layers = world.get::<&RenderLayer>(entity).map_or(RENDER_LAYER_DEFAULT, |l| l.0);
+        self.gpu_render_meta[s * 2 + 1] |= (layers & 0xFFFF) << RENDER_META_LAYER_SHIFT;
         if let Ok(tex) = world.get::<&TextureLayerIndex>(entity) {
             self.gpu_tex_indices[s] = tex.0;
         }
@@ -680,6 +997,8 @@ impl RenderState {
         } else {
             self.gpu_depths[s] = 0.0;
         }
+
+        self.gpu_tints[s] = world.get::<&Tint>(entity).map_or(TINT_WHITE, |t| t.0);
     }
 
     /// Process all pending despawns via batch swap-remove.
@@ -708,6 +1027,8 @@ impl RenderState {
         // This guarantees that when we swap the "last" entity into the dead slot,
         // "last" is always a live entity (not one pending removal).
         despawn_slots.sort_unstable_by(|a, b| b.cmp(a));
+        // An entity hidden and then despawned in the same frame is queued twice.
+        despawn_slots.dedup();
 
         for slot in despawn_slots {
             let last = self.gpu_count - 1;
@@ -722,6 +1043,7 @@ impl RenderState {
                 self.dirty_tracker.mark_transform_dirty(slot as usize);
                 self.dirty_tracker.mark_bounds_dirty(slot as usize);
                 self.dirty_tracker.mark_meta_dirty(slot as usize);
+                self.dirty_tracker.mark_tint_dirty(slot as usize);
             }
 
             // Remove the dead entity from the mapping
@@ -735,6 +1057,7 @@ impl RenderState {
     fn copy_soa_slot(&mut self, src: u32, dst: u32) {
         let s = src as usize;
         let d = dst as usize;
+        self.mark_rewritten(d);
 
         // transforms: 16 f32 per slot
         let (ts, td) = (s * 16, d * 16);
@@ -760,6 +1083,73 @@ impl RenderState {
 
         // depths: 1 f32 per slot
         self.gpu_depths[d] = self.gpu_depths[s];
+
+        // prev_transforms: 16 f32 per slot
+        self.gpu_prev_transforms.copy_within(ts..ts + 16, td);
+
+        // tints: 1 u32 per slot
+        self.gpu_tints[d] = self.gpu_tints[s];
+    }
+
+    /// Swap the SoA data and entity mappings of two live slots and mark both
+    /// fully dirty, so the GPU copies are rewritten.
+    pub fn swap_slots(&mut self, a: u32, b: u32) {
+        if a != b {
+            self.mark_rewritten(a as usize);
+            self.mark_rewritten(b as usize);
+            self.swap_slot_data(a, b);
+        }
+    }
+
+    /// Invalidate the cached defragmentation and transparent sort keys of `slot`.
+    fn mark_rewritten(&mut self, slot: usize) {
+        self.defrag_stale.set(slot);
+        self.transparent_stale.set(slot);
+    }
+
+    /// `swap_slots` without invalidating the cached defragmentation keys.
+    fn swap_slot_data(&mut self, a: u32, b: u32) {
+        let (x, y) = (a as usize, b as usize);
+        self.transparent_stale.set(x);
+        self.transparent_stale.set(y);
+        swap_chunks(&mut self.gpu_transforms, 16, x, y);
+        swap_chunks(&mut self.gpu_bounds, 4, x, y);
+        swap_chunks(&mut self.gpu_render_meta, 2, x, y);
+        self.gpu_tex_indices.swap(x, y);
+        swap_chunks(&mut self.gpu_prim_params, 8, x, y);
+        self.gpu_entity_ids.swap(x, y);
+        self.gpu_depths.swap(x, y);
+        swap_chunks(&mut self.gpu_prev_transforms, 16, x, y);
+        self.gpu_tints.swap(x, y);
+
+        self.slot_to_entity.swap(x, y);
+        self.entity_to_slot[self.slot_to_entity[x].id() as usize] = a;
+        self.entity_to_slot[self.slot_to_entity[y].id() as usize] = b;
+        for slot in [x, y] {
+            self.dirty_tracker.mark_transform_dirty(slot);
+            self.dirty_tracker.mark_bounds_dirty(slot);
+            self.dirty_tracker.mark_meta_dirty(slot);
+            self.dirty_tracker.mark_tint_dirty(slot);
+        }
+    }
+
+    /// Give up a live entity's slot at the next flush, as a despawn would.
+    /// Used to hide entities without despawning them. Repeated releases are
+    /// deduplicated by the flush.
+    pub fn release_slot(&mut self, entity: hecs::Entity) {
+        if self.get_slot(entity).is_some() {
+            self.pending_despawns.push(entity);
+        }
+    }
+
+    /// Undo `release_slot`: cancel a pending release, or assign a fresh
+    /// (fully dirty) slot if the old one was already flushed.
+    pub fn restore_slot(&mut self, entity: hecs::Entity) -> u32 {
+        self.pending_despawns.retain(|&e| e != entity);
+        match self.get_slot(entity) {
+            Some(slot) => slot,
+            None => self.assign_slot(entity),
+        }
     }
 
     /// Look up the GPU slot for an entity. Returns None if not assigned.
@@ -772,6 +1162,11 @@ impl RenderState {
         if slot == u32::MAX { None } else { Some(slot) }
     }
 
+    /// The entity occupying `slot`, if the slot is live.
+    pub fn slot_entity(&self, slot: u32) -> Option<hecs::Entity> {
+        if slot < self.gpu_count { Some(self.slot_to_entity[slot as usize]) } else { None }
+    }
+
     /// Collect dirty entity data into a compact staging buffer for GPU scatter upload.
     /// Call flush_pending_despawns() before this.
     pub fn collect_dirty_staging(&mut self, world: &World) -> DirtyStagingResult {
@@ -792,7 +1187,8 @@ impl RenderState {
             let t = self.dirty_tracker.is_transform_dirty(slot);
             let b = self.dirty_tracker.is_bounds_dirty(slot);
             let m = self.dirty_tracker.is_meta_dirty(slot);
-            if t || b || m {
+            let c = self.dirty_tracker.is_tint_dirty(slot);
+            if t || b || m || c {
                 dirty_indices.push(slot as u32);
                 dirty_count += 1;
             }
@@ -800,8 +1196,8 @@ impl RenderState {
 
         let dirty_ratio = dirty_count as f32 / total as f32;
 
-        // Build staging buffer: 32 u32 per dirty entity
-        let mut staging = Vec::with_capacity(dirty_count as usize * 32);
+        // Build staging buffer: STAGING_STRIDE u32 per dirty entity
+        let mut staging = Vec::with_capacity(dirty_count as usize * STAGING_STRIDE);
         for &slot in &dirty_indices {
             let s = slot as usize;
             let entity = self.slot_to_entity[s];
@@ -890,6 +1286,8 @@ impl RenderState {
             }
             // Format flag: 0 = compressed 2D, 1 = pre-computed mat4x4
             staging.push(if is_root { 0 } else { 1 });
+            // Tint (RGBA8)
+            staging.push(self.gpu_tints[s]);
         }
 
         self.dirty_tracker.clear();
@@ -956,6 +1354,397 @@ impl RenderState {
         self.dirty_tracker.transforms_words_len()
     }
 
+    // --- Render layers ---
+
+    /// Render layers of the entity in `slot` (`RenderLayer` bits).
+    pub fn slot_layers(&self, slot: u32) -> u32 {
+        self.gpu_render_meta[slot as usize * 2 + 1] >> RENDER_META_LAYER_SHIFT
+    }
+
+    /// Build (or stop building) per-layer slot lists in `update_layers`.
+    pub fn set_layer_lists_enabled(&mut self, enabled: bool) {
+        self.layer_lists_enabled = enabled;
+        if !enabled {
+            self.layer_offsets = [0; RENDER_LAYER_COUNT + 1];
+            self.layer_slots.clear();
+        }
+    }
+
+    /// Recount slots per render layer and, if enabled, rebuild the per-layer
+    /// slot lists. An entity on several layers counts toward each.
+    pub fn update_layers(&mut self) {
+        self.layer_counts = [0; RENDER_LAYER_COUNT];
+        let count = self.gpu_count;
+        for slot in 0..count {
+            let mut layers = self.slot_layers(slot);
+            while layers != 0 {
+                self.layer_counts[layers.trailing_zeros() as usize] += 1;
+                layers &= layers - 1;
+            }
+        }
+        if !self.layer_lists_enabled {
+            return;
+        }
+        for l in 0..RENDER_LAYER_COUNT {
+            self.layer_offsets[l + 1] = self.layer_offsets[l] + self.layer_counts[l];
+        }
+        self.layer_slots.resize(self.layer_offsets[RENDER_LAYER_COUNT] as usize, 0);
+        let mut cursor = self.layer_offsets;
+        for slot in 0..count {
+            let mut layers = self.slot_layers(slot);
+            while layers != 0 {
+                let l = layers.trailing_zeros() as usize;
+                self.layer_slots[cursor[l] as usize] = slot;
+                cursor[l] += 1;
+                layers &= layers - 1;
+            }
+        }
+    }
+
+    /// Number of slots on each render layer as of the last `update_layers()`.
+    pub fn layer_counts(&self) -> &[u32; RENDER_LAYER_COUNT] {
+        &self.layer_counts
+    }
+
+    /// Slots on render layer `layer`, ascending. Empty unless layer lists are enabled.
+    pub fn layer_slots(&self, layer: usize) -> &[u32] {
+        if layer >= RENDER_LAYER_COUNT {
+            return &[];
+        }
+        &self.layer_slots[self.layer_offsets[layer] as usize..self.layer_offsets[layer + 1] as usize]
+    }
+
+    /// Pointer to the concatenated per-layer slot lists. Returns null if empty.
+    pub fn layer_slots_ptr(&self) -> *const u32 {
+        if self.layer_slots.is_empty() { std::ptr::null() } else { self.layer_slots.as_ptr() }
+    }
+
+    /// Pointer to the `RENDER_LAYER_COUNT + 1` list offsets into `layer_slots_ptr`.
+    pub fn layer_offsets_ptr(&self) -> *const u32 {
+        self.layer_offsets.as_ptr()
+    }
+
+    /// Pointer to the `RENDER_LAYER_COUNT` per-layer slot counts.
+    pub fn layer_counts_ptr(&self) -> *const u32 {
+        self.layer_counts.as_ptr()
+    }
+
+    // --- Draw batches ---
+
+    /// Maintain (or stop maintaining) the draw batches in `update_batches`.
+    pub fn set_batching_enabled(&mut self, enabled: bool) {
+        self.batching_enabled = enabled;
+        if !enabled {
+            self.draw_batches.clear();
+        }
+    }
+
+    /// Update the draw batches from the render meta and texture indices of
+    /// every slot. No-op unless batching is enabled. Returns `true` if the
+    /// batches changed.
+    pub fn update_batches(&mut self) -> bool {
+        self.batching_enabled
+            && self.draw_batches.update(&self.gpu_render_meta, &self.gpu_tex_indices, self.gpu_count as usize)
+    }
+
+    /// Draw batches, batch slot lists and indirect arguments as of the last
+    /// `update_batches()`.
+    pub fn draw_batches(&self) -> &DrawBatches {
+        &self.draw_batches
+    }
+
+    /// Set the index count drawn for `mesh` in the indirect arguments.
+    pub fn set_mesh_index_count(&mut self, mesh: u32, index_count: u32) {
+        self.draw_batches.set_mesh_index_count(mesh, index_count);
+    }
+
+    // --- Slot defragmentation ---
+
+    /// Choose the key `defragment_slots` sorts by and how many slot swaps it
+    /// may make per call.
+    pub fn set_slot_order(&mut self, order: SlotOrder, max_swaps: u32) {
+        self.slot_order = order;
+        self.max_defrag_swaps = max_swaps;
+        self.defrag_keys.clear();
+        if order == SlotOrder::None {
+            self.defrag_keys = Vec::new();
+            self.defrag_sorted = Vec::new();
+            self.defrag_misplaced = HashMap::new();
+        }
+    }
+
+    pub fn slot_order(&self) -> SlotOrder {
+        self.slot_order
+    }
+
+    fn slot_sort_key(&self, slot: usize) -> u64 {
+        match self.slot_order {
+            SlotOrder::None => 0,
+            SlotOrder::Batch => {
+                batch_key(self.gpu_render_meta[slot * 2 + 1], self.gpu_render_meta[slot * 2], self.gpu_tex_indices[slot])
+            }
+            SlotOrder::Depth => u64::from(float_sort_key(self.gpu_depths[slot])),
+            SlotOrder::ExternalId => u64::from(self.gpu_entity_ids[slot]),
+        }
+    }
+
+    /// Bring the cached keys up to date: recompute stale slots and slots
+    /// added since the last call, drop slots past `gpu_count`. Any change
+    /// invalidates the swap plan.
+    fn refresh_defrag_keys(&mut self) {
+        let count = self.gpu_count as usize;
+        let cached = self.defrag_keys.len().min(count);
+        if cached != self.defrag_keys.len() || cached != count {
+            self.defrag_planned = false;
+        }
+        self.defrag_keys.truncate(count);
+        let mut stale = std::mem::replace(&mut self.defrag_stale, BitSet::new(0));
+        for slot in stale.ones().take_while(|&slot| slot < cached) {
+            let key = self.slot_sort_key(slot);
+            if self.defrag_keys[slot] != key {
+                self.defrag_keys[slot] = key;
+                self.defrag_planned = false;
+            }
+        }
+        stale.clear();
+        self.defrag_stale = stale;
+        for slot in cached..count {
+            self.defrag_keys.push(self.slot_sort_key(slot));
+        }
+    }
+
+    /// Sort a copy of the keys into the target order and index the
+    /// misplaced slots, then restart the swap pass from slot 0.
+    fn plan_defrag(&mut self) {
+        self.defrag_planned = true;
+        self.defrag_misplaced.clear();
+        let keys = &self.defrag_keys;
+        if keys.is_sorted() {
+            self.defrag_sorted.clear();
+            self.defrag_cursor = keys.len();
+            return;
+        }
+        self.defrag_cursor = 0;
+        self.defrag_sorted.clear();
+        self.defrag_sorted.extend_from_slice(keys);
+        self.defrag_sorted.sort_unstable();
+        for slot in (0..keys.len()).rev() {
+            if keys[slot] != self.defrag_sorted[slot] {
+                self.defrag_misplaced.entry(keys[slot]).or_default().push(slot as u32);
+            }
+        }
+    }
+
+    /// Move slots toward ascending [`SlotOrder`] key with at most the
+    /// configured number of swaps, filling the sorted prefix first so
+    /// ranges become contiguous from slot 0 up. Flushes pending despawns.
+    /// Returns the number of swaps made.
+    ///
+    /// Keys are cached per slot and recomputed only for slots rewritten or
+    /// moved since the last call. The sorted target is rebuilt only when a
+    /// key changed; otherwise the pass resumes where the last call stopped.
+    /// Should the plan ever disagree with the cached keys, the pass stops and
+    /// the next call rebuilds every key.
+    ///
+    /// Swapped slots are marked fully dirty; call before the staging cache
+    /// is built.
+    pub fn defragment_slots(&mut self) -> u32 {
+        if self.slot_order == SlotOrder::None || self.max_defrag_swaps == 0 {
+            return 0;
+        }
+        self.flush_pending_despawns();
+        self.refresh_defrag_keys();
+        if !self.defrag_planned {
+            self.plan_defrag();
+        }
+
+        let count = self.gpu_count as usize;
+        let mut keys = std::mem::take(&mut self.defrag_keys);
+        let sorted = std::mem::take(&mut self.defrag_sorted);
+        let mut misplaced = std::mem::take(&mut self.defrag_misplaced);
+        let mut swaps = 0;
+        let mut slot = self.defrag_cursor;
+        while slot < count && swaps < self.max_defrag_swaps {
+            let want = sorted[slot];
+            if keys[slot] != want {
+                // Entries go stale once a swap moves them; skip those.
+                let candidates = misplaced.get_mut(&want);
+                let other = candidates.and_then(|candidates| {
+                    std::iter::from_fn(|| candidates.pop())
+                        .map(|s| s as usize)
+                        .find(|&s| s > slot && keys[s] == want && sorted[s] != want)
+                });
+                let Some(other) = other else {
+                    // The plan no longer matches the slots: rebuild every key and replan next call.
+                    keys.clear();
+                    self.defrag_planned = false;
+                    break;
+                };
+                self.swap_slot_data(slot as u32, other as u32);
+                keys.swap(slot, other);
+                if keys[other] != sorted[other] {
+                    misplaced.entry(keys[other]).or_default().push(other as u32);
+                }
+                swaps += 1;
+            }
+            slot += 1;
+        }
+        self.defrag_cursor = slot;
+        self.defrag_keys = keys;
+        self.defrag_sorted = sorted;
+        self.defrag_misplaced = misplaced;
+        swaps
+    }
+
+    // --- Transparent sorting ---
+
+    /// Maintain (or stop maintaining) the back-to-front transparent order in
+    /// `sort_transparent`.
+    pub fn set_transparent_sort_enabled(&mut self, enabled: bool) {
+        self.transparent_sort_enabled = enabled;
+        self.transparent_keys.clear();
+        if !enabled {
+            self.transparent_order.clear();
+        }
+    }
+
+    fn transparent_key(&self, world: &World, slot: usize) -> (u32, i32, u32) {
+        let order = world.get::<&SortOrder>(self.slot_to_entity[slot]).map_or(0, |o| o.0);
+        (!float_sort_key(self.gpu_depths[slot]), order, self.gpu_entity_ids[slot])
+    }
+
+    /// Recompute the cached transparent keys of rewritten and new slots.
+    fn refresh_transparent_keys(&mut self, world: &World) {
+        let count = self.gpu_count as usize;
+        let cached = self.transparent_keys.len().min(count);
+        self.transparent_keys.truncate(count);
+        let mut stale = std::mem::replace(&mut self.transparent_stale, BitSet::new(0));
+        for slot in stale.ones().take_while(|&slot| slot < cached) {
+            self.transparent_keys[slot] = self.transparent_key(world, slot);
+        }
+        stale.clear();
+        self.transparent_stale = stale;
+        for slot in cached..count {
+            self.transparent_keys.push(self.transparent_key(world, slot));
+        }
+    }
+
+    /// Re-sort the transparent slots back to front: descending `gpu_depths`,
+    /// then ascending `SortOrder`, then ascending external ID. No-op unless
+    /// transparent sorting is enabled.
+    ///
+    /// Keys are cached per slot and looked up in `world` only for slots
+    /// rewritten since the last call, so a `SortOrder` change is seen once
+    /// its slot is marked dirty. Starts from last frame's order, so the usual
+    /// nearly sorted input costs an insertion sort of O(n + moves); heavily
+    /// shuffled input falls back to a full sort.
+    pub fn sort_transparent(&mut self, world: &World) {
+        if !self.transparent_sort_enabled {
+            return;
+        }
+        self.refresh_transparent_keys(world);
+        let count = self.gpu_count as usize;
+        let mut entries = std::mem::take(&mut self.transparent_scratch);
+        entries.clear();
+        self.transparent_listed.ensure_capacity(count);
+        self.transparent_listed.clear();
+
+        // Last frame's order for slots that are still transparent, then new ones.
+        let is_transparent = |meta: &[u32], slot: usize| meta[slot * 2 + 1] & RENDER_META_TRANSPARENT != 0;
+        for i in 0..self.transparent_order.len() {
+            let slot = self.transparent_order[i] as usize;
+            if slot < count && is_transparent(&self.gpu_render_meta, slot) && !self.transparent_listed.get(slot) {
+                self.transparent_listed.set(slot);
+                entries.push((self.transparent_keys[slot], slot as u32));
+            }
+        }
+        for slot in 0..count {
+            if is_transparent(&self.gpu_render_meta, slot) && !self.transparent_listed.get(slot) {
+                entries.push((self.transparent_keys[slot], slot as u32));
+            }
+        }
+
+        let budget = entries.len() * TRANSPARENT_SORT_MAX_SHIFTS;
+        let mut shifts = 0;
+        for i in 1..entries.len() {
+            let mut j = i;
+            while j > 0 && entries[j - 1].0 > entries[j].0 {
+                entries.swap(j - 1, j);
+                j -= 1;
+            }
+            shifts += i - j;
+            if shifts > budget {
+                entries.sort_unstable_by_key(|&(key, _)| key);
+                break;
+            }
+        }
+
+        self.transparent_order.clear();
+        self.transparent_order.extend(entries.iter().map(|&(_, slot)| slot));
+        self.transparent_scratch = entries;
+    }
+
+    /// Transparent slots, back to front, as of the last `sort_transparent()`.
+    pub fn transparent_order(&self) -> &[u32] {
+        &self.transparent_order
+    }
+
+    /// Pointer to the back-to-front transparent slots. Returns null if empty.
+    pub fn transparent_order_ptr(&self) -> *const u32 {
+        if self.transparent_order.is_empty() { std::ptr::null() } else { self.transparent_order.as_ptr() }
+    }
+
+    /// Number of slots in the transparent order.
+    pub fn transparent_order_len(&self) -> u32 {
+        self.transparent_order.len() as u32
+    }
+
+    // --- CPU frustum culling ---
+
+    /// Set the column-major view-projection matrix of cull `view`
+    /// (`0..MAX_CULL_VIEWS`). Returns `false` if `view` is out of range.
+    pub fn set_cull_view(&mut self, view: usize, view_projection: &[f32; 16]) -> bool {
+        self.cull_views.set_view(view, view_projection)
+    }
+
+    /// Remove every cull view.
+    pub fn clear_cull_views(&mut self) {
+        self.cull_views.clear();
+    }
+
+    /// Restrict cull `view` to slots on a render layer in `mask` (all by
+    /// default). Returns `false` if `view` is out of range.
+    pub fn set_cull_view_layers(&mut self, view: usize, mask: u32) -> bool {
+        self.cull_views.set_layers(view, mask)
+    }
+
+    /// Recompute each cull view's visible-slot list from `gpu_bounds`.
+    /// No-op without cull views.
+    pub fn cull(&mut self) {
+        if !self.cull_views.is_empty() {
+            let meta = &self.gpu_render_meta;
+            self.cull_views.cull(&self.gpu_bounds[..self.gpu_count as usize * 4], |slot| {
+                meta[slot as usize * 2 + 1] >> RENDER_META_LAYER_SHIFT
+            });
+        }
+    }
+
+    /// Visible slot indices of cull `view` from the last `cull()`, ascending.
+    pub fn visible_slots(&self, view: usize) -> &[u32] {
+        self.cull_views.visible(view)
+    }
+
+    /// Pointer to the visible-slot list of `view`. Returns null if empty.
+    pub fn visible_slots_ptr(&self, view: usize) -> *const u32 {
+        let visible = self.cull_views.visible(view);
+        if visible.is_empty() { std::ptr::null() } else { visible.as_ptr() }
+    }
+
+    /// Number of visible slots of `view`.
+    pub fn visible_count(&self, view: usize) -> u32 {
+        self.cull_views.visible(view).len() as u32
+    }
+
     /// Release excess heap memory from all internal buffers.
     /// Call after a large batch of entity despawns to reclaim memory.
     pub fn shrink_to_fit(&mut self) {
@@ -967,6 +1756,7 @@ impl RenderState {
         self.gpu_prim_params.shrink_to_fit();
         self.gpu_entity_ids.shrink_to_fit();
         self.gpu_depths.shrink_to_fit();
+        self.gpu_tints.shrink_to_fit();
     }
 }
 
@@ -983,6 +1773,31 @@ mod tests {
     use crate::systems::transform_system;
     use glam::{Quat, Vec3};
 
+    #[test]
+    fn prev_transforms_follow_copy_and_snap() {
+        let mut world = World::new();
+        let mut rs = RenderState::new();
+        let a = world.spawn((Position(Vec3::new(1.0, 0.0, 0.0)), ModelMatrix::default()));
+        let b = world.spawn((Position(Vec3::ZERO), ModelMatrix::default()));
+        rs.assign_slot(a);
+        rs.assign_slot(b);
+        rs.collect_and_cache_dirty(&world);
+        rs.update_prev_transforms(false);
+        assert_eq!(rs.gpu_prev_transforms(), rs.gpu_transforms());
+
+        // Capture a distinct previous transform for b, then despawn a so b moves to slot 0.
+        let mut prev = [0.0f32; 16];
+        prev[12] = -5.0;
+        rs.begin_prev_capture();
+        rs.capture_prev_transform(b, &prev);
+        rs.pending_despawns.push(a);
+        rs.collect_and_cache_dirty(&world);
+        rs.update_prev_transforms(true);
+        assert_eq!(rs.get_slot(b), Some(0));
+        assert_eq!(rs.gpu_prev_transforms()[12], -5.0);
+        assert_eq!(rs.gpu_prev_transforms_f32_len(), 16);
+    }
+
     #[test]
     fn collect_gathers_active_matrices() {
         let mut world = World::new();
@@ -1345,6 +2160,17 @@ mod tests {
         assert!(!bs.get(127));
     }
 
+    #[test]
+    fn bitset_ones_lists_set_bits_in_order() {
+        let mut bs = BitSet::new(256);
+        for i in [200, 0, 63, 64, 130] {
+            bs.set(i);
+        }
+        assert_eq!(bs.ones().collect::<Vec<_>>(), [0, 63, 64, 130, 200]);
+        bs.clear();
+        assert_eq!(bs.ones().next(), None);
+    }
+
     #[test]
     fn bitset_ensure_capacity_grows() {
         let mut bs = BitSet::new(64);
@@ -1559,7 +2385,7 @@ mod tests {
         assert_eq!(rs.get_slot(e2), Some(1)); // e2 moved to slot 1
         assert_eq!(rs.get_slot(e1), None);    // e1 gone
         // e2's data now at slot 1
-        assert_eq!(rs.gpu_bounds[1 * 4], 77.0);
+        assert_eq!(rs.gpu_bounds[4], 77.0);
     }
 
     #[test]
@@ -1682,6 +2508,254 @@ mod tests {
         assert_eq!(result.dirty_indices[0], 0); // slot 0
     }
 
+    #[test]
+    fn tints_follow_slots() {
+        let mut rs = RenderState::new();
+        let mut world = World::new();
+        let a = world.spawn((Position::default(), ModelMatrix::default(), Tint(0x8000_00FF), Active));
+        let b = world.spawn((Position::default(), ModelMatrix::default(), Active));
+        let c = world.spawn((Position::default(), ModelMatrix::default(), Tint(0x1122_3344), Active));
+        for e in [a, b, c] {
+            let slot = rs.assign_slot(e);
+            rs.write_slot(slot, &world, e);
+        }
+        assert_eq!(rs.gpu_tints(), [0x8000_00FF, TINT_WHITE, 0x1122_3344]);
+
+        // Each staging record ends with the slot's tint.
+        let staging = rs.collect_dirty_staging(&world).staging;
+        assert_eq!(staging.len(), 3 * STAGING_STRIDE);
+        assert_eq!(staging[STAGING_STRIDE - 1], 0x8000_00FF);
+        assert_eq!(staging[3 * STAGING_STRIDE - 1], 0x1122_3344);
+
+        // A tint change alone dirties only the tint and stages its slot.
+        world.insert_one(b, Tint(0x0000_00FF)).unwrap();
+        rs.dirty_tracker.mark_tint_dirty(1);
+        assert!(!rs.dirty_tracker.is_meta_dirty(1));
+        let result = rs.collect_dirty_staging(&world);
+        assert_eq!(result.dirty_indices, [1]);
+        assert_eq!(result.staging[STAGING_STRIDE - 1], 0x0000_00FF);
+
+        // Despawning `a` moves `c` into slot 0 along with its tint.
+        rs.pending_despawns.push(a);
+        rs.flush_pending_despawns();
+        assert_eq!(rs.gpu_tints()[..2], [0x1122_3344, 0x0000_00FF]);
+    }
+
+    #[test]
+    fn render_layers_pack_into_meta_and_build_lists() {
+        let mut rs = RenderState::new();
+        let mut world = World::new();
+        let a = world.spawn((Position::default(), ModelMatrix::default(), Active));
+        let b = world.spawn((Position::default(), ModelMatrix::default(), RenderLayer(0b110), Active));
+        let c = world.spawn((Position::default(), ModelMatrix::default(), RenderLayer(0b100), Active));
+        for e in [a, b, c] {
+            let slot = rs.assign_slot(e);
+            rs.write_slot(slot, &world, e);
+        }
+        assert_eq!(rs.slot_layers(0), RENDER_LAYER_DEFAULT);
+        assert_eq!(rs.slot_layers(1), 0b110);
+        assert_eq!(rs.gpu_render_meta[3] & 0xFF, 0, "primitive bits untouched");
+
+        rs.update_layers();
+        assert_eq!(rs.layer_counts()[..4], [1, 1, 2, 0]);
+        assert!(rs.layer_slots(2).is_empty(), "lists are off by default");
+
+        rs.set_layer_lists_enabled(true);
+        rs.update_layers();
+        assert_eq!(rs.layer_slots(0), [0]);
+        assert_eq!(rs.layer_slots(1), [1]);
+        assert_eq!(rs.layer_slots(2), [1, 2]);
+        assert!(rs.layer_slots(RENDER_LAYER_COUNT).is_empty());
+    }
+
+    #[test]
+    fn defragment_sorts_slots_within_the_swap_budget() {
+        let mut rs = RenderState::new();
+        let mut world = World::new();
+        let ids = [5u32, 3, 7, 0, 6, 1, 4, 2];
+        let entities: Vec<_> = ids
+            .iter()
+            .map(|&id| {
+                let e = world.spawn((Position::default(), ModelMatrix::default(), ExternalId(id), Tint(id), Active));
+                let slot = rs.assign_slot(e);
+                rs.write_slot(slot, &world, e);
+                e
+            })
+            .collect();
+        rs.dirty_tracker.clear();
+        rs.set_slot_order(SlotOrder::ExternalId, 2);
+
+        assert_eq!(rs.defragment_slots(), 2);
+        assert_eq!(rs.gpu_entity_ids()[..2], [0, 1]);
+        assert!(rs.dirty_tracker.is_transform_dirty(0) && rs.dirty_tracker.is_meta_dirty(3));
+        while rs.defragment_slots() > 0 {}
+        assert_eq!(rs.gpu_entity_ids(), [0, 1, 2, 3, 4, 5, 6, 7]);
+        // Per-slot data and both slot maps moved with their entities.
+        assert_eq!(rs.gpu_tints(), [0, 1, 2, 3, 4, 5, 6, 7]);
+        for (&e, &id) in entities.iter().zip(&ids) {
+            assert_eq!(rs.get_slot(e), Some(id));
+            assert_eq!(rs.slot_entity(id), Some(e));
+        }
+    }
+
+    #[test]
+    fn defragment_refreshes_only_rewritten_slots() {
+        let mut rs = RenderState::new();
+        let mut world = World::new();
+        let entities: Vec<_> = (0..6u32)
+            .map(|id| {
+                let e = world.spawn((Position::default(), ModelMatrix::default(), ExternalId(id), Active));
+                let slot = rs.assign_slot(e);
+                rs.write_slot(slot, &world, e);
+                e
+            })
+            .collect();
+        rs.set_slot_order(SlotOrder::ExternalId, 1);
+        assert_eq!(rs.defragment_slots(), 0);
+
+        // A changed component is only seen once its slot is rewritten.
+        world.get::<&mut ExternalId>(entities[0]).unwrap().0 = 10;
+        assert_eq!(rs.defragment_slots(), 0);
+        rs.write_slot(0, &world, entities[0]);
+        let mut swaps = 0;
+        while rs.defragment_slots() > 0 {
+            swaps += 1;
+        }
+        assert_eq!(swaps, 5, "one swap per call, resuming after the last");
+        assert_eq!(rs.gpu_entity_ids(), [1, 2, 3, 4, 5, 10]);
+        assert_eq!(rs.get_slot(entities[0]), Some(5));
+
+        // Despawning moves the last slot into the hole; the keys follow.
+        rs.pending_despawns.push(entities[1]);
+        while rs.defragment_slots() > 0 {}
+        assert_eq!(rs.gpu_entity_ids()[..5], [2, 3, 4, 5, 10]);
+    }
+
+    #[test]
+    fn defragment_survives_hide_unhide_and_despawn_in_one_frame() {
+        let mut rs = RenderState::new();
+        let mut world = World::new();
+        let entities: Vec<_> = [7u32, 3, 9, 1, 8, 2, 6, 0]
+            .iter()
+            .map(|&id| {
+                let e = world.spawn((Position::default(), ModelMatrix::default(), ExternalId(id), Active));
+                let slot = rs.assign_slot(e);
+                rs.write_slot(slot, &world, e);
+                e
+            })
+            .collect();
+        rs.set_slot_order(SlotOrder::ExternalId, 2);
+        assert_eq!(rs.defragment_slots(), 2);
+
+        // Hide and unhide, hide then despawn, plain despawn.
+        rs.release_slot(entities[1]);
+        rs.release_slot(entities[4]);
+        rs.restore_slot(entities[1]);
+        rs.pending_despawns.push(entities[4]);
+        rs.pending_despawns.push(entities[6]);
+        rs.defragment_slots();
+
+        // Unhide after the release was flushed: the entity gets a fresh slot.
+        rs.release_slot(entities[2]);
+        rs.defragment_slots();
+        let slot = rs.restore_slot(entities[2]);
+        rs.write_slot(slot, &world, entities[2]);
+        rs.pending_despawns.push(entities[0]);
+        while rs.defragment_slots() > 0 {}
+
+        let count = rs.gpu_count as usize;
+        assert_eq!(rs.gpu_entity_ids()[..count], [0, 1, 2, 3, 9]);
+        for slot in 0..rs.gpu_count {
+            assert_eq!(rs.get_slot(rs.slot_entity(slot).unwrap()), Some(slot));
+        }
+    }
+
+    #[test]
+    fn transparent_order_is_back_to_front_with_tie_breaks() {
+        let mut rs = RenderState::new();
+        let mut world = World::new();
+        // (external ID, depth, sort order)
+        let spec = [(0u32, 1.0f32, 0i32), (1, 5.0, 0), (2, 1.0, -1), (3, 3.0, 0), (4, 1.0, 0)];
+        let entities: Vec<_> = spec
+            .iter()
+            .map(|&(id, depth, order)| {
+                let e = world.spawn((
+                    Position::default(),
+                    ModelMatrix::default(),
+                    ExternalId(id),
+                    Depth(depth),
+                    SortOrder(order),
+                    RenderPrimitive::default(),
+                    Transparent(1),
+                    Active,
+                ));
+                let slot = rs.assign_slot(e);
+                rs.write_slot(slot, &world, e);
+                e
+            })
+            .collect();
+        let opaque = world.spawn((Position::default(), ModelMatrix::default(), ExternalId(9), Active));
+        let slot = rs.assign_slot(opaque);
+        rs.write_slot(slot, &world, opaque);
+
+        rs.sort_transparent(&world);
+        assert!(rs.transparent_order().is_empty(), "off by default");
+        rs.set_transparent_sort_enabled(true);
+        rs.sort_transparent(&world);
+        assert_eq!(rs.transparent_order(), [1, 3, 2, 0, 4]);
+
+        // Moving one entity forward re-sorts from the previous order.
+        world.insert_one(entities[1], Depth(0.0)).unwrap();
+        rs.write_slot(1, &world, entities[1]);
+        world.remove_one::<Transparent>(entities[3]).unwrap();
+        rs.write_slot(3, &world, entities[3]);
+        rs.sort_transparent(&world);
+        assert_eq!(rs.transparent_order(), [2, 0, 4, 1]);
+        assert_eq!(rs.transparent_order_len(), 4);
+    }
+
+    #[test]
+    fn transparent_sort_falls_back_when_shift_budget_overflows() {
+        let mut rs = RenderState::new();
+        let mut world = World::new();
+        rs.set_transparent_sort_enabled(true);
+        let entities: Vec<_> = (0..20u32)
+            .map(|id| {
+                let e = world.spawn((
+                    Position::default(),
+                    ModelMatrix::default(),
+                    ExternalId(id),
+                    Depth(id as f32),
+                    Transparent(1),
+                    Active,
+                ));
+                let slot = rs.assign_slot(e);
+                rs.write_slot(slot, &world, e);
+                e
+            })
+            .collect();
+        rs.sort_transparent(&world);
+        let back_to_front: Vec<u32> = (0..20).rev().collect();
+        assert_eq!(rs.transparent_order(), back_to_front);
+
+        // Reversing every depth needs 190 shifts, over the 20 * 8 budget.
+        for (slot, &e) in entities.iter().enumerate() {
+            world.insert_one(e, Depth(-(slot as f32))).unwrap();
+            rs.write_slot(slot as u32, &world, e);
+        }
+        rs.sort_transparent(&world);
+        let front_to_back: Vec<u32> = (0..20).collect();
+        assert_eq!(rs.transparent_order(), front_to_back);
+
+        // Without a rewrite the cached key still holds the old depth.
+        world.insert_one(entities[0], Depth(-100.0)).unwrap();
+        rs.sort_transparent(&world);
+        assert_eq!(rs.transparent_order(), front_to_back);
+        rs.write_slot(0, &world, entities[0]);
+        rs.sort_transparent(&world);
+        assert_eq!(rs.transparent_order()[19], 0);
+    }
+
     #[test]
     fn collect_dirty_staging_compressed_root() {
         let mut rs = RenderState::new();
@@ -89,6 +89,79 @@ pub enum CommandType {
     CreateCharacterController = 44, // 1B: reserved flags
     SetCharacterConfig = 45,        // 16B: packed config
     MoveCharacter = 46,             // 8B: dx(f32) + dy(f32)
+
+    // ── Editor ──
+    HistoryMarker = 47,             // 1B: 0=begin undo group, 1=end undo group
+
+    // ── Simulation control ──
+    SetTickRate = 48,               // 4B: ticks per second (u32)
+    SetTimeScale = 49,              // 4B: scale (f32), 1.0 = real time
+    SetPaused = 50,                 // 1B: 0=running, 1=paused
+    StepTicks = 51,                 // 4B: tick count (u32)
+
+    // ── Generic components ──
+    SetComponent = 52,              // 16B: component_id(u8) + data (up to 15B, size from registry)
+    RemoveComponent = 53,           // 1B: component_id(u8)
+
+    // ── Activation / visibility ──
+    SetActive = 54,                 // 2B: active(u8) + cascade to children(u8)
+    SetVisible = 55,                // 2B: visible(u8) + cascade to children(u8)
+
+    // ── Tags / groups (group commands ignore entity_id) ──
+    AddTags = 56,                   // 4B: tag mask(u32)
+    RemoveTags = 57,                // 4B: tag mask(u32)
+    DespawnGroup = 58,              // 4B: tag mask(u32)
+    SetGroupVisible = 59,           // 5B: tag mask(u32) + visible(u8)
+    ApplyGroupImpulse = 60,         // 12B: tag mask(u32) + ix(f32) + iy(f32)
+    SetGroupTextureLayer = 61,      // 8B: tag mask(u32) + packed layer index(u32)
+    TranslateGroup = 62,            // 16B: tag mask(u32) + dx, dy, dz(f32)
+
+    // ── Motion (non-physics integration) ──
+    SetAngularVelocity = 63,        // 12B: wx, wy, wz(f32) rad/s; 2D uses wz
+    SetAcceleration = 64,           // 12B: ax, ay, az(f32)
+    SetDrag = 65,                   // 8B: linear(f32) + angular(f32)
+
+    // ── Tweens ──
+    StartTween = 66,                // 16B: target, field, easing, flags(u8) + from, to(f32) + duration, delay(u16 ticks)
+    StopTweens = 67,                // 2B: target(u8, 0xFF = all) + field(u8)
+
+    // ── Sprite animation ──
+    PlaySpriteAnimation = 68,       // 11B: clip(u32) + speed(f32) + mode(u8) + start frame(u16)
+    SetSpriteAnimationSpeed = 69,   // 4B: speed(f32), 0 = paused
+    StopSpriteAnimation = 70,       // 0B: keeps the current frame
+
+    // ── Lifetime ──
+    SetLifetime = 71,               // 5B: ticks(u32, 0 = clear) + cascade(u8)
+
+    // ── Scheduling ──
+    ScheduleAt = 72,                // 9B: tick(u64) + mode(u8: 0=absolute, 1=relative); wraps the next command
+
+    // ── Particles ──
+    AttachEmitter = 73,             // 8B: config(u32) + seed(u32)
+    EmitterBurst = 74,              // 4B: count(u32)
+    SetEmitterRate = 75,            // 4B: particles/s(f32, <0 = config rate)
+    DetachEmitter = 76,             // 0B
+
+    // ── Picking ──
+    SetPickLayer = 77,              // 4B: layer mask(u32)
+
+    // ── Cameras ──
+    AttachCamera = 78,              // 16B: fov_y(f32, 0 = orthographic) + near(f32) + far(f32) + seed(u32)
+    SetCameraViewport = 79,         // 16B: x, y, width, height (f32 pixels)
+    SetCameraZoom = 80,             // 4B: zoom(f32)
+    SetCameraFollow = 81,           // 16B: target(u32, u32::MAX = none) + damping(f32) + deadzone x, y(f32)
+    SetCameraBounds = 82,           // 16B: min x, y + max x, y (f32; min > max = unclamped)
+    AddCameraTrauma = 83,           // 16B: trauma(f32) + decay/s(f32) + max offset(f32) + max roll(f32)
+    DetachCamera = 84,              // 0B
+
+    // ── Tint ──
+    SetTint = 85,                   // 4B: RGBA8(u32, R in the low byte)
+
+    // ── Render layers ──
+    SetRenderLayer = 86,            // 4B: layer mask(u32, layers 0..15)
+
+    // ── Sort order ──
+    SetSortOrder = 87,              // 4B: order(i32, equal-depth tie-break)
 }
 
 impl CommandType {
@@ -147,6 +220,63 @@ impl CommandType {
             44 => Some(Self::CreateCharacterController),
             45 => Some(Self::SetCharacterConfig),
             46 => Some(Self::MoveCharacter),
+            // Editor
+            47 => Some(Self::HistoryMarker),
+            // Simulation control
+            48 => Some(Self::SetTickRate),
+            49 => Some(Self::SetTimeScale),
+            50 => Some(Self::SetPaused),
+            51 => Some(Self::StepTicks),
+            // Generic components
+            52 => Some(Self::SetComponent),
+            53 => Some(Self::RemoveComponent),
+            // Activation / visibility
+            54 => Some(Self::SetActive),
+            55 => Some(Self::SetVisible),
+            // Tags / groups
+            56 => Some(Self::AddTags),
+            57 => Some(Self::RemoveTags),
+            58 => Some(Self::DespawnGroup),
+            59 => Some(Self::SetGroupVisible),
+            60 => Some(Self::ApplyGroupImpulse),
+            61 => Some(Self::SetGroupTextureLayer),
+            62 => Some(Self::TranslateGroup),
+            // Motion
+            63 => Some(Self::SetAngularVelocity),
+            64 => Some(Self::SetAcceleration),
+            65 => Some(Self::SetDrag),
+            // Tweens
+            66 => Some(Self::StartTween),
+            67 => Some(Self::StopTweens),
+            // Sprite animation
+            68 => Some(Self::PlaySpriteAnimation),
+            69 => Some(Self::SetSpriteAnimationSpeed),
+            70 => Some(Self::StopSpriteAnimation),
+            // Lifetime
+            71 => Some(Self::SetLifetime),
+            // Scheduling
+            72 => Some(Self::ScheduleAt),
+            // Particles
+            73 => Some(Self::AttachEmitter),
+            74 => Some(Self::EmitterBurst),
+            75 => Some(Self::SetEmitterRate),
+            76 => Some(Self::DetachEmitter),
+            // Picking
+            77 => Some(Self::SetPickLayer),
+            // Cameras
+            78 => Some(Self::AttachCamera),
+            79 => Some(Self::SetCameraViewport),
+            80 => Some(Self::SetCameraZoom),
+            81 => Some(Self::SetCameraFollow),
+            82 => Some(Self::SetCameraBounds),
+            83 => Some(Self::AddCameraTrauma),
+            84 => Some(Self::DetachCamera),
+            // Tint
+            85 => Some(Self::SetTint),
+            // Render layers
+            86 => Some(Self::SetRenderLayer),
+            // Sort order
+            87 => Some(Self::SetSortOrder),
             _ => None,
         }
     }
@@ -154,7 +284,7 @@ impl CommandType {
     /// Number of payload bytes that follow the 5-byte header (cmd_type + entity_id).
     pub fn payload_size(self) -> usize {
         match self {
-            Self::Noop | Self::DespawnEntity => 0,
+            Self::Noop | Self::DespawnEntity | Self::StopSpriteAnimation => 0,
             Self::SpawnEntity => 1,           // u8: 0=3D, 1=2D
             Self::SetPosition | Self::SetScale | Self::SetVelocity => 12, // 3 x f32
             Self::SetRotation => 16, // 4 x f32
@@ -191,6 +321,56 @@ impl CommandType {
             Self::CreateCharacterController => 1,  // reserved flags
             Self::SetCharacterConfig => 16,        // packed config (see spec §3.2)
             Self::MoveCharacter => 8,              // dx(f32) + dy(f32)
+            // Editor
+            Self::HistoryMarker => 1,              // u8: 0=begin group, 1=end group
+            // Simulation control
+            Self::SetTickRate | Self::SetTimeScale | Self::StepTicks => 4, // u32 or f32
+            Self::SetPaused => 1,                  // u8 bool
+            // Generic components
+            Self::SetComponent => 16,              // component_id + data
+            Self::RemoveComponent => 1,            // component_id
+            // Activation / visibility
+            Self::SetActive | Self::SetVisible => 2, // u8 flag + u8 cascade
+            // Tags / groups
+            Self::AddTags | Self::RemoveTags | Self::DespawnGroup => 4, // tag mask
+            Self::SetGroupVisible => 5,            // mask + u8 visible
+            Self::ApplyGroupImpulse => 12,         // mask + 2×f32
+            Self::SetGroupTextureLayer => 8,       // mask + u32
+            Self::TranslateGroup => 16,            // mask + 3×f32
+            // Motion
+            Self::SetAngularVelocity | Self::SetAcceleration => 12, // 3×f32
+            Self::SetDrag => 8,                    // 2×f32
+            // Tweens
+            Self::StartTween => 16,                // 4×u8 + 2×f32 + 2×u16
+            Self::StopTweens => 2,                 // target + field
+            // Sprite animation
+            Self::PlaySpriteAnimation => 11,       // u32 + f32 + u8 + u16
+            Self::SetSpriteAnimationSpeed => 4,    // f32
+            // Lifetime
+            Self::SetLifetime => 5,                // u32 ticks + u8 cascade
+            // Scheduling
+            Self::ScheduleAt => 9,                 // u64 tick + u8 mode
+            // Particles
+            Self::AttachEmitter => 8,              // u32 config + u32 seed
+            Self::EmitterBurst => 4,               // u32 count
+            Self::SetEmitterRate => 4,             // f32
+            Self::DetachEmitter => 0,
+            // Picking
+            Self::SetPickLayer => 4,               // u32 layer mask
+            // Cameras
+            Self::AttachCamera => 16,              // 3 x f32 + u32 seed
+            Self::SetCameraViewport => 16,         // 4 x f32
+            Self::SetCameraZoom => 4,              // f32
+            Self::SetCameraFollow => 16,           // u32 target + 3 x f32
+            Self::SetCameraBounds => 16,           // 4 x f32
+            Self::AddCameraTrauma => 16,           // 4 x f32
+            Self::DetachCamera => 0,
+            // Tint
+            Self::SetTint => 4,                    // u32 RGBA8
+            // Render layers
+            Self::SetRenderLayer => 4,             // u32 layer mask
+            // Sort order
+            Self::SetSortOrder => 4,               // i32 order
         }
     }
 
@@ -214,6 +394,36 @@ pub struct Command {
     pub payload: [u8; 16],
 }
 
+/// First opcode of the plugin range. Opcodes `128..=255` are never assigned
+/// to `CommandType`; plugins register them with their payload sizes.
+pub const PLUGIN_OPCODE_START: u8 = 128;
+
+/// A command in the plugin opcode range, decoded using the payload size the
+/// owning plugin registered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginCommand {
+    pub opcode: u8,
+    pub entity_id: u32,
+    /// Payload bytes (up to 16), zero-padded like `Command::payload`.
+    pub payload: [u8; 16],
+    /// Number of meaningful payload bytes.
+    pub payload_len: u8,
+}
+
+impl PluginCommand {
+    /// The meaningful payload bytes.
+    pub fn bytes(&self) -> &[u8] {
+        &self.payload[..self.payload_len as usize]
+    }
+}
+
+/// One entry of a parsed command stream, in wire order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedCommand {
+    Core(Command),
+    Plugin(PluginCommand),
+}
+
 // ---------------------------------------------------------------------------
 // parse_commands (flat byte-slice parser)
 // ---------------------------------------------------------------------------
@@ -224,16 +434,39 @@ pub struct Command {
 /// Used when the Worker extracts bytes from the SharedArrayBuffer and passes
 /// them to WASM as a contiguous `&[u8]`.
 pub fn parse_commands(data: &[u8]) -> Vec<Command> {
+    parse_command_stream(data, |_| None)
+        .into_iter()
+        .filter_map(|c| match c {
+            ParsedCommand::Core(cmd) => Some(cmd),
+            ParsedCommand::Plugin(_) => None,
+        })
+        .collect()
+}
+
+/// Parse core and plugin commands from a flat byte slice, preserving order.
+///
+/// `plugin_payload_size` returns the payload size of a registered plugin
+/// opcode. Parsing stops at the first unknown opcode or incomplete message.
+pub fn parse_command_stream(
+    data: &[u8],
+    plugin_payload_size: impl Fn(u8) -> Option<usize>,
+) -> Vec<ParsedCommand> {
     let mut commands = Vec::new();
     let mut pos = 0;
 
     while pos < data.len() {
         let cmd_byte = data[pos];
-        let Some(cmd_type) = CommandType::from_u8(cmd_byte) else {
-            break;
+        let cmd_type = CommandType::from_u8(cmd_byte);
+        let psize = match cmd_type {
+            Some(cmd_type) => cmd_type.payload_size(),
+            None if cmd_byte >= PLUGIN_OPCODE_START => match plugin_payload_size(cmd_byte) {
+                Some(size) if size <= 16 => size,
+                _ => break,
+            },
+            None => break,
         };
 
-        let msg_size = cmd_type.message_size();
+        let msg_size = 1 + 4 + psize;
         if pos + msg_size > data.len() {
             break;
         }
@@ -243,15 +476,22 @@ pub fn parse_commands(data: &[u8]) -> Vec<Command> {
         let entity_id = u32::from_le_bytes(id_bytes);
 
         let mut payload = [0u8; 16];
-        let psize = cmd_type.payload_size();
         if psize > 0 {
             payload[..psize].copy_from_slice(&data[pos + 5..pos + 5 + psize]);
         }
 
-        commands.push(Command {
-            cmd_type,
-            entity_id,
-            payload,
+        commands.push(match cmd_type {
+            Some(cmd_type) => ParsedCommand::Core(Command {
+                cmd_type,
+                entity_id,
+                payload,
+            }),
+            None => ParsedCommand::Plugin(PluginCommand {
+                opcode: cmd_byte,
+                entity_id,
+                payload,
+                payload_len: psize as u8,
+            }),
         });
 
         pos += msg_size;
@@ -653,6 +893,25 @@ mod tests {
         assert!(cmds.is_empty());
     }
 
+    #[test]
+    fn parse_command_stream_interleaves_plugin_commands() {
+        let mut data = vec![CommandType::SpawnEntity as u8, 1, 0, 0, 0, 0];
+        data.extend([200, 2, 0, 0, 0, 0xAA, 0xBB]);
+        data.extend([CommandType::DespawnEntity as u8, 1, 0, 0, 0]);
+        let sizes = |op: u8| (op == 200).then_some(2);
+
+        let cmds = parse_command_stream(&data, sizes);
+        assert_eq!(cmds.len(), 3);
+        assert!(matches!(&cmds[0], ParsedCommand::Core(c) if c.cmd_type == CommandType::SpawnEntity));
+        let ParsedCommand::Plugin(p) = &cmds[1] else { panic!("expected plugin command") };
+        assert_eq!((p.opcode, p.entity_id, p.bytes()), (200, 2, &[0xAA, 0xBB][..]));
+        assert!(matches!(&cmds[2], ParsedCommand::Core(c) if c.cmd_type == CommandType::DespawnEntity));
+
+        // Unregistered plugin opcodes stop parsing like unknown core opcodes.
+        assert_eq!(parse_command_stream(&data, |_| None).len(), 1);
+        assert_eq!(parse_commands(&data).len(), 1);
+    }
+
     #[test]
     fn parse_commands_handles_empty() {
         let cmds = parse_commands(&[]);
@@ -932,7 +1191,132 @@ mod tests {
             let ct = CommandType::from_u8(val);
             assert!(ct.is_some(), "CommandType::from_u8({val}) should be Some");
         }
-        assert!(CommandType::from_u8(47).is_none(), "47 should be None");
+    }
+
+    #[test]
+    fn history_marker_round_trip() {
+        let ct = CommandType::from_u8(47).unwrap();
+        assert_eq!(ct, CommandType::HistoryMarker);
+        assert_eq!(ct.payload_size(), 1);
+    }
+
+    #[test]
+    fn simulation_control_command_types_round_trip() {
+        assert_eq!(CommandType::from_u8(48), Some(CommandType::SetTickRate));
+        assert_eq!(CommandType::from_u8(49), Some(CommandType::SetTimeScale));
+        assert_eq!(CommandType::from_u8(50), Some(CommandType::SetPaused));
+        assert_eq!(CommandType::from_u8(51), Some(CommandType::StepTicks));
+        assert_eq!(CommandType::SetPaused.payload_size(), 1);
+        assert_eq!(CommandType::StepTicks.payload_size(), 4);
+    }
+
+    #[test]
+    fn component_command_types_round_trip() {
+        assert_eq!(CommandType::from_u8(52), Some(CommandType::SetComponent));
+        assert_eq!(CommandType::from_u8(53), Some(CommandType::RemoveComponent));
+        assert_eq!(CommandType::SetComponent.payload_size(), 16);
+        assert_eq!(CommandType::RemoveComponent.payload_size(), 1);
+    }
+
+    #[test]
+    fn activation_command_types_round_trip() {
+        assert_eq!(CommandType::from_u8(54), Some(CommandType::SetActive));
+        assert_eq!(CommandType::from_u8(55), Some(CommandType::SetVisible));
+        assert_eq!(CommandType::SetActive.payload_size(), 2);
+        assert_eq!(CommandType::SetVisible.payload_size(), 2);
+    }
+
+    #[test]
+    fn tag_command_types_round_trip() {
+        assert_eq!(CommandType::from_u8(56), Some(CommandType::AddTags));
+        assert_eq!(CommandType::from_u8(62), Some(CommandType::TranslateGroup));
+        assert_eq!(CommandType::AddTags.payload_size(), 4);
+        assert_eq!(CommandType::SetGroupVisible.payload_size(), 5);
+        assert_eq!(CommandType::ApplyGroupImpulse.payload_size(), 12);
+        assert_eq!(CommandType::TranslateGroup.payload_size(), 16);
+    }
+
+    #[test]
+    fn motion_command_types_round_trip() {
+        assert_eq!(CommandType::from_u8(63), Some(CommandType::SetAngularVelocity));
+        assert_eq!(CommandType::from_u8(64), Some(CommandType::SetAcceleration));
+        assert_eq!(CommandType::from_u8(65), Some(CommandType::SetDrag));
+        assert_eq!(CommandType::SetAngularVelocity.payload_size(), 12);
+        assert_eq!(CommandType::SetDrag.payload_size(), 8);
+    }
+
+    #[test]
+    fn tween_command_types_round_trip() {
+        assert_eq!(CommandType::from_u8(66), Some(CommandType::StartTween));
+        assert_eq!(CommandType::from_u8(67), Some(CommandType::StopTweens));
+        assert_eq!(CommandType::StartTween.payload_size(), 16);
+        assert_eq!(CommandType::StopTweens.payload_size(), 2);
+    }
+
+    #[test]
+    fn sprite_animation_command_types_round_trip() {
+        assert_eq!(CommandType::from_u8(68), Some(CommandType::PlaySpriteAnimation));
+        assert_eq!(CommandType::from_u8(69), Some(CommandType::SetSpriteAnimationSpeed));
+        assert_eq!(CommandType::from_u8(70), Some(CommandType::StopSpriteAnimation));
+        assert_eq!(CommandType::PlaySpriteAnimation.payload_size(), 11);
+        assert_eq!(CommandType::SetSpriteAnimationSpeed.payload_size(), 4);
+        assert_eq!(CommandType::StopSpriteAnimation.payload_size(), 0);
+    }
+
+    #[test]
+    fn lifetime_command_type_round_trip() {
+        assert_eq!(CommandType::from_u8(71), Some(CommandType::SetLifetime));
+        assert_eq!(CommandType::SetLifetime.payload_size(), 5);
+    }
+
+    #[test]
+    fn schedule_command_type_round_trip() {
+        assert_eq!(CommandType::from_u8(72), Some(CommandType::ScheduleAt));
+        assert_eq!(CommandType::ScheduleAt.payload_size(), 9);
+    }
+
+    #[test]
+    fn particle_command_types_round_trip() {
+        assert_eq!(CommandType::from_u8(73), Some(CommandType::AttachEmitter));
+        assert_eq!(CommandType::from_u8(74), Some(CommandType::EmitterBurst));
+        assert_eq!(CommandType::from_u8(75), Some(CommandType::SetEmitterRate));
+        assert_eq!(CommandType::from_u8(76), Some(CommandType::DetachEmitter));
+        assert_eq!(CommandType::AttachEmitter.payload_size(), 8);
+        assert_eq!(CommandType::DetachEmitter.payload_size(), 0);
+    }
+
+    #[test]
+    fn pick_layer_command_type_round_trip() {
+        assert_eq!(CommandType::from_u8(77), Some(CommandType::SetPickLayer));
+        assert_eq!(CommandType::SetPickLayer.payload_size(), 4);
+    }
+
+    #[test]
+    fn camera_command_types_round_trip() {
+        assert_eq!(CommandType::from_u8(78), Some(CommandType::AttachCamera));
+        assert_eq!(CommandType::from_u8(81), Some(CommandType::SetCameraFollow));
+        assert_eq!(CommandType::from_u8(84), Some(CommandType::DetachCamera));
+        assert_eq!(CommandType::SetCameraZoom.payload_size(), 4);
+        assert_eq!(CommandType::AddCameraTrauma.payload_size(), 16);
+    }
+
+    #[test]
+    fn tint_command_type_round_trip() {
+        assert_eq!(CommandType::from_u8(85), Some(CommandType::SetTint));
+        assert_eq!(CommandType::SetTint.payload_size(), 4);
+    }
+
+    #[test]
+    fn render_layer_command_type_round_trip() {
+        assert_eq!(CommandType::from_u8(86), Some(CommandType::SetRenderLayer));
+        assert_eq!(CommandType::SetRenderLayer.payload_size(), 4);
+    }
+
+    #[test]
+    fn sort_order_command_type_round_trip() {
+        assert_eq!(CommandType::from_u8(87), Some(CommandType::SetSortOrder));
+        assert_eq!(CommandType::SetSortOrder.payload_size(), 4);
+        assert!(CommandType::from_u8(88).is_none(), "88 should be None");
     }
 
     #[test]
@@ -0,0 +1,674 @@
+//! Rollback-and-resimulate support.
+//!
+//! A fixed-capacity ring of per-tick captures. Each capture holds one compact
+//! record per entity (only the components the tick loop reads or writes) and,
+//! optionally, a clone of the Rapier simulation state. Unlike
+//! `snapshot_create()`, nothing is serialized and record storage is reused
+//! across ticks, so capturing every tick stays cheap.
+//!
+//! The Rapier clone is the exception: it copies every body, collider, joint
+//! and the broad and narrow phases, so its cost grows with the scene. It is
+//! therefore opt-in and taken only every `physics_interval` ticks; with
+//! physics capture on, only those ticks can be rolled back to.
+//!
+//! A capture for tick `T` is taken at the start of fixed tick `T`, after the
+//! commands of that frame were applied. Restoring it puts the engine back at
+//! `tick_count() == T`; the caller then applies corrected commands and
+//! resimulates forward.
+
+use hecs::World;
+
+use crate::camera::Camera;
+use crate::command_processor::EntityMap;
+use crate::components::*;
+use crate::lifetime::Lifetime;
+use crate::particles::{Emitter, Particles};
+use crate::render_state::RenderState;
+use crate::ring_buffer::{Command, CommandType};
+use crate::schedule::{CommandSchedule, ScheduledCommand};
+use crate::sprite_animation::SpriteAnimation;
+use crate::tween::Tweens;
+
+/// Default number of ticks kept (2 seconds at 60 Hz).
+pub const DEFAULT_ROLLBACK_CAPACITY: usize = 120;
+
+const FLAG_ACTIVE: u8 = 1 << 0;
+const FLAG_TRANSPARENT: u8 = 1 << 1;
+const FLAG_DEPTH: u8 = 1 << 2;
+const FLAG_HIDDEN: u8 = 1 << 3;
+
+/// Sentinel tick for a ring slot that holds no capture.
+const EMPTY_TICK: u64 = u64::MAX;
+
+/// Spatial transform of a captured entity, by archetype.
+#[derive(Clone, Copy)]
+enum RecordTransform {
+    TwoD(Transform2D),
+    ThreeD(Position, Rotation, Scale),
+}
+
+/// Physics components attached to a captured entity.
+#[cfg(feature = "physics-2d")]
+#[derive(Clone, Default)]
+struct PhysicsRecord {
+    body: Option<rapier2d::prelude::RigidBodyHandle>,
+    collider: Option<rapier2d::prelude::ColliderHandle>,
+    controlled: bool,
+    pending_body: Option<crate::physics::PendingRigidBody>,
+    pending_collider: Option<crate::physics::PendingCollider>,
+}
+
+/// Optional motion components driven by the velocity systems.
+#[derive(Clone, Copy, Default)]
+struct MotionRecord {
+    angular_velocity: Option<AngularVelocity>,
+    acceleration: Option<Acceleration>,
+    linear_drag: Option<LinearDrag>,
+    angular_drag: Option<AngularDrag>,
+}
+
+/// Compact per-entity record captured each tick.
+#[derive(Clone)]
+struct EntityRecord {
+    ext_id: u32,
+    /// `FLAG_*` bits for optional marker components.
+    flags: u8,
+    transform: RecordTransform,
+    velocity: Velocity,
+    bounding_radius: BoundingRadius,
+    texture_layer: TextureLayerIndex,
+    mesh: MeshHandle,
+    primitive: RenderPrimitive,
+    params: PrimitiveParams,
+    parent: Parent,
+    depth: Depth,
+    /// `Tags` bitmask; 0 = no `Tags` component.
+    tags: u32,
+    pick_layer: Option<PickLayer>,
+    tint: Option<Tint>,
+    render_layer: Option<RenderLayer>,
+    sort_order: Option<SortOrder>,
+    motion: MotionRecord,
+    /// Running tweens, cloned (entities without tweens cost nothing).
+    tweens: Option<Tweens>,
+    sprite_animation: Option<SpriteAnimation>,
+    lifetime: Option<Lifetime>,
+    emitter: Option<Emitter>,
+    /// Boxed: cameras are rare and much larger than the rest of the record.
+    camera: Option<Box<Camera>>,
+    /// Start of this entity's children in `TickState::children`:
+    /// `children_inline` inline entries followed by `children_overflow` overflow entries.
+    children_start: u32,
+    children_inline: u8,
+    children_overflow: u32,
+    #[cfg(feature = "physics-2d")]
+    physics: PhysicsRecord,
+}
+
+/// Physics components of one entity, fetched alongside the rest of its record.
+#[cfg(feature = "physics-2d")]
+type PhysicsFetch = (
+    Option<&'static crate::physics::PhysicsBodyHandle>,
+    Option<&'static crate::physics::PhysicsColliderHandle>,
+    hecs::Satisfies<&'static crate::physics::PhysicsControlled>,
+    Option<&'static crate::physics::PendingRigidBody>,
+    Option<&'static crate::physics::PendingCollider>,
+);
+#[cfg(not(feature = "physics-2d"))]
+type PhysicsFetch = ();
+
+/// Engine state at the start of one fixed tick.
+pub struct TickState {
+    tick: u64,
+    entities: Vec<EntityRecord>,
+    /// Shared pool of child external IDs, sliced by `EntityRecord`.
+    children: Vec<u32>,
+    /// Listener position, previous position, and velocity.
+    listener: [[f32; 3]; 3],
+    /// Commands still queued by `ScheduleAt`, in release order.
+    scheduled: Vec<ScheduledCommand>,
+    /// A `ScheduleAt` that ended the last batch, waiting for its command.
+    dangling: Option<Command>,
+    /// Live particles (emitter RNG and counters live in `EntityRecord`).
+    particles: Particles,
+    #[cfg(feature = "physics-2d")]
+    physics: Option<crate::physics::PhysicsState>,
+}
+
+impl TickState {
+    fn empty() -> Self {
+        Self {
+            tick: EMPTY_TICK,
+            entities: Vec::new(),
+            children: Vec::new(),
+            listener: [[0.0; 3]; 3],
+            scheduled: Vec::new(),
+            dangling: None,
+            particles: Particles::default(),
+            #[cfg(feature = "physics-2d")]
+            physics: None,
+        }
+    }
+
+    /// The tick this state was captured at.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Number of entities captured.
+    pub fn entity_count(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Listener position, previous position, and velocity.
+    pub fn listener(&self) -> [[f32; 3]; 3] {
+        self.listener
+    }
+
+    /// Commands that were still scheduled, in release order.
+    pub fn scheduled(&self) -> &[ScheduledCommand] {
+        &self.scheduled
+    }
+
+    /// The `ScheduleAt` that was waiting for its command, if any.
+    pub fn dangling(&self) -> Option<&Command> {
+        self.dangling.as_ref()
+    }
+
+    /// Particles alive at the start of the tick.
+    pub fn particles(&self) -> &Particles {
+        &self.particles
+    }
+
+    /// The captured Rapier state, if this tick was a physics capture tick.
+    #[cfg(feature = "physics-2d")]
+    pub fn physics(&self) -> Option<&crate::physics::PhysicsState> {
+        self.physics.as_ref()
+    }
+}
+
+/// Ring buffer of per-tick captures, indexed by `tick % capacity`.
+pub struct RollbackBuffer {
+    states: Vec<TickState>,
+    physics_interval: u32,
+}
+
+impl RollbackBuffer {
+    /// Create a buffer keeping the last `capacity` ticks (minimum 1).
+    /// A non-zero `physics_interval` also clones the Rapier state on every
+    /// tick divisible by it (see the module doc for the cost).
+    pub fn new(capacity: usize, physics_interval: u32) -> Self {
+        Self {
+            states: (0..capacity.max(1)).map(|_| TickState::empty()).collect(),
+            physics_interval,
+        }
+    }
+
+    /// Number of ticks kept.
+    pub fn capacity(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Ticks between Rapier captures; 0 = the ECS only.
+    pub fn physics_interval(&self) -> u32 {
+        self.physics_interval
+    }
+
+    /// Whether `tick` gets a Rapier capture.
+    fn is_physics_tick(&self, tick: u64) -> bool {
+        self.physics_interval > 0 && tick.is_multiple_of(self.physics_interval as u64)
+    }
+
+    /// The capture for `tick`, if it is still in the ring and can be restored
+    /// (with physics capture on, only physics capture ticks can).
+    pub fn get(&self, tick: u64) -> Option<&TickState> {
+        let state = &self.states[(tick % self.states.len() as u64) as usize];
+        (state.tick == tick && (self.physics_interval == 0 || self.is_physics_tick(tick))).then_some(state)
+    }
+
+    /// Oldest tick that can still be rolled back to.
+    pub fn oldest_tick(&self) -> Option<u64> {
+        self.states
+            .iter()
+            .map(|s| s.tick)
+            .filter(|&t| t != EMPTY_TICK && (self.physics_interval == 0 || self.is_physics_tick(t)))
+            .min()
+    }
+
+    /// Drop every capture newer than `tick`. Those futures are invalid after a rollback.
+    pub fn discard_after(&mut self, tick: u64) {
+        for state in &mut self.states {
+            if state.tick != EMPTY_TICK && state.tick > tick {
+                state.tick = EMPTY_TICK;
+            }
+        }
+    }
+
+    /// Drop all captures (keeps allocations).
+    pub fn clear(&mut self) {
+        for state in &mut self.states {
+            state.tick = EMPTY_TICK;
+        }
+    }
+
+    /// Capture the world at the start of `tick`, overwriting the oldest slot.
+    pub fn capture(
+        &mut self,
+        tick: u64,
+        world: &World,
+        listener: [[f32; 3]; 3],
+        schedule: &CommandSchedule,
+        particles: &Particles,
+        #[cfg(feature = "physics-2d")] physics: &crate::physics::PhysicsWorld,
+    ) {
+        let idx = (tick % self.states.len() as u64) as usize;
+        let state = &mut self.states[idx];
+        state.tick = tick;
+        state.listener = listener;
+        state.scheduled.clear();
+        state.scheduled.extend_from_slice(schedule.pending());
+        state.dangling = schedule.dangling().cloned();
+        state.particles.clone_from(particles);
+        state.entities.clear();
+        state.children.clear();
+
+        let mut query = world.query::<(
+            &ExternalId,
+            (Option<&Transform2D>, Option<&Position>, Option<&Rotation>, Option<&Scale>),
+            (&Velocity, &BoundingRadius, &TextureLayerIndex, &MeshHandle, &RenderPrimitive, &PrimitiveParams),
+            (&Parent, &Children, Option<&OverflowChildren>),
+            (Option<&Active>, Option<&Transparent>, Option<&Depth>, Option<&Hidden>),
+            (Option<&Tags>, Option<&PickLayer>, Option<&Tint>, Option<&RenderLayer>, Option<&SortOrder>),
+            (Option<&AngularVelocity>, Option<&Acceleration>, Option<&LinearDrag>, Option<&AngularDrag>),
+            (Option<&Tweens>, Option<&SpriteAnimation>, Option<&Lifetime>, Option<&Emitter>, Option<&Camera>),
+            PhysicsFetch,
+        )>();
+        for (ext, (t2d, pos, rot, scale), render, (parent, children, overflow), markers, layers, motion, runtime, _phys) in
+            query.iter()
+        {
+            let (velocity, radius, tex, mesh, prim, params) = render;
+            let (active, transparent, depth, hidden) = markers;
+            let (tags, pick_layer, tint, render_layer, sort_order) = layers;
+            let (angular_velocity, acceleration, linear_drag, angular_drag) = motion;
+            let (tweens, sprite_animation, lifetime, emitter, camera) = runtime;
+
+            let transform = match (t2d, pos, rot, scale) {
+                (Some(t), ..) => RecordTransform::TwoD(*t),
+                (None, Some(p), Some(r), Some(s)) => RecordTransform::ThreeD(*p, *r, *s),
+                _ => continue,
+            };
+
+            let mut flags = 0;
+            if active.is_some() {
+                flags |= FLAG_ACTIVE;
+            }
+            if transparent.is_some() {
+                flags |= FLAG_TRANSPARENT;
+            }
+            if depth.is_some() {
+                flags |= FLAG_DEPTH;
+            }
+            if hidden.is_some() {
+                flags |= FLAG_HIDDEN;
+            }
+
+            let children_start = state.children.len() as u32;
+            state.children.extend_from_slice(children.as_slice());
+            let overflow_items = overflow.map_or(&[][..], |o| o.items.as_slice());
+            state.children.extend_from_slice(overflow_items);
+
+            #[cfg(feature = "physics-2d")]
+            let physics_record = {
+                let (body, collider, controlled, pending_body, pending_collider) = _phys;
+                PhysicsRecord {
+                    body: body.map(|h| h.0),
+                    collider: collider.map(|h| h.0),
+                    controlled,
+                    pending_body: pending_body.cloned(),
+                    pending_collider: pending_collider.cloned(),
+                }
+            };
+
+            state.entities.push(EntityRecord {
+                ext_id: ext.0,
+                flags,
+                transform,
+                velocity: *velocity,
+                bounding_radius: *radius,
+                texture_layer: *tex,
+                mesh: *mesh,
+                primitive: *prim,
+                params: *params,
+                parent: *parent,
+                depth: depth.copied().unwrap_or(Depth(0.0)),
+                tags: tags.map_or(0, |t| t.0),
+                pick_layer: pick_layer.copied(),
+                tint: tint.copied(),
+                render_layer: render_layer.copied(),
+                sort_order: sort_order.copied(),
+                motion: MotionRecord {
+                    angular_velocity: angular_velocity.copied(),
+                    acceleration: acceleration.copied(),
+                    linear_drag: linear_drag.copied(),
+                    angular_drag: angular_drag.copied(),
+                },
+                tweens: tweens.cloned(),
+                sprite_animation: sprite_animation.copied(),
+                lifetime: lifetime.copied(),
+                emitter: emitter.copied(),
+                camera: camera.map(|c| Box::new(*c)),
+                children_start,
+                children_inline: children.count,
+                children_overflow: overflow_items.len() as u32,
+                #[cfg(feature = "physics-2d")]
+                physics: physics_record,
+            });
+        }
+        drop(query);
+
+        #[cfg(feature = "physics-2d")]
+        {
+            state.physics = self.is_physics_tick(tick).then(|| physics.save_state());
+        }
+    }
+}
+
+/// Bring the set of live entities in line with `state`.
+///
+/// Entities created after the capture are despawned (their render slots are
+/// released on the next flush); `on_despawn` runs first so the caller can
+/// clean up resources not covered by the capture. Returns `SpawnEntity`
+/// commands for captured entities that no longer exist, in capture order.
+pub(crate) fn reconcile_entities(
+    state: &TickState,
+    world: &mut World,
+    entity_map: &mut EntityMap,
+    render_state: &mut RenderState,
+    mut on_despawn: impl FnMut(&World, hecs::Entity),
+) -> Vec<Command> {
+    let max_id = state.entities.iter().map(|r| r.ext_id as usize + 1).max().unwrap_or(0);
+    let mut captured = vec![false; max_id];
+    for record in &state.entities {
+        captured[record.ext_id as usize] = true;
+    }
+
+    let extras: Vec<(u32, hecs::Entity)> = entity_map
+        .iter_mapped()
+        .filter(|&(ext_id, _)| !captured.get(ext_id as usize).copied().unwrap_or(false))
+        .collect();
+    for (ext_id, entity) in extras {
+        on_despawn(world, entity);
+        render_state.pending_despawns.push(entity);
+        let _ = world.despawn(entity);
+        entity_map.remove(ext_id);
+    }
+
+    state
+        .entities
+        .iter()
+        .filter(|r| entity_map.get(r.ext_id).is_none())
+        .map(|r| {
+            let mut payload = [0u8; 16];
+            payload[0] = matches!(r.transform, RecordTransform::TwoD(_)) as u8;
+            Command {
+                cmd_type: CommandType::SpawnEntity,
+                entity_id: r.ext_id,
+                payload,
+            }
+        })
+        .collect()
+}
+
+/// Overwrite every captured entity's components with the recorded values and
+/// mark its render slot dirty. Call after `reconcile_entities()` spawns are applied.
+pub(crate) fn apply_records(
+    state: &TickState,
+    world: &mut World,
+    entity_map: &EntityMap,
+    render_state: &mut RenderState,
+) {
+    for record in &state.entities {
+        let Some(entity) = entity_map.get(record.ext_id) else {
+            continue;
+        };
+
+        match record.transform {
+            RecordTransform::TwoD(t) => {
+                if let Ok(mut t2d) = world.get::<&mut Transform2D>(entity) {
+                    *t2d = t;
+                }
+            }
+            RecordTransform::ThreeD(p, r, s) => {
+                if let Ok((pos, rot, scale)) =
+                    world.query_one_mut::<(&mut Position, &mut Rotation, &mut Scale)>(entity)
+                {
+                    *pos = p;
+                    *rot = r;
+                    *scale = s;
+                }
+            }
+        }
+
+        let start = record.children_start as usize;
+        let inline_end = start + record.children_inline as usize;
+        let overflow_end = inline_end + record.children_overflow as usize;
+        if let Ok((vel, radius, tex, mesh, prim, params, parent, children)) = world
+            .query_one_mut::<(
+                &mut Velocity,
+                &mut BoundingRadius,
+                &mut TextureLayerIndex,
+                &mut MeshHandle,
+                &mut RenderPrimitive,
+                &mut PrimitiveParams,
+                &mut Parent,
+                &mut Children,
+            )>(entity)
+        {
+            *vel = record.velocity;
+            *radius = record.bounding_radius;
+            *tex = record.texture_layer;
+            *mesh = record.mesh;
+            *prim = record.primitive;
+            *params = record.params;
+            *parent = record.parent;
+            children.slots[..record.children_inline as usize]
+                .copy_from_slice(&state.children[start..inline_end]);
+            children.count = record.children_inline;
+        }
+        if record.children_overflow > 0 {
+            let items = state.children[inline_end..overflow_end].to_vec();
+            let _ = world.insert_one(entity, OverflowChildren { items });
+        } else {
+            let _ = world.remove_one::<OverflowChildren>(entity);
+        }
+
+        set_marker(world, entity, record.flags & FLAG_ACTIVE != 0, Active);
+        set_marker(world, entity, record.flags & FLAG_TRANSPARENT != 0, Transparent(1));
+        set_marker(world, entity, record.flags & FLAG_DEPTH != 0, record.depth);
+        set_marker(world, entity, record.tags != 0, Tags(record.tags));
+        set_marker(world, entity, record.pick_layer.is_some(), record.pick_layer.unwrap_or_default());
+        set_marker(world, entity, record.tint.is_some(), record.tint.unwrap_or_default());
+        set_marker(world, entity, record.render_layer.is_some(), record.render_layer.unwrap_or_default());
+        set_marker(world, entity, record.sort_order.is_some(), record.sort_order.unwrap_or_default());
+        let motion = &record.motion;
+        set_marker(world, entity, motion.angular_velocity.is_some(), motion.angular_velocity.unwrap_or_default());
+        set_marker(world, entity, motion.acceleration.is_some(), motion.acceleration.unwrap_or_default());
+        set_marker(world, entity, motion.linear_drag.is_some(), motion.linear_drag.unwrap_or_default());
+        set_marker(world, entity, motion.angular_drag.is_some(), motion.angular_drag.unwrap_or_default());
+        set_marker(world, entity, record.tweens.is_some(), record.tweens.clone().unwrap_or_default());
+        set_marker(world, entity, record.lifetime.is_some(), record.lifetime.unwrap_or_default());
+        set_marker(world, entity, record.sprite_animation.is_some(), record.sprite_animation.unwrap_or_else(bytemuck::Zeroable::zeroed));
+        set_marker(world, entity, record.emitter.is_some(), record.emitter.unwrap_or_else(bytemuck::Zeroable::zeroed));
+        set_marker(world, entity, record.camera.is_some(), record.camera.as_deref().copied().unwrap_or_else(bytemuck::Zeroable::zeroed));
+        let hidden = record.flags & FLAG_HIDDEN != 0;
+        if hidden != world.satisfies::<&Hidden>(entity) {
+            set_marker(world, entity, hidden, Hidden);
+            if hidden {
+                render_state.release_slot(entity);
+            } else {
+                render_state.restore_slot(entity);
+            }
+        }
+
+        #[cfg(feature = "physics-2d")]
+        {
+            use crate::physics::*;
+            let p = &record.physics;
+            set_marker(world, entity, p.body.is_some(), PhysicsBodyHandle(p.body.unwrap_or_default()));
+            set_marker(
+                world,
+                entity,
+                p.collider.is_some(),
+                PhysicsColliderHandle(p.collider.unwrap_or_default()),
+            );
+            set_marker(world, entity, p.controlled, PhysicsControlled);
+            match &p.pending_body {
+                Some(pending) => set_marker(world, entity, true, pending.clone()),
+                None => set_marker(world, entity, false, PendingRigidBody::default()),
+            }
+            match &p.pending_collider {
+                Some(pending) => set_marker(world, entity, true, pending.clone()),
+                None => set_marker(world, entity, false, PendingCollider::default()),
+            }
+        }
+
+        if let Some(slot) = render_state.get_slot(entity) {
+            render_state.dirty_tracker.mark_transform_dirty(slot as usize);
+            render_state.dirty_tracker.mark_bounds_dirty(slot as usize);
+            render_state.dirty_tracker.mark_meta_dirty(slot as usize);
+            render_state.dirty_tracker.mark_tint_dirty(slot as usize);
+        }
+    }
+}
+
+/// Insert `component` when `present`, otherwise remove any existing one.
+fn set_marker<T: hecs::Component>(world: &mut World, entity: hecs::Entity, present: bool, component: T) {
+    if present {
+        let _ = world.insert_one(entity, component);
+    } else {
+        let _ = world.remove_one::<T>(entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_2d(world: &mut World, entity_map: &mut EntityMap, ext_id: u32, x: f32) -> hecs::Entity {
+        let entity = world.spawn((
+            Transform2D { x, ..Default::default() },
+            Velocity::default(),
+            ModelMatrix::default(),
+            BoundingRadius::default(),
+            TextureLayerIndex::default(),
+            MeshHandle::default(),
+            RenderPrimitive::default(),
+            PrimitiveParams::default(),
+            ExternalId(ext_id),
+            Parent::default(),
+            Children::default(),
+            Active,
+        ));
+        entity_map.insert(ext_id, entity);
+        entity_map.set_2d_flag(ext_id, true);
+        entity
+    }
+
+    fn capture(buffer: &mut RollbackBuffer, tick: u64, world: &World) {
+        #[cfg(feature = "physics-2d")]
+        buffer.capture(tick, world, [[0.0; 3]; 3], &CommandSchedule::new(), &Particles::default(), &crate::physics::PhysicsWorld::new());
+        #[cfg(not(feature = "physics-2d"))]
+        buffer.capture(tick, world, [[0.0; 3]; 3], &CommandSchedule::new(), &Particles::default());
+    }
+
+    #[test]
+    fn ring_overwrites_oldest_tick() {
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        spawn_2d(&mut world, &mut map, 0, 1.0);
+        let mut buffer = RollbackBuffer::new(3, 0);
+        for tick in 0..5 {
+            capture(&mut buffer, tick, &world);
+        }
+        assert!(buffer.get(1).is_none());
+        assert_eq!(buffer.get(2).map(|s| s.tick()), Some(2));
+        assert_eq!(buffer.get(4).map(|s| s.entity_count()), Some(1));
+        assert_eq!(buffer.oldest_tick(), Some(2));
+
+        buffer.discard_after(2);
+        assert!(buffer.get(3).is_none());
+        assert!(buffer.get(4).is_none());
+        assert!(buffer.get(2).is_some());
+    }
+
+    #[test]
+    fn physics_interval_limits_restorable_ticks() {
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        spawn_2d(&mut world, &mut map, 0, 1.0);
+        let mut buffer = RollbackBuffer::new(8, 3);
+        for tick in 1..8 {
+            capture(&mut buffer, tick, &world);
+        }
+        assert!(buffer.get(5).is_none());
+        assert!(buffer.get(6).is_some());
+        assert_eq!(buffer.oldest_tick(), Some(3));
+        #[cfg(feature = "physics-2d")]
+        {
+            assert!(buffer.get(3).unwrap().physics().is_some());
+        }
+    }
+
+    #[test]
+    fn apply_records_restores_components_and_markers() {
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut rs = RenderState::new();
+        let e = spawn_2d(&mut world, &mut map, 0, 1.0);
+        let child = spawn_2d(&mut world, &mut map, 1, 0.0);
+        world.get::<&mut Parent>(child).unwrap().0 = 0;
+        world.get::<&mut Children>(e).unwrap().add(1);
+        world.insert_one(e, Depth(3.0)).unwrap();
+
+        let mut buffer = RollbackBuffer::new(4, 0);
+        capture(&mut buffer, 0, &world);
+
+        world.get::<&mut Transform2D>(e).unwrap().x = 9.0;
+        world.get::<&mut Velocity>(e).unwrap().0.x = 5.0;
+        world.get::<&mut Children>(e).unwrap().remove(1);
+        world.remove_one::<Depth>(e).unwrap();
+        world.insert_one(e, Transparent(1)).unwrap();
+
+        let state = buffer.get(0).unwrap();
+        let spawns = reconcile_entities(state, &mut world, &mut map, &mut rs, |_, _| {});
+        assert!(spawns.is_empty());
+        apply_records(state, &mut world, &map, &mut rs);
+
+        assert_eq!(world.get::<&Transform2D>(e).unwrap().x, 1.0);
+        assert_eq!(world.get::<&Velocity>(e).unwrap().0.x, 0.0);
+        assert_eq!(world.get::<&Children>(e).unwrap().as_slice(), &[1]);
+        assert_eq!(world.get::<&Depth>(e).unwrap().0, 3.0);
+        assert!(world.get::<&Transparent>(e).is_err());
+    }
+
+    #[test]
+    fn reconcile_despawns_new_and_requests_missing_entities() {
+        let mut world = World::new();
+        let mut map = EntityMap::new();
+        let mut rs = RenderState::new();
+        let e0 = spawn_2d(&mut world, &mut map, 0, 0.0);
+        let mut buffer = RollbackBuffer::new(2, 0);
+        capture(&mut buffer, 7, &world);
+
+        world.despawn(e0).unwrap();
+        map.remove(0);
+        spawn_2d(&mut world, &mut map, 1, 0.0);
+
+        let spawns = reconcile_entities(buffer.get(7).unwrap(), &mut world, &mut map, &mut rs, |_, _| {});
+        assert!(map.get(1).is_none());
+        assert_eq!(spawns.len(), 1);
+        assert_eq!(spawns[0].cmd_type, CommandType::SpawnEntity);
+        assert_eq!(spawns[0].entity_id, 0);
+        assert_eq!(spawns[0].payload[0], 1);
+    }
+}
@@ -0,0 +1,219 @@
+//! Commands deferred to a future fixed tick.
+//!
+//! A `ScheduleAt` command wraps the command that follows it in the same
+//! batch: instead of being applied, that command is queued and released at
+//! the start of the target fixed tick, before any system runs. Because the
+//! queue is keyed on the tick count rather than wall time, replays, rollback
+//! resimulation and snapshot restores release it at exactly the same tick.
+//!
+//! Commands due at the same tick are released in the order they were
+//! scheduled. Targets already in the past are released at the next tick.
+//! A `ScheduleAt` that ends a batch wraps the first command of the next one
+//! (the ring buffer may be read between the two writes). Only core commands
+//! can be scheduled; a `ScheduleAt` wrapping another `ScheduleAt` or a plugin
+//! command is dropped together with it.
+
+use crate::ring_buffer::{Command, CommandType};
+
+/// `ScheduleAt` mode: the payload tick is an absolute tick count.
+pub const SCHEDULE_ABSOLUTE: u8 = 0;
+/// `ScheduleAt` mode: the payload tick is relative to the next tick to run.
+pub const SCHEDULE_RELATIVE: u8 = 1;
+
+/// Encoded size of one queued command: tick(8) + cmd_type(1) + entity_id(4) + payload(16).
+const ENTRY_SIZE: usize = 8 + 1 + 4 + 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledCommand {
+    /// Tick at whose start the command is applied.
+    pub tick: u64,
+    pub command: Command,
+}
+
+/// Pending scheduled commands, ordered by tick then scheduling order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandSchedule {
+    queue: Vec<ScheduledCommand>,
+    /// A `ScheduleAt` that ended the previous batch, waiting for its command.
+    dangling: Option<Command>,
+}
+
+impl CommandSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `command` for `tick`, after anything already queued for that tick.
+    pub fn push(&mut self, tick: u64, command: Command) {
+        let at = self.queue.partition_point(|s| s.tick <= tick);
+        self.queue.insert(at, ScheduledCommand { tick, command });
+    }
+
+    /// Move every `ScheduleAt` + wrapped command pair of `commands` into the
+    /// queue and return the commands to apply now. `next_tick` is the tick
+    /// the next `fixed_tick` will run (the base for relative targets).
+    pub fn extract(&mut self, commands: &[Command], next_tick: u64) -> Vec<Command> {
+        let mut immediate = Vec::with_capacity(commands.len());
+        let mut wrapper = self.dangling.take();
+        for cmd in commands {
+            match wrapper.take() {
+                Some(_) if cmd.cmd_type == CommandType::ScheduleAt => {}
+                Some(w) => {
+                    let at = u64::from_le_bytes(w.payload[0..8].try_into().unwrap());
+                    let tick = if w.payload[8] == SCHEDULE_RELATIVE { next_tick.saturating_add(at) } else { at };
+                    self.push(tick, cmd.clone());
+                }
+                None if cmd.cmd_type == CommandType::ScheduleAt => wrapper = Some(cmd.clone()),
+                None => immediate.push(cmd.clone()),
+            }
+        }
+        self.dangling = wrapper;
+        immediate
+    }
+
+    /// Remove and return a `ScheduleAt` still waiting for its command.
+    pub fn take_dangling(&mut self) -> Option<Command> {
+        self.dangling.take()
+    }
+
+    /// Remove and return the commands due at or before `tick`, in release order.
+    pub fn take_due(&mut self, tick: u64) -> Vec<Command> {
+        let due = self.queue.partition_point(|s| s.tick <= tick);
+        self.queue.drain(..due).map(|s| s.command).collect()
+    }
+
+    /// Pending commands in release order.
+    pub fn pending(&self) -> &[ScheduledCommand] {
+        &self.queue
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Drop every pending command, including a dangling `ScheduleAt`.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.dangling = None;
+    }
+
+    /// A `ScheduleAt` that ended the last batch, still waiting for its command.
+    pub fn dangling(&self) -> Option<&Command> {
+        self.dangling.as_ref()
+    }
+
+    /// Replace the queue with `pending` (already in release order) and the
+    /// waiting `ScheduleAt` with `dangling`.
+    pub fn restore(&mut self, pending: &[ScheduledCommand], dangling: Option<&Command>) {
+        self.queue.clear();
+        self.queue.extend_from_slice(pending);
+        self.dangling = dangling.cloned();
+    }
+
+    /// Snapshot encoding: `[count u32]` then per entry
+    /// `[tick u64][cmd_type u8][entity_id u32][payload 16B]`.
+    pub fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(self.queue.len() as u32).to_le_bytes());
+        for s in &self.queue {
+            out.extend_from_slice(&s.tick.to_le_bytes());
+            out.push(s.command.cmd_type as u8);
+            out.extend_from_slice(&s.command.entity_id.to_le_bytes());
+            out.extend_from_slice(&s.command.payload);
+        }
+    }
+
+    /// Decode `write_bytes` output at `*cursor`, advancing it. `None` if
+    /// truncated or a command type is unknown.
+    pub fn read_bytes(data: &[u8], cursor: &mut usize) -> Option<Self> {
+        let count = u32::from_le_bytes(data.get(*cursor..*cursor + 4)?.try_into().unwrap()) as usize;
+        *cursor += 4;
+        let bytes = data.get(*cursor..*cursor + count.checked_mul(ENTRY_SIZE)?)?;
+        *cursor += bytes.len();
+        let queue = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|e| {
+                Some(ScheduledCommand {
+                    tick: u64::from_le_bytes(e[0..8].try_into().unwrap()),
+                    command: Command {
+                        cmd_type: CommandType::from_u8(e[8])?,
+                        entity_id: u32::from_le_bytes(e[9..13].try_into().unwrap()),
+                        payload: e[13..29].try_into().unwrap(),
+                    },
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { queue, dangling: None })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd(cmd_type: CommandType, entity_id: u32) -> Command {
+        Command { cmd_type, entity_id, payload: [0; 16] }
+    }
+
+    fn schedule_at(tick: u64, mode: u8) -> Command {
+        let mut c = cmd(CommandType::ScheduleAt, 0);
+        c.payload[0..8].copy_from_slice(&tick.to_le_bytes());
+        c.payload[8] = mode;
+        c
+    }
+
+    #[test]
+    fn extract_queues_wrapped_commands_only() {
+        let mut schedule = CommandSchedule::new();
+        let immediate = schedule.extract(
+            &[
+                cmd(CommandType::SpawnEntity, 0),
+                schedule_at(5, SCHEDULE_RELATIVE),
+                cmd(CommandType::DespawnEntity, 0),
+                schedule_at(1, SCHEDULE_ABSOLUTE),
+                schedule_at(2, SCHEDULE_ABSOLUTE),
+                cmd(CommandType::SetVelocity, 1),
+                schedule_at(9, SCHEDULE_ABSOLUTE),
+            ],
+            10,
+        );
+        assert_eq!(immediate, [cmd(CommandType::SpawnEntity, 0), cmd(CommandType::SetVelocity, 1)]);
+        assert_eq!(schedule.len(), 1);
+        assert_eq!(schedule.pending()[0].tick, 15);
+
+        // The trailing wrapper applies to the first command of the next batch.
+        let immediate = schedule.extract(&[cmd(CommandType::DespawnEntity, 4), cmd(CommandType::SpawnEntity, 5)], 11);
+        assert_eq!(immediate, [cmd(CommandType::SpawnEntity, 5)]);
+        assert_eq!(schedule.pending()[0], ScheduledCommand { tick: 9, command: cmd(CommandType::DespawnEntity, 4) });
+    }
+
+    #[test]
+    fn take_due_releases_in_tick_then_schedule_order() {
+        let mut schedule = CommandSchedule::new();
+        schedule.push(3, cmd(CommandType::SetVelocity, 1));
+        schedule.push(1, cmd(CommandType::SetVelocity, 2));
+        schedule.push(3, cmd(CommandType::SetVelocity, 3));
+        assert!(schedule.take_due(0).is_empty());
+        assert_eq!(schedule.take_due(2), [cmd(CommandType::SetVelocity, 2)]);
+        assert_eq!(schedule.take_due(5), [cmd(CommandType::SetVelocity, 1), cmd(CommandType::SetVelocity, 3)]);
+        assert!(schedule.is_empty());
+    }
+
+    #[test]
+    fn snapshot_bytes_round_trip() {
+        let mut schedule = CommandSchedule::new();
+        let mut velocity = cmd(CommandType::SetVelocity, 7);
+        velocity.payload[0..4].copy_from_slice(&2.5f32.to_le_bytes());
+        schedule.push(40, velocity);
+        schedule.push(12, cmd(CommandType::DespawnEntity, 3));
+        let mut bytes = Vec::new();
+        schedule.write_bytes(&mut bytes);
+        let mut cursor = 0;
+        assert_eq!(CommandSchedule::read_bytes(&bytes, &mut cursor), Some(schedule));
+        assert_eq!(cursor, bytes.len());
+        assert_eq!(CommandSchedule::read_bytes(&bytes[..bytes.len() - 1], &mut 0), None);
+    }
+}
@@ -0,0 +1,423 @@
+//! Spatial index for entity queries without physics.
+//!
+//! A uniform hash grid over each entity's bounding circle: the world-space
+//! translation of its `ModelMatrix` plus its `BoundingRadius`. Entities are
+//! indexed by their XY footprint (z is ignored), which covers 2D scenes and
+//! top-down queries in 3D ones.
+//!
+//! The index follows the GPU slots: `sync` drops released slots and re-reads
+//! every slot whose bounds are dirty, so only moved entities are touched each
+//! frame. Hidden entities hold no slot and are therefore not indexed.
+//!
+//! Queries write external entity IDs into an index-owned result buffer
+//! (`results`), ordered by ID for area queries and by distance (ties by ID)
+//! for k-nearest and ray queries, which also fill `distances`.
+
+use std::collections::HashMap;
+
+use glam::Vec2;
+use hecs::World;
+
+use crate::components::{BoundingRadius, ExternalId, ModelMatrix};
+use crate::render_state::RenderState;
+
+/// Default grid cell size in world units.
+pub const DEFAULT_CELL_SIZE: f32 = 64.0;
+
+/// Entries covering more cells than this per axis go to an unbucketed list
+/// checked by every query instead.
+const MAX_CELL_SPAN: i32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CellRange {
+    min: (i32, i32),
+    max: (i32, i32),
+}
+
+impl CellRange {
+    fn is_oversized(&self) -> bool {
+        self.max.0 - self.min.0 >= MAX_CELL_SPAN || self.max.1 - self.min.1 >= MAX_CELL_SPAN
+    }
+
+    fn cells(self) -> impl Iterator<Item = (i32, i32)> {
+        (self.min.1..=self.max.1).flat_map(move |y| (self.min.0..=self.max.0).map(move |x| (x, y)))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    id: u32,
+    center: Vec2,
+    radius: f32,
+    cells: CellRange,
+}
+
+impl Entry {
+    /// Distance from `p` to the bounding circle (0 inside it).
+    fn distance(&self, p: Vec2) -> f32 {
+        (self.center.distance(p) - self.radius).max(0.0)
+    }
+
+    /// Ray parameter where `origin + dir * t` (unit `dir`) enters the circle.
+    fn ray_hit(&self, origin: Vec2, dir: Vec2) -> Option<f32> {
+        let m = origin - self.center;
+        let c = m.length_squared() - self.radius * self.radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+        let b = m.dot(dir);
+        let disc = b * b - c;
+        if b > 0.0 || disc < 0.0 {
+            return None;
+        }
+        Some(-b - disc.sqrt())
+    }
+}
+
+#[derive(Debug)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<hecs::Entity>>,
+    oversized: Vec<hecs::Entity>,
+    entries: HashMap<hecs::Entity, Entry>,
+    results: Vec<u32>,
+    distances: Vec<f32>,
+}
+
+impl SpatialIndex {
+    /// An empty index with `cell_size` grid cells (non-positive = default).
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: if cell_size > 0.0 && cell_size.is_finite() { cell_size } else { DEFAULT_CELL_SIZE },
+            cells: HashMap::new(),
+            oversized: Vec::new(),
+            entries: HashMap::new(),
+            results: Vec::new(),
+            distances: Vec::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Number of indexed entities.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn cell(&self, p: Vec2) -> (i32, i32) {
+        ((p.x / self.cell_size).floor() as i32, (p.y / self.cell_size).floor() as i32)
+    }
+
+    fn range(&self, min: Vec2, max: Vec2) -> CellRange {
+        CellRange { min: self.cell(min), max: self.cell(max) }
+    }
+
+    /// Insert or move `entity` (external ID `id`).
+    pub fn update(&mut self, entity: hecs::Entity, id: u32, center: Vec2, radius: f32) {
+        let radius = radius.max(0.0);
+        let cells = self.range(center - radius, center + radius);
+        if let Some(entry) = self.entries.get_mut(&entity)
+            && entry.cells == cells
+        {
+            *entry = Entry { id, center, radius, cells };
+            return;
+        }
+        self.remove(entity);
+        if cells.is_oversized() {
+            self.oversized.push(entity);
+        } else {
+            for cell in cells.cells() {
+                self.cells.entry(cell).or_default().push(entity);
+            }
+        }
+        self.entries.insert(entity, Entry { id, center, radius, cells });
+    }
+
+    pub fn remove(&mut self, entity: hecs::Entity) {
+        let Some(entry) = self.entries.remove(&entity) else {
+            return;
+        };
+        if entry.cells.is_oversized() {
+            self.oversized.retain(|&e| e != entity);
+            return;
+        }
+        for cell in entry.cells.cells() {
+            if let Some(bucket) = self.cells.get_mut(&cell) {
+                bucket.retain(|&e| e != entity);
+                if bucket.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.oversized.clear();
+        self.entries.clear();
+    }
+
+    fn update_slot(&mut self, slot: u32, world: &World, render_state: &RenderState) {
+        let Some(entity) = render_state.slot_entity(slot) else {
+            return;
+        };
+        let (Ok(ext), Ok(matrix)) = (world.get::<&ExternalId>(entity), world.get::<&ModelMatrix>(entity)) else {
+            return;
+        };
+        let center = Vec2::new(matrix.0[12], matrix.0[13]);
+        let radius = world.get::<&BoundingRadius>(entity).map_or(0.0, |r| r.0);
+        self.update(entity, ext.0, center, radius);
+    }
+
+    /// Bring the index up to date with the render state: flush pending slot
+    /// releases (removing those entities) and re-read bounds-dirty slots.
+    /// Call after transforms are propagated and before the dirty bits are cleared.
+    pub fn sync(&mut self, world: &World, render_state: &mut RenderState) {
+        for &entity in &render_state.pending_despawns {
+            self.remove(entity);
+        }
+        render_state.flush_pending_despawns();
+        for slot in 0..render_state.gpu_entity_count() {
+            if render_state.dirty_tracker.is_bounds_dirty(slot as usize) {
+                self.update_slot(slot, world, render_state);
+            }
+        }
+    }
+
+    /// Re-index every live slot from scratch.
+    pub fn rebuild(&mut self, world: &World, render_state: &RenderState) {
+        self.clear();
+        for slot in 0..render_state.gpu_entity_count() {
+            self.update_slot(slot, world, render_state);
+        }
+    }
+
+    /// Candidate entries from the cells of `range` plus oversized entries.
+    /// May contain duplicates.
+    fn candidates(&self, range: CellRange) -> impl Iterator<Item = &Entry> {
+        range
+            .cells()
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .chain(&self.oversized)
+            .map(|e| &self.entries[e])
+    }
+
+    /// True if scanning `range` would visit more cells than are occupied,
+    /// so a linear scan over all entries is cheaper.
+    fn prefer_scan(&self, range: CellRange) -> bool {
+        let w = (range.max.0 as i64 - range.min.0 as i64 + 1) as u64;
+        let h = (range.max.1 as i64 - range.min.1 as i64 + 1) as u64;
+        w.saturating_mul(h) > self.cells.len() as u64
+    }
+
+    fn finish_ids(&mut self) -> &[u32] {
+        self.results.sort_unstable();
+        self.results.dedup();
+        self.distances.clear();
+        &self.results
+    }
+
+    fn finish_ranked(&mut self, mut hits: Vec<(f32, u32)>, limit: usize) -> &[u32] {
+        hits.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        hits.dedup_by_key(|h| h.1);
+        hits.truncate(limit);
+        self.results.clear();
+        self.distances.clear();
+        for (d, id) in hits {
+            self.results.push(id);
+            self.distances.push(d);
+        }
+        &self.results
+    }
+
+    /// Entities whose bounding circle overlaps the rectangle `[min, max]`.
+    pub fn query_rect(&mut self, min: Vec2, max: Vec2) -> &[u32] {
+        self.results.clear();
+        let range = self.range(min, max);
+        let overlaps = |e: &Entry| e.center.clamp(min, max).distance_squared(e.center) <= e.radius * e.radius;
+        if self.prefer_scan(range) {
+            self.results.extend(self.entries.values().filter(|e| overlaps(e)).map(|e| e.id));
+        } else {
+            let hits: Vec<u32> = self.candidates(range).filter(|e| overlaps(e)).map(|e| e.id).collect();
+            self.results = hits;
+        }
+        self.finish_ids()
+    }
+
+    /// Entities whose bounding circle overlaps the circle at `center`.
+    pub fn query_circle(&mut self, center: Vec2, radius: f32) -> &[u32] {
+        self.results.clear();
+        let range = self.range(center - radius, center + radius);
+        let overlaps = |e: &Entry| e.distance(center) <= radius;
+        if self.prefer_scan(range) {
+            self.results.extend(self.entries.values().filter(|e| overlaps(e)).map(|e| e.id));
+        } else {
+            let hits: Vec<u32> = self.candidates(range).filter(|e| overlaps(e)).map(|e| e.id).collect();
+            self.results = hits;
+        }
+        self.finish_ids()
+    }
+
+    /// The `k` entities nearest to `p`, measured to their bounding circle.
+    pub fn query_knn(&mut self, p: Vec2, k: usize) -> &[u32] {
+        let center = self.cell(p);
+        let mut hits: Vec<(f32, u32)> = Vec::new();
+        let mut ring = 0;
+        loop {
+            let range = CellRange {
+                min: (center.0.saturating_sub(ring), center.1.saturating_sub(ring)),
+                max: (center.0.saturating_add(ring), center.1.saturating_add(ring)),
+            };
+            if k >= self.entries.len() || self.prefer_scan(range) {
+                hits = self.entries.values().map(|e| (e.distance(p), e.id)).collect();
+                break;
+            }
+            hits.clear();
+            hits.extend(self.candidates(range).map(|e| (e.distance(p), e.id)));
+            hits.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+            hits.dedup_by_key(|h| h.1);
+            // Anything not seen yet lies entirely outside the scanned square,
+            // at least `ring` whole cells away.
+            if hits.len() >= k && hits[k - 1].0 <= ring as f32 * self.cell_size {
+                break;
+            }
+            ring += 1;
+        }
+        self.finish_ranked(hits, k)
+    }
+
+    /// Entities hit by the ray from `origin` along `dir` within `max_dist`,
+    /// nearest first. `distances` holds the entry distance along the ray.
+    pub fn query_ray(&mut self, origin: Vec2, dir: Vec2, max_dist: f32) -> &[u32] {
+        let Some(dir) = dir.try_normalize() else {
+            return self.finish_ranked(Vec::new(), 0);
+        };
+        let test = |e: &Entry| e.ray_hit(origin, dir).filter(|&t| t <= max_dist).map(|t| (t, e.id));
+        let end = origin + dir * max_dist;
+        let bounds = self.range(origin.min(end), origin.max(end));
+        let hits: Vec<(f32, u32)> = if !end.is_finite() || self.prefer_scan(bounds) {
+            self.entries.values().filter_map(test).collect()
+        } else {
+            // Walk the cells the ray crosses (2D DDA).
+            let mut hits = Vec::new();
+            let mut cell = self.cell(origin);
+            let last = self.cell(end);
+            let step = (dir.x.signum() as i32, dir.y.signum() as i32);
+            let next_edge = |c: i32, s: i32| (c + (s > 0) as i32) as f32 * self.cell_size;
+            let mut t_max = Vec2::new(
+                if dir.x != 0.0 { (next_edge(cell.0, step.0) - origin.x) / dir.x } else { f32::INFINITY },
+                if dir.y != 0.0 { (next_edge(cell.1, step.1) - origin.y) / dir.y } else { f32::INFINITY },
+            );
+            let t_delta = Vec2::new(self.cell_size / dir.x.abs(), self.cell_size / dir.y.abs());
+            loop {
+                if let Some(bucket) = self.cells.get(&cell) {
+                    hits.extend(bucket.iter().filter_map(|e| test(&self.entries[e])));
+                }
+                if cell == last || t_max.x.min(t_max.y) > max_dist {
+                    break;
+                }
+                if t_max.x < t_max.y {
+                    cell.0 += step.0;
+                    t_max.x += t_delta.x;
+                } else {
+                    cell.1 += step.1;
+                    t_max.y += t_delta.y;
+                }
+            }
+            hits.extend(self.oversized.iter().filter_map(|e| test(&self.entries[e])));
+            hits
+        };
+        self.finish_ranked(hits, usize::MAX)
+    }
+
+    /// External IDs from the last query.
+    pub fn results(&self) -> &[u32] {
+        &self.results
+    }
+
+    pub fn results_ptr(&self) -> *const u32 {
+        if self.results.is_empty() { std::ptr::null() } else { self.results.as_ptr() }
+    }
+
+    /// Per-result distances from the last k-nearest or ray query (empty otherwise).
+    pub fn distances(&self) -> &[f32] {
+        &self.distances
+    }
+
+    pub fn distances_ptr(&self) -> *const f32 {
+        if self.distances.is_empty() { std::ptr::null() } else { self.distances.as_ptr() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Index of circles `(x, y, radius)` with external IDs 0, 1, 2, ...
+    fn build(circles: &[(f32, f32, f32)]) -> (SpatialIndex, Vec<hecs::Entity>) {
+        let mut world = World::new();
+        let mut index = SpatialIndex::new(10.0);
+        let entities: Vec<_> = circles
+            .iter()
+            .enumerate()
+            .map(|(id, &(x, y, r))| {
+                let e = world.spawn(());
+                index.update(e, id as u32, Vec2::new(x, y), r);
+                e
+            })
+            .collect();
+        (index, entities)
+    }
+
+    #[test]
+    fn rect_and_circle_queries_test_bounding_circles() {
+        let (mut index, _) = build(&[(0.0, 0.0, 1.0), (12.0, 0.0, 3.0), (50.0, 50.0, 1.0), (-30.0, 5.0, 100.0)]);
+        assert_eq!(index.query_rect(Vec2::new(-5.0, -5.0), Vec2::new(9.5, 5.0)), [0, 1, 3]);
+        assert_eq!(index.query_rect(Vec2::new(40.0, 40.0), Vec2::new(49.5, 49.5)), [2, 3]);
+        assert_eq!(index.query_circle(Vec2::new(6.0, 0.0), 3.0), [1, 3]);
+        assert!(index.distances().is_empty());
+    }
+
+    #[test]
+    fn update_moves_between_cells_and_remove_drops() {
+        let (mut index, entities) = build(&[(0.0, 0.0, 1.0), (5.0, 5.0, 1.0)]);
+        index.update(entities[0], 0, Vec2::new(100.0, 100.0), 1.0);
+        assert_eq!(index.query_circle(Vec2::ZERO, 3.0), [] as [u32; 0]);
+        assert_eq!(index.query_circle(Vec2::new(100.0, 100.0), 3.0), [0]);
+        index.remove(entities[1]);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.query_rect(Vec2::splat(-50.0), Vec2::splat(50.0)), [] as [u32; 0]);
+    }
+
+    #[test]
+    fn knn_orders_by_distance_to_circle() {
+        let circles: Vec<_> = (0..40).map(|i| (i as f32 * 7.0, 0.0, 0.5)).collect();
+        let (mut index, _) = build(&circles);
+        assert_eq!(index.query_knn(Vec2::new(71.0, 3.0), 3), [10, 11, 9]);
+        let d = index.distances();
+        assert!(d[0] <= d[1] && d[1] <= d[2]);
+        // A big circle far away still wins once the point is inside it.
+        let (mut index, _) = build(&[(0.0, 0.0, 1.0), (200.0, 0.0, 150.0)]);
+        assert_eq!(index.query_knn(Vec2::new(60.0, 0.0), 1), [1]);
+        assert_eq!(index.distances(), [0.0]);
+    }
+
+    #[test]
+    fn ray_returns_hits_in_order_within_range() {
+        let (mut index, _) = build(&[(30.0, 0.0, 2.0), (10.0, 0.5, 1.0), (10.0, 20.0, 1.0), (-10.0, 0.0, 1.0), (80.0, 0.0, 1.0)]);
+        assert_eq!(index.query_ray(Vec2::ZERO, Vec2::new(2.0, 0.0), 50.0), [1, 0]);
+        let d = index.distances();
+        assert!((d[0] - (10.0 - 0.75f32.sqrt())).abs() < 1e-4);
+        assert!((d[1] - 28.0).abs() < 1e-4);
+        assert_eq!(index.query_ray(Vec2::ZERO, Vec2::X, f32::INFINITY), [1, 0, 4]);
+        assert_eq!(index.query_ray(Vec2::new(10.0, 0.0), Vec2::Y, 50.0), [1, 2]);
+        assert_eq!(index.query_ray(Vec2::ZERO, Vec2::ZERO, 50.0), [] as [u32; 0]);
+    }
+}
@@ -0,0 +1,307 @@
+//! Engine-side sprite flipbook animation.
+//!
+//! Clips are registered once (`SpriteClips::register`) as a list of packed
+//! tier/layer indices with per-frame durations in ticks. Entities play a clip
+//! through the `SpriteAnimation` component; the animation system advances it
+//! every fixed tick, writes `TextureLayerIndex` on frame changes and marks the
+//! slot's meta dirty, so JS no longer sends one `SetTextureLayer` per frame.
+//!
+//! Clips are engine assets like meshes and textures: they are not part of
+//! snapshots or rollback captures, only the per-entity playback state is.
+
+use bytemuck::{Pod, Zeroable};
+use hecs::{With, World};
+
+use crate::components::{Active, ExternalId, TextureLayerIndex};
+use crate::events::{EVENT_SPRITE_FINISHED, EVENT_SPRITE_FRAME, EngineEvent};
+use crate::render_state::RenderState;
+
+/// Play once and hold the last frame.
+pub const SPRITE_ONCE: u8 = 0;
+/// Restart from the first frame after the last.
+pub const SPRITE_LOOP: u8 = 1;
+/// Play forward then backward, forever.
+pub const SPRITE_PING_PONG: u8 = 2;
+
+/// Playing backward (ping-pong).
+const FLAG_REVERSE: u8 = 1 << 0;
+/// The current frame has been written to `TextureLayerIndex`.
+const FLAG_APPLIED: u8 = 1 << 1;
+/// A `SPRITE_ONCE` animation reached its end.
+const FLAG_FINISHED: u8 = 1 << 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpriteClipError {
+    /// The clip has no frames.
+    Empty,
+    /// `layers` and `durations` have different lengths.
+    LengthMismatch,
+    /// The clip has more than `u16::MAX` frames.
+    TooManyFrames,
+}
+
+/// One registered flipbook clip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteClip {
+    /// Packed `TextureLayerIndex` value per frame.
+    pub layers: Vec<u32>,
+    /// Frame durations in ticks (0 is treated as 1).
+    pub durations: Vec<u16>,
+    /// Frames that raise `EVENT_SPRITE_FRAME` when reached.
+    pub event_frames: Vec<bool>,
+}
+
+/// Registered clips, addressed by the ID returned from `register`.
+#[derive(Debug, Default)]
+pub struct SpriteClips {
+    clips: Vec<SpriteClip>,
+}
+
+impl SpriteClips {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a clip and return its ID. Out-of-range `event_frames` are ignored.
+    pub fn register(&mut self, layers: &[u32], durations: &[u16], event_frames: &[u16]) -> Result<u32, SpriteClipError> {
+        if layers.is_empty() {
+            return Err(SpriteClipError::Empty);
+        }
+        if layers.len() != durations.len() {
+            return Err(SpriteClipError::LengthMismatch);
+        }
+        if layers.len() > u16::MAX as usize {
+            return Err(SpriteClipError::TooManyFrames);
+        }
+        let mut marked = vec![false; layers.len()];
+        for &frame in event_frames {
+            if let Some(m) = marked.get_mut(frame as usize) {
+                *m = true;
+            }
+        }
+        self.clips.push(SpriteClip { layers: layers.to_vec(), durations: durations.to_vec(), event_frames: marked });
+        Ok(self.clips.len() as u32 - 1)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&SpriteClip> {
+        self.clips.get(id as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.clips.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clips.is_empty()
+    }
+}
+
+/// Playback state of a sprite clip on one entity.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct SpriteAnimation {
+    pub clip: u32,
+    /// Ticks of progress per tick (1.0 = authored speed, 0.0 = paused).
+    pub speed: f32,
+    /// Ticks spent on the current frame.
+    pub elapsed: f32,
+    pub frame: u16,
+    /// `SPRITE_ONCE`, `SPRITE_LOOP` or `SPRITE_PING_PONG`.
+    pub mode: u8,
+    /// Internal `FLAG_*` bits.
+    pub flags: u8,
+}
+
+impl SpriteAnimation {
+    /// Decode a `PlaySpriteAnimation` payload:
+    /// `[clip u32][speed f32][mode u8][start frame u16]`.
+    pub fn from_payload(p: &[u8; 16]) -> Self {
+        Self {
+            clip: u32::from_le_bytes(p[0..4].try_into().unwrap()),
+            speed: f32::from_le_bytes(p[4..8].try_into().unwrap()),
+            elapsed: 0.0,
+            frame: u16::from_le_bytes([p[9], p[10]]),
+            mode: p[8],
+            flags: 0,
+        }
+    }
+
+    /// True once a `SPRITE_ONCE` animation has shown its last frame.
+    pub fn is_finished(&self) -> bool {
+        self.flags & FLAG_FINISHED != 0
+    }
+
+    /// Move to the next frame per the loop mode. Returns false if the clip ended.
+    fn step(&mut self, len: u16) -> bool {
+        let last = len - 1;
+        match self.mode {
+            SPRITE_LOOP => self.frame = if self.frame >= last { 0 } else { self.frame + 1 },
+            SPRITE_PING_PONG => {
+                if last == 0 {
+                    return true;
+                }
+                if self.frame == last {
+                    self.flags |= FLAG_REVERSE;
+                } else if self.frame == 0 {
+                    self.flags &= !FLAG_REVERSE;
+                }
+                if self.flags & FLAG_REVERSE != 0 {
+                    self.frame -= 1;
+                } else {
+                    self.frame += 1;
+                }
+            }
+            _ => {
+                if self.frame >= last {
+                    self.flags |= FLAG_FINISHED;
+                    return false;
+                }
+                self.frame += 1;
+            }
+        }
+        true
+    }
+}
+
+/// Advance every sprite animation of active entities by one tick. Animations
+/// referencing an unknown clip are removed.
+pub fn sprite_animation_system(
+    world: &mut World,
+    clips: &SpriteClips,
+    render_state: &mut RenderState,
+    events: &mut Vec<EngineEvent>,
+) {
+    let mut invalid = Vec::new();
+    for (entity, ext_id, anim, tex) in world
+        .query_mut::<With<(hecs::Entity, &ExternalId, &mut SpriteAnimation, &mut TextureLayerIndex), &Active>>()
+    {
+        let Some(clip) = clips.get(anim.clip) else {
+            invalid.push(entity);
+            continue;
+        };
+        let len = clip.layers.len() as u16;
+        let frame_before = tex.0;
+        let mut reached = |frame: u16, events: &mut Vec<EngineEvent>| {
+            tex.0 = clip.layers[frame as usize];
+            if clip.event_frames[frame as usize] {
+                events.push(EngineEvent { entity_id: ext_id.0, kind: EVENT_SPRITE_FRAME, data: frame as u32 });
+            }
+        };
+
+        if anim.frame >= len {
+            anim.frame = len - 1;
+        }
+        if anim.flags & FLAG_APPLIED == 0 {
+            anim.flags |= FLAG_APPLIED;
+            reached(anim.frame, events);
+        }
+        if !anim.is_finished() {
+            anim.elapsed += anim.speed.max(0.0);
+            loop {
+                let duration = clip.durations[anim.frame as usize].max(1) as f32;
+                if anim.elapsed < duration {
+                    break;
+                }
+                anim.elapsed -= duration;
+                if !anim.step(len) {
+                    anim.elapsed = 0.0;
+                    events.push(EngineEvent { entity_id: ext_id.0, kind: EVENT_SPRITE_FINISHED, data: anim.clip });
+                    break;
+                }
+                reached(anim.frame, events);
+            }
+        }
+
+        if tex.0 != frame_before
+            && let Some(slot) = render_state.get_slot(entity)
+        {
+            render_state.dirty_tracker.mark_meta_dirty(slot as usize);
+        }
+    }
+    for entity in invalid {
+        let _ = world.remove_one::<SpriteAnimation>(entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(clip: u32, speed: f32, mode: u8) -> SpriteAnimation {
+        let mut p = [0u8; 16];
+        p[0..4].copy_from_slice(&clip.to_le_bytes());
+        p[4..8].copy_from_slice(&speed.to_le_bytes());
+        p[8] = mode;
+        SpriteAnimation::from_payload(&p)
+    }
+
+    fn frames(world: &mut World, clips: &SpriteClips, e: hecs::Entity, ticks: usize) -> (Vec<u32>, Vec<EngineEvent>) {
+        let mut rs = RenderState::new();
+        let mut events = Vec::new();
+        let mut layers = Vec::new();
+        for _ in 0..ticks {
+            sprite_animation_system(world, clips, &mut rs, &mut events);
+            layers.push(world.get::<&TextureLayerIndex>(e).unwrap().0);
+        }
+        (layers, events)
+    }
+
+    #[test]
+    fn register_validates_clip() {
+        let mut clips = SpriteClips::new();
+        assert_eq!(clips.register(&[], &[], &[]), Err(SpriteClipError::Empty));
+        assert_eq!(clips.register(&[1, 2], &[1], &[]), Err(SpriteClipError::LengthMismatch));
+        assert_eq!(clips.register(&[1, 2], &[1, 1], &[5]), Ok(0));
+        assert_eq!(clips.register(&[3], &[1], &[]), Ok(1));
+        assert_eq!(clips.get(0).unwrap().event_frames, [false, false]);
+    }
+
+    #[test]
+    fn loop_mode_honours_per_frame_durations() {
+        let mut clips = SpriteClips::new();
+        clips.register(&[10, 11, 12], &[1, 2, 1], &[]).unwrap();
+        let mut world = World::new();
+        let e = world.spawn((ExternalId(0), TextureLayerIndex(0), play(0, 1.0, SPRITE_LOOP), Active));
+        let (layers, events) = frames(&mut world, &clips, e, 6);
+        assert_eq!(layers, [11, 11, 12, 10, 11, 11]);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn once_mode_holds_last_frame_and_finishes() {
+        let mut clips = SpriteClips::new();
+        clips.register(&[10, 11, 12], &[2, 2, 2], &[1]).unwrap();
+        let mut world = World::new();
+        let e = world.spawn((ExternalId(4), TextureLayerIndex(0), play(0, 2.0, SPRITE_ONCE), Active));
+        let (layers, events) = frames(&mut world, &clips, e, 4);
+        assert_eq!(layers, [11, 12, 12, 12]);
+        assert_eq!(
+            events,
+            [
+                EngineEvent { entity_id: 4, kind: EVENT_SPRITE_FRAME, data: 1 },
+                EngineEvent { entity_id: 4, kind: EVENT_SPRITE_FINISHED, data: 0 },
+            ]
+        );
+        assert!(world.get::<&SpriteAnimation>(e).unwrap().is_finished());
+    }
+
+    #[test]
+    fn ping_pong_bounces_at_both_ends() {
+        let mut clips = SpriteClips::new();
+        clips.register(&[10, 11, 12], &[1, 1, 1], &[]).unwrap();
+        let mut world = World::new();
+        let e = world.spawn((ExternalId(0), TextureLayerIndex(0), play(0, 1.0, SPRITE_PING_PONG), Active));
+        let (layers, _) = frames(&mut world, &clips, e, 6);
+        assert_eq!(layers, [11, 12, 11, 10, 11, 12]);
+    }
+
+    #[test]
+    fn unknown_clip_removes_animation() {
+        let clips = SpriteClips::new();
+        let mut world = World::new();
+        let e = world.spawn((ExternalId(0), TextureLayerIndex(7), play(3, 1.0, SPRITE_LOOP), Active));
+        let (layers, _) = frames(&mut world, &clips, e, 1);
+        assert_eq!(layers, [7]);
+        assert!(!world.satisfies::<&SpriteAnimation>(e));
+    }
+}
@@ -0,0 +1,336 @@
+//! Deterministic world state hashing for desync detection.
+//!
+//! Every hashed component value is reduced to a 64-bit FNV-1a hash of
+//! `(external_id, component_id, raw bytes)`. The world hash is the wrapping
+//! sum of those values mixed with their count, so it does not depend on hecs
+//! archetype or iteration order — only on `ExternalId` and component data.
+//! FNV-1a is used instead of `DefaultHasher` because its output is fixed
+//! across Rust versions and targets (wasm vs native).
+//!
+//! Entities with a live Rapier body skip `Transform2D`/`Position`: those are
+//! only written back once per frame, so they depend on how many ticks ran in
+//! the frame. The body state is hashed instead.
+//!
+//! Breakdowns and divergence reports name components by their
+//! `component_registry` IDs; the body state uses the reserved
+//! `COMPONENT_PHYSICS_BODY`.
+
+use std::collections::VecDeque;
+
+use hecs::World;
+
+use crate::component_registry::{ComponentId, COMPONENT_POSITION, COMPONENT_TRANSFORM_2D, COMPONENT_VELOCITY};
+use crate::components::{ExternalId, Position, Transform2D, Velocity};
+
+/// Mask bits selecting which components are hashed.
+pub const HASH_TRANSFORM_2D: u32 = 1 << 0;
+pub const HASH_POSITION: u32 = 1 << 1;
+pub const HASH_VELOCITY: u32 = 1 << 2;
+pub const HASH_PHYSICS: u32 = 1 << 3;
+pub const HASH_ALL: u32 = HASH_TRANSFORM_2D | HASH_POSITION | HASH_VELOCITY | HASH_PHYSICS;
+
+/// Default number of per-tick hashes kept.
+pub const DEFAULT_HASH_HISTORY: usize = 600;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Hash of one component value of one entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentHash {
+    pub external_id: u32,
+    pub component: ComponentId,
+    pub hash: u64,
+}
+
+impl ComponentHash {
+    fn new(external_id: u32, component: ComponentId, floats: &[f32]) -> Self {
+        let mut hash = fnv1a(FNV_OFFSET, &external_id.to_le_bytes());
+        hash = fnv1a(hash, &[component]);
+        for &f in floats {
+            // Canonicalize NaN payloads; every other bit pattern is significant.
+            let bits = if f.is_nan() { f32::NAN.to_bits() } else { f.to_bits() };
+            hash = fnv1a(hash, &bits.to_le_bytes());
+        }
+        Self { external_id, component, hash }
+    }
+}
+
+/// First point where two breakdowns disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    pub external_id: u32,
+    pub component: ComponentId,
+    /// Whether each side has this entity/component at all.
+    pub in_local: bool,
+    pub in_remote: bool,
+}
+
+/// Collect the hash of every selected component, sorted by
+/// `(external_id, component)`.
+pub fn breakdown(
+    world: &World,
+    mask: u32,
+    #[cfg(feature = "physics-2d")] physics: &crate::physics::PhysicsWorld,
+) -> Vec<ComponentHash> {
+    let mut out = Vec::new();
+    visit(
+        world,
+        mask,
+        #[cfg(feature = "physics-2d")]
+        physics,
+        |c| out.push(c),
+    );
+    out.sort_unstable_by_key(|c| (c.external_id, c.component));
+    out
+}
+
+/// World hash of the selected components, equal to `combine(&breakdown(..))`
+/// without collecting or sorting the breakdown.
+pub fn world_hash(
+    world: &World,
+    mask: u32,
+    #[cfg(feature = "physics-2d")] physics: &crate::physics::PhysicsWorld,
+) -> u64 {
+    let (mut sum, mut count) = (0u64, 0u64);
+    visit(
+        world,
+        mask,
+        #[cfg(feature = "physics-2d")]
+        physics,
+        |c| {
+            sum = sum.wrapping_add(c.hash);
+            count += 1;
+        },
+    );
+    finish(sum, count)
+}
+
+/// Call `out` with the hash of every selected component, in world order.
+fn visit(
+    world: &World,
+    mask: u32,
+    #[cfg(feature = "physics-2d")] physics: &crate::physics::PhysicsWorld,
+    mut out: impl FnMut(ComponentHash),
+) {
+    for (entity, ext) in world.query::<(hecs::Entity, &ExternalId)>().iter() {
+        let id = ext.0;
+
+        #[cfg(feature = "physics-2d")]
+        let body = world
+            .get::<&crate::physics::PhysicsBodyHandle>(entity)
+            .ok()
+            .and_then(|h| physics.rigid_body_set.get(h.0));
+        #[cfg(not(feature = "physics-2d"))]
+        let body: Option<()> = None;
+
+        if mask & HASH_POSITION != 0
+            && body.is_none()
+            && let Ok(p) = world.get::<&Position>(entity)
+        {
+            out(ComponentHash::new(id, COMPONENT_POSITION, &p.0.to_array()));
+        }
+        if mask & HASH_VELOCITY != 0
+            && let Ok(v) = world.get::<&Velocity>(entity)
+        {
+            out(ComponentHash::new(id, COMPONENT_VELOCITY, &v.0.to_array()));
+        }
+        if mask & HASH_TRANSFORM_2D != 0
+            && body.is_none()
+            && let Ok(t) = world.get::<&Transform2D>(entity)
+        {
+            out(ComponentHash::new(id, COMPONENT_TRANSFORM_2D, &[t.x, t.y, t.rot, t.sx, t.sy]));
+        }
+        #[cfg(feature = "physics-2d")]
+        if mask & HASH_PHYSICS != 0
+            && let Some(body) = body
+        {
+            let t = body.translation();
+            let v = body.linvel();
+            let state = [t.x, t.y, body.rotation().angle(), v.x, v.y, body.angvel()];
+            out(ComponentHash::new(id, crate::component_registry::COMPONENT_PHYSICS_BODY, &state));
+        }
+    }
+}
+
+/// Combine a breakdown into a single order-independent world hash.
+pub fn combine(components: &[ComponentHash]) -> u64 {
+    let sum = components.iter().fold(0u64, |acc, c| acc.wrapping_add(c.hash));
+    finish(sum, components.len() as u64)
+}
+
+fn finish(sum: u64, count: u64) -> u64 {
+    let hash = fnv1a(FNV_OFFSET, &sum.to_le_bytes());
+    fnv1a(hash, &count.to_le_bytes())
+}
+
+/// Find the first `(external_id, component)` where two sorted breakdowns differ.
+pub fn first_divergence(local: &[ComponentHash], remote: &[ComponentHash]) -> Option<Divergence> {
+    let (mut i, mut j) = (0, 0);
+    while i < local.len() || j < remote.len() {
+        let key_l = local.get(i).map(|c| (c.external_id, c.component));
+        let key_r = remote.get(j).map(|c| (c.external_id, c.component));
+        match (key_l, key_r) {
+            (Some(l), Some(r)) if l == r => {
+                if local[i].hash != remote[j].hash {
+                    return Some(Divergence { external_id: l.0, component: l.1, in_local: true, in_remote: true });
+                }
+                i += 1;
+                j += 1;
+            }
+            (Some(l), r) if r.is_none_or(|r| l < r) => {
+                return Some(Divergence { external_id: l.0, component: l.1, in_local: true, in_remote: false });
+            }
+            (_, Some(r)) => {
+                return Some(Divergence { external_id: r.0, component: r.1, in_local: false, in_remote: true });
+            }
+            _ => unreachable!(),
+        }
+    }
+    None
+}
+
+/// Per-tick hash recorder with a bounded history.
+pub struct StateHasher {
+    mask: u32,
+    capacity: usize,
+    /// `(tick, hash)` in ascending tick order; `tick` is the fixed tick that produced the state.
+    history: VecDeque<(u64, u64)>,
+}
+
+impl StateHasher {
+    pub fn new(mask: u32, capacity: usize) -> Self {
+        Self {
+            mask,
+            capacity: capacity.max(1),
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Components included in each hash.
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+
+    /// Record the hash produced by `tick`, evicting the oldest entry if full.
+    pub fn record(&mut self, tick: u64, hash: u64) {
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back((tick, hash));
+    }
+
+    /// Hash recorded after `tick`, if still in history.
+    pub fn hash_for_tick(&self, tick: u64) -> Option<u64> {
+        let first = self.history.front()?.0;
+        let idx = tick.checked_sub(first)? as usize;
+        self.history.get(idx).filter(|&&(t, _)| t == tick).map(|&(_, h)| h)
+    }
+
+    /// Most recent `(tick, hash)`.
+    pub fn latest(&self) -> Option<(u64, u64)> {
+        self.history.back().copied()
+    }
+
+    /// Recorded `(tick, hash)` pairs, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.history.iter().copied()
+    }
+
+    /// Drop hashes for `tick` and later (they are re-recorded on resimulation).
+    pub fn discard_from(&mut self, tick: u64) {
+        while self.history.back().is_some_and(|&(t, _)| t >= tick) {
+            self.history.pop_back();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::*;
+
+    fn hash(world: &World) -> u64 {
+        #[cfg(feature = "physics-2d")]
+        let hash = world_hash(world, HASH_ALL, &crate::physics::PhysicsWorld::new());
+        #[cfg(not(feature = "physics-2d"))]
+        let hash = world_hash(world, HASH_ALL);
+        assert_eq!(hash, combine(&parts(world)));
+        hash
+    }
+
+    fn parts(world: &World) -> Vec<ComponentHash> {
+        #[cfg(feature = "physics-2d")]
+        return breakdown(world, HASH_ALL, &crate::physics::PhysicsWorld::new());
+        #[cfg(not(feature = "physics-2d"))]
+        breakdown(world, HASH_ALL)
+    }
+
+    #[test]
+    fn hash_is_independent_of_spawn_order() {
+        let mut a = World::new();
+        a.spawn((ExternalId(1), Transform2D::default(), Velocity(glam::Vec3::X)));
+        a.spawn((ExternalId(2), Position(glam::Vec3::ONE), Velocity::default()));
+
+        let mut b = World::new();
+        b.spawn((ExternalId(2), Position(glam::Vec3::ONE), Velocity::default()));
+        b.spawn((ExternalId(1), Transform2D::default(), Velocity(glam::Vec3::X)));
+
+        assert_eq!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    fn hash_changes_with_component_value() {
+        let mut world = World::new();
+        let e = world.spawn((ExternalId(0), Transform2D::default()));
+        let before = hash(&world);
+        world.get::<&mut Transform2D>(e).unwrap().x = f32::EPSILON;
+        assert_ne!(hash(&world), before);
+    }
+
+    #[test]
+    fn first_divergence_names_entity_and_component() {
+        let mut a = World::new();
+        a.spawn((ExternalId(3), Transform2D::default(), Velocity::default()));
+        a.spawn((ExternalId(5), Transform2D::default(), Velocity::default()));
+        let mut b = World::new();
+        b.spawn((ExternalId(3), Transform2D::default(), Velocity::default()));
+        b.spawn((ExternalId(5), Transform2D::default(), Velocity(glam::Vec3::Y)));
+
+        assert_eq!(first_divergence(&parts(&a), &parts(&a)), None);
+        let d = first_divergence(&parts(&a), &parts(&b)).unwrap();
+        assert_eq!((d.external_id, d.component), (5, COMPONENT_VELOCITY));
+        assert!(d.in_local && d.in_remote);
+
+        b.spawn((ExternalId(4), Velocity::default()));
+        let d = first_divergence(&parts(&a), &parts(&b)).unwrap();
+        assert_eq!((d.external_id, d.in_local, d.in_remote), (4, false, true));
+    }
+
+    #[test]
+    fn history_is_bounded_and_discardable() {
+        let mut hasher = StateHasher::new(HASH_ALL, 3);
+        for tick in 0..5 {
+            hasher.record(tick, tick * 10);
+        }
+        assert_eq!(hasher.hash_for_tick(1), None);
+        assert_eq!(hasher.hash_for_tick(3), Some(30));
+        assert_eq!(hasher.latest(), Some((4, 40)));
+
+        hasher.discard_from(3);
+        assert_eq!(hasher.latest(), Some((2, 20)));
+        assert_eq!(hasher.history().count(), 1);
+    }
+}
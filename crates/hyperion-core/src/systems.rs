@@ -2,21 +2,69 @@
 
 use std::collections::HashMap;
 
-use glam::Mat4;
-use hecs::World;
+use glam::{Mat4, Quat, Vec3};
+use hecs::{With, World};
 
-use crate::components::{Active, ModelMatrix, Parent, Position, Rotation, Scale, Transform2D, Velocity};
+use crate::components::{
+    Acceleration, Active, AngularDrag, AngularVelocity, LinearDrag, ModelMatrix, Parent, Position, Rotation, Scale,
+    Transform2D, Velocity,
+};
 
 #[cfg(feature = "physics-2d")]
 use crate::physics::PhysicsControlled;
 #[cfg(feature = "physics-2d")]
 use hecs::Without;
 
-/// Apply velocity to position. Runs once per fixed-timestep tick.
+/// Integrate 3D motion with semi-implicit Euler. Runs once per fixed-timestep tick.
+///
+/// Velocities are stepped first (`Acceleration`, then drag), and positions and
+/// rotations advance with the new velocities. Inactive entities (no `Active`
+/// marker) are frozen.
 pub fn velocity_system(world: &mut World, dt: f32) {
-    for (pos, vel) in world.query_mut::<(&mut Position, &Velocity)>() {
+    for (pos, vel, acc, drag) in world.query_mut::<With<
+        (&mut Position, &mut Velocity, Option<&Acceleration>, Option<&LinearDrag>),
+        &Active,
+    >>() {
+        vel.0 = step_linear(vel.0, acc, drag, dt);
         pos.0 += vel.0 * dt;
     }
+    for (rot, ang, drag) in
+        world.query_mut::<With<(&mut Rotation, &mut AngularVelocity, Option<&AngularDrag>), &Active>>()
+    {
+        ang.0 = step_angular(ang.0, drag, dt);
+        rot.0 = rotate_3d(rot.0, ang.0, dt);
+    }
+}
+
+/// Semi-implicit Euler velocity step: add acceleration, then apply drag.
+#[inline]
+fn step_linear(v: Vec3, acc: Option<&Acceleration>, drag: Option<&LinearDrag>, dt: f32) -> Vec3 {
+    let v = match acc {
+        Some(a) => v + a.0 * dt,
+        None => v,
+    };
+    match drag {
+        Some(d) => v / (1.0 + d.0 * dt),
+        None => v,
+    }
+}
+
+/// Apply angular drag to an angular velocity.
+#[inline]
+fn step_angular(w: Vec3, drag: Option<&AngularDrag>, dt: f32) -> Vec3 {
+    match drag {
+        Some(d) => w / (1.0 + d.0 * dt),
+        None => w,
+    }
+}
+
+/// Rotate `rot` by angular velocity `w` over `dt` (world-space axis).
+#[inline]
+fn rotate_3d(rot: Quat, w: Vec3, dt: f32) -> Quat {
+    if w == Vec3::ZERO {
+        return rot;
+    }
+    (Quat::from_scaled_axis(w * dt) * rot).normalize()
 }
 
 /// Recompute model matrices from Position, Rotation, Scale.
@@ -30,14 +78,24 @@ pub fn transform_system(world: &mut World) {
     }
 }
 
-/// Apply velocity to 2D entities (hot path).
-/// Only modifies x/y; vel.0.z is ignored for 2D entities.
+/// Integrate 2D motion with semi-implicit Euler (hot path).
+/// Only x/y and the z spin are used; `vel.0.z` is ignored for 2D entities.
 pub fn velocity_system_2d(world: &mut World, dt: f32) {
-    for (transform, vel) in world.query_mut::<(&mut Transform2D, &Velocity)>() {
+    for (transform, vel, acc, drag) in world.query_mut::<With<
+        (&mut Transform2D, &mut Velocity, Option<&Acceleration>, Option<&LinearDrag>),
+        &Active,
+    >>() {
+        vel.0 = step_linear(vel.0, acc, drag, dt);
         transform.x += vel.0.x * dt;
         transform.y += vel.0.y * dt;
         // vel.0.z ignored for 2D entities
     }
+    for (transform, ang, drag) in
+        world.query_mut::<With<(&mut Transform2D, &mut AngularVelocity, Option<&AngularDrag>), &Active>>()
+    {
+        ang.0 = step_angular(ang.0, drag, dt);
+        transform.rot += ang.0.z * dt;
+    }
 }
 
 /// Build ModelMatrix from Transform2D (hot path).
@@ -65,25 +123,63 @@ pub fn transform_system_2d(world: &mut World) {
     }
 }
 
-/// Apply velocity to position, EXCLUDING PhysicsControlled entities.
+/// Column-major 4×4 matrix for a 2D transform, same layout as `transform_system_2d`.
+pub fn matrix_2d(x: f32, y: f32, rot: f32, sx: f32, sy: f32) -> [f32; 16] {
+    let (sin, cos) = rot.sin_cos();
+    [
+        sx * cos, sx * sin, 0.0, 0.0,
+        -sy * sin, sy * cos, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        x, y, 0.0, 1.0,
+    ]
+}
+
+/// `velocity_system`, EXCLUDING PhysicsControlled entities.
 /// Used when physics-2d feature is enabled — Rapier drives those entities.
 #[cfg(feature = "physics-2d")]
 pub fn velocity_system_filtered(world: &mut World, dt: f32) {
-    for (pos, vel) in world.query_mut::<Without<(&mut Position, &Velocity), &PhysicsControlled>>()
-    {
+    for (pos, vel, acc, drag) in world.query_mut::<Without<
+        With<(&mut Position, &mut Velocity, Option<&Acceleration>, Option<&LinearDrag>), &Active>,
+        &PhysicsControlled,
+    >>() {
+        vel.0 = step_linear(vel.0, acc, drag, dt);
         pos.0 += vel.0 * dt;
     }
+    for (rot, ang, drag) in world.query_mut::<Without<
+        With<(&mut Rotation, &mut AngularVelocity, Option<&AngularDrag>), &Active>,
+        &PhysicsControlled,
+    >>() {
+        ang.0 = step_angular(ang.0, drag, dt);
+        rot.0 = rotate_3d(rot.0, ang.0, dt);
+    }
 }
 
-/// Apply velocity to 2D entities, EXCLUDING PhysicsControlled entities.
+/// `velocity_system_2d`, EXCLUDING PhysicsControlled entities.
 #[cfg(feature = "physics-2d")]
 pub fn velocity_system_2d_filtered(world: &mut World, dt: f32) {
-    for (transform, vel) in
-        world.query_mut::<Without<(&mut Transform2D, &Velocity), &PhysicsControlled>>()
-    {
+    for (transform, vel, acc, drag) in world.query_mut::<Without<
+        With<(&mut Transform2D, &mut Velocity, Option<&Acceleration>, Option<&LinearDrag>), &Active>,
+        &PhysicsControlled,
+    >>() {
+        vel.0 = step_linear(vel.0, acc, drag, dt);
         transform.x += vel.0.x * dt;
         transform.y += vel.0.y * dt;
     }
+    for (transform, ang, drag) in world.query_mut::<Without<
+        With<(&mut Transform2D, &mut AngularVelocity, Option<&AngularDrag>), &Active>,
+        &PhysicsControlled,
+    >>() {
+        ang.0 = step_angular(ang.0, drag, dt);
+        transform.rot += ang.0.z * dt;
+    }
+}
+
+/// Whether an entity's transform changes over a tick: non-zero linear or
+/// angular velocity, or a non-zero acceleration.
+pub fn is_moving(vel: &Velocity, ang: Option<&AngularVelocity>, acc: Option<&Acceleration>) -> bool {
+    vel.0 != Vec3::ZERO
+        || ang.is_some_and(|a| a.0 != Vec3::ZERO)
+        || acc.is_some_and(|a| a.0 != Vec3::ZERO)
 }
 
 /// Count active entities. Useful for debug overlay.
@@ -121,6 +217,318 @@ pub fn propagate_transforms(world: &mut World, ext_to_entity: &HashMap<u32, hecs
     }
 }
 
+// ── Scheduler ──────────────────────────────────────────────────
+
+/// Execution stage of a scheduled system.
+///
+/// `PreTick`, `Tick` and `PostTick` run once per fixed tick, in that order.
+/// `PreRender` runs once per frame after all ticks, before GPU data is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    PreTick = 0,
+    Tick = 1,
+    PostTick = 2,
+    PreRender = 3,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [Stage::PreTick, Stage::Tick, Stage::PostTick, Stage::PreRender];
+
+    pub fn from_u8(v: u8) -> Option<Self> {
+        Self::ALL.get(v as usize).copied()
+    }
+}
+
+/// Per-call data passed to custom systems.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemContext {
+    /// Fixed timestep in tick stages; frame time in `PreRender`.
+    pub dt: f32,
+    /// Current tick (the tick being simulated in tick stages).
+    pub tick: u64,
+}
+
+/// A user-registered system.
+pub type SystemFn = Box<dyn FnMut(&mut World, &SystemContext)>;
+
+/// Engine systems that need state beyond the `World` (physics, render state,
+/// entity map). The engine runs these itself when the scheduler reaches them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinSystem {
+    PhysicsSyncPre,
+    PhysicsStep,
+    Velocity,
+    Tween,
+    SpriteAnimation,
+    Lifetime,
+    Particles,
+    Camera,
+    Listener,
+    PhysicsSyncPost,
+    Transform,
+    Propagate,
+    ParticleExport,
+    CameraExport,
+    MarkDirty,
+    Collect,
+}
+
+pub enum SystemKind {
+    Builtin(BuiltinSystem),
+    Custom(SystemFn),
+    /// System `system` of the engine's `plugin`-th plugin.
+    Plugin { plugin: usize, system: u32 },
+}
+
+/// Timing counters for one system. Times are in milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SystemTiming {
+    pub runs: u64,
+    pub last_ms: f64,
+    pub total_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleError {
+    DuplicateName,
+    UnknownSystem,
+    /// Ordering constraints only apply within a stage.
+    DifferentStages,
+    /// The constraint would make the stage's order cyclic.
+    Cycle,
+}
+
+struct SystemEntry {
+    name: String,
+    stage: Stage,
+    kind: SystemKind,
+    enabled: bool,
+    timing: SystemTiming,
+}
+
+/// Ordered, toggleable list of systems per stage.
+///
+/// Within a stage, systems run in registration order unless an ordering
+/// constraint says otherwise.
+#[derive(Default)]
+pub struct Scheduler {
+    systems: Vec<SystemEntry>,
+    /// `(first, then)` index pairs: `first` runs before `then`.
+    constraints: Vec<(usize, usize)>,
+    /// Execution order per stage, indexed by `Stage as usize`.
+    order: [Vec<usize>; 4],
+}
+
+impl Scheduler {
+    /// A scheduler with the engine's built-in systems in their default order.
+    pub fn with_builtins() -> Self {
+        let mut scheduler = Self::default();
+        let builtins: &[(&str, Stage, BuiltinSystem)] = &[
+            #[cfg(feature = "physics-2d")]
+            ("physics_sync_pre", Stage::PreTick, BuiltinSystem::PhysicsSyncPre),
+            #[cfg(feature = "physics-2d")]
+            ("physics_step", Stage::Tick, BuiltinSystem::PhysicsStep),
+            ("velocity", Stage::Tick, BuiltinSystem::Velocity),
+            ("tween", Stage::Tick, BuiltinSystem::Tween),
+            ("sprite_animation", Stage::Tick, BuiltinSystem::SpriteAnimation),
+            ("lifetime", Stage::Tick, BuiltinSystem::Lifetime),
+            ("particles", Stage::Tick, BuiltinSystem::Particles),
+            ("camera", Stage::Tick, BuiltinSystem::Camera),
+            ("listener", Stage::Tick, BuiltinSystem::Listener),
+            #[cfg(feature = "physics-2d")]
+            ("physics_sync_post", Stage::PreRender, BuiltinSystem::PhysicsSyncPost),
+            ("transform", Stage::PreRender, BuiltinSystem::Transform),
+            ("propagate", Stage::PreRender, BuiltinSystem::Propagate),
+            ("particles_export", Stage::PreRender, BuiltinSystem::ParticleExport),
+            ("camera_export", Stage::PreRender, BuiltinSystem::CameraExport),
+            ("mark_dirty", Stage::PreRender, BuiltinSystem::MarkDirty),
+            ("collect", Stage::PreRender, BuiltinSystem::Collect),
+        ];
+        for &(name, stage, builtin) in builtins {
+            let _ = scheduler.insert(name, stage, SystemKind::Builtin(builtin));
+        }
+        scheduler
+    }
+
+    /// Register a custom system at the end of `stage`.
+    pub fn add_system(
+        &mut self,
+        name: &str,
+        stage: Stage,
+        system: impl FnMut(&mut World, &SystemContext) + 'static,
+    ) -> Result<(), ScheduleError> {
+        self.insert(name, stage, SystemKind::Custom(Box::new(system)))
+    }
+
+    /// Register a plugin system at the end of `stage`.
+    pub(crate) fn add_plugin_system(
+        &mut self,
+        name: &str,
+        stage: Stage,
+        plugin: usize,
+        system: u32,
+    ) -> Result<(), ScheduleError> {
+        self.insert(name, stage, SystemKind::Plugin { plugin, system })
+    }
+
+    fn insert(&mut self, name: &str, stage: Stage, kind: SystemKind) -> Result<(), ScheduleError> {
+        if self.index_of(name).is_some() {
+            return Err(ScheduleError::DuplicateName);
+        }
+        self.systems.push(SystemEntry {
+            name: name.to_owned(),
+            stage,
+            kind,
+            enabled: true,
+            timing: SystemTiming::default(),
+        });
+        self.rebuild_order(stage);
+        Ok(())
+    }
+
+    /// Remove a system (built-in or custom). Returns `false` if unknown.
+    pub fn remove_system(&mut self, name: &str) -> bool {
+        let Some(idx) = self.index_of(name) else {
+            return false;
+        };
+        self.systems.remove(idx);
+        self.constraints.retain(|&(a, b)| a != idx && b != idx);
+        for (a, b) in &mut self.constraints {
+            *a -= (*a > idx) as usize;
+            *b -= (*b > idx) as usize;
+        }
+        for stage in Stage::ALL {
+            self.rebuild_order(stage);
+        }
+        true
+    }
+
+    /// Require `first` to run before `then`. Both must be in the same stage.
+    pub fn add_ordering(&mut self, first: &str, then: &str) -> Result<(), ScheduleError> {
+        let a = self.index_of(first).ok_or(ScheduleError::UnknownSystem)?;
+        let b = self.index_of(then).ok_or(ScheduleError::UnknownSystem)?;
+        let stage = self.systems[a].stage;
+        if stage != self.systems[b].stage {
+            return Err(ScheduleError::DifferentStages);
+        }
+        if a == b {
+            return Err(ScheduleError::Cycle);
+        }
+        self.constraints.push((a, b));
+        if !self.rebuild_order(stage) {
+            self.constraints.pop();
+            self.rebuild_order(stage);
+            return Err(ScheduleError::Cycle);
+        }
+        Ok(())
+    }
+
+    /// Enable or disable a system. Returns `false` if unknown.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.index_of(name) {
+            Some(idx) => {
+                self.systems[idx].enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        self.index_of(name).map(|idx| self.systems[idx].enabled)
+    }
+
+    pub fn timing(&self, name: &str) -> Option<SystemTiming> {
+        self.index_of(name).map(|idx| self.systems[idx].timing)
+    }
+
+    pub fn reset_timings(&mut self) {
+        for entry in &mut self.systems {
+            entry.timing = SystemTiming::default();
+        }
+    }
+
+    /// System names of `stage` in execution order.
+    pub fn stage_order(&self, stage: Stage) -> impl Iterator<Item = &str> + '_ {
+        self.order[stage as usize].iter().map(|&idx| self.systems[idx].name.as_str())
+    }
+
+    /// Number of systems scheduled in `stage` (enabled or not).
+    pub fn stage_len(&self, stage: Stage) -> usize {
+        self.order[stage as usize].len()
+    }
+
+    /// The `pos`-th system of `stage` in execution order, if enabled.
+    pub fn enabled_at(&mut self, stage: Stage, pos: usize) -> Option<&mut SystemKind> {
+        let idx = self.order[stage as usize][pos];
+        let entry = &mut self.systems[idx];
+        entry.enabled.then_some(&mut entry.kind)
+    }
+
+    /// Add one run of `elapsed_ms` to the `pos`-th system of `stage`.
+    pub fn record_timing(&mut self, stage: Stage, pos: usize, elapsed_ms: f64) {
+        let idx = self.order[stage as usize][pos];
+        let timing = &mut self.systems[idx].timing;
+        timing.runs += 1;
+        timing.last_ms = elapsed_ms;
+        timing.total_ms += elapsed_ms;
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.systems.iter().position(|e| e.name == name)
+    }
+
+    /// Topologically sort `stage`, preferring registration order among ready
+    /// systems. Returns `false` (leaving the old order) if constraints are cyclic.
+    fn rebuild_order(&mut self, stage: Stage) -> bool {
+        let members: Vec<usize> = (0..self.systems.len())
+            .filter(|&i| self.systems[i].stage == stage)
+            .collect();
+        let mut indegree = vec![0usize; self.systems.len()];
+        for &(_, b) in &self.constraints {
+            indegree[b] += 1;
+        }
+        let mut order = Vec::with_capacity(members.len());
+        let mut placed = vec![false; self.systems.len()];
+        while order.len() < members.len() {
+            let Some(&next) = members.iter().find(|&&i| !placed[i] && indegree[i] == 0) else {
+                return false;
+            };
+            placed[next] = true;
+            order.push(next);
+            for &(a, b) in &self.constraints {
+                if a == next {
+                    indegree[b] -= 1;
+                }
+            }
+        }
+        self.order[stage as usize] = order;
+        true
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+/// Monotonic clock in milliseconds for system timing.
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> f64 {
+    performance_now()
+}
+
+/// Monotonic clock in milliseconds for system timing.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,6 +546,61 @@ mod tests {
         ))
     }
 
+    fn noop(_: &mut World, _: &SystemContext) {}
+
+    #[test]
+    fn scheduler_orders_by_registration_and_constraints() {
+        let mut scheduler = Scheduler::default();
+        scheduler.add_system("a", Stage::Tick, noop).unwrap();
+        scheduler.add_system("b", Stage::Tick, noop).unwrap();
+        scheduler.add_system("c", Stage::Tick, noop).unwrap();
+        assert_eq!(scheduler.stage_order(Stage::Tick).collect::<Vec<_>>(), ["a", "b", "c"]);
+
+        scheduler.add_ordering("c", "a").unwrap();
+        assert_eq!(scheduler.stage_order(Stage::Tick).collect::<Vec<_>>(), ["b", "c", "a"]);
+
+        assert_eq!(scheduler.add_ordering("a", "c"), Err(ScheduleError::Cycle));
+        assert_eq!(scheduler.stage_order(Stage::Tick).collect::<Vec<_>>(), ["b", "c", "a"]);
+    }
+
+    #[test]
+    fn scheduler_rejects_bad_registrations() {
+        let mut scheduler = Scheduler::default();
+        scheduler.add_system("a", Stage::Tick, noop).unwrap();
+        scheduler.add_system("r", Stage::PreRender, noop).unwrap();
+        assert_eq!(scheduler.add_system("a", Stage::PostTick, noop), Err(ScheduleError::DuplicateName));
+        assert_eq!(scheduler.add_ordering("a", "missing"), Err(ScheduleError::UnknownSystem));
+        assert_eq!(scheduler.add_ordering("a", "r"), Err(ScheduleError::DifferentStages));
+    }
+
+    #[test]
+    fn scheduler_remove_keeps_remaining_constraints() {
+        let mut scheduler = Scheduler::default();
+        scheduler.add_system("a", Stage::Tick, noop).unwrap();
+        scheduler.add_system("b", Stage::Tick, noop).unwrap();
+        scheduler.add_system("c", Stage::Tick, noop).unwrap();
+        scheduler.add_ordering("c", "b").unwrap();
+        assert!(scheduler.remove_system("a"));
+        assert_eq!(scheduler.stage_order(Stage::Tick).collect::<Vec<_>>(), ["c", "b"]);
+        assert!(!scheduler.remove_system("a"));
+    }
+
+    #[test]
+    fn scheduler_toggle_and_timing() {
+        let mut scheduler = Scheduler::with_builtins();
+        assert_eq!(scheduler.is_enabled("velocity"), Some(true));
+        assert!(scheduler.set_enabled("velocity", false));
+        let pos = scheduler.stage_order(Stage::Tick).position(|n| n == "velocity").unwrap();
+        assert!(scheduler.enabled_at(Stage::Tick, pos).is_none());
+
+        scheduler.record_timing(Stage::Tick, pos, 2.0);
+        scheduler.record_timing(Stage::Tick, pos, 1.0);
+        let t = scheduler.timing("velocity").unwrap();
+        assert_eq!((t.runs, t.last_ms, t.total_ms), (2, 1.0, 3.0));
+        scheduler.reset_timings();
+        assert_eq!(scheduler.timing("velocity").unwrap().runs, 0);
+    }
+
     #[test]
     fn velocity_moves_position() {
         let mut world = World::new();
@@ -298,14 +761,84 @@ mod tests {
         assert!((matrix.0[12] - 5.0).abs() < 0.001);
     }
 
+    #[test]
+    fn velocity_system_skips_inactive_entities() {
+        let mut world = World::new();
+        let frozen = world.spawn((Position(Vec3::ZERO), Velocity(Vec3::X)));
+        let moving = world.spawn((Position(Vec3::ZERO), Velocity(Vec3::X), Active));
+        velocity_system(&mut world, 1.0);
+        assert_eq!(world.get::<&Position>(frozen).unwrap().0, Vec3::ZERO);
+        assert_eq!(world.get::<&Position>(moving).unwrap().0, Vec3::X);
+    }
+
+    #[test]
+    fn velocity_system_is_semi_implicit_euler() {
+        let mut world = World::new();
+        let e = world.spawn((Position(Vec3::ZERO), Velocity(Vec3::ZERO), Acceleration(Vec3::X * 10.0), Active));
+        velocity_system(&mut world, 0.5);
+        // Velocity is updated first, so the position already moves this tick.
+        assert_eq!(world.get::<&Velocity>(e).unwrap().0, Vec3::X * 5.0);
+        assert_eq!(world.get::<&Position>(e).unwrap().0, Vec3::X * 2.5);
+    }
+
+    #[test]
+    fn drag_decays_linear_and_angular_velocity() {
+        let mut world = World::new();
+        let e = world.spawn((
+            Position(Vec3::ZERO),
+            Rotation(Quat::IDENTITY),
+            Velocity(Vec3::X * 3.0),
+            AngularVelocity(Vec3::Z * 3.0),
+            LinearDrag(2.0),
+            AngularDrag(4.0),
+            Active,
+        ));
+        velocity_system(&mut world, 0.5);
+        assert!((world.get::<&Velocity>(e).unwrap().0.x - 1.5).abs() < 1e-6);
+        assert!((world.get::<&AngularVelocity>(e).unwrap().0.z - 1.0).abs() < 1e-6);
+        assert!((world.get::<&Position>(e).unwrap().0.x - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn angular_velocity_rotates_3d_entities() {
+        let mut world = World::new();
+        let e = world.spawn((
+            Position(Vec3::ZERO),
+            Rotation(Quat::IDENTITY),
+            Velocity(Vec3::ZERO),
+            AngularVelocity(Vec3::Z * std::f32::consts::PI),
+            Active,
+        ));
+        velocity_system(&mut world, 0.5);
+        let rot = world.get::<&Rotation>(e).unwrap().0;
+        assert!(rot.abs_diff_eq(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2), 1e-5));
+    }
+
     // ── 2D system tests ──────────────────────────────────────────────
 
+    #[test]
+    fn velocity_system_2d_spins_and_accelerates() {
+        let mut world = World::new();
+        let e = world.spawn((
+            Transform2D { x: 0.0, y: 0.0, rot: 0.0, sx: 1.0, sy: 1.0 },
+            Velocity(Vec3::ZERO),
+            Acceleration(Vec3::new(0.0, -4.0, 0.0)),
+            AngularVelocity(Vec3::new(9.0, 9.0, 2.0)), // only z spins 2D entities
+            Active,
+        ));
+        velocity_system_2d(&mut world, 0.5);
+        let t = world.get::<&Transform2D>(e).unwrap();
+        assert!((t.y + 1.0).abs() < 1e-6);
+        assert!((t.rot - 1.0).abs() < 1e-6);
+    }
+
     #[test]
     fn velocity_system_2d_updates_transform2d() {
         let mut world = World::new();
         let e = world.spawn((
             Transform2D { x: 0.0, y: 0.0, rot: 0.0, sx: 1.0, sy: 1.0 },
             Velocity(Vec3::new(10.0, 20.0, 5.0)), // z=5 should be ignored
+            Active,
         ));
         velocity_system_2d(&mut world, 0.5);
         let t = world.get::<&Transform2D>(e).unwrap();
@@ -385,12 +918,14 @@ mod tests {
             world.spawn((
                 Position(Vec3::ZERO),
                 Velocity(Vec3::new(60.0, 0.0, 0.0)),
+                Active,
             ));
             // Physics-controlled entity — should NOT move
             let phys = world.spawn((
                 Position(Vec3::ZERO),
                 Velocity(Vec3::new(60.0, 0.0, 0.0)),
                 PhysicsControlled,
+                Active,
             ));
 
             velocity_system_filtered(&mut world, 1.0 / 60.0);
@@ -406,12 +941,14 @@ mod tests {
             world.spawn((
                 Transform2D { x: 0.0, y: 0.0, rot: 0.0, sx: 1.0, sy: 1.0 },
                 Velocity(Vec3::new(60.0, 120.0, 0.0)),
+                Active,
             ));
             // Physics-controlled 2D entity — should NOT move
             let phys = world.spawn((
                 Transform2D { x: 0.0, y: 0.0, rot: 0.0, sx: 1.0, sy: 1.0 },
                 Velocity(Vec3::new(60.0, 120.0, 0.0)),
                 PhysicsControlled,
+                Active,
             ));
 
             velocity_system_2d_filtered(&mut world, 1.0 / 60.0);
@@ -0,0 +1,524 @@
+//! Engine-side tweens: scalar channels animated over fixed ticks.
+//!
+//! A tween animates one scalar channel of an entity (`Position.y`,
+//! `Transform2D.rot`, a `PrimitiveParams` slot, ...) from `from` to `to` over
+//! `duration` ticks with an easing curve. An entity can run several tweens in
+//! parallel: each `StartTween` opens a new track, and tweens flagged
+//! `TWEEN_SEQUENCE` are appended to the entity's last track instead, starting
+//! when the one before them completes.
+//!
+//! Tweens run in the `Tick` stage, after velocity integration, so a tweened
+//! channel wins over velocity for that tick. Physics-controlled entities are
+//! driven by Rapier and should not be tweened on their transform.
+
+use std::collections::VecDeque;
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Quat, Vec3};
+use hecs::{With, World};
+
+use crate::components::{Active, Depth, ExternalId, Position, PrimitiveParams, Rotation, Scale, Transform2D};
+use crate::events::{EVENT_TWEEN_COMPLETE, EVENT_TWEEN_LOOP, EngineEvent};
+use crate::render_state::RenderState;
+
+/// `Transform2D` field: 0=x, 1=y, 2=rot, 3=sx, 4=sy.
+pub const TARGET_TRANSFORM_2D: u8 = 0;
+/// `Position` axis: 0=x, 1=y, 2=z.
+pub const TARGET_POSITION: u8 = 1;
+/// `Scale` axis: 0=x, 1=y, 2=z.
+pub const TARGET_SCALE: u8 = 2;
+/// `Rotation`, set to the tweened angle (radians) about axis `field` (0=x, 1=y, 2=z).
+pub const TARGET_ROTATION: u8 = 3;
+/// `PrimitiveParams` slot 0–7.
+pub const TARGET_PRIM_PARAMS: u8 = 4;
+/// `Depth` (field ignored). Inserted if missing.
+pub const TARGET_DEPTH: u8 = 5;
+
+/// `StopTweens` target matching every tween.
+pub const TARGET_ALL: u8 = 0xFF;
+
+/// Restart from the beginning after each cycle, forever.
+pub const TWEEN_LOOP: u8 = 1 << 0;
+/// Play forward then backward in each cycle (cycle = 2 × duration).
+pub const TWEEN_PING_PONG: u8 = 1 << 1;
+/// Queue behind the entity's most recent track instead of starting a new one.
+pub const TWEEN_SEQUENCE: u8 = 1 << 2;
+/// Ignore `from` and start from the channel's value when the tween starts.
+pub const TWEEN_FROM_CURRENT: u8 = 1 << 3;
+
+/// Easing curves, by `StartTween` easing byte.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear = 0,
+    QuadIn = 1,
+    QuadOut = 2,
+    QuadInOut = 3,
+    CubicIn = 4,
+    CubicOut = 5,
+    CubicInOut = 6,
+    SineIn = 7,
+    SineOut = 8,
+    SineInOut = 9,
+    ExpoIn = 10,
+    ExpoOut = 11,
+    BackIn = 12,
+    BackOut = 13,
+    ElasticOut = 14,
+    BounceOut = 15,
+}
+
+impl Easing {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        use Easing::*;
+        const ALL: [Easing; 16] = [
+            Linear, QuadIn, QuadOut, QuadInOut, CubicIn, CubicOut, CubicInOut, SineIn, SineOut, SineInOut, ExpoIn,
+            ExpoOut, BackIn, BackOut, ElasticOut, BounceOut,
+        ];
+        ALL.get(v as usize).copied()
+    }
+
+    /// Map linear progress `t` in [0, 1] to eased progress. `apply(0) == 0`
+    /// and `apply(1) == 1`; back and elastic curves overshoot in between.
+    pub fn apply(self, t: f32) -> f32 {
+        use std::f32::consts::{FRAC_PI_2, PI, TAU};
+        const BACK: f32 = 1.70158;
+        match self {
+            Self::Linear => t,
+            Self::QuadIn => t * t,
+            Self::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::QuadInOut => {
+                if t < 0.5 { 2.0 * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0 }
+            }
+            Self::CubicIn => t * t * t,
+            Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Self::CubicInOut => {
+                if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0 }
+            }
+            Self::SineIn => 1.0 - (t * FRAC_PI_2).cos(),
+            Self::SineOut => (t * FRAC_PI_2).sin(),
+            Self::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Self::ExpoIn => {
+                if t <= 0.0 { 0.0 } else { 2f32.powf(10.0 * t - 10.0) }
+            }
+            Self::ExpoOut => {
+                if t >= 1.0 { 1.0 } else { 1.0 - 2f32.powf(-10.0 * t) }
+            }
+            Self::BackIn => (BACK + 1.0) * t * t * t - BACK * t * t,
+            Self::BackOut => 1.0 + (BACK + 1.0) * (t - 1.0).powi(3) + BACK * (t - 1.0).powi(2),
+            Self::ElasticOut => {
+                if t <= 0.0 || t >= 1.0 {
+                    t.clamp(0.0, 1.0)
+                } else {
+                    2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (TAU / 3.0)).sin() + 1.0
+                }
+            }
+            Self::BounceOut => {
+                const N: f32 = 7.5625;
+                const D: f32 = 2.75;
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+        }
+    }
+}
+
+/// One tween. Plain data (20 bytes) so tweens snapshot byte-for-byte.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct Tween {
+    /// `TARGET_*`.
+    pub target: u8,
+    pub field: u8,
+    /// `Easing` discriminant.
+    pub easing: u8,
+    /// `TWEEN_*` bits.
+    pub flags: u8,
+    pub from: f32,
+    pub to: f32,
+    /// Ticks per direction.
+    pub duration: u16,
+    /// Ticks left before the tween starts.
+    pub delay: u16,
+    /// Ticks into the current cycle.
+    pub elapsed: u16,
+    /// 1 once the delay has passed and `from` is resolved.
+    pub started: u8,
+    pub _pad: u8,
+}
+
+impl Tween {
+    /// Decode a `StartTween` payload:
+    /// `[target u8][field u8][easing u8][flags u8][from f32][to f32][duration u16][delay u16]`.
+    /// Returns `None` for unknown targets, fields or easings.
+    pub fn from_payload(p: &[u8; 16]) -> Option<Self> {
+        let tween = Self {
+            target: p[0],
+            field: p[1],
+            easing: p[2],
+            flags: p[3],
+            from: f32::from_le_bytes(p[4..8].try_into().unwrap()),
+            to: f32::from_le_bytes(p[8..12].try_into().unwrap()),
+            duration: u16::from_le_bytes([p[12], p[13]]),
+            delay: u16::from_le_bytes([p[14], p[15]]),
+            elapsed: 0,
+            started: 0,
+            _pad: 0,
+        };
+        let field_count = match tween.target {
+            TARGET_TRANSFORM_2D => 5,
+            TARGET_POSITION | TARGET_SCALE | TARGET_ROTATION => 3,
+            TARGET_PRIM_PARAMS => 8,
+            TARGET_DEPTH => u8::MAX,
+            _ => return None,
+        };
+        (tween.field < field_count && Easing::from_u8(tween.easing).is_some()).then_some(tween)
+    }
+
+    /// Event `data` identifying this tween's channel.
+    pub fn channel(&self) -> u32 {
+        self.target as u32 | (self.field as u32) << 8
+    }
+
+    fn matches(&self, target: u8, field: u8) -> bool {
+        target == TARGET_ALL || (self.target == target && self.field == field)
+    }
+
+    /// Ticks per cycle (never 0, so zero-length tweens finish in one tick).
+    fn cycle_ticks(&self) -> u16 {
+        let duration = self.duration.max(1);
+        if self.flags & TWEEN_PING_PONG != 0 { duration.saturating_mul(2) } else { duration }
+    }
+
+    /// Channel value at the current `elapsed`.
+    fn value(&self) -> f32 {
+        let duration = self.duration.max(1) as f32;
+        let elapsed = self.elapsed as f32;
+        let t = if self.flags & TWEEN_PING_PONG != 0 && elapsed > duration {
+            (2.0 * duration - elapsed) / duration
+        } else {
+            (elapsed / duration).min(1.0)
+        };
+        let eased = Easing::from_u8(self.easing).unwrap_or(Easing::Linear).apply(t);
+        self.from + (self.to - self.from) * eased
+    }
+}
+
+/// Active tweens of an entity: parallel tracks, each a queue run front-first.
+/// Removed once every track has finished.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tweens {
+    pub tracks: Vec<VecDeque<Tween>>,
+}
+
+impl Tweens {
+    /// Add a tween: appended to the last track for `TWEEN_SEQUENCE`, else on a new track.
+    pub fn push(&mut self, tween: Tween) {
+        match self.tracks.last_mut() {
+            Some(track) if tween.flags & TWEEN_SEQUENCE != 0 => track.push_back(tween),
+            _ => self.tracks.push(VecDeque::from([tween])),
+        }
+    }
+
+    /// Drop every queued tween on `target`/`field` (`TARGET_ALL` = all).
+    pub fn stop(&mut self, target: u8, field: u8) {
+        for track in &mut self.tracks {
+            track.retain(|t| !t.matches(target, field));
+        }
+        self.tracks.retain(|track| !track.is_empty());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Snapshot encoding: `[track_count u8]` then per track `[len u8][len × Tween]`.
+    pub fn write_bytes(&self, out: &mut Vec<u8>) {
+        let tracks = &self.tracks[..self.tracks.len().min(u8::MAX as usize)];
+        out.push(tracks.len() as u8);
+        for track in tracks {
+            let len = track.len().min(u8::MAX as usize);
+            out.push(len as u8);
+            for tween in track.iter().take(len) {
+                out.extend_from_slice(bytemuck::bytes_of(tween));
+            }
+        }
+    }
+
+    /// Decode `write_bytes` output at `*cursor`, advancing it. `None` if truncated.
+    pub fn read_bytes(data: &[u8], cursor: &mut usize) -> Option<Self> {
+        let size = size_of::<Tween>();
+        let mut tweens = Self::default();
+        let track_count = *data.get(*cursor)?;
+        *cursor += 1;
+        for _ in 0..track_count {
+            let len = *data.get(*cursor)? as usize;
+            *cursor += 1;
+            let bytes = data.get(*cursor..*cursor + len * size)?;
+            *cursor += len * size;
+            tweens
+                .tracks
+                .push(bytes.chunks_exact(size).map(bytemuck::pod_read_unaligned::<Tween>).collect());
+        }
+        Some(tweens)
+    }
+}
+
+/// Current value of a tween channel, if the entity has it.
+fn read_channel(world: &World, entity: hecs::Entity, target: u8, field: u8) -> Option<f32> {
+    let f = field as usize;
+    match target {
+        TARGET_TRANSFORM_2D => world.get::<&Transform2D>(entity).ok().map(|t| [t.x, t.y, t.rot, t.sx, t.sy][f]),
+        TARGET_POSITION => world.get::<&Position>(entity).ok().map(|p| p.0[f]),
+        TARGET_SCALE => world.get::<&Scale>(entity).ok().map(|s| s.0[f]),
+        TARGET_ROTATION => world.get::<&Rotation>(entity).ok().map(|r| {
+            let (axis, angle) = r.0.to_axis_angle();
+            // Signed angle about the tweened axis.
+            angle * axis[f]
+        }),
+        TARGET_PRIM_PARAMS => world.get::<&PrimitiveParams>(entity).ok().map(|p| p.0[f]),
+        TARGET_DEPTH => Some(world.get::<&Depth>(entity).map_or(0.0, |d| d.0)),
+        _ => None,
+    }
+}
+
+/// Write a tween channel and mark the slot data it feeds dirty.
+fn write_channel(
+    world: &mut World,
+    render_state: &mut RenderState,
+    entity: hecs::Entity,
+    target: u8,
+    field: u8,
+    value: f32,
+) {
+    let f = field as usize;
+    let transform = match target {
+        TARGET_TRANSFORM_2D => {
+            if let Ok(mut t) = world.get::<&mut Transform2D>(entity) {
+                match f {
+                    0 => t.x = value,
+                    1 => t.y = value,
+                    2 => t.rot = value,
+                    3 => t.sx = value,
+                    _ => t.sy = value,
+                }
+            }
+            true
+        }
+        TARGET_POSITION => {
+            if let Ok(mut p) = world.get::<&mut Position>(entity) {
+                p.0[f] = value;
+            }
+            true
+        }
+        TARGET_SCALE => {
+            if let Ok(mut s) = world.get::<&mut Scale>(entity) {
+                s.0[f] = value;
+            }
+            true
+        }
+        TARGET_ROTATION => {
+            if let Ok(mut r) = world.get::<&mut Rotation>(entity) {
+                let mut axis = Vec3::ZERO;
+                axis[f] = 1.0;
+                r.0 = Quat::from_axis_angle(axis, value);
+            }
+            true
+        }
+        TARGET_PRIM_PARAMS => {
+            if let Ok(mut p) = world.get::<&mut PrimitiveParams>(entity) {
+                p.0[f] = value;
+            }
+            false
+        }
+        _ => {
+            let _ = world.insert_one(entity, Depth(value));
+            false
+        }
+    };
+    if let Some(slot) = render_state.get_slot(entity) {
+        if transform {
+            render_state.dirty_tracker.mark_transform_dirty(slot as usize);
+            render_state.dirty_tracker.mark_bounds_dirty(slot as usize);
+        } else {
+            render_state.dirty_tracker.mark_meta_dirty(slot as usize);
+        }
+    }
+}
+
+/// Advance every tween of active entities by one tick, write the tweened channels, and push
+/// completion/loop events. Runs once per fixed tick.
+pub fn tween_system(world: &mut World, render_state: &mut RenderState, events: &mut Vec<EngineEvent>) {
+    let entities: Vec<(hecs::Entity, u32)> = world
+        .query_mut::<With<(hecs::Entity, &ExternalId, &Tweens), &Active>>()
+        .into_iter()
+        .map(|(e, id, _)| (e, id.0))
+        .collect();
+
+    for (entity, ext_id) in entities {
+        let Some(mut tweens) = world.get::<&Tweens>(entity).ok().map(|t| (*t).clone()) else {
+            continue;
+        };
+        for track in &mut tweens.tracks {
+            let Some(tween) = track.front_mut() else {
+                continue;
+            };
+            if tween.delay > 0 {
+                tween.delay -= 1;
+                continue;
+            }
+            if tween.started == 0 {
+                tween.started = 1;
+                if tween.flags & TWEEN_FROM_CURRENT != 0
+                    && let Some(current) = read_channel(world, entity, tween.target, tween.field)
+                {
+                    tween.from = current;
+                }
+            }
+            tween.elapsed += 1;
+            write_channel(world, render_state, entity, tween.target, tween.field, tween.value());
+
+            if tween.elapsed >= tween.cycle_ticks() {
+                if tween.flags & TWEEN_LOOP != 0 {
+                    tween.elapsed = 0;
+                    events.push(EngineEvent { entity_id: ext_id, kind: EVENT_TWEEN_LOOP, data: tween.channel() });
+                } else {
+                    events.push(EngineEvent { entity_id: ext_id, kind: EVENT_TWEEN_COMPLETE, data: tween.channel() });
+                    track.pop_front();
+                }
+            }
+        }
+        tweens.tracks.retain(|track| !track.is_empty());
+
+        if tweens.is_empty() {
+            let _ = world.remove_one::<Tweens>(entity);
+        } else if let Ok(mut stored) = world.get::<&mut Tweens>(entity) {
+            *stored = tweens;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tween(target: u8, field: u8, from: f32, to: f32, duration: u16, flags: u8) -> Tween {
+        let mut p = [0u8; 16];
+        p[0] = target;
+        p[1] = field;
+        p[3] = flags;
+        p[4..8].copy_from_slice(&from.to_le_bytes());
+        p[8..12].copy_from_slice(&to.to_le_bytes());
+        p[12..14].copy_from_slice(&duration.to_le_bytes());
+        Tween::from_payload(&p).unwrap()
+    }
+
+    fn run(world: &mut World, ticks: usize) -> Vec<EngineEvent> {
+        let mut rs = RenderState::new();
+        let mut events = Vec::new();
+        for _ in 0..ticks {
+            tween_system(world, &mut rs, &mut events);
+        }
+        events
+    }
+
+    #[test]
+    fn easing_curves_hit_endpoints() {
+        for v in 0..16 {
+            let easing = Easing::from_u8(v).unwrap();
+            assert!(easing.apply(0.0).abs() < 1e-5, "{easing:?}");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-5, "{easing:?}");
+        }
+        assert!(Easing::from_u8(16).is_none());
+        assert!(Easing::QuadIn.apply(0.5) < 0.5 && Easing::QuadOut.apply(0.5) > 0.5);
+    }
+
+    #[test]
+    fn from_payload_rejects_unknown_targets_and_fields() {
+        let mut p = [0u8; 16];
+        p[0] = TARGET_POSITION;
+        p[1] = 3;
+        assert!(Tween::from_payload(&p).is_none());
+        p[1] = 2;
+        assert!(Tween::from_payload(&p).is_some());
+        p[0] = 9;
+        assert!(Tween::from_payload(&p).is_none());
+    }
+
+    #[test]
+    fn tween_reaches_target_then_completes() {
+        let mut world = World::new();
+        let mut tweens = Tweens::default();
+        tweens.push(tween(TARGET_POSITION, 0, 0.0, 10.0, 4, 0));
+        let e = world.spawn((Position::default(), Active, ExternalId(7), tweens));
+
+        assert!(run(&mut world, 2).is_empty());
+        assert_eq!(world.get::<&Position>(e).unwrap().0.x, 5.0);
+        let events = run(&mut world, 2);
+        assert_eq!(world.get::<&Position>(e).unwrap().0.x, 10.0);
+        assert_eq!(events, [EngineEvent { entity_id: 7, kind: EVENT_TWEEN_COMPLETE, data: TARGET_POSITION as u32 }]);
+        assert!(!world.satisfies::<&Tweens>(e));
+    }
+
+    #[test]
+    fn ping_pong_loop_returns_and_repeats() {
+        let mut world = World::new();
+        let mut tweens = Tweens::default();
+        tweens.push(tween(TARGET_TRANSFORM_2D, 2, 0.0, 1.0, 2, TWEEN_PING_PONG | TWEEN_LOOP));
+        let e = world.spawn((Transform2D::default(), Active, ExternalId(1), tweens));
+
+        run(&mut world, 2);
+        assert_eq!(world.get::<&Transform2D>(e).unwrap().rot, 1.0);
+        let events = run(&mut world, 2);
+        assert_eq!(world.get::<&Transform2D>(e).unwrap().rot, 0.0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, EVENT_TWEEN_LOOP);
+        assert!(world.satisfies::<&Tweens>(e));
+    }
+
+    #[test]
+    fn delay_and_sequence_run_in_order() {
+        let mut world = World::new();
+        let mut tweens = Tweens::default();
+        let mut first = tween(TARGET_POSITION, 1, 0.0, 2.0, 2, 0);
+        first.delay = 1;
+        tweens.push(first);
+        tweens.push(tween(TARGET_POSITION, 1, 0.0, 6.0, 2, TWEEN_SEQUENCE | TWEEN_FROM_CURRENT));
+        let e = world.spawn((Position::default(), Active, ExternalId(0), tweens));
+
+        run(&mut world, 1);
+        assert_eq!(world.get::<&Position>(e).unwrap().0.y, 0.0, "still delayed");
+        run(&mut world, 2);
+        assert_eq!(world.get::<&Position>(e).unwrap().0.y, 2.0);
+        run(&mut world, 1);
+        // Second tween starts from 2.0, the value when it began.
+        assert_eq!(world.get::<&Position>(e).unwrap().0.y, 4.0);
+        let events = run(&mut world, 1);
+        assert_eq!(world.get::<&Position>(e).unwrap().0.y, 6.0);
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn snapshot_bytes_round_trip() {
+        let mut tweens = Tweens::default();
+        tweens.push(tween(TARGET_DEPTH, 0, 1.0, 2.0, 30, 0));
+        tweens.push(tween(TARGET_SCALE, 1, 1.0, 3.0, 10, TWEEN_SEQUENCE));
+        tweens.push(tween(TARGET_PRIM_PARAMS, 7, 0.0, 1.0, 5, TWEEN_LOOP));
+        let mut bytes = Vec::new();
+        tweens.write_bytes(&mut bytes);
+        let mut cursor = 0;
+        assert_eq!(Tweens::read_bytes(&bytes, &mut cursor), Some(tweens));
+        assert_eq!(cursor, bytes.len());
+        assert_eq!(Tweens::read_bytes(&bytes[..bytes.len() - 1], &mut 0), None);
+    }
+}
@@ -4,7 +4,7 @@ use wasm_bindgen::prelude::*;
 use std::cell::RefCell;
 
 thread_local! {
-    static DOCS: RefCell<Vec<LoroDoc>> = RefCell::new(Vec::new());
+    static DOCS: RefCell<Vec<LoroDoc>> = const { RefCell::new(Vec::new()) };
 }
 
 /// Creates a new LoroDoc and returns its index.
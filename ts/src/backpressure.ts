@@ -1,4 +1,5 @@
-import { RingBufferProducer, CommandType } from './ring-buffer';
+import { RingBufferProducer, CommandType, PAYLOAD_SIZES, tagPayload, tweenPayload, schedulePayload, packTint } from './ring-buffer';
+import type { TweenOptions } from './ring-buffer';
 import type { JointHandle, CharacterControllerConfig } from './physics-api';
 
 export type BackpressureMode = 'retry-queue' | 'drop';
@@ -22,7 +23,7 @@ export interface FlushStats {
  * Maximum command type value (exclusive). Used for despawn purge iteration.
  * Must be updated if new CommandType variants are added.
  */
-const MAX_COMMAND_TYPE = 47; // CommandType values: 0..46
+const MAX_COMMAND_TYPE = 88; // CommandType values: 0..87
 
 /**
  * Returns true for commands that must NOT be coalesced (last-write-wins).
@@ -40,6 +41,11 @@ function isNonCoalescable(cmd: CommandType): boolean {
   // same entity with two joints + same cmdType = same key = silent overwrite.
   if (cmd >= CommandType.CreateRevoluteJoint && cmd <= CommandType.SetJointAnchorA) return true; // 33-43
   if (cmd === CommandType.CreateCharacterController) return true; // 44
+  if (cmd === CommandType.ScheduleAt) return true; // 72: must stay in front of the command it wraps
+  // 73-74: bursts are additive and must not overtake the attach they follow.
+  if (cmd === CommandType.AttachEmitter || cmd === CommandType.EmitterBurst) return true;
+  // 78, 83: trauma is additive and must not overtake the attach it follows.
+  if (cmd === CommandType.AttachCamera || cmd === CommandType.AddCameraTrauma) return true;
   return false;
 }
 
@@ -67,6 +73,14 @@ export class PrioritizedCommandQueue {
     }
   }
 
+  /**
+   * Queue a command on the ordered critical path without coalescing or
+   * purging (e.g. the pair written by `scheduleAt`).
+   */
+  enqueueOrdered(cmd: CommandType, entityId: number, payload?: Float32Array | Uint8Array): void {
+    this.critical.push({ cmd, entityId, payload });
+  }
+
   /**
    * Purge ALL pending overwrites for a given entity.
    * O(MAX_COMMAND_TYPE) per despawn — not O(map.size).
@@ -105,6 +119,9 @@ export class PrioritizedCommandQueue {
     let i = 0;
     for (; i < this.critical.length; i++) {
       const c = this.critical[i];
+      // Never split a ScheduleAt from the command it wraps.
+      const wrapped = c.cmd === CommandType.ScheduleAt ? this.critical[i + 1] : undefined;
+      if (wrapped && rb.freeSpace < 2 * 5 + PAYLOAD_SIZES[c.cmd] + (PAYLOAD_SIZES[wrapped.cmd] ?? 0)) break;
       if (!rb.writeCommand(c.cmd, c.entityId, c.payload)) break;
       stats.writtenCount++;
       if (tap) {
@@ -261,6 +278,177 @@ export class BackpressuredProducer {
     return this.writeCommand(CommandType.SetDepth, entityId, new Float32Array([z]));
   }
 
+  setActive(entityId: number, active: boolean, cascade = false): boolean {
+    return this.writeCommand(CommandType.SetActive, entityId, new Uint8Array([+active, +cascade]));
+  }
+
+  setVisible(entityId: number, visible: boolean, cascade = false): boolean {
+    return this.writeCommand(CommandType.SetVisible, entityId, new Uint8Array([+visible, +cascade]));
+  }
+
+  addTags(entityId: number, mask: number): boolean {
+    return this.writeCommand(CommandType.AddTags, entityId, tagPayload(mask));
+  }
+
+  removeTags(entityId: number, mask: number): boolean {
+    return this.writeCommand(CommandType.RemoveTags, entityId, tagPayload(mask));
+  }
+
+  despawnGroup(mask: number): boolean {
+    return this.writeCommand(CommandType.DespawnGroup, 0, tagPayload(mask));
+  }
+
+  setGroupVisible(mask: number, visible: boolean): boolean {
+    const payload = tagPayload(mask);
+    payload[4] = +visible;
+    return this.writeCommand(CommandType.SetGroupVisible, 0, payload);
+  }
+
+  applyGroupImpulse(mask: number, ix: number, iy: number): boolean {
+    return this.writeCommand(CommandType.ApplyGroupImpulse, 0, tagPayload(mask, ix, iy));
+  }
+
+  setGroupTextureLayer(mask: number, packedIndex: number): boolean {
+    const payload = tagPayload(mask);
+    new DataView(payload.buffer).setUint32(4, packedIndex >>> 0, true);
+    return this.writeCommand(CommandType.SetGroupTextureLayer, 0, payload);
+  }
+
+  translateGroup(mask: number, dx: number, dy: number, dz = 0): boolean {
+    return this.writeCommand(CommandType.TranslateGroup, 0, tagPayload(mask, dx, dy, dz));
+  }
+
+  setAngularVelocity(entityId: number, wx: number, wy: number, wz: number): boolean {
+    return this.writeCommand(CommandType.SetAngularVelocity, entityId, new Float32Array([wx, wy, wz]));
+  }
+
+  setAcceleration(entityId: number, ax: number, ay: number, az: number): boolean {
+    return this.writeCommand(CommandType.SetAcceleration, entityId, new Float32Array([ax, ay, az]));
+  }
+
+  setDrag(entityId: number, linear: number, angular: number): boolean {
+    return this.writeCommand(CommandType.SetDrag, entityId, new Float32Array([linear, angular]));
+  }
+
+  startTween(
+    entityId: number, target: number, field: number, from: number, to: number, duration: number,
+    opts: TweenOptions = {},
+  ): boolean {
+    return this.writeCommand(CommandType.StartTween, entityId, tweenPayload(target, field, from, to, duration, opts));
+  }
+
+  stopTweens(entityId: number, target = 0xFF, field = 0): boolean {
+    return this.writeCommand(CommandType.StopTweens, entityId, new Uint8Array([target & 0xFF, field & 0xFF]));
+  }
+
+  /** Play a clip registered with `engine_register_sprite_clip`. mode: 0=once, 1=loop, 2=ping-pong. */
+  playSpriteAnimation(entityId: number, clip: number, speed = 1, mode = 1, startFrame = 0): boolean {
+    const u8 = new Uint8Array(11);
+    const dv = new DataView(u8.buffer);
+    dv.setUint32(0, clip >>> 0, true);
+    dv.setFloat32(4, speed, true);
+    u8[8] = mode & 0xFF;
+    dv.setUint16(9, startFrame & 0xFFFF, true);
+    return this.writeCommand(CommandType.PlaySpriteAnimation, entityId, u8);
+  }
+
+  setSpriteAnimationSpeed(entityId: number, speed: number): boolean {
+    return this.writeCommand(CommandType.SetSpriteAnimationSpeed, entityId, new Float32Array([speed]));
+  }
+
+  stopSpriteAnimation(entityId: number): boolean {
+    return this.writeCommand(CommandType.StopSpriteAnimation, entityId);
+  }
+
+  /** Apply a command at the start of fixed tick `tick` instead of now. */
+  scheduleAt(
+    tick: number | bigint, relative: boolean, cmd: CommandType, entityId: number, payload?: Float32Array | Uint8Array,
+  ): boolean {
+    this.queue.enqueueOrdered(CommandType.ScheduleAt, 0, schedulePayload(tick, relative));
+    this.queue.enqueueOrdered(cmd, entityId, payload);
+    return true;
+  }
+
+  /** Despawn after `ticks` fixed ticks (0 clears a pending lifetime). */
+  setLifetime(entityId: number, ticks: number, cascade = false): boolean {
+    const u8 = new Uint8Array(5);
+    new DataView(u8.buffer).setUint32(0, ticks >>> 0, true);
+    u8[4] = cascade ? 1 : 0;
+    return this.writeCommand(CommandType.SetLifetime, entityId, u8);
+  }
+
+  attachEmitter(entityId: number, config: number, seed = 0): boolean {
+    return this.writeCommand(CommandType.AttachEmitter, entityId, new Uint8Array(new Uint32Array([config, seed]).buffer));
+  }
+
+  emitterBurst(entityId: number, count: number): boolean {
+    return this.writeCommand(CommandType.EmitterBurst, entityId, new Uint8Array(new Uint32Array([count]).buffer));
+  }
+
+  setEmitterRate(entityId: number, rate: number): boolean {
+    return this.writeCommand(CommandType.SetEmitterRate, entityId, new Float32Array([rate]));
+  }
+
+  detachEmitter(entityId: number): boolean {
+    return this.writeCommand(CommandType.DetachEmitter, entityId);
+  }
+
+  setPickLayer(entityId: number, mask: number): boolean {
+    return this.writeCommand(CommandType.SetPickLayer, entityId, new Uint8Array(new Uint32Array([mask >>> 0]).buffer));
+  }
+
+  attachCamera(entityId: number, fovY = 0, near = -1, far = 1000, seed = 0): boolean {
+    const u8 = new Uint8Array(16);
+    const view = new DataView(u8.buffer);
+    view.setFloat32(0, fovY, true);
+    view.setFloat32(4, near, true);
+    view.setFloat32(8, far, true);
+    view.setUint32(12, seed >>> 0, true);
+    return this.writeCommand(CommandType.AttachCamera, entityId, u8);
+  }
+
+  setCameraViewport(entityId: number, x: number, y: number, width: number, height: number): boolean {
+    return this.writeCommand(CommandType.SetCameraViewport, entityId, new Float32Array([x, y, width, height]));
+  }
+
+  setCameraZoom(entityId: number, zoom: number): boolean {
+    return this.writeCommand(CommandType.SetCameraZoom, entityId, new Float32Array([zoom]));
+  }
+
+  setCameraFollow(entityId: number, target: number, damping = 0, deadzoneX = 0, deadzoneY = 0): boolean {
+    const u8 = new Uint8Array(16);
+    const view = new DataView(u8.buffer);
+    view.setUint32(0, target >>> 0, true);
+    view.setFloat32(4, damping, true);
+    view.setFloat32(8, deadzoneX, true);
+    view.setFloat32(12, deadzoneY, true);
+    return this.writeCommand(CommandType.SetCameraFollow, entityId, u8);
+  }
+
+  setCameraBounds(entityId: number, minX: number, minY: number, maxX: number, maxY: number): boolean {
+    return this.writeCommand(CommandType.SetCameraBounds, entityId, new Float32Array([minX, minY, maxX, maxY]));
+  }
+
+  addCameraTrauma(entityId: number, trauma: number, decay = 1, maxOffset = 8, maxRoll = 0.05): boolean {
+    return this.writeCommand(CommandType.AddCameraTrauma, entityId, new Float32Array([trauma, decay, maxOffset, maxRoll]));
+  }
+
+  detachCamera(entityId: number): boolean {
+    return this.writeCommand(CommandType.DetachCamera, entityId);
+  }
+
+  setTint(entityId: number, r: number, g: number, b: number, a = 1): boolean {
+    return this.writeCommand(CommandType.SetTint, entityId, new Uint8Array(new Uint32Array([packTint(r, g, b, a)]).buffer));
+  }
+
+  setRenderLayer(entityId: number, mask: number): boolean {
+    return this.writeCommand(CommandType.SetRenderLayer, entityId, new Uint8Array(new Uint32Array([mask >>> 0]).buffer));
+  }
+
+  setSortOrder(entityId: number, order: number): boolean {
+    return this.writeCommand(CommandType.SetSortOrder, entityId, new Uint8Array(new Int32Array([order]).buffer));
+  }
+
   // ── Physics: body ──
 
   createRigidBody(entityId: number, bodyType: number): boolean {
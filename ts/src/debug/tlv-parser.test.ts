@@ -37,9 +37,19 @@ describe('TLV Parser', () => {
     expect(components).toHaveLength(2);
   });
 
-  it('COMPONENT_NAMES covers all types 1-15', () => {
-    for (let i = 1; i <= 15; i++) {
+  it('COMPONENT_NAMES covers all core types 1-25', () => {
+    for (let i = 1; i <= 25; i++) {
       expect(COMPONENT_NAMES[i]).toBeDefined();
     }
   });
+
+  it('decodes SortOrder as a signed value', () => {
+    const buf = new Uint8Array(7);
+    const dv = new DataView(buf.buffer);
+    buf[0] = 25; dv.setUint16(1, 4, true);
+    dv.setInt32(3, -3, true);
+    const [component] = parseTLV(buf);
+    expect(component.name).toBe('SortOrder');
+    expect(component.values).toEqual({ order: -3 });
+  });
 });
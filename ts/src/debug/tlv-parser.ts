@@ -21,6 +21,16 @@ export const COMPONENT_NAMES: Record<number, string> = {
   13: 'PrimitiveParams',
   14: 'LocalMatrix',
   15: 'Children',
+  16: 'Transform2D',
+  17: 'Tags',
+  18: 'AngularVelocity',
+  19: 'Acceleration',
+  20: 'LinearDrag',
+  21: 'AngularDrag',
+  22: 'PickLayer',
+  23: 'Tint',
+  24: 'RenderLayer',
+  25: 'SortOrder',
 };
 
 export interface ParsedComponent {
@@ -88,6 +98,13 @@ function decodeValues(type: number, data: DataView, offset: number, len: number)
       }
       return { count, childIds };
     }
+    case 22: // PickLayer (u32 mask)
+    case 24: // RenderLayer (u32 mask)
+      return { mask: data.getUint32(offset, true) };
+    case 23: // Tint (RGBA8, R in the low byte)
+      return { rgba: data.getUint32(offset, true) };
+    case 25: // SortOrder (i32)
+      return { order: data.getInt32(offset, true) };
     default:
       return { raw: len };
   }
@@ -4,7 +4,8 @@
  * Engine Logic Worker.
  * Loads the WASM module, extracts commands from the shared ring buffer,
  * and runs the engine tick loop. After each tick, exports SoA GPU data
- * (transforms, bounds, renderMeta, texIndices) as transferable ArrayBuffers.
+ * (transforms, bounds, renderMeta, texIndices) as transferable ArrayBuffers,
+ * plus the previous-tick transforms when render interpolation is enabled.
  */
 
 import { extractUnread, HEARTBEAT_W1_OFFSET } from "./ring-buffer";
@@ -32,6 +33,11 @@ interface WasmEngine {
   engine_gpu_prim_params_f32_len(): number;
   engine_gpu_entity_ids_ptr(): number;
   engine_gpu_entity_ids_len(): number;
+  // Render interpolation exports
+  engine_set_interpolation(enabled: boolean): void;
+  engine_gpu_prev_transforms_ptr(): number;
+  engine_gpu_prev_transforms_f32_len(): number;
+  engine_interpolation_alpha(): number;
   // Listener position exports
   engine_listener_x(): number;
   engine_listener_y(): number;
@@ -41,10 +47,12 @@ interface WasmEngine {
 
 let wasm: WasmEngine | null = null;
 let commandBuffer: SharedArrayBuffer | null = null;
+let interpolation = false;
 
 interface InitMessage {
   type: "init";
   commandBuffer: SharedArrayBuffer;
+  interpolation?: boolean;
 }
 
 interface TickMessage {
@@ -66,6 +74,8 @@ self.onmessage = async (event: MessageEvent<WorkerMessage>) => {
         commandBuffer = msg.commandBuffer;
 
         wasm.engine_init();
+        interpolation = msg.interpolation ?? false;
+        wasm.engine_set_interpolation(interpolation);
 
         self.postMessage({ type: "ready" });
       } catch (e) {
@@ -98,6 +108,8 @@ self.onmessage = async (event: MessageEvent<WorkerMessage>) => {
         texIndices: ArrayBuffer;
         primParams: ArrayBuffer;
         entityIds: ArrayBuffer;
+        prevTransforms: ArrayBuffer | null;
+        interpolationAlpha: number;
         listenerX: number;
         listenerY: number;
         listenerZ: number;
@@ -136,6 +148,14 @@ self.onmessage = async (event: MessageEvent<WorkerMessage>) => {
         const entityIds = new Uint32Array(eidLen);
         if (eidPtr) entityIds.set(new Uint32Array(wasm.engine_memory().buffer, eidPtr, eidLen));
 
+        let prevTransforms: Float32Array | null = null;
+        const ptPtr = interpolation ? wasm.engine_gpu_prev_transforms_ptr() : 0;
+        if (ptPtr) {
+          const ptLen = wasm.engine_gpu_prev_transforms_f32_len();
+          prevTransforms = new Float32Array(ptLen);
+          prevTransforms.set(new Float32Array(wasm.engine_memory().buffer, ptPtr, ptLen));
+        }
+
         renderState = {
           entityCount: count,
           transforms: transforms.buffer as ArrayBuffer,
@@ -144,6 +164,8 @@ self.onmessage = async (event: MessageEvent<WorkerMessage>) => {
           texIndices: texIndices.buffer as ArrayBuffer,
           primParams: primParams.buffer as ArrayBuffer,
           entityIds: entityIds.buffer as ArrayBuffer,
+          prevTransforms: prevTransforms ? prevTransforms.buffer as ArrayBuffer : null,
+          interpolationAlpha: wasm.engine_interpolation_alpha(),
           listenerX: wasm!.engine_listener_x(),
           listenerY: wasm!.engine_listener_y(),
           listenerZ: wasm!.engine_listener_z(),
@@ -151,9 +173,11 @@ self.onmessage = async (event: MessageEvent<WorkerMessage>) => {
       }
 
       if (renderState) {
+        const transferables = [renderState.transforms, renderState.bounds, renderState.renderMeta, renderState.texIndices, renderState.primParams, renderState.entityIds];
+        if (renderState.prevTransforms) transferables.push(renderState.prevTransforms);
         self.postMessage(
           { type: "tick-done", dt: msg.dt, tickCount, renderState },
-          [renderState.transforms, renderState.bounds, renderState.renderMeta, renderState.texIndices, renderState.primParams, renderState.entityIds]
+          transferables
         );
       } else {
         self.postMessage({
@@ -79,6 +79,7 @@ function defaultConfig(): ResolvedConfig {
     scatterThreshold: 0.3,
     textureStreaming: false,
     streamingBudgetBytesPerFrame: 256 * 1024,
+    interpolation: false,
   };
 }
 
@@ -222,6 +223,7 @@ describe('Hyperion', () => {
       primParams: new Float32Array(0), entityIds: new Uint32Array(0),
       listenerX: 0, listenerY: 0, listenerZ: 0, tickCount: 42,
       dirtyCount: 0, dirtyRatio: 0, stagingData: null, dirtyIndices: null, dirtyBits: null,
+      prevTransforms: null, interpolationAlpha: 0,
     };
     const engine = Hyperion.fromParts(defaultConfig(), bridge, mockRenderer());
     expect(engine.stats.tickCount).toBe(42);
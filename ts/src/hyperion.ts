@@ -151,13 +151,13 @@ export class Hyperion implements Disposable {
             console.warn('[Hyperion] No WebGPU adapter on main thread, skipping Mode A');
             continue;
           }
-          bridge = createFullIsolationBridge(config.canvas);
+          bridge = createFullIsolationBridge(config.canvas, config.interpolation);
           rendererOnMain = false;
         } else if (mode === ExecutionMode.PartialIsolation && caps.sharedArrayBuffer) {
-          bridge = createWorkerBridge(mode);
+          bridge = createWorkerBridge(mode, config.interpolation);
           rendererOnMain = true;
         } else {
-          bridge = await createDirectBridge();
+          bridge = await createDirectBridge(config.interpolation);
           rendererOnMain = true;
         }
         await bridge.ready();
@@ -172,7 +172,7 @@ export class Hyperion implements Disposable {
 
     if (!bridge) {
       // Last resort: Mode C should always work
-      bridge = await createDirectBridge();
+      bridge = await createDirectBridge(config.interpolation);
       await bridge.ready();
       rendererOnMain = true;
     }
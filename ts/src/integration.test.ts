@@ -73,6 +73,8 @@ describe("Integration: Texture Layer Index Pipeline", () => {
       stagingData: null,
       dirtyIndices: null,
       dirtyBits: null,
+      prevTransforms: null,
+      interpolationAlpha: 0,
     };
     expect(state.transforms.length).toBe(16);
     expect(state.bounds.length).toBe(4);
@@ -75,6 +75,7 @@ function defaultConfig(): ResolvedConfig {
     scatterThreshold: 0.3,
     textureStreaming: false,
     streamingBudgetBytesPerFrame: 256 * 1024,
+    interpolation: false,
   };
 }
 
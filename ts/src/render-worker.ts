@@ -29,6 +29,8 @@ interface RenderState {
   listenerY?: number;
   listenerZ?: number;
   tickCount?: number;
+  prevTransforms?: ArrayBuffer | null;
+  interpolationAlpha?: number;
 }
 
 let latestRenderState: RenderState | null = null;
@@ -93,6 +95,8 @@ function renderLoop(): void {
         stagingData: null,
         dirtyIndices: null,
         dirtyBits: null,
+        prevTransforms: latestRenderState.prevTransforms ? new Float32Array(latestRenderState.prevTransforms) : null,
+        interpolationAlpha: latestRenderState.interpolationAlpha ?? 0,
       }, camera);
     }
 
@@ -12,6 +12,7 @@ describe('ScatterPass', () => {
     expect(pass.writes).toContain('render-meta');
     expect(pass.writes).toContain('tex-indices');
     expect(pass.writes).toContain('prim-params');
+    expect(pass.writes).toContain('entity-tints');
     expect(pass.optional).toBe(true);
   });
 
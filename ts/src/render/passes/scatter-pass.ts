@@ -9,18 +9,23 @@ import type { ResourcePool } from '../resource-pool';
  * The RenderGraph DAG ensures this by declaring writes to the same
  * buffers that CullPass reads.
  *
- * Staging buffer layout per dirty entity (STAGING_STRIDE = 32 u32):
+ * Staging buffer layout per dirty entity (STAGING_STRIDE = 33 u32):
  *   [0..15]  transforms (16 u32 = mat4x4)
  *   [16..19] bounds     (4 u32)
  *   [20..21] renderMeta (2 u32)
  *   [22]     texIndices  (1 u32)
  *   [23..30] primParams (8 u32)
  *   [31]     format flag (0 = compressed 2D, 1 = pre-computed mat4x4)
+ *   [32]     tint        (1 u32 RGBA8, R in the low byte)
+ *
+ * Tints have no full-buffer upload: the staging records are their only
+ * path to `entity-tints`, so the renderer runs this pass on full-upload
+ * frames too.
  */
 export class ScatterPass implements RenderPass {
   readonly name = 'scatter';
   readonly reads: string[] = [];
-  readonly writes = ['entity-transforms', 'entity-bounds', 'render-meta', 'tex-indices', 'prim-params'];
+  readonly writes = ['entity-transforms', 'entity-bounds', 'render-meta', 'tex-indices', 'prim-params', 'entity-tints'];
   readonly optional = true;
 
   /**
@@ -78,6 +83,7 @@ export class ScatterPass implements RenderPass {
         { binding: 2, visibility: GPUShaderStage.COMPUTE, buffer: { type: 'storage' } },
         { binding: 3, visibility: GPUShaderStage.COMPUTE, buffer: { type: 'storage' } },
         { binding: 4, visibility: GPUShaderStage.COMPUTE, buffer: { type: 'storage' } },
+        { binding: 5, visibility: GPUShaderStage.COMPUTE, buffer: { type: 'storage' } },
       ],
     });
 
@@ -163,8 +169,9 @@ export class ScatterPass implements RenderPass {
     const renderMetaBuf = pool.getBuffer('render-meta');
     const texIndicesBuf = pool.getBuffer('tex-indices');
     const primParamsBuf = pool.getBuffer('prim-params');
+    const tintsBuf = pool.getBuffer('entity-tints');
 
-    if (transformsBuf && boundsBuf && renderMetaBuf && texIndicesBuf && primParamsBuf) {
+    if (transformsBuf && boundsBuf && renderMetaBuf && texIndicesBuf && primParamsBuf && tintsBuf) {
       this.destBindGroup = device.createBindGroup({
         layout: this.destBindGroupLayout!,
         entries: [
@@ -173,6 +180,7 @@ export class ScatterPass implements RenderPass {
           { binding: 2, resource: { buffer: renderMetaBuf } },
           { binding: 3, resource: { buffer: texIndicesBuf } },
           { binding: 4, resource: { buffer: primParamsBuf } },
+          { binding: 5, resource: { buffer: tintsBuf } },
         ],
       });
     }
@@ -150,6 +150,12 @@ export async function createRenderer(
     usage: GPUBufferUsage.STORAGE | GPUBufferUsage.COPY_DST,
   }));
 
+  // Written only by ScatterPass from the staging records.
+  resources.setBuffer('entity-tints', device.createBuffer({
+    size: MAX_ENTITIES * 4,  // 1 u32/entity (RGBA8)
+    usage: GPUBufferUsage.STORAGE | GPUBufferUsage.COPY_DST,
+  }));
+
   // Selection mask buffer: 1 u32 per entity (0=unselected, 1=selected)
   const selectionMaskBuffer = device.createBuffer({
     size: MAX_ENTITIES * 4,
@@ -551,7 +557,8 @@ export async function createRenderer(
       // Scatter/full upload branching:
       // When dirty ratio is below threshold and scatter pass is available,
       // upload only dirty entities via GPU compute scatter. Otherwise, fall
-      // back to full writeBuffer uploads for all SoA buffers.
+      // back to full writeBuffer uploads for all SoA buffers. Tints only
+      // travel in the staging records, so the full path scatters them too.
       const useScatter = state.dirtyCount > 0
         && state.dirtyRatio <= resolvedScatterThreshold
         && scatterPass
@@ -608,6 +615,17 @@ export async function createRenderer(
             state.entityCount * 8,
           );
         }
+
+        // Scatter the same records; tints have no full-buffer upload.
+        if (scatterPass && state.dirtyCount > 0 && state.stagingData && state.dirtyIndices) {
+          scatterPass.prepareDirtyData(
+            device,
+            resources,
+            state.stagingData,
+            state.dirtyIndices,
+            state.dirtyCount,
+          );
+        }
       }
 
       // Upload selection mask if dirty
@@ -68,6 +68,79 @@ export const enum CommandType {
   CreateCharacterController = 44,
   SetCharacterConfig = 45,
   MoveCharacter = 46,
+
+  // Editor
+  HistoryMarker = 47,
+
+  // Simulation control
+  SetTickRate = 48,
+  SetTimeScale = 49,
+  SetPaused = 50,
+  StepTicks = 51,
+
+  // Generic components
+  SetComponent = 52,
+  RemoveComponent = 53,
+
+  // Activation / visibility
+  SetActive = 54,
+  SetVisible = 55,
+
+  // Tags / groups (group commands ignore the entity ID)
+  AddTags = 56,
+  RemoveTags = 57,
+  DespawnGroup = 58,
+  SetGroupVisible = 59,
+  ApplyGroupImpulse = 60,
+  SetGroupTextureLayer = 61,
+  TranslateGroup = 62,
+
+  // Motion (non-physics integration)
+  SetAngularVelocity = 63,
+  SetAcceleration = 64,
+  SetDrag = 65,
+
+  // Tweens
+  StartTween = 66,
+  StopTweens = 67,
+
+  // Sprite animation
+  PlaySpriteAnimation = 68,
+  SetSpriteAnimationSpeed = 69,
+  StopSpriteAnimation = 70,
+
+  // Lifetime
+  SetLifetime = 71,
+
+  // Scheduling
+  ScheduleAt = 72,
+
+  // Particles
+  AttachEmitter = 73,
+  EmitterBurst = 74,
+  SetEmitterRate = 75,
+  DetachEmitter = 76,
+
+  // Picking
+  SetPickLayer = 77,
+
+  // Cameras
+  AttachCamera = 78,
+  SetCameraViewport = 79,
+  SetCameraZoom = 80,
+  SetCameraFollow = 81,
+  SetCameraBounds = 82,
+  AddCameraTrauma = 83,
+  DetachCamera = 84,
+
+  // Tint
+  SetTint = 85,
+
+  // Render layers
+  SetRenderLayer = 86,
+
+  // Sort order
+  SetSortOrder = 87,
 }
 
 /** Payload sizes in bytes for each command type (excluding type + entity_id). */
@@ -127,8 +200,164 @@ export const PAYLOAD_SIZES: Record<CommandType, number> = {
   [CommandType.CreateCharacterController]: 1,
   [CommandType.SetCharacterConfig]: 16,
   [CommandType.MoveCharacter]: 8,
+
+  // Editor
+  [CommandType.HistoryMarker]: 1,   // u8: 0=begin undo group, 1=end undo group
+
+  // Simulation control
+  [CommandType.SetTickRate]: 4,     // u32: ticks per second
+  [CommandType.SetTimeScale]: 4,    // f32
+  [CommandType.SetPaused]: 1,       // u8: 0=running, 1=paused
+  [CommandType.StepTicks]: 4,       // u32: tick count
+
+  // Generic components
+  [CommandType.SetComponent]: 16,   // u8 component_id + data (up to 15 bytes)
+  [CommandType.RemoveComponent]: 1, // u8 component_id
+
+  // Activation / visibility
+  [CommandType.SetActive]: 2,       // u8 active + u8 cascade to children
+  [CommandType.SetVisible]: 2,      // u8 visible + u8 cascade to children
+
+  // Tags / groups
+  [CommandType.AddTags]: 4,         // u32 tag mask
+  [CommandType.RemoveTags]: 4,      // u32 tag mask
+  [CommandType.DespawnGroup]: 4,    // u32 tag mask
+  [CommandType.SetGroupVisible]: 5, // u32 tag mask + u8 visible
+  [CommandType.ApplyGroupImpulse]: 12, // u32 tag mask + ix, iy (f32)
+  [CommandType.SetGroupTextureLayer]: 8, // u32 tag mask + u32 packed layer index
+  [CommandType.TranslateGroup]: 16, // u32 tag mask + dx, dy, dz (f32)
+
+  // Motion
+  [CommandType.SetAngularVelocity]: 12, // wx, wy, wz rad/s (f32); 2D uses wz
+  [CommandType.SetAcceleration]: 12, // ax, ay, az (f32)
+  [CommandType.SetDrag]: 8,         // linear + angular drag (f32)
+
+  // Tweens
+  [CommandType.StartTween]: 16,     // target, field, easing, flags (u8) + from, to (f32) + duration, delay (u16 ticks)
+  [CommandType.StopTweens]: 2,      // target (u8, 0xFF = all) + field (u8)
+
+  // Sprite animation
+  [CommandType.PlaySpriteAnimation]: 11, // clip (u32) + speed (f32) + mode (u8) + start frame (u16)
+  [CommandType.SetSpriteAnimationSpeed]: 4, // speed (f32), 0 = paused
+  [CommandType.StopSpriteAnimation]: 0,
+
+  // Lifetime
+  [CommandType.SetLifetime]: 5,     // ticks (u32, 0 = clear) + cascade (u8)
+
+  // Scheduling
+  [CommandType.ScheduleAt]: 9,      // tick (u64) + mode (u8: 0=absolute, 1=relative); wraps the next command
+
+  // Particles
+  [CommandType.AttachEmitter]: 8,   // config (u32) + seed (u32)
+  [CommandType.EmitterBurst]: 4,    // count (u32)
+  [CommandType.SetEmitterRate]: 4,  // particles/s (f32, < 0 = config rate)
+  [CommandType.DetachEmitter]: 0,
+
+  // Picking
+  [CommandType.SetPickLayer]: 4,    // layer mask (u32), 0 = unpickable
+
+  // Cameras
+  [CommandType.AttachCamera]: 16,      // fov_y (f32, 0 = orthographic) + near (f32) + far (f32) + seed (u32)
+  [CommandType.SetCameraViewport]: 16, // x, y, width, height (f32 pixels)
+  [CommandType.SetCameraZoom]: 4,      // zoom (f32)
+  [CommandType.SetCameraFollow]: 16,   // target (u32, 0xFFFFFFFF = none) + damping (f32) + deadzone x, y (f32)
+  [CommandType.SetCameraBounds]: 16,   // min x, y + max x, y (f32; min > max = unclamped)
+  [CommandType.AddCameraTrauma]: 16,   // trauma (f32) + decay/s (f32) + max offset (f32) + max roll (f32)
+  [CommandType.DetachCamera]: 0,
+
+  // Tint
+  [CommandType.SetTint]: 4,            // RGBA8 (u32, R in the low byte)
+
+  // Render layers
+  [CommandType.SetRenderLayer]: 4,     // layer mask (u32, layers 0..15)
+
+  // Sort order
+  [CommandType.SetSortOrder]: 4,       // order (i32, equal-depth tie-break)
 };
 
+/** Pack an RGBA color (components in 0..1) into the `SetTint` RGBA8 word. */
+export function packTint(r: number, g: number, b: number, a = 1): number {
+  const byte = (v: number) => Math.round(Math.min(Math.max(v, 0), 1) * 255);
+  return (byte(r) | (byte(g) << 8) | (byte(b) << 16) | (byte(a) << 24)) >>> 0;
+}
+
+/** ScheduleAt payload. `tick` is absolute, or relative to the engine's next tick. */
+export function schedulePayload(tick: number | bigint, relative: boolean): Uint8Array {
+  const u8 = new Uint8Array(9);
+  new DataView(u8.buffer).setBigUint64(0, BigInt(tick), true);
+  u8[8] = relative ? 1 : 0;
+  return u8;
+}
+
+/** Tween channel targets (`StartTween` target byte). */
+export const enum TweenTarget {
+  Transform2D = 0, // field: 0=x, 1=y, 2=rot, 3=sx, 4=sy
+  Position = 1,    // field: axis
+  Scale = 2,       // field: axis
+  Rotation = 3,    // field: axis; value is the angle in radians
+  PrimParams = 4,  // field: slot 0-7
+  Depth = 5,
+  All = 0xFF,      // StopTweens only
+}
+
+/** Tween flags (`StartTween` flags byte). */
+export const TWEEN_LOOP = 1 << 0;
+export const TWEEN_PING_PONG = 1 << 1;
+export const TWEEN_SEQUENCE = 1 << 2;
+export const TWEEN_FROM_CURRENT = 1 << 3;
+
+/** Options for `startTween`. Durations and delays are in fixed ticks. */
+export interface TweenOptions {
+  easing?: number; // Easing id 0-15 (0 = linear)
+  flags?: number;  // TWEEN_* bits
+  delay?: number;
+}
+
+/** StartTween payload. */
+export function tweenPayload(
+  target: number, field: number, from: number, to: number, duration: number, opts: TweenOptions = {},
+): Uint8Array {
+  const u8 = new Uint8Array(16);
+  const dv = new DataView(u8.buffer);
+  u8[0] = target & 0xFF;
+  u8[1] = field & 0xFF;
+  u8[2] = (opts.easing ?? 0) & 0xFF;
+  u8[3] = (opts.flags ?? 0) & 0xFF;
+  dv.setFloat32(4, from, true);
+  dv.setFloat32(8, to, true);
+  dv.setUint16(12, duration & 0xFFFF, true);
+  dv.setUint16(14, (opts.delay ?? 0) & 0xFFFF, true);
+  return u8;
+}
+
+/** Tag/group payload: u32 tag mask followed by up to three f32 arguments. */
+export function tagPayload(mask: number, ...values: number[]): Uint8Array {
+  const u8 = new Uint8Array(16);
+  const dv = new DataView(u8.buffer);
+  dv.setUint32(0, mask >>> 0, true);
+  for (let i = 0; i < Math.min(values.length, 3); i++) {
+    dv.setFloat32(4 + i * 4, values[i], true);
+  }
+  return u8;
+}
+
+/** First opcode of the plugin range (128–255), owned by Rust `HyperionPlugin`s. */
+export const PLUGIN_OPCODE_START = 128;
+
+/** Payload sizes of plugin opcodes, registered to match the Rust plugin. */
+const pluginPayloadSizes = new Map<number, number>();
+
+/** Register the payload size of a plugin opcode so it can be written to the ring buffer. */
+export function registerPluginCommand(opcode: number, payloadSize: number): void {
+  if (opcode < PLUGIN_OPCODE_START || opcode > 255) {
+    throw new Error(`Plugin opcode must be in ${PLUGIN_OPCODE_START}..255, got ${opcode}`);
+  }
+  if (payloadSize < 0 || payloadSize > 16) {
+    throw new Error(`Plugin payload size must be 0..16, got ${payloadSize}`);
+  }
+  pluginPayloadSizes.set(opcode, payloadSize);
+}
+
 export class RingBufferProducer {
   private readonly header: Int32Array;
   private readonly data: DataView;
@@ -175,7 +404,11 @@ export class RingBufferProducer {
   }
 
   writeCommand(cmd: CommandType, entityId: number, payload?: Float32Array | Uint8Array): boolean {
-    const payloadSize = PAYLOAD_SIZES[cmd];
+    const payloadSize = PAYLOAD_SIZES[cmd] ?? pluginPayloadSizes.get(cmd);
+    if (payloadSize === undefined) {
+      console.warn("Unregistered command opcode, dropping command", cmd);
+      return false;
+    }
     const msgSize = 1 + 4 + payloadSize;
 
     if (this.freeSpace < msgSize) {
@@ -307,6 +540,195 @@ export class RingBufferProducer {
     const payload = new Float32Array([z]);
     return this.writeCommand(CommandType.SetDepth, entityId, payload);
   }
+
+  setActive(entityId: number, active: boolean, cascade = false): boolean {
+    return this.writeCommand(CommandType.SetActive, entityId, new Uint8Array([+active, +cascade]));
+  }
+
+  setVisible(entityId: number, visible: boolean, cascade = false): boolean {
+    return this.writeCommand(CommandType.SetVisible, entityId, new Uint8Array([+visible, +cascade]));
+  }
+
+  addTags(entityId: number, mask: number): boolean {
+    return this.writeCommand(CommandType.AddTags, entityId, tagPayload(mask));
+  }
+
+  removeTags(entityId: number, mask: number): boolean {
+    return this.writeCommand(CommandType.RemoveTags, entityId, tagPayload(mask));
+  }
+
+  despawnGroup(mask: number): boolean {
+    return this.writeCommand(CommandType.DespawnGroup, 0, tagPayload(mask));
+  }
+
+  setGroupVisible(mask: number, visible: boolean): boolean {
+    const payload = tagPayload(mask);
+    payload[4] = +visible;
+    return this.writeCommand(CommandType.SetGroupVisible, 0, payload);
+  }
+
+  applyGroupImpulse(mask: number, ix: number, iy: number): boolean {
+    return this.writeCommand(CommandType.ApplyGroupImpulse, 0, tagPayload(mask, ix, iy));
+  }
+
+  setGroupTextureLayer(mask: number, packedIndex: number): boolean {
+    const payload = tagPayload(mask);
+    new DataView(payload.buffer).setUint32(4, packedIndex >>> 0, true);
+    return this.writeCommand(CommandType.SetGroupTextureLayer, 0, payload);
+  }
+
+  translateGroup(mask: number, dx: number, dy: number, dz = 0): boolean {
+    return this.writeCommand(CommandType.TranslateGroup, 0, tagPayload(mask, dx, dy, dz));
+  }
+
+  setAngularVelocity(entityId: number, wx: number, wy: number, wz: number): boolean {
+    return this.writeCommand(CommandType.SetAngularVelocity, entityId, new Float32Array([wx, wy, wz]));
+  }
+
+  setAcceleration(entityId: number, ax: number, ay: number, az: number): boolean {
+    return this.writeCommand(CommandType.SetAcceleration, entityId, new Float32Array([ax, ay, az]));
+  }
+
+  setDrag(entityId: number, linear: number, angular: number): boolean {
+    return this.writeCommand(CommandType.SetDrag, entityId, new Float32Array([linear, angular]));
+  }
+
+  startTween(
+    entityId: number, target: number, field: number, from: number, to: number, duration: number,
+    opts: TweenOptions = {},
+  ): boolean {
+    return this.writeCommand(CommandType.StartTween, entityId, tweenPayload(target, field, from, to, duration, opts));
+  }
+
+  stopTweens(entityId: number, target = 0xFF, field = 0): boolean {
+    return this.writeCommand(CommandType.StopTweens, entityId, new Uint8Array([target & 0xFF, field & 0xFF]));
+  }
+
+  /** Play a clip registered with `engine_register_sprite_clip`. mode: 0=once, 1=loop, 2=ping-pong. */
+  playSpriteAnimation(entityId: number, clip: number, speed = 1, mode = 1, startFrame = 0): boolean {
+    const u8 = new Uint8Array(11);
+    const dv = new DataView(u8.buffer);
+    dv.setUint32(0, clip >>> 0, true);
+    dv.setFloat32(4, speed, true);
+    u8[8] = mode & 0xFF;
+    dv.setUint16(9, startFrame & 0xFFFF, true);
+    return this.writeCommand(CommandType.PlaySpriteAnimation, entityId, u8);
+  }
+
+  setSpriteAnimationSpeed(entityId: number, speed: number): boolean {
+    return this.writeCommand(CommandType.SetSpriteAnimationSpeed, entityId, new Float32Array([speed]));
+  }
+
+  stopSpriteAnimation(entityId: number): boolean {
+    return this.writeCommand(CommandType.StopSpriteAnimation, entityId);
+  }
+
+  /**
+   * Apply a command at the start of fixed tick `tick` instead of now. Both
+   * commands are written, or neither when the buffer lacks room for the pair.
+   */
+  scheduleAt(
+    tick: number | bigint, relative: boolean, cmd: CommandType, entityId: number, payload?: Float32Array | Uint8Array,
+  ): boolean {
+    const needed = 2 * 5 + PAYLOAD_SIZES[CommandType.ScheduleAt] + (PAYLOAD_SIZES[cmd] ?? 0);
+    if (this.freeSpace < needed) return false;
+    return this.writeCommand(CommandType.ScheduleAt, 0, schedulePayload(tick, relative))
+      && this.writeCommand(cmd, entityId, payload);
+  }
+
+  /** Despawn after `ticks` fixed ticks (0 clears a pending lifetime). */
+  setLifetime(entityId: number, ticks: number, cascade = false): boolean {
+    const u8 = new Uint8Array(5);
+    new DataView(u8.buffer).setUint32(0, ticks >>> 0, true);
+    u8[4] = cascade ? 1 : 0;
+    return this.writeCommand(CommandType.SetLifetime, entityId, u8);
+  }
+
+  /** Attach a particle emitter using a config registered with `engine_register_emitter`. */
+  attachEmitter(entityId: number, config: number, seed = 0): boolean {
+    return this.writeCommand(CommandType.AttachEmitter, entityId, new Uint8Array(new Uint32Array([config, seed]).buffer));
+  }
+
+  /** Emit `count` extra particles on the next tick. */
+  emitterBurst(entityId: number, count: number): boolean {
+    return this.writeCommand(CommandType.EmitterBurst, entityId, new Uint8Array(new Uint32Array([count]).buffer));
+  }
+
+  /** Override the emission rate (particles/s); a negative rate restores the config's. */
+  setEmitterRate(entityId: number, rate: number): boolean {
+    return this.writeCommand(CommandType.SetEmitterRate, entityId, new Float32Array([rate]));
+  }
+
+  detachEmitter(entityId: number): boolean {
+    return this.writeCommand(CommandType.DetachEmitter, entityId);
+  }
+
+  /** Pick layer bitmask checked against `engine_pick` masks (default 1; 0 = unpickable). */
+  setPickLayer(entityId: number, mask: number): boolean {
+    return this.writeCommand(CommandType.SetPickLayer, entityId, new Uint8Array(new Uint32Array([mask >>> 0]).buffer));
+  }
+
+  /**
+   * Make the entity a camera. `fovY` of 0 gives an orthographic (2D) camera;
+   * `seed` drives its shake.
+   */
+  attachCamera(entityId: number, fovY = 0, near = -1, far = 1000, seed = 0): boolean {
+    const u8 = new Uint8Array(16);
+    const view = new DataView(u8.buffer);
+    view.setFloat32(0, fovY, true);
+    view.setFloat32(4, near, true);
+    view.setFloat32(8, far, true);
+    view.setUint32(12, seed >>> 0, true);
+    return this.writeCommand(CommandType.AttachCamera, entityId, u8);
+  }
+
+  setCameraViewport(entityId: number, x: number, y: number, width: number, height: number): boolean {
+    return this.writeCommand(CommandType.SetCameraViewport, entityId, new Float32Array([x, y, width, height]));
+  }
+
+  setCameraZoom(entityId: number, zoom: number): boolean {
+    return this.writeCommand(CommandType.SetCameraZoom, entityId, new Float32Array([zoom]));
+  }
+
+  /** Follow `target` (-1 = stop). `damping` is in 1/s, 0 = snap. */
+  setCameraFollow(entityId: number, target: number, damping = 0, deadzoneX = 0, deadzoneY = 0): boolean {
+    const u8 = new Uint8Array(16);
+    const view = new DataView(u8.buffer);
+    view.setUint32(0, target >>> 0, true);
+    view.setFloat32(4, damping, true);
+    view.setFloat32(8, deadzoneX, true);
+    view.setFloat32(12, deadzoneY, true);
+    return this.writeCommand(CommandType.SetCameraFollow, entityId, u8);
+  }
+
+  /** Clamp the camera to a world rectangle; `min > max` removes the clamp. */
+  setCameraBounds(entityId: number, minX: number, minY: number, maxX: number, maxY: number): boolean {
+    return this.writeCommand(CommandType.SetCameraBounds, entityId, new Float32Array([minX, minY, maxX, maxY]));
+  }
+
+  /** Add shake trauma (total capped at 1), decaying by `decay` per second. */
+  addCameraTrauma(entityId: number, trauma: number, decay = 1, maxOffset = 8, maxRoll = 0.05): boolean {
+    return this.writeCommand(CommandType.AddCameraTrauma, entityId, new Float32Array([trauma, decay, maxOffset, maxRoll]));
+  }
+
+  detachCamera(entityId: number): boolean {
+    return this.writeCommand(CommandType.DetachCamera, entityId);
+  }
+
+  /** Multiply the entity's color by RGBA in 0..1 (alpha fades it). */
+  setTint(entityId: number, r: number, g: number, b: number, a = 1): boolean {
+    return this.writeCommand(CommandType.SetTint, entityId, new Uint8Array(new Uint32Array([packTint(r, g, b, a)]).buffer));
+  }
+
+  /** Put the entity on the render layers in `mask` (bit n = layer n, default layer 0). */
+  setRenderLayer(entityId: number, mask: number): boolean {
+    return this.writeCommand(CommandType.SetRenderLayer, entityId, new Uint8Array(new Uint32Array([mask >>> 0]).buffer));
+  }
+
+  /** Break transparent depth ties: lower `order` draws first (underneath). */
+  setSortOrder(entityId: number, order: number): boolean {
+    return this.writeCommand(CommandType.SetSortOrder, entityId, new Uint8Array(new Int32Array([order]).buffer));
+  }
 }
 
 export function createRingBuffer(capacity: number): SharedArrayBuffer | ArrayBuffer {
@@ -15,8 +15,9 @@ struct ScatterUniforms {
 @group(1) @binding(2) var<storage, read_write> render_meta: array<u32>;
 @group(1) @binding(3) var<storage, read_write> tex_indices: array<u32>;
 @group(1) @binding(4) var<storage, read_write> prim_params: array<u32>;
+@group(1) @binding(5) var<storage, read_write> tints: array<u32>;
 
-const STAGING_STRIDE: u32 = 32u;
+const STAGING_STRIDE: u32 = 33u;
 
 @compute @workgroup_size(64)
 fn scatter(@builtin(global_invocation_id) gid: vec3u) {
@@ -85,4 +86,7 @@ fn scatter(@builtin(global_invocation_id) gid: vec3u) {
     for (var j = 0u; j < 8u; j++) {
         prim_params[p + j] = staging[src + 23u + j];
     }
+
+    // Tint: 1 u32 (RGBA8)
+    tints[dst] = staging[src + 32u];
 }
@@ -20,6 +20,8 @@ export interface HyperionConfig {
   textureStreaming?: boolean;
   /** Max bytes fetched per frame for texture streaming. Default: 256KB. */
   streamingBudgetBytesPerFrame?: number;
+  /** Capture previous-tick transforms into `GPURenderState.prevTransforms` for render interpolation. Default: false. */
+  interpolation?: boolean;
 }
 
 /** Resolved config with all defaults applied. */
@@ -36,6 +38,7 @@ export interface ResolvedConfig {
   scatterThreshold: number;
   textureStreaming: boolean;
   streamingBudgetBytesPerFrame: number;
+  interpolation: boolean;
 }
 
 /** Live engine statistics. */
@@ -87,5 +90,6 @@ export function validateConfig(config: HyperionConfig): ResolvedConfig {
     scatterThreshold: config.scatterThreshold ?? 0.3,
     textureStreaming: config.textureStreaming ?? false,
     streamingBudgetBytesPerFrame: config.streamingBudgetBytesPerFrame ?? 256 * 1024,
+    interpolation: config.interpolation ?? false,
   };
 }
@@ -25,10 +25,14 @@ export interface GPURenderState {
   // Dirty staging data (for scatter upload path)
   dirtyCount: number;
   dirtyRatio: number;
-  stagingData: Uint32Array | null;    // 32 u32 per dirty entity
+  stagingData: Uint32Array | null;    // 33 u32 per dirty entity
   dirtyIndices: Uint32Array | null;   // slot index per dirty entity
   // Dirty bitfield for temporal culling (1 bit per entity slot, packed u32)
   dirtyBits: Uint32Array | null;
+  // Render interpolation input: draw each slot at
+  // mix(prevTransforms, transforms, interpolationAlpha).
+  prevTransforms: Float32Array | null; // 16 f32/entity (previous-tick world mat4x4), null unless `interpolation` is on
+  interpolationAlpha: number;          // fraction of a tick elapsed since the latest one (0..1)
 }
 
 export interface EngineBridge {
@@ -50,7 +54,8 @@ export interface EngineBridge {
  * Create the engine bridge for Mode B (Partial Isolation: Worker ECS + Main Thread Render).
  */
 export function createWorkerBridge(
-  mode: ExecutionMode.PartialIsolation
+  mode: ExecutionMode.PartialIsolation,
+  interpolation = false,
 ): EngineBridge {
   const sab = createRingBuffer(RING_BUFFER_CAPACITY) as SharedArrayBuffer;
   const producer = new RingBufferProducer(sab);
@@ -101,11 +106,13 @@ export function createWorkerBridge(
         stagingData: rs.stagingData ? new Uint32Array(rs.stagingData) : null,
         dirtyIndices: rs.dirtyIndices ? new Uint32Array(rs.dirtyIndices) : null,
         dirtyBits: rs.dirtyBits ? new Uint32Array(rs.dirtyBits) : null,
+        prevTransforms: rs.prevTransforms ? new Float32Array(rs.prevTransforms) : null,
+        interpolationAlpha: rs.interpolationAlpha ?? 0,
       };
     }
   };
 
-  worker.postMessage({ type: "init", commandBuffer: sab });
+  worker.postMessage({ type: "init", commandBuffer: sab, interpolation });
 
   return {
     mode,
@@ -132,7 +139,8 @@ export function createWorkerBridge(
  * The canvas is transferred to the Render Worker via OffscreenCanvas.
  */
 export function createFullIsolationBridge(
-  canvas: HTMLCanvasElement
+  canvas: HTMLCanvasElement,
+  interpolation = false,
 ): EngineBridge {
   const sab = createRingBuffer(RING_BUFFER_CAPACITY) as SharedArrayBuffer;
   const producer = new RingBufferProducer(sab);
@@ -205,12 +213,15 @@ export function createFullIsolationBridge(
         stagingData: rs.stagingData ? new Uint32Array(new Uint32Array(rs.stagingData)) : null,
         dirtyIndices: rs.dirtyIndices ? new Uint32Array(new Uint32Array(rs.dirtyIndices)) : null,
         dirtyBits: rs.dirtyBits ? new Uint32Array(new Uint32Array(rs.dirtyBits)) : null,
+        prevTransforms: rs.prevTransforms ? new Float32Array(new Float32Array(rs.prevTransforms)) : null,
+        interpolationAlpha: rs.interpolationAlpha ?? 0,
       };
 
       // Forward full render state to Render Worker.
       const transferables = [rs.transforms, rs.bounds, rs.renderMeta, rs.texIndices];
       if (rs.primParams) transferables.push(rs.primParams);
       if (rs.entityIds) transferables.push(rs.entityIds);
+      if (rs.prevTransforms) transferables.push(rs.prevTransforms);
       channel.port1.postMessage(
         { renderState: rs },
         transferables,
@@ -241,7 +252,7 @@ export function createFullIsolationBridge(
     [offscreen, channel.port2]
   );
 
-  ecsWorker.postMessage({ type: "init", commandBuffer: sab });
+  ecsWorker.postMessage({ type: "init", commandBuffer: sab, interpolation });
 
   return {
     mode: ExecutionMode.FullIsolation,
@@ -270,7 +281,7 @@ export function createFullIsolationBridge(
 /**
  * Create the engine bridge for Mode C (single-thread, no Worker).
  */
-export async function createDirectBridge(): Promise<EngineBridge> {
+export async function createDirectBridge(interpolation = false): Promise<EngineBridge> {
   const buffer = createRingBuffer(RING_BUFFER_CAPACITY);
   const producer = new RingBufferProducer(buffer as SharedArrayBuffer);
   const commandBuffer = new BackpressuredProducer(producer);
@@ -299,6 +310,11 @@ export async function createDirectBridge(): Promise<EngineBridge> {
     engine_gpu_prim_params_f32_len(): number;
     engine_gpu_entity_ids_ptr(): number;
     engine_gpu_entity_ids_len(): number;
+    // Render interpolation exports
+    engine_set_interpolation(enabled: boolean): void;
+    engine_gpu_prev_transforms_ptr(): number;
+    engine_gpu_prev_transforms_f32_len(): number;
+    engine_interpolation_alpha(): number;
     // Listener position exports
     engine_listener_x(): number;
     engine_listener_y(): number;
@@ -318,6 +334,7 @@ export async function createDirectBridge(): Promise<EngineBridge> {
   };
 
   engine.engine_init();
+  engine.engine_set_interpolation(interpolation);
 
   let latestRenderState: GPURenderState | null = null;
 
@@ -380,6 +397,8 @@ export async function createDirectBridge(): Promise<EngineBridge> {
         const ppLen = engine.engine_gpu_prim_params_f32_len();
         const eidPtr = engine.engine_gpu_entity_ids_ptr();
         const eidLen = engine.engine_gpu_entity_ids_len();
+        const ptPtr = interpolation ? engine.engine_gpu_prev_transforms_ptr() : 0;
+        const ptLen = engine.engine_gpu_prev_transforms_f32_len();
 
         // Copy from WASM memory — live views become stale after next engine_update().
         latestRenderState = {
@@ -399,6 +418,8 @@ export async function createDirectBridge(): Promise<EngineBridge> {
           stagingData,
           dirtyIndices: dirtyIndicesArr,
           dirtyBits: dirtyBitsArr,
+          prevTransforms: ptPtr ? new Float32Array(new Float32Array(engine.engine_memory().buffer, ptPtr, ptLen)) : null,
+          interpolationAlpha: engine.engine_interpolation_alpha(),
         };
       } else {
         latestRenderState = {
@@ -418,6 +439,8 @@ export async function createDirectBridge(): Promise<EngineBridge> {
           stagingData: null,
           dirtyIndices: null,
           dirtyBits: null,
+          prevTransforms: null,
+          interpolationAlpha: engine.engine_interpolation_alpha(),
         };
       }
     },